@@ -0,0 +1,190 @@
+use std::str::FromStr;
+
+use chrono::{DateTime, Utc};
+use criterion::{black_box, criterion_group, criterion_main, BatchSize, Criterion};
+use events_api_http_server::{
+    csv_format::to_csv,
+    cursor::EventsPage,
+    meta::EventTable,
+    nft_events::NftTransferEvent,
+    registry::page_query,
+    trade_events::{TradePoolChangeEvent, TradeSwapEvent},
+    utils::{split_list, VecBalance},
+};
+use serde_json::json;
+use sqlx::types::BigDecimal;
+
+/// Events of a page with the default `limit`
+const BATCH_SIZE: usize = 10_000;
+
+fn timestamp(i: usize) -> DateTime<Utc> {
+    DateTime::from_timestamp_nanos(1_714_988_307_491_111_000 + i as i64 * 1_100_000_000)
+}
+
+fn nft_transfers() -> Vec<NftTransferEvent> {
+    (0..BATCH_SIZE)
+        .map(|i| NftTransferEvent {
+            old_owner_id: format!("seller{i}.near"),
+            new_owner_id: format!("buyer{i}.near"),
+            token_ids: vec![i.to_string(), (i + 1).to_string(), (i + 2).to_string()],
+            memo: None,
+            token_prices_near: VecBalance(vec![
+                BigDecimal::from_str("1500000000000000000000000")
+                    .unwrap();
+                3
+            ]),
+            transaction_id: "2Dgs6ZoWN4aE1SXcT8CKxqaCsFrxgpm7SDzZBWQZK5Qr".to_string(),
+            receipt_id: "Cz3VmB6qvdQhgzTJpKUqsvMvj4qf14xC3Dbh5WzzvbRL".to_string(),
+            block_height: 118058295 + i as i64,
+            timestamp: timestamp(i),
+            contract_id: "uwon.hot.tg".to_string(),
+        })
+        .collect()
+}
+
+fn trade_swaps() -> Vec<TradeSwapEvent> {
+    (0..BATCH_SIZE)
+        .map(|i| TradeSwapEvent {
+            trader: format!("trader{i}.near"),
+            block_height: 118058295 + i as i64,
+            timestamp: timestamp(i),
+            transaction_id: "AaK3s9Lq2Wx7Vb5Nc1Md8Fg4Hj6Kp3Rt9Ye2Uw5Zq7Bx".to_string(),
+            receipt_id: "BbL4t1Mr3Xy8Wc6Od2Ne9Gh5Ik7Lq4Su1Zf3Vx6Ar8Cy".to_string(),
+            balance_changes: json!({
+                "wrap.near": "-1000000000000000000000000",
+                "usdt.tether-token.near": "7123456",
+            }),
+        })
+        .collect()
+}
+
+fn trade_pool_changes() -> Vec<TradePoolChangeEvent> {
+    (0..BATCH_SIZE)
+        .map(|i| TradePoolChangeEvent {
+            pool_id: format!("REF-{i}"),
+            receipt_id: "BbL4t1Mr3Xy8Wc6Od2Ne9Gh5Ik7Lq4Su1Zf3Vx6Ar8Cy".to_string(),
+            timestamp: timestamp(i),
+            block_height: 118058295 + i as i64,
+            pool: json!({
+                "SimplePool": {
+                    "token_account_ids": ["wrap.near", "usdt.tether-token.near"],
+                    "amounts": ["5000000000000000000000000000", "35617280000"],
+                    "volumes": [{"input": "0", "output": "0"}, {"input": "0", "output": "0"}],
+                    "total_fee": 30,
+                    "exchange_fee": 0,
+                    "referral_fee": 0,
+                    "shares_prefix": [],
+                    "shares_total_supply": "1000000000000000000000000",
+                }
+            })
+            .into(),
+        })
+        .collect()
+}
+
+/// Page as the endpoint returns it, with a cursor inside the last block
+fn page<T>(events: Vec<T>) -> EventsPage<T> {
+    EventsPage {
+        events,
+        cursor: Some(format!(
+            "{:016x}{}",
+            1_714_988_307_491_111_000_i64,
+            hex::encode("Cz3VmB6qvdQhgzTJpKUqsvMvj4qf14xC3Dbh5WzzvbRL")
+        )),
+    }
+}
+
+fn serialization(c: &mut Criterion) {
+    let mut group = c.benchmark_group("serialize");
+    group.sample_size(20);
+
+    let transfers = page(nft_transfers());
+    group.bench_function("nft_transfer", |b| {
+        b.iter(|| serde_json::to_vec(black_box(&transfers)).unwrap())
+    });
+    group.bench_function("nft_transfer/csv", |b| {
+        b.iter(|| to_csv(black_box(&transfers.events)))
+    });
+
+    let swaps = page(trade_swaps());
+    group.bench_function("trade_swap", |b| {
+        b.iter(|| serde_json::to_vec(black_box(&swaps)).unwrap())
+    });
+    group.bench_function("trade_swap/msgpack", |b| {
+        b.iter(|| rmp_serde::to_vec_named(black_box(&swaps)).unwrap())
+    });
+    group.bench_function("trade_swap/csv", |b| {
+        b.iter(|| to_csv(black_box(&swaps.events)))
+    });
+
+    let pool_changes = page(trade_pool_changes());
+    group.bench_function("trade_pool_change", |b| {
+        b.iter(|| serde_json::to_vec(black_box(&pool_changes)).unwrap())
+    });
+    group.bench_function("trade_pool_change/csv", |b| {
+        b.iter(|| to_csv(black_box(&pool_changes.events)))
+    });
+
+    group.finish();
+}
+
+/// Building the SQL of a page from the query string, the same way as the
+/// endpoint
+fn queries(c: &mut Criterion) {
+    let accounts = (0..100)
+        .map(|i| format!("account{i}.near"))
+        .collect::<Vec<_>>()
+        .join(",");
+    let cursor = page(Vec::<()>::new()).cursor.unwrap();
+    let queries = [
+        (
+            "ft_transfer",
+            EventTable::FtTransfer,
+            format!(
+                "blocks=10&token_account_id=usdt.tether-token.near&exclude_account_id={accounts}"
+            ),
+        ),
+        (
+            "nft_transfer/cursor",
+            EventTable::NftTransfer,
+            format!(
+                "blocks=100&limit=1000&involved_account_ids=alice.near,bob.near&cursor={cursor}"
+            ),
+        ),
+        (
+            "trade_swap/desc",
+            EventTable::TradeSwap,
+            format!("order=desc&blocks=50&account_id={accounts}&token_bought=wrap.near"),
+        ),
+        (
+            "price_change/sampled",
+            EventTable::PriceChange,
+            "blocks=1000&sample_every_n_blocks=60&token_account_id=wrap.near".to_string(),
+        ),
+    ];
+
+    let mut group = c.benchmark_group("page_query");
+    for (name, table, query) in &queries {
+        group.bench_function(*name, |b| {
+            b.iter(|| page_query(*table, black_box(query)).ok().unwrap())
+        });
+    }
+    group.finish();
+}
+
+fn filters(c: &mut Criterion) {
+    let involved_account_ids = (0..100)
+        .map(|i| format!("account{i}.near"))
+        .collect::<Vec<_>>()
+        .join(",");
+    c.bench_function("split_list/100", |b| {
+        b.iter_batched(
+            || involved_account_ids.clone(),
+            |s| split_list(black_box(&s)),
+            BatchSize::SmallInput,
+        )
+    });
+}
+
+criterion_group!(benches, serialization, queries, filters);
+criterion_main!(benches);
@@ -0,0 +1,17 @@
+fn main() {
+    // protoc is vendored, so building doesn't need it installed
+    std::env::set_var(
+        "PROTOC",
+        protoc_bin_vendored::protoc_bin_path().expect("protoc is available for this platform"),
+    );
+    tonic_build::configure()
+        .build_client(false)
+        // Events are converted from their JSON representation, and requests
+        // to query strings with filters
+        .type_attribute(
+            ".",
+            "#[derive(serde::Serialize, serde::Deserialize)] #[serde(default)]",
+        )
+        .compile_protos(&["proto/events.proto"], &["proto"])
+        .expect("Failed to compile protos");
+}
@@ -0,0 +1,554 @@
+//! Typed client of the [Intear Events API](https://events.intear.tech/).
+//!
+//! ```no_run
+//! use futures_util::TryStreamExt;
+//! use inteat_events_api_client::{
+//!     types::{nft::NftTransferFilter, nft::NftTransferEvent, utils::AnyAccountId},
+//!     Client, Pagination,
+//! };
+//!
+//! async fn example() -> Result<(), inteat_events_api_client::Error> {
+//!     let client = Client::default();
+//!     let filter = NftTransferFilter {
+//!         token_account_id: Some(AnyAccountId(vec!["x.paras.near".to_owned()])),
+//!         ..Default::default()
+//!     };
+//!     let page = client.nft_transfers(&filter, &Pagination::default()).await?;
+//!     println!("{} transfers", page.events.len());
+//!
+//!     // Or all pages up to the latest indexed block
+//!     let transfers: Vec<NftTransferEvent> = client
+//!         .paginate::<NftTransferEvent>(filter, Pagination::default())
+//!         .try_collect()
+//!         .await?;
+//!     Ok(())
+//! }
+//! ```
+
+use std::{collections::HashSet, fmt};
+
+use futures_util::{stream, Stream, TryStreamExt};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+pub use inteat_events_api_types as types;
+pub use inteat_events_api_types::EventsPage;
+use inteat_events_api_types::{
+    account::*, bridge::*, burrow::*, dao::*, ft::*, launch::*, nft::*, potlock::*, price::*,
+    raw::*, socialdb::*, staking::*, trade::*,
+};
+
+/// The public API
+pub const DEFAULT_BASE_URL: &str = "https://events.intear.tech";
+
+/// Response header with the maximum `blocks` the endpoint accepts, 50 unless
+/// the server is configured otherwise
+const MAX_BLOCKS_HEADER: &str = "x-max-blocks";
+
+/// Event type with an endpoint that returns pages of it
+pub trait Event: Serialize + DeserializeOwned + 'static {
+    type Filter: Serialize + 'static;
+    /// Path of the endpoint, e.g. `/v0/nft/nft_transfer`
+    const PATH: &'static str;
+
+    fn block_timestamp_nanosec(&self) -> i64;
+}
+
+/// Which events of the endpoint to return, the server's defaults are used for
+/// fields that aren't set
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct Pagination {
+    pub start_block_timestamp_nanosec: Option<i64>,
+    /// Exclusive
+    pub end_block_timestamp_nanosec: Option<i64>,
+    /// Number of blocks with events to return
+    pub blocks: Option<i64>,
+    /// Maximum number of events to return, pages end before the block that
+    /// doesn't fit
+    pub limit: Option<i64>,
+    /// `cursor` of a previous page, instead of `start_block_timestamp_nanosec`
+    pub cursor: Option<String>,
+}
+
+#[derive(Debug)]
+pub enum Error {
+    /// The request couldn't be sent, or the response couldn't be parsed
+    Request(reqwest::Error),
+    /// The server responded with an error
+    Api {
+        status: reqwest::StatusCode,
+        /// e.g. `bad_filter` or `validation_failed`
+        code: String,
+        message: String,
+        /// Quote it when reporting an error
+        request_id: Option<String>,
+    },
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Request(err) => write!(f, "Request failed: {err}"),
+            Error::Api {
+                status,
+                code,
+                message,
+                ..
+            } => write!(f, "{status} {code}: {message}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Request(err) => Some(err),
+            Error::Api { .. } => None,
+        }
+    }
+}
+
+impl From<reqwest::Error> for Error {
+    fn from(err: reqwest::Error) -> Self {
+        Error::Request(err)
+    }
+}
+
+#[derive(Deserialize)]
+struct ErrorResponse {
+    error: ErrorDetails,
+}
+
+#[derive(Deserialize)]
+struct ErrorDetails {
+    code: String,
+    message: String,
+    request_id: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Client {
+    http: reqwest::Client,
+    base_url: String,
+}
+
+impl Default for Client {
+    fn default() -> Self {
+        Self::with_base_url(DEFAULT_BASE_URL)
+    }
+}
+
+impl Client {
+    /// Client of a self-hosted server, e.g. `http://localhost:8080`
+    pub fn with_base_url(base_url: impl Into<String>) -> Self {
+        Self::with_http_client(reqwest::Client::new(), base_url)
+    }
+
+    /// Same as `with_base_url`, with a `reqwest` client configured by the
+    /// caller, e.g. with timeouts or a proxy
+    pub fn with_http_client(http: reqwest::Client, base_url: impl Into<String>) -> Self {
+        Self {
+            http,
+            base_url: base_url.into().trim_end_matches('/').to_owned(),
+        }
+    }
+
+    /// One page of events of type `E`
+    pub async fn page<E: Event>(
+        &self,
+        filter: &E::Filter,
+        pagination: &Pagination,
+    ) -> Result<EventsPage<E>, Error> {
+        self.request(filter, pagination)
+            .await
+            .map(|(page, _max_blocks)| page)
+    }
+
+    /// Page of events, and the maximum `blocks` of the endpoint
+    async fn request<E: Event>(
+        &self,
+        filter: &E::Filter,
+        pagination: &Pagination,
+    ) -> Result<(EventsPage<E>, Option<i64>), Error> {
+        let response = self
+            .http
+            .get(format!("{}{}", self.base_url, E::PATH))
+            .query(pagination)
+            .query(filter)
+            .send()
+            .await?;
+        let status = response.status();
+        if status.is_success() {
+            let max_blocks = response
+                .headers()
+                .get(MAX_BLOCKS_HEADER)
+                .and_then(|max_blocks| max_blocks.to_str().ok()?.parse().ok());
+            return Ok((response.json().await?, max_blocks));
+        }
+        let body = response.text().await?;
+        Err(match serde_json::from_str::<ErrorResponse>(&body) {
+            Ok(ErrorResponse { error }) => Error::Api {
+                status,
+                code: error.code,
+                message: error.message,
+                request_id: error.request_id,
+            },
+            // e.g. from a proxy in front of the server
+            Err(_) => Error::Api {
+                status,
+                code: String::new(),
+                message: body,
+                request_id: None,
+            },
+        })
+    }
+
+    /// All events of type `E` from `pagination` on, fetching the next page
+    /// when the previous one is consumed. Ends at the latest indexed block, or
+    /// at `end_block_timestamp_nanosec`.
+    pub fn paginate<E: Event>(
+        &self,
+        filter: E::Filter,
+        pagination: Pagination,
+    ) -> impl Stream<Item = Result<E, Error>> + '_ {
+        stream::try_unfold(
+            (filter, Some(pagination)),
+            move |(filter, pagination)| async move {
+                let Some(pagination) = pagination else {
+                    return Ok::<_, Error>(None);
+                };
+                let page = self.page::<E>(&filter, &pagination).await?;
+                // Pages without events have no cursor
+                let next = page.cursor.map(|cursor| Pagination {
+                    start_block_timestamp_nanosec: None,
+                    cursor: Some(cursor),
+                    ..pagination
+                });
+                let events = stream::iter(page.events.into_iter().map(Ok::<E, Error>));
+                Ok::<_, Error>(Some((events, (filter, next))))
+            },
+        )
+        .try_flatten()
+    }
+
+    /// All events of type `E` from `start_block_timestamp_nanosec` until the
+    /// latest indexed block, e.g. to catch up after downtime. Pages are as
+    /// large as the server allows. Each page starts at the last block of the
+    /// previous one instead of after it, so that events of a block that was
+    /// still being indexed aren't missed, and events that were already
+    /// yielded are skipped. Pages that end inside a block with more events
+    /// than fit in a page are followed by their cursor instead.
+    pub fn fetch_all_since<E: Event>(
+        &self,
+        filter: E::Filter,
+        start_block_timestamp_nanosec: i64,
+    ) -> impl Stream<Item = Result<E, Error>> + '_ {
+        let state = FetchAllSince {
+            filter,
+            start_block_timestamp_nanosec,
+            blocks: None,
+            boundary: HashSet::new(),
+            cursor: None,
+        };
+        stream::try_unfold(state, move |mut state| async move {
+            let pagination = match state.cursor.take() {
+                Some(cursor) => Pagination {
+                    blocks: state.blocks,
+                    cursor: Some(cursor),
+                    ..Default::default()
+                },
+                None => Pagination {
+                    start_block_timestamp_nanosec: Some(state.start_block_timestamp_nanosec),
+                    blocks: state.blocks,
+                    ..Default::default()
+                },
+            };
+            let (page, max_blocks) = self.request::<E>(&state.filter, &pagination).await?;
+            // The first page uses the server's default, which is smaller
+            state.blocks = max_blocks.or(state.blocks);
+
+            let Some(last) = page.events.iter().map(E::block_timestamp_nanosec).max() else {
+                return Ok::<_, Error>(None);
+            };
+            let mut boundary = HashSet::new();
+            let mut events = Vec::with_capacity(page.events.len());
+            for event in page.events {
+                let timestamp = event.block_timestamp_nanosec();
+                if timestamp != state.start_block_timestamp_nanosec && timestamp != last {
+                    events.push(event);
+                    continue;
+                }
+                // Events don't have an id, but the same event is serialized
+                // the same way
+                let key = serde_json::to_string(&event).expect("events are serializable");
+                let seen = timestamp == state.start_block_timestamp_nanosec
+                    && state.boundary.contains(&key);
+                if timestamp == last {
+                    boundary.insert(key);
+                }
+                if !seen {
+                    events.push(event);
+                }
+            }
+            // The page continued the block that the previous one ended in, so
+            // its earlier events are still at the boundary
+            if last == state.start_block_timestamp_nanosec {
+                boundary.extend(state.boundary);
+            }
+            let inside_block = page.cursor.as_deref().is_some_and(ends_inside_block);
+            // Only the last block of the previous page is left
+            if events.is_empty() && !inside_block {
+                return Ok(None);
+            }
+            state.start_block_timestamp_nanosec = last;
+            state.boundary = boundary;
+            if inside_block {
+                state.cursor = page.cursor;
+            }
+            let events = stream::iter(events.into_iter().map(Ok::<E, Error>));
+            Ok(Some((events, state)))
+        })
+        .try_flatten()
+    }
+}
+
+/// Whether a page ended inside its last block, because the block has more
+/// events than fit in a page. The server appends the tie breaker of the last
+/// event to the 16 hex digits of the block timestamp in such cursors.
+fn ends_inside_block(cursor: &str) -> bool {
+    cursor.len() > 16
+}
+
+struct FetchAllSince<F> {
+    filter: F,
+    start_block_timestamp_nanosec: i64,
+    blocks: Option<i64>,
+    /// Serialized events of the block at `start_block_timestamp_nanosec`,
+    /// which were yielded with the previous pages
+    boundary: HashSet<String>,
+    /// Cursor of the previous page if it ended inside its last block
+    cursor: Option<String>,
+}
+
+macro_rules! endpoints {
+    ($($method:ident($event:ident, $filter:ident) = $path:literal,)*) => {
+        $(
+            impl Event for $event {
+                type Filter = $filter;
+                const PATH: &'static str = $path;
+
+                fn block_timestamp_nanosec(&self) -> i64 {
+                    self.timestamp.timestamp_nanos_opt().unwrap_or_default()
+                }
+            }
+        )*
+
+        impl Client {
+            $(
+                #[doc = concat!("One page of `", $path, "`")]
+                pub async fn $method(
+                    &self,
+                    filter: &$filter,
+                    pagination: &Pagination,
+                ) -> Result<EventsPage<$event>, Error> {
+                    self.page::<$event>(filter, pagination).await
+                }
+            )*
+        }
+    };
+}
+
+endpoints! {
+    ft_mints(FtMintEvent, FtMintFilter) = "/v0/ft/ft_mint",
+    ft_transfers(FtTransferEvent, FtTransferFilter) = "/v0/ft/ft_transfer",
+    ft_burns(FtBurnEvent, FtBurnFilter) = "/v0/ft/ft_burn",
+    nft_mints(NftMintEvent, NftMintFilter) = "/v0/nft/nft_mint",
+    nft_transfers(NftTransferEvent, NftTransferFilter) = "/v0/nft/nft_transfer",
+    nft_burns(NftBurnEvent, NftBurnFilter) = "/v0/nft/nft_burn",
+    nft_sales(NftSaleEvent, NftSaleFilter) = "/v0/nft/nft_sale",
+    potlock_donations(PotlockDonationEvent, PotlockDonationFilter) = "/v0/potlock/potlock_donation",
+    potlock_pot_project_donations(PotlockPotProjectDonationEvent, PotlockPotProjectDonationFilter) = "/v0/potlock/potlock_pot_project_donation",
+    potlock_pot_donations(PotlockPotDonationEvent, PotlockPotDonationFilter) = "/v0/potlock/potlock_pot_donation",
+    potlock_pot_deployments(PotlockPotDeploymentEvent, PotlockPotDeploymentFilter) = "/v0/potlock/potlock_pot_deployment",
+    potlock_pot_applications(PotlockPotApplicationEvent, PotlockPotApplicationFilter) = "/v0/potlock/potlock_pot_application",
+    potlock_pot_application_reviews(PotlockPotApplicationReviewEvent, PotlockPotApplicationReviewFilter) = "/v0/potlock/potlock_pot_application_review",
+    potlock_pot_payouts(PotlockPotPayoutEvent, PotlockPotPayoutFilter) = "/v0/potlock/potlock_pot_payout",
+    potlock_pot_payout_challenges(PotlockPotPayoutChallengeEvent, PotlockPotPayoutChallengeFilter) = "/v0/potlock/potlock_pot_payout_challenge",
+    trade_pools(TradePoolEvent, TradePoolFilter) = "/v0/trade/trade_pool",
+    trade_swaps(TradeSwapEvent, TradeSwapFilter) = "/v0/trade/trade_swap",
+    trade_pool_changes(TradePoolChangeEvent, TradePoolChangeFilter) = "/v0/trade/trade_pool_change",
+    trade_liquidity_additions(TradeLiquidityAddEvent, TradeLiquidityAddFilter) = "/v0/trade/trade_liquidity_add",
+    trade_liquidity_removals(TradeLiquidityRemoveEvent, TradeLiquidityRemoveFilter) = "/v0/trade/trade_liquidity_remove",
+    trade_wraps(TradeWrapEvent, TradeWrapFilter) = "/v0/trade/trade_wrap",
+    trade_unwraps(TradeUnwrapEvent, TradeUnwrapFilter) = "/v0/trade/trade_unwrap",
+    socialdb_posts(SocialdbPostEvent, SocialdbPostFilter) = "/v0/socialdb/socialdb_post",
+    socialdb_comments(SocialdbCommentEvent, SocialdbCommentFilter) = "/v0/socialdb/socialdb_comment",
+    socialdb_likes(SocialdbLikeEvent, SocialdbLikeFilter) = "/v0/socialdb/socialdb_like",
+    socialdb_follows(SocialdbFollowEvent, SocialdbFollowFilter) = "/v0/socialdb/socialdb_follow",
+    socialdb_profile_updates(SocialdbProfileUpdateEvent, SocialdbProfileUpdateFilter) = "/v0/socialdb/socialdb_profile_update",
+    price_changes(PriceChangeEvent, PriceChangeFilter) = "/v0/price/price_change",
+    burrow_deposits(BurrowDepositEvent, BurrowDepositFilter) = "/v0/burrow/burrow_deposit",
+    burrow_withdrawals(BurrowWithdrawEvent, BurrowWithdrawFilter) = "/v0/burrow/burrow_withdraw",
+    burrow_borrows(BurrowBorrowEvent, BurrowBorrowFilter) = "/v0/burrow/burrow_borrow",
+    burrow_repayments(BurrowRepayEvent, BurrowRepayFilter) = "/v0/burrow/burrow_repay",
+    burrow_liquidations(BurrowLiquidationEvent, BurrowLiquidationFilter) = "/v0/burrow/burrow_liquidation",
+    launch_creations(LaunchCreateEvent, LaunchCreateFilter) = "/v0/launch/launch_create",
+    launch_deposits(LaunchDepositEvent, LaunchDepositFilter) = "/v0/launch/launch_deposit",
+    launch_withdrawals(LaunchWithdrawEvent, LaunchWithdrawFilter) = "/v0/launch/launch_withdraw",
+    launch_finalizations(LaunchFinalizeEvent, LaunchFinalizeFilter) = "/v0/launch/launch_finalize",
+    staking_stakes(StakingStakeEvent, StakingStakeFilter) = "/v0/staking/staking_stake",
+    staking_unstakes(StakingUnstakeEvent, StakingUnstakeFilter) = "/v0/staking/staking_unstake",
+    staking_withdrawals(StakingWithdrawEvent, StakingWithdrawFilter) = "/v0/staking/staking_withdraw",
+    account_creations(AccountCreateEvent, AccountCreateFilter) = "/v0/account/account_create",
+    account_deletions(AccountDeleteEvent, AccountDeleteFilter) = "/v0/account/account_delete",
+    key_additions(KeyAddedEvent, KeyAddedFilter) = "/v0/account/key_added",
+    key_deletions(KeyDeletedEvent, KeyDeletedFilter) = "/v0/account/key_deleted",
+    dao_proposals(DaoProposalEvent, DaoProposalFilter) = "/v0/dao/dao_proposal",
+    dao_votes(DaoVoteEvent, DaoVoteFilter) = "/v0/dao/dao_vote",
+    dao_proposal_executions(DaoProposalExecuteEvent, DaoProposalExecuteFilter) = "/v0/dao/dao_proposal_execute",
+    bridge_deposits(BridgeDepositEvent, BridgeDepositFilter) = "/v0/bridge/bridge_deposit",
+    bridge_withdrawals(BridgeWithdrawEvent, BridgeWithdrawFilter) = "/v0/bridge/bridge_withdraw",
+    log_events(LogEvent, LogEventFilter) = "/v0/raw/log_events",
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use actix_web::{web, App, HttpResponse, HttpServer};
+    use futures_util::StreamExt;
+    use serde_json::json;
+
+    use super::*;
+
+    /// Events of the mock server, `(block timestamp, receipt id)`
+    type Events = Arc<Mutex<Vec<(i64, &'static str)>>>;
+
+    #[derive(Deserialize)]
+    struct MockQuery {
+        start_block_timestamp_nanosec: Option<i64>,
+        cursor: Option<String>,
+    }
+
+    fn hex(tie_breaker: &str) -> String {
+        tie_breaker
+            .bytes()
+            .map(|byte| format!("{byte:02x}"))
+            .collect()
+    }
+
+    /// Pages of at most `limit` events, which end before the block that
+    /// doesn't fit, or inside it if it's the only block, like the server's
+    async fn mock_page(
+        events: web::Data<Events>,
+        limit: web::Data<usize>,
+        query: web::Query<MockQuery>,
+    ) -> HttpResponse {
+        let mut events = events.lock().unwrap().clone();
+        events.sort();
+        let after = match &query.cursor {
+            Some(cursor) => {
+                let (block, tie_breaker) = cursor.split_at(16);
+                let block = i64::from_str_radix(block, 16).unwrap();
+                events
+                    .iter()
+                    .position(|&(timestamp, receipt)| {
+                        timestamp > block
+                            || (timestamp == block && hex(receipt).as_str() > tie_breaker)
+                    })
+                    .unwrap_or(events.len())
+            }
+            None => {
+                let start = query.start_block_timestamp_nanosec.unwrap_or_default();
+                events.partition_point(|&(timestamp, _)| timestamp < start)
+            }
+        };
+        let mut page = events[after..].to_vec();
+        let mut cursor = page
+            .last()
+            .map(|&(timestamp, _)| format!("{:016x}", timestamp + 1));
+        if page.len() > **limit {
+            let next = page[**limit];
+            page.truncate(**limit);
+            if page[0].0 == next.0 {
+                let (timestamp, receipt) = page[page.len() - 1];
+                cursor = Some(format!("{timestamp:016x}{}", hex(receipt)));
+            } else {
+                page.retain(|&(timestamp, _)| timestamp != next.0);
+                cursor = Some(format!("{:016x}", page[page.len() - 1].0 + 1));
+            }
+        }
+        let page = page
+            .into_iter()
+            .map(|(timestamp, receipt)| {
+                json!({
+                    "account_id": format!("{receipt}.near"),
+                    "beneficiary_id": "near",
+                    "transaction_id": receipt,
+                    "receipt_id": receipt,
+                    "block_height": timestamp,
+                    "block_timestamp_nanosec": timestamp,
+                })
+            })
+            .collect::<Vec<_>>();
+        HttpResponse::Ok().json(json!({ "events": page, "cursor": cursor }))
+    }
+
+    fn mock_server(events: &Events, limit: usize) -> Client {
+        let events = events.clone();
+        let server = HttpServer::new(move || {
+            App::new()
+                .app_data(web::Data::new(events.clone()))
+                .app_data(web::Data::new(limit))
+                .route(AccountDeleteEvent::PATH, web::get().to(mock_page))
+        })
+        .workers(1)
+        .bind(("127.0.0.1", 0))
+        .unwrap();
+        let addr = server.addrs()[0];
+        actix_web::rt::spawn(server.run());
+        Client::with_base_url(format!("http://{addr}"))
+    }
+
+    fn yielded(events: Vec<AccountDeleteEvent>) -> Vec<(i64, String)> {
+        events
+            .into_iter()
+            .map(|event| (event.block_timestamp_nanosec(), event.receipt_id))
+            .collect()
+    }
+
+    fn expected(events: &[(i64, &str)]) -> Vec<(i64, String)> {
+        events
+            .iter()
+            .map(|&(timestamp, receipt)| (timestamp, receipt.to_owned()))
+            .collect()
+    }
+
+    #[actix_web::test]
+    async fn fetch_all_since_follows_cursors_inside_blocks_larger_than_a_page() {
+        let all = [(1, "a"), (1, "b"), (1, "c"), (1, "d"), (1, "e"), (2, "a")];
+        let events = Arc::new(Mutex::new(all.to_vec()));
+        let client = mock_server(&events, 2);
+        let fetched = client
+            .fetch_all_since::<AccountDeleteEvent>(Default::default(), 0)
+            .try_collect()
+            .await
+            .unwrap();
+        assert_eq!(yielded(fetched), expected(&all));
+    }
+
+    #[actix_web::test]
+    async fn fetch_all_since_skips_events_of_the_last_block_that_were_yielded() {
+        let events = Arc::new(Mutex::new(vec![(1, "a"), (2, "a")]));
+        let client = mock_server(&events, 3);
+        let mut stream =
+            Box::pin(client.fetch_all_since::<AccountDeleteEvent>(Default::default(), 0));
+        let mut fetched = Vec::new();
+        for _ in 0..2 {
+            fetched.push(stream.next().await.unwrap().unwrap());
+        }
+        // Indexed after the first page, the next one starts at its block again
+        events.lock().unwrap().push((2, "b"));
+        fetched.extend(stream.try_collect::<Vec<_>>().await.unwrap());
+        assert_eq!(yielded(fetched), expected(&[(1, "a"), (2, "a"), (2, "b")]));
+    }
+}
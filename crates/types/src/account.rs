@@ -0,0 +1,187 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::utils::{AnyAccountId, AnyOf, Balance, OptionalBalance};
+
+pub type TransactionId = String;
+pub type ReceiptId = String;
+pub type AccountId = String;
+pub type BlockHeight = i64;
+
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "sqlx", derive(sqlx::FromRow))]
+pub struct AccountCreateEvent {
+    /// Account that was created
+    pub account_id: AccountId,
+    /// Account that created it, e.g. `near` for top-level accounts or
+    /// `tg` for `alice.tg`
+    pub creator_id: AccountId,
+    /// Amount transferred to the new account, in yoctoNEAR
+    #[cfg_attr(feature = "sqlx", sqlx(try_from = "bigdecimal::BigDecimal"))]
+    pub initial_balance: Balance,
+
+    pub transaction_id: TransactionId,
+    pub receipt_id: ReceiptId,
+    pub block_height: BlockHeight,
+    #[cfg_attr(feature = "utoipa", schema(value_type = i64))]
+    #[serde(
+        with = "chrono::serde::ts_nanoseconds",
+        rename = "block_timestamp_nanosec"
+    )]
+    pub timestamp: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "sqlx", derive(sqlx::FromRow))]
+pub struct AccountDeleteEvent {
+    /// Account that was deleted
+    pub account_id: AccountId,
+    /// Account that received the remaining balance
+    pub beneficiary_id: AccountId,
+
+    pub transaction_id: TransactionId,
+    pub receipt_id: ReceiptId,
+    pub block_height: BlockHeight,
+    #[cfg_attr(feature = "utoipa", schema(value_type = i64))]
+    #[serde(
+        with = "chrono::serde::ts_nanoseconds",
+        rename = "block_timestamp_nanosec"
+    )]
+    pub timestamp: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "sqlx", derive(sqlx::FromRow))]
+pub struct KeyAddedEvent {
+    /// Account the key was added to
+    pub account_id: AccountId,
+    /// Public key, e.g. `ed25519:6E8sCci9badyRkXb3JoRpBj5p8C6Tw41ELDZoiihKEtp`
+    pub public_key: String,
+    /// Whether the key can sign any transaction of the account. Function
+    /// call keys can only call `receiver_id`.
+    pub full_access: bool,
+    /// Contract that a function call key can call, `None` for full
+    /// access keys
+    pub receiver_id: Option<AccountId>,
+    /// Methods that a function call key can call, any method if empty
+    pub method_names: Vec<String>,
+    /// Amount of gas fees in yoctoNEAR that a function call key can
+    /// spend, `None` if it's unlimited or the key has full access
+    #[cfg_attr(feature = "sqlx", sqlx(try_from = "Option<bigdecimal::BigDecimal>"))]
+    pub allowance: OptionalBalance,
+
+    pub transaction_id: TransactionId,
+    pub receipt_id: ReceiptId,
+    pub block_height: BlockHeight,
+    #[cfg_attr(feature = "utoipa", schema(value_type = i64))]
+    #[serde(
+        with = "chrono::serde::ts_nanoseconds",
+        rename = "block_timestamp_nanosec"
+    )]
+    pub timestamp: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "sqlx", derive(sqlx::FromRow))]
+pub struct KeyDeletedEvent {
+    /// Account the key was deleted from
+    pub account_id: AccountId,
+    pub public_key: String,
+
+    pub transaction_id: TransactionId,
+    pub receipt_id: ReceiptId,
+    pub block_height: BlockHeight,
+    #[cfg_attr(feature = "utoipa", schema(value_type = i64))]
+    #[serde(
+        with = "chrono::serde::ts_nanoseconds",
+        rename = "block_timestamp_nanosec"
+    )]
+    pub timestamp: DateTime<Utc>,
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[cfg_attr(
+    feature = "utoipa",
+    derive(utoipa::IntoParams),
+    into_params(parameter_in = Query)
+)]
+pub struct AccountCreateFilter {
+    /// Account that was created
+    pub account_id: Option<AnyAccountId>,
+    /// Account that created it
+    pub creator_id: Option<AnyAccountId>,
+    /// Only accounts that end with any of these, e.g. `.tg` for all Telegram
+    /// accounts
+    pub suffix: Option<AnyOf>,
+    /// Hash of the transaction that emitted the event
+    pub transaction_id: Option<AnyOf>,
+    /// Id of the receipt that emitted the event
+    pub receipt_id: Option<AnyOf>,
+    /// Excludes events where `creator_id` is any of these accounts, e.g.
+    /// known bots
+    pub exclude_account_id: Option<AnyAccountId>,
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[cfg_attr(
+    feature = "utoipa",
+    derive(utoipa::IntoParams),
+    into_params(parameter_in = Query)
+)]
+pub struct AccountDeleteFilter {
+    /// Account that was deleted
+    pub account_id: Option<AnyAccountId>,
+    /// Account that received the remaining balance
+    pub beneficiary_id: Option<AnyAccountId>,
+    /// Only accounts that end with any of these, e.g. `.tg` for all Telegram
+    /// accounts
+    pub suffix: Option<AnyOf>,
+    /// Hash of the transaction that emitted the event
+    pub transaction_id: Option<AnyOf>,
+    /// Id of the receipt that emitted the event
+    pub receipt_id: Option<AnyOf>,
+    /// Excludes events where `account_id` is any of these accounts, e.g.
+    /// known bots
+    pub exclude_account_id: Option<AnyAccountId>,
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[cfg_attr(
+    feature = "utoipa",
+    derive(utoipa::IntoParams),
+    into_params(parameter_in = Query)
+)]
+pub struct KeyAddedFilter {
+    /// Account the key was added to
+    pub account_id: Option<AnyAccountId>,
+    /// Public key, e.g. `ed25519:6E8sCci9badyRkXb3JoRpBj5p8C6Tw41ELDZoiihKEtp`
+    pub public_key: Option<AnyOf>,
+    /// Only full access keys if `true`, or only function call keys if
+    /// `false`
+    pub full_access: Option<bool>,
+    /// Hash of the transaction that emitted the event
+    pub transaction_id: Option<AnyOf>,
+    /// Id of the receipt that emitted the event
+    pub receipt_id: Option<AnyOf>,
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[cfg_attr(
+    feature = "utoipa",
+    derive(utoipa::IntoParams),
+    into_params(parameter_in = Query)
+)]
+pub struct KeyDeletedFilter {
+    /// Account the key was deleted from
+    pub account_id: Option<AnyAccountId>,
+    /// Public key, e.g. `ed25519:6E8sCci9badyRkXb3JoRpBj5p8C6Tw41ELDZoiihKEtp`
+    pub public_key: Option<AnyOf>,
+    /// Hash of the transaction that emitted the event
+    pub transaction_id: Option<AnyOf>,
+    /// Id of the receipt that emitted the event
+    pub receipt_id: Option<AnyOf>,
+}
@@ -0,0 +1,101 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::utils::{AnyAccountId, AnyOf, Balance};
+
+pub type TransactionId = String;
+pub type ReceiptId = String;
+pub type AccountId = String;
+pub type BlockHeight = i64;
+
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "sqlx", derive(sqlx::FromRow))]
+pub struct BridgeDepositEvent {
+    /// Account that received the tokens on NEAR
+    pub account_id: AccountId,
+    /// Chain the tokens were bridged from, e.g. `eth`, `sol`, or `btc`
+    pub origin_chain: String,
+    /// Address of the sender on `origin_chain`
+    pub sender: String,
+    /// NEAR account id of the bridged token
+    pub token_id: AccountId,
+    /// Amount in the smallest units of `token_id`
+    #[cfg_attr(feature = "sqlx", sqlx(try_from = "bigdecimal::BigDecimal"))]
+    pub amount: Balance,
+
+    pub transaction_id: TransactionId,
+    pub receipt_id: ReceiptId,
+    pub block_height: BlockHeight,
+    #[cfg_attr(feature = "utoipa", schema(value_type = i64))]
+    #[serde(
+        with = "chrono::serde::ts_nanoseconds",
+        rename = "block_timestamp_nanosec"
+    )]
+    pub timestamp: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "sqlx", derive(sqlx::FromRow))]
+pub struct BridgeWithdrawEvent {
+    /// Account that sent the tokens from NEAR
+    pub account_id: AccountId,
+    /// Chain the tokens were bridged to, e.g. `eth`, `sol`, or `btc`
+    pub destination_chain: String,
+    /// Address of the recipient on `destination_chain`
+    pub recipient: String,
+    /// NEAR account id of the bridged token
+    pub token_id: AccountId,
+    /// Amount in the smallest units of `token_id`
+    #[cfg_attr(feature = "sqlx", sqlx(try_from = "bigdecimal::BigDecimal"))]
+    pub amount: Balance,
+
+    pub transaction_id: TransactionId,
+    pub receipt_id: ReceiptId,
+    pub block_height: BlockHeight,
+    #[cfg_attr(feature = "utoipa", schema(value_type = i64))]
+    #[serde(
+        with = "chrono::serde::ts_nanoseconds",
+        rename = "block_timestamp_nanosec"
+    )]
+    pub timestamp: DateTime<Utc>,
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[cfg_attr(
+    feature = "utoipa",
+    derive(utoipa::IntoParams),
+    into_params(parameter_in = Query)
+)]
+pub struct BridgeDepositFilter {
+    /// Account that received the tokens on NEAR
+    pub account_id: Option<AnyAccountId>,
+    /// NEAR account id of the bridged token, e.g. `eth.bridge.near`
+    pub token_id: Option<AnyAccountId>,
+    /// Chain the tokens were bridged from, e.g. `eth`
+    pub origin_chain: Option<AnyOf>,
+    /// Hash of the transaction that emitted the event
+    pub transaction_id: Option<AnyOf>,
+    /// Id of the receipt that emitted the event
+    pub receipt_id: Option<AnyOf>,
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[cfg_attr(
+    feature = "utoipa",
+    derive(utoipa::IntoParams),
+    into_params(parameter_in = Query)
+)]
+pub struct BridgeWithdrawFilter {
+    /// Account that sent the tokens from NEAR
+    pub account_id: Option<AnyAccountId>,
+    /// NEAR account id of the bridged token, e.g. `eth.bridge.near`
+    pub token_id: Option<AnyAccountId>,
+    /// Chain the tokens were bridged to, e.g. `eth`
+    pub destination_chain: Option<AnyOf>,
+    /// Hash of the transaction that emitted the event
+    pub transaction_id: Option<AnyOf>,
+    /// Id of the receipt that emitted the event
+    pub receipt_id: Option<AnyOf>,
+}
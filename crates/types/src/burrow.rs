@@ -0,0 +1,228 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::utils::{AnyAccountId, AnyOf, Balance};
+
+pub type TransactionId = String;
+pub type ReceiptId = String;
+pub type AccountId = String;
+pub type BlockHeight = i64;
+
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "sqlx", derive(sqlx::FromRow))]
+pub struct BurrowDepositEvent {
+    pub account_id: AccountId,
+    /// Account id of the token contract
+    pub token_id: AccountId,
+    #[cfg_attr(feature = "sqlx", sqlx(try_from = "bigdecimal::BigDecimal"))]
+    pub amount: Balance,
+
+    pub transaction_id: TransactionId,
+    pub receipt_id: ReceiptId,
+    pub block_height: BlockHeight,
+    #[cfg_attr(feature = "utoipa", schema(value_type = i64))]
+    #[serde(
+        with = "chrono::serde::ts_nanoseconds",
+        rename = "block_timestamp_nanosec"
+    )]
+    pub timestamp: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "sqlx", derive(sqlx::FromRow))]
+pub struct BurrowWithdrawEvent {
+    pub account_id: AccountId,
+    /// Account id of the token contract
+    pub token_id: AccountId,
+    #[cfg_attr(feature = "sqlx", sqlx(try_from = "bigdecimal::BigDecimal"))]
+    pub amount: Balance,
+
+    pub transaction_id: TransactionId,
+    pub receipt_id: ReceiptId,
+    pub block_height: BlockHeight,
+    #[cfg_attr(feature = "utoipa", schema(value_type = i64))]
+    #[serde(
+        with = "chrono::serde::ts_nanoseconds",
+        rename = "block_timestamp_nanosec"
+    )]
+    pub timestamp: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "sqlx", derive(sqlx::FromRow))]
+pub struct BurrowBorrowEvent {
+    pub account_id: AccountId,
+    /// Account id of the token contract
+    pub token_id: AccountId,
+    #[cfg_attr(feature = "sqlx", sqlx(try_from = "bigdecimal::BigDecimal"))]
+    pub amount: Balance,
+
+    pub transaction_id: TransactionId,
+    pub receipt_id: ReceiptId,
+    pub block_height: BlockHeight,
+    #[cfg_attr(feature = "utoipa", schema(value_type = i64))]
+    #[serde(
+        with = "chrono::serde::ts_nanoseconds",
+        rename = "block_timestamp_nanosec"
+    )]
+    pub timestamp: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "sqlx", derive(sqlx::FromRow))]
+pub struct BurrowRepayEvent {
+    pub account_id: AccountId,
+    /// Account id of the token contract
+    pub token_id: AccountId,
+    #[cfg_attr(feature = "sqlx", sqlx(try_from = "bigdecimal::BigDecimal"))]
+    pub amount: Balance,
+
+    pub transaction_id: TransactionId,
+    pub receipt_id: ReceiptId,
+    pub block_height: BlockHeight,
+    #[cfg_attr(feature = "utoipa", schema(value_type = i64))]
+    #[serde(
+        with = "chrono::serde::ts_nanoseconds",
+        rename = "block_timestamp_nanosec"
+    )]
+    pub timestamp: DateTime<Utc>,
+}
+
+/// Liquidation of an account whose debt exceeded its collateral. The
+/// liquidator repays some of the debt and takes collateral of a higher value.
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "sqlx", derive(sqlx::FromRow))]
+pub struct BurrowLiquidationEvent {
+    /// Account that was liquidated
+    pub account_id: AccountId,
+    pub liquidator_id: AccountId,
+    /// Tokens of the collateral that the liquidator took
+    pub collateral_token_ids: Vec<AccountId>,
+    /// Tokens of the debt that the liquidator repaid
+    pub repaid_token_ids: Vec<AccountId>,
+    /// Value of the taken collateral in USD, as a decimal string
+    #[cfg_attr(feature = "utoipa", schema(value_type = String))]
+    #[cfg_attr(feature = "sqlx", sqlx(try_from = "bigdecimal::BigDecimal"))]
+    pub collateral_sum: Balance,
+    /// Value of the repaid debt in USD, as a decimal string
+    #[cfg_attr(feature = "utoipa", schema(value_type = String))]
+    #[cfg_attr(feature = "sqlx", sqlx(try_from = "bigdecimal::BigDecimal"))]
+    pub repaid_sum: Balance,
+
+    pub transaction_id: TransactionId,
+    pub receipt_id: ReceiptId,
+    pub block_height: BlockHeight,
+    #[cfg_attr(feature = "utoipa", schema(value_type = i64))]
+    #[serde(
+        with = "chrono::serde::ts_nanoseconds",
+        rename = "block_timestamp_nanosec"
+    )]
+    pub timestamp: DateTime<Utc>,
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[cfg_attr(
+    feature = "utoipa",
+    derive(utoipa::IntoParams),
+    into_params(parameter_in = Query)
+)]
+pub struct BurrowDepositFilter {
+    /// Account that deposited the tokens
+    pub account_id: Option<AnyAccountId>,
+    /// Account id of the token contract
+    pub token_id: Option<AnyAccountId>,
+    /// Hash of the transaction that emitted the event
+    pub transaction_id: Option<AnyOf>,
+    /// Id of the receipt that emitted the event
+    pub receipt_id: Option<AnyOf>,
+    /// Excludes events where `account_id` is any of these accounts, e.g.
+    /// known bots
+    pub exclude_account_id: Option<AnyAccountId>,
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[cfg_attr(
+    feature = "utoipa",
+    derive(utoipa::IntoParams),
+    into_params(parameter_in = Query)
+)]
+pub struct BurrowWithdrawFilter {
+    /// Account that withdrew the tokens
+    pub account_id: Option<AnyAccountId>,
+    /// Account id of the token contract
+    pub token_id: Option<AnyAccountId>,
+    /// Hash of the transaction that emitted the event
+    pub transaction_id: Option<AnyOf>,
+    /// Id of the receipt that emitted the event
+    pub receipt_id: Option<AnyOf>,
+    /// Excludes events where `account_id` is any of these accounts, e.g.
+    /// known bots
+    pub exclude_account_id: Option<AnyAccountId>,
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[cfg_attr(
+    feature = "utoipa",
+    derive(utoipa::IntoParams),
+    into_params(parameter_in = Query)
+)]
+pub struct BurrowBorrowFilter {
+    /// Account that borrowed the tokens
+    pub account_id: Option<AnyAccountId>,
+    /// Account id of the token contract
+    pub token_id: Option<AnyAccountId>,
+    /// Hash of the transaction that emitted the event
+    pub transaction_id: Option<AnyOf>,
+    /// Id of the receipt that emitted the event
+    pub receipt_id: Option<AnyOf>,
+    /// Excludes events where `account_id` is any of these accounts, e.g.
+    /// known bots
+    pub exclude_account_id: Option<AnyAccountId>,
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[cfg_attr(
+    feature = "utoipa",
+    derive(utoipa::IntoParams),
+    into_params(parameter_in = Query)
+)]
+pub struct BurrowRepayFilter {
+    /// Account whose debt was repaid
+    pub account_id: Option<AnyAccountId>,
+    /// Account id of the token contract
+    pub token_id: Option<AnyAccountId>,
+    /// Hash of the transaction that emitted the event
+    pub transaction_id: Option<AnyOf>,
+    /// Id of the receipt that emitted the event
+    pub receipt_id: Option<AnyOf>,
+    /// Excludes events where `account_id` is any of these accounts, e.g.
+    /// known bots
+    pub exclude_account_id: Option<AnyAccountId>,
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[cfg_attr(
+    feature = "utoipa",
+    derive(utoipa::IntoParams),
+    into_params(parameter_in = Query)
+)]
+pub struct BurrowLiquidationFilter {
+    /// Account that was liquidated
+    pub account_id: Option<AnyAccountId>,
+    /// Account that liquidated it
+    pub liquidator_id: Option<AnyAccountId>,
+    /// Only liquidations that took or repaid any of these tokens
+    pub token_id: Option<AnyAccountId>,
+    /// Hash of the transaction that emitted the event
+    pub transaction_id: Option<AnyOf>,
+    /// Id of the receipt that emitted the event
+    pub receipt_id: Option<AnyOf>,
+    /// Excludes events where `account_id` or `liquidator_id` is any of these
+    /// accounts, e.g. known bots
+    pub exclude_account_id: Option<AnyAccountId>,
+}
@@ -0,0 +1,152 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::utils::{AnyAccountId, AnyId, AnyOf};
+
+pub type TransactionId = String;
+pub type ReceiptId = String;
+pub type AccountId = String;
+pub type BlockHeight = i64;
+/// Id of a proposal in its DAO, e.g. `42`
+pub type ProposalId = i64;
+
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "sqlx", derive(sqlx::FromRow))]
+pub struct DaoProposalEvent {
+    /// DAO contract
+    pub dao_id: AccountId,
+    pub proposal_id: ProposalId,
+    /// Account that created the proposal
+    pub proposer: AccountId,
+    /// e.g. `Transfer`, `FunctionCall`, or `AddMemberToRole`
+    pub proposal_kind: String,
+    pub description: String,
+
+    pub transaction_id: TransactionId,
+    pub receipt_id: ReceiptId,
+    pub block_height: BlockHeight,
+    #[cfg_attr(feature = "utoipa", schema(value_type = i64))]
+    #[serde(
+        with = "chrono::serde::ts_nanoseconds",
+        rename = "block_timestamp_nanosec"
+    )]
+    pub timestamp: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "sqlx", derive(sqlx::FromRow))]
+pub struct DaoVoteEvent {
+    /// DAO contract
+    pub dao_id: AccountId,
+    pub proposal_id: ProposalId,
+    /// Account that voted
+    pub account_id: AccountId,
+    /// `Approve`, `Reject`, or `Remove`
+    pub vote: String,
+    /// e.g. `Transfer`, `FunctionCall`, or `AddMemberToRole`
+    pub proposal_kind: String,
+
+    pub transaction_id: TransactionId,
+    pub receipt_id: ReceiptId,
+    pub block_height: BlockHeight,
+    #[cfg_attr(feature = "utoipa", schema(value_type = i64))]
+    #[serde(
+        with = "chrono::serde::ts_nanoseconds",
+        rename = "block_timestamp_nanosec"
+    )]
+    pub timestamp: DateTime<Utc>,
+}
+
+/// A proposal that got enough votes to be approved, and whose action was
+/// executed
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "sqlx", derive(sqlx::FromRow))]
+pub struct DaoProposalExecuteEvent {
+    /// DAO contract
+    pub dao_id: AccountId,
+    pub proposal_id: ProposalId,
+    /// Account that created the proposal
+    pub proposer: AccountId,
+    /// e.g. `Transfer`, `FunctionCall`, or `AddMemberToRole`
+    pub proposal_kind: String,
+    /// Account whose vote approved the proposal
+    pub account_id: AccountId,
+    /// Whether the action succeeded, a failed proposal can be executed again
+    pub success: bool,
+
+    pub transaction_id: TransactionId,
+    pub receipt_id: ReceiptId,
+    pub block_height: BlockHeight,
+    #[cfg_attr(feature = "utoipa", schema(value_type = i64))]
+    #[serde(
+        with = "chrono::serde::ts_nanoseconds",
+        rename = "block_timestamp_nanosec"
+    )]
+    pub timestamp: DateTime<Utc>,
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[cfg_attr(
+    feature = "utoipa",
+    derive(utoipa::IntoParams),
+    into_params(parameter_in = Query)
+)]
+pub struct DaoProposalFilter {
+    /// DAO contract, e.g. `marketing.sputnik-dao.near`
+    pub dao_id: Option<AnyAccountId>,
+    /// Id of the proposal in its DAO
+    pub proposal_id: Option<AnyId>,
+    /// Account that created the proposal
+    pub proposer: Option<AnyAccountId>,
+    /// Kind of the proposal, e.g. `Transfer` or `FunctionCall`
+    pub proposal_kind: Option<AnyOf>,
+    /// Hash of the transaction that emitted the event
+    pub transaction_id: Option<AnyOf>,
+    /// Id of the receipt that emitted the event
+    pub receipt_id: Option<AnyOf>,
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[cfg_attr(
+    feature = "utoipa",
+    derive(utoipa::IntoParams),
+    into_params(parameter_in = Query)
+)]
+pub struct DaoVoteFilter {
+    /// DAO contract, e.g. `marketing.sputnik-dao.near`
+    pub dao_id: Option<AnyAccountId>,
+    /// Id of the proposal in its DAO
+    pub proposal_id: Option<AnyId>,
+    /// Account that voted
+    pub account_id: Option<AnyAccountId>,
+    /// Kind of the proposal, e.g. `Transfer` or `FunctionCall`
+    pub proposal_kind: Option<AnyOf>,
+    /// Hash of the transaction that emitted the event
+    pub transaction_id: Option<AnyOf>,
+    /// Id of the receipt that emitted the event
+    pub receipt_id: Option<AnyOf>,
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[cfg_attr(
+    feature = "utoipa",
+    derive(utoipa::IntoParams),
+    into_params(parameter_in = Query)
+)]
+pub struct DaoProposalExecuteFilter {
+    /// DAO contract, e.g. `marketing.sputnik-dao.near`
+    pub dao_id: Option<AnyAccountId>,
+    /// Id of the proposal in its DAO
+    pub proposal_id: Option<AnyId>,
+    /// Account that created the proposal
+    pub proposer: Option<AnyAccountId>,
+    /// Kind of the proposal, e.g. `Transfer` or `FunctionCall`
+    pub proposal_kind: Option<AnyOf>,
+    /// Hash of the transaction that emitted the event
+    pub transaction_id: Option<AnyOf>,
+    /// Id of the receipt that emitted the event
+    pub receipt_id: Option<AnyOf>,
+}
@@ -0,0 +1,143 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::utils::{AnyAccountId, AnyOf, Balance};
+
+pub type TransactionId = String;
+pub type ReceiptId = String;
+pub type AccountId = String;
+pub type BlockHeight = i64;
+
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "sqlx", derive(sqlx::FromRow))]
+pub struct FtMintEvent {
+    pub owner_id: AccountId,
+    #[cfg_attr(feature = "sqlx", sqlx(try_from = "bigdecimal::BigDecimal"))]
+    pub amount: Balance,
+    pub memo: Option<String>,
+
+    pub transaction_id: TransactionId,
+    pub receipt_id: ReceiptId,
+    pub block_height: BlockHeight,
+    #[cfg_attr(feature = "utoipa", schema(value_type = i64))]
+    #[serde(
+        with = "chrono::serde::ts_nanoseconds",
+        rename = "block_timestamp_nanosec"
+    )]
+    pub timestamp: DateTime<Utc>,
+    pub token_id: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "sqlx", derive(sqlx::FromRow))]
+pub struct FtTransferEvent {
+    pub old_owner_id: AccountId,
+    pub new_owner_id: AccountId,
+    #[cfg_attr(feature = "sqlx", sqlx(try_from = "bigdecimal::BigDecimal"))]
+    pub amount: Balance,
+    pub memo: Option<String>,
+
+    pub transaction_id: TransactionId,
+    pub receipt_id: ReceiptId,
+    pub block_height: BlockHeight,
+    #[cfg_attr(feature = "utoipa", schema(value_type = i64))]
+    #[serde(
+        with = "chrono::serde::ts_nanoseconds",
+        rename = "block_timestamp_nanosec"
+    )]
+    pub timestamp: DateTime<Utc>,
+    pub token_id: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "sqlx", derive(sqlx::FromRow))]
+pub struct FtBurnEvent {
+    pub owner_id: AccountId,
+    #[cfg_attr(feature = "sqlx", sqlx(try_from = "bigdecimal::BigDecimal"))]
+    pub amount: Balance,
+    pub memo: Option<String>,
+
+    pub transaction_id: TransactionId,
+    pub receipt_id: ReceiptId,
+    pub block_height: BlockHeight,
+    #[cfg_attr(feature = "utoipa", schema(value_type = i64))]
+    #[serde(
+        with = "chrono::serde::ts_nanoseconds",
+        rename = "block_timestamp_nanosec"
+    )]
+    pub timestamp: DateTime<Utc>,
+    pub token_id: String,
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[cfg_attr(
+    feature = "utoipa",
+    derive(utoipa::IntoParams),
+    into_params(parameter_in = Query)
+)]
+pub struct FtMintFilter {
+    /// Account id of the FT contract
+    pub token_account_id: Option<AnyAccountId>,
+    /// Account that received the minted tokens
+    pub account_id: Option<AnyAccountId>,
+    /// Hash of the transaction that emitted the event
+    pub transaction_id: Option<AnyOf>,
+    /// Id of the receipt that emitted the event
+    pub receipt_id: Option<AnyOf>,
+    /// Excludes events where `owner_id` is any of these accounts, e.g. known
+    /// bots
+    pub exclude_account_id: Option<AnyAccountId>,
+    /// Excludes events of any of these token contracts, e.g. scam tokens
+    pub exclude_token_account_id: Option<AnyAccountId>,
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[cfg_attr(
+    feature = "utoipa",
+    derive(utoipa::IntoParams),
+    into_params(parameter_in = Query)
+)]
+pub struct FtTransferFilter {
+    /// Account id of the FT contract
+    pub token_account_id: Option<AnyAccountId>,
+    /// Sender of the tokens
+    pub old_owner_id: Option<AnyAccountId>,
+    /// Receiver of the tokens
+    pub new_owner_id: Option<AnyAccountId>,
+    /// Comma-separated list of accounts that must all be involved in the transfer
+    pub involved_account_ids: Option<AnyAccountId>,
+    /// Hash of the transaction that emitted the event
+    pub transaction_id: Option<AnyOf>,
+    /// Id of the receipt that emitted the event
+    pub receipt_id: Option<AnyOf>,
+    /// Excludes events where `old_owner_id` or `new_owner_id` is any of these
+    /// accounts, e.g. known bots
+    pub exclude_account_id: Option<AnyAccountId>,
+    /// Excludes events of any of these token contracts, e.g. scam tokens
+    pub exclude_token_account_id: Option<AnyAccountId>,
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[cfg_attr(
+    feature = "utoipa",
+    derive(utoipa::IntoParams),
+    into_params(parameter_in = Query)
+)]
+pub struct FtBurnFilter {
+    /// Account id of the FT contract
+    pub token_account_id: Option<AnyAccountId>,
+    /// Account whose tokens were burned
+    pub account_id: Option<AnyAccountId>,
+    /// Hash of the transaction that emitted the event
+    pub transaction_id: Option<AnyOf>,
+    /// Id of the receipt that emitted the event
+    pub receipt_id: Option<AnyOf>,
+    /// Excludes events where `owner_id` is any of these accounts, e.g. known
+    /// bots
+    pub exclude_account_id: Option<AnyAccountId>,
+    /// Excludes events of any of these token contracts, e.g. scam tokens
+    pub exclude_token_account_id: Option<AnyAccountId>,
+}
@@ -0,0 +1,216 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::utils::{AnyAccountId, AnyId, AnyOf, Balance, OptionalBalance};
+
+pub type TransactionId = String;
+pub type ReceiptId = String;
+pub type AccountId = String;
+pub type BlockHeight = i64;
+/// Id of a meme on its launchpad, e.g. `123` on `meme-cooking.near`
+pub type MemeId = i64;
+
+/// A new meme on a launchpad. Its token is only deployed to `token_id` when
+/// the launch is finalized.
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "sqlx", derive(sqlx::FromRow))]
+pub struct LaunchCreateEvent {
+    pub meme_id: MemeId,
+    /// Account the token will be deployed to
+    pub token_id: AccountId,
+    pub owner_id: AccountId,
+    pub name: String,
+    pub symbol: String,
+    pub decimals: i32,
+    #[cfg_attr(feature = "sqlx", sqlx(try_from = "bigdecimal::BigDecimal"))]
+    pub total_supply: Balance,
+    /// Link to the metadata of the meme, e.g. on IPFS
+    pub reference: Option<String>,
+    /// Token that is deposited to buy into the launch, e.g. `wrap.near`
+    pub deposit_token_id: AccountId,
+    /// Deposits are accepted until then, in milliseconds
+    pub end_timestamp_ms: i64,
+    /// Launchpad contract
+    pub contract_id: AccountId,
+
+    pub transaction_id: TransactionId,
+    pub receipt_id: ReceiptId,
+    pub block_height: BlockHeight,
+    #[cfg_attr(feature = "utoipa", schema(value_type = i64))]
+    #[serde(
+        with = "chrono::serde::ts_nanoseconds",
+        rename = "block_timestamp_nanosec"
+    )]
+    pub timestamp: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "sqlx", derive(sqlx::FromRow))]
+pub struct LaunchDepositEvent {
+    pub meme_id: MemeId,
+    pub token_id: AccountId,
+    pub account_id: AccountId,
+    /// Amount of the deposit token, after fees
+    #[cfg_attr(feature = "sqlx", sqlx(try_from = "bigdecimal::BigDecimal"))]
+    pub amount: Balance,
+    #[cfg_attr(feature = "sqlx", sqlx(try_from = "bigdecimal::BigDecimal"))]
+    pub protocol_fee: Balance,
+    pub referrer_id: Option<AccountId>,
+    #[cfg_attr(feature = "sqlx", sqlx(try_from = "Option<bigdecimal::BigDecimal>"))]
+    pub referrer_fee: OptionalBalance,
+    /// Launchpad contract
+    pub contract_id: AccountId,
+
+    pub transaction_id: TransactionId,
+    pub receipt_id: ReceiptId,
+    pub block_height: BlockHeight,
+    #[cfg_attr(feature = "utoipa", schema(value_type = i64))]
+    #[serde(
+        with = "chrono::serde::ts_nanoseconds",
+        rename = "block_timestamp_nanosec"
+    )]
+    pub timestamp: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "sqlx", derive(sqlx::FromRow))]
+pub struct LaunchWithdrawEvent {
+    pub meme_id: MemeId,
+    pub token_id: AccountId,
+    pub account_id: AccountId,
+    /// Amount of the deposit token that was returned, after the fee
+    #[cfg_attr(feature = "sqlx", sqlx(try_from = "bigdecimal::BigDecimal"))]
+    pub amount: Balance,
+    #[cfg_attr(feature = "sqlx", sqlx(try_from = "bigdecimal::BigDecimal"))]
+    pub fee: Balance,
+    /// Launchpad contract
+    pub contract_id: AccountId,
+
+    pub transaction_id: TransactionId,
+    pub receipt_id: ReceiptId,
+    pub block_height: BlockHeight,
+    #[cfg_attr(feature = "utoipa", schema(value_type = i64))]
+    #[serde(
+        with = "chrono::serde::ts_nanoseconds",
+        rename = "block_timestamp_nanosec"
+    )]
+    pub timestamp: DateTime<Utc>,
+}
+
+/// A launch that reached its goal, when the token is deployed and its pool
+/// is created with the deposits
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "sqlx", derive(sqlx::FromRow))]
+pub struct LaunchFinalizeEvent {
+    pub meme_id: MemeId,
+    pub token_id: AccountId,
+    /// Pool of the token and the deposit token, e.g. `REF-123`
+    pub pool_id: Option<String>,
+    /// Sum of all deposits
+    #[cfg_attr(feature = "sqlx", sqlx(try_from = "bigdecimal::BigDecimal"))]
+    pub total_deposit: Balance,
+    /// Launchpad contract
+    pub contract_id: AccountId,
+
+    pub transaction_id: TransactionId,
+    pub receipt_id: ReceiptId,
+    pub block_height: BlockHeight,
+    #[cfg_attr(feature = "utoipa", schema(value_type = i64))]
+    #[serde(
+        with = "chrono::serde::ts_nanoseconds",
+        rename = "block_timestamp_nanosec"
+    )]
+    pub timestamp: DateTime<Utc>,
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[cfg_attr(
+    feature = "utoipa",
+    derive(utoipa::IntoParams),
+    into_params(parameter_in = Query)
+)]
+pub struct LaunchCreateFilter {
+    /// Id of the meme on its launchpad
+    pub meme_id: Option<AnyId>,
+    /// Account the token will be deployed to
+    pub token_id: Option<AnyAccountId>,
+    /// Account that created the meme
+    pub account_id: Option<AnyAccountId>,
+    /// Launchpad contract, e.g. `meme-cooking.near`
+    pub contract_id: Option<AnyAccountId>,
+    /// Hash of the transaction that emitted the event
+    pub transaction_id: Option<AnyOf>,
+    /// Id of the receipt that emitted the event
+    pub receipt_id: Option<AnyOf>,
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[cfg_attr(
+    feature = "utoipa",
+    derive(utoipa::IntoParams),
+    into_params(parameter_in = Query)
+)]
+pub struct LaunchDepositFilter {
+    /// Id of the meme on its launchpad
+    pub meme_id: Option<AnyId>,
+    /// Account the token is or will be deployed to
+    pub token_id: Option<AnyAccountId>,
+    /// Account that deposited
+    pub account_id: Option<AnyAccountId>,
+    /// Launchpad contract, e.g. `meme-cooking.near`
+    pub contract_id: Option<AnyAccountId>,
+    /// Hash of the transaction that emitted the event
+    pub transaction_id: Option<AnyOf>,
+    /// Id of the receipt that emitted the event
+    pub receipt_id: Option<AnyOf>,
+    /// Excludes events where `account_id` is any of these accounts, e.g.
+    /// known bots
+    pub exclude_account_id: Option<AnyAccountId>,
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[cfg_attr(
+    feature = "utoipa",
+    derive(utoipa::IntoParams),
+    into_params(parameter_in = Query)
+)]
+pub struct LaunchWithdrawFilter {
+    /// Id of the meme on its launchpad
+    pub meme_id: Option<AnyId>,
+    /// Account the token is or will be deployed to
+    pub token_id: Option<AnyAccountId>,
+    /// Account that withdrew
+    pub account_id: Option<AnyAccountId>,
+    /// Launchpad contract, e.g. `meme-cooking.near`
+    pub contract_id: Option<AnyAccountId>,
+    /// Hash of the transaction that emitted the event
+    pub transaction_id: Option<AnyOf>,
+    /// Id of the receipt that emitted the event
+    pub receipt_id: Option<AnyOf>,
+    /// Excludes events where `account_id` is any of these accounts, e.g.
+    /// known bots
+    pub exclude_account_id: Option<AnyAccountId>,
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[cfg_attr(
+    feature = "utoipa",
+    derive(utoipa::IntoParams),
+    into_params(parameter_in = Query)
+)]
+pub struct LaunchFinalizeFilter {
+    /// Id of the meme on its launchpad
+    pub meme_id: Option<AnyId>,
+    /// Account the token was deployed to
+    pub token_id: Option<AnyAccountId>,
+    /// Launchpad contract, e.g. `meme-cooking.near`
+    pub contract_id: Option<AnyAccountId>,
+    /// Hash of the transaction that emitted the event
+    pub transaction_id: Option<AnyOf>,
+    /// Id of the receipt that emitted the event
+    pub receipt_id: Option<AnyOf>,
+}
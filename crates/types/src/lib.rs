@@ -0,0 +1,34 @@
+//! Events and filters of the [Intear Events API](https://events.intear.tech/),
+//! the same definitions the server uses, so that clients can't drift out of
+//! sync with it.
+//!
+//! The `sqlx` and `utoipa` features derive what the server needs to read
+//! events from the database and document them, clients don't need them.
+
+pub mod account;
+pub mod bridge;
+pub mod burrow;
+pub mod dao;
+pub mod ft;
+pub mod launch;
+pub mod nft;
+pub mod potlock;
+pub mod price;
+pub mod raw;
+pub mod ref_pool;
+pub mod socialdb;
+pub mod staking;
+pub mod trade;
+pub mod utils;
+
+use serde::{Deserialize, Serialize};
+
+/// Page of an event endpoint
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+pub struct EventsPage<T> {
+    pub events: Vec<T>,
+    /// Pass as `cursor` to get the next page. `None` if there were no events,
+    /// in which case the same request should be repeated later.
+    pub cursor: Option<String>,
+}
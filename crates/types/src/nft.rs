@@ -0,0 +1,212 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::utils::{AnyAccountId, AnyOf, Balance, VecBalance};
+
+pub type TransactionId = String;
+pub type ReceiptId = String;
+pub type AccountId = String;
+pub type NftTokenId = String;
+pub type BlockHeight = i64;
+
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "sqlx", derive(sqlx::FromRow))]
+pub struct NftMintEvent {
+    pub owner_id: AccountId,
+    pub token_ids: Vec<NftTokenId>,
+    pub memo: Option<String>,
+
+    pub transaction_id: TransactionId,
+    pub receipt_id: ReceiptId,
+    pub block_height: BlockHeight,
+    #[cfg_attr(feature = "utoipa", schema(value_type = i64))]
+    #[serde(
+        with = "chrono::serde::ts_nanoseconds",
+        rename = "block_timestamp_nanosec"
+    )]
+    pub timestamp: DateTime<Utc>,
+    pub contract_id: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "sqlx", derive(sqlx::FromRow))]
+pub struct NftTransferEvent {
+    pub old_owner_id: AccountId,
+    pub new_owner_id: AccountId,
+    pub token_ids: Vec<NftTokenId>,
+    pub memo: Option<String>,
+    #[cfg_attr(feature = "sqlx", sqlx(try_from = "Vec<bigdecimal::BigDecimal>"))]
+    pub token_prices_near: VecBalance,
+
+    pub transaction_id: TransactionId,
+    pub receipt_id: ReceiptId,
+    pub block_height: BlockHeight,
+    #[cfg_attr(feature = "utoipa", schema(value_type = i64))]
+    #[serde(
+        with = "chrono::serde::ts_nanoseconds",
+        rename = "block_timestamp_nanosec"
+    )]
+    pub timestamp: DateTime<Utc>,
+    pub contract_id: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "sqlx", derive(sqlx::FromRow))]
+pub struct NftBurnEvent {
+    pub owner_id: AccountId,
+    pub token_ids: Vec<NftTokenId>,
+    pub memo: Option<String>,
+
+    pub transaction_id: TransactionId,
+    pub receipt_id: ReceiptId,
+    pub block_height: BlockHeight,
+    #[cfg_attr(feature = "utoipa", schema(value_type = i64))]
+    #[serde(
+        with = "chrono::serde::ts_nanoseconds",
+        rename = "block_timestamp_nanosec"
+    )]
+    pub timestamp: DateTime<Utc>,
+    pub contract_id: String,
+}
+
+/// A token bought on a marketplace, e.g. Mintbase or Paras
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "sqlx", derive(sqlx::FromRow))]
+pub struct NftSaleEvent {
+    pub token_id: NftTokenId,
+    pub seller_id: AccountId,
+    pub buyer_id: AccountId,
+    /// Price in the smallest units of `ft_token_id`, or in yoctoNEAR
+    #[cfg_attr(feature = "sqlx", sqlx(try_from = "bigdecimal::BigDecimal"))]
+    pub price: Balance,
+    /// Token the price was paid in, `None` if it was paid in NEAR
+    pub ft_token_id: Option<AccountId>,
+    /// Marketplace contract, e.g. `simple.market.mintbase1.near`
+    pub marketplace_id: AccountId,
+
+    pub transaction_id: TransactionId,
+    pub receipt_id: ReceiptId,
+    pub block_height: BlockHeight,
+    #[cfg_attr(feature = "utoipa", schema(value_type = i64))]
+    #[serde(
+        with = "chrono::serde::ts_nanoseconds",
+        rename = "block_timestamp_nanosec"
+    )]
+    pub timestamp: DateTime<Utc>,
+    pub contract_id: String,
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[cfg_attr(
+    feature = "utoipa",
+    derive(utoipa::IntoParams),
+    into_params(parameter_in = Query)
+)]
+pub struct NftMintFilter {
+    /// Account id of the NFT contract
+    pub token_account_id: Option<AnyAccountId>,
+    /// Account that received the minted tokens
+    pub account_id: Option<AnyAccountId>,
+    /// Only events that include this token, e.g. to get the provenance of a
+    /// single NFT together with `token_account_id`
+    pub token_id: Option<AnyOf>,
+    /// Hash of the transaction that emitted the event
+    pub transaction_id: Option<AnyOf>,
+    /// Id of the receipt that emitted the event
+    pub receipt_id: Option<AnyOf>,
+    /// Excludes events where `owner_id` is any of these accounts, e.g. known
+    /// bots
+    pub exclude_account_id: Option<AnyAccountId>,
+    /// Excludes events of any of these token contracts, e.g. spam NFT
+    /// collections
+    pub exclude_token_account_id: Option<AnyAccountId>,
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[cfg_attr(
+    feature = "utoipa",
+    derive(utoipa::IntoParams),
+    into_params(parameter_in = Query)
+)]
+pub struct NftTransferFilter {
+    /// Account id of the NFT contract
+    pub token_account_id: Option<AnyAccountId>,
+    /// Old owner of the tokens
+    pub old_owner_id: Option<AnyAccountId>,
+    /// New owner of the tokens
+    pub new_owner_id: Option<AnyAccountId>,
+    /// Comma-separated list of accounts that must all be involved in the transfer. With this parameter, `old_owner_id` and `new_owner_id` are ignored
+    pub involved_account_ids: Option<AnyAccountId>,
+    /// Only events that include this token, e.g. to get the provenance of a
+    /// single NFT together with `token_account_id`
+    pub token_id: Option<AnyOf>,
+    /// Hash of the transaction that emitted the event
+    pub transaction_id: Option<AnyOf>,
+    /// Id of the receipt that emitted the event
+    pub receipt_id: Option<AnyOf>,
+    /// Excludes events where `old_owner_id` or `new_owner_id` is any of these
+    /// accounts, e.g. known bots
+    pub exclude_account_id: Option<AnyAccountId>,
+    /// Excludes events of any of these token contracts, e.g. spam NFT
+    /// collections
+    pub exclude_token_account_id: Option<AnyAccountId>,
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[cfg_attr(
+    feature = "utoipa",
+    derive(utoipa::IntoParams),
+    into_params(parameter_in = Query)
+)]
+pub struct NftBurnFilter {
+    /// Account id of the NFT contract
+    pub token_account_id: Option<AnyAccountId>,
+    /// Account that burned the tokens
+    pub account_id: Option<AnyAccountId>,
+    /// Only events that include this token, e.g. to get the provenance of a
+    /// single NFT together with `token_account_id`
+    pub token_id: Option<AnyOf>,
+    /// Hash of the transaction that emitted the event
+    pub transaction_id: Option<AnyOf>,
+    /// Id of the receipt that emitted the event
+    pub receipt_id: Option<AnyOf>,
+    /// Excludes events where `owner_id` is any of these accounts, e.g. known
+    /// bots
+    pub exclude_account_id: Option<AnyAccountId>,
+    /// Excludes events of any of these token contracts, e.g. spam NFT
+    /// collections
+    pub exclude_token_account_id: Option<AnyAccountId>,
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[cfg_attr(
+    feature = "utoipa",
+    derive(utoipa::IntoParams),
+    into_params(parameter_in = Query)
+)]
+pub struct NftSaleFilter {
+    /// Account id of the NFT contract
+    pub contract_id: Option<AnyAccountId>,
+    /// Only sales of this token, e.g. to get the price history of a single
+    /// NFT together with `contract_id`
+    pub token_id: Option<AnyOf>,
+    /// Seller or buyer of the token
+    pub account_id: Option<AnyAccountId>,
+    /// Account that sold the token
+    pub seller_id: Option<AnyAccountId>,
+    /// Account that bought the token
+    pub buyer_id: Option<AnyAccountId>,
+    /// Marketplace contract
+    pub marketplace_id: Option<AnyAccountId>,
+    /// Hash of the transaction that emitted the event
+    pub transaction_id: Option<AnyOf>,
+    /// Id of the receipt that emitted the event
+    pub receipt_id: Option<AnyOf>,
+    /// Excludes events where `seller_id` or `buyer_id` is any of these
+    /// accounts, e.g. known bots
+    pub exclude_account_id: Option<AnyAccountId>,
+}
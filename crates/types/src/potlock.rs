@@ -0,0 +1,396 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::utils::{AnyAccountId, AnyOf, Balance, OptionalBalance};
+
+pub type TransactionId = String;
+pub type ReceiptId = String;
+pub type AccountId = String;
+pub type BlockHeight = i64;
+pub type DonationId = i64;
+pub type ProjectId = AccountId;
+
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "sqlx", derive(sqlx::FromRow))]
+pub struct PotlockDonationEvent {
+    pub transaction_id: TransactionId,
+    pub receipt_id: ReceiptId,
+    pub block_height: BlockHeight,
+    #[cfg_attr(feature = "utoipa", schema(value_type = i64))]
+    #[serde(
+        with = "chrono::serde::ts_nanoseconds",
+        rename = "block_timestamp_nanosec"
+    )]
+    pub timestamp: DateTime<Utc>,
+
+    pub donation_id: DonationId,
+    pub donor_id: AccountId,
+    #[cfg_attr(feature = "sqlx", sqlx(try_from = "bigdecimal::BigDecimal"))]
+    pub total_amount: Balance,
+    pub ft_id: AccountId,
+    pub message: Option<String>,
+    #[cfg_attr(feature = "utoipa", schema(value_type = i64))]
+    #[serde(with = "chrono::serde::ts_milliseconds")]
+    pub donated_at: DateTime<Utc>,
+    pub project_id: ProjectId,
+    #[cfg_attr(feature = "sqlx", sqlx(try_from = "bigdecimal::BigDecimal"))]
+    pub protocol_fee: Balance,
+    pub referrer_id: Option<AccountId>,
+    #[cfg_attr(feature = "sqlx", sqlx(try_from = "Option<bigdecimal::BigDecimal>"))]
+    pub referrer_fee: OptionalBalance,
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[cfg_attr(
+    feature = "utoipa",
+    derive(utoipa::IntoParams),
+    into_params(parameter_in = Query)
+)]
+pub struct PotlockDonationFilter {
+    /// Project that received the donation
+    pub project_id: Option<AnyAccountId>,
+    /// Account that donated
+    pub donor_id: Option<AnyAccountId>,
+    /// Referrer of the donation
+    pub referrer_id: Option<AnyAccountId>,
+    /// Hash of the transaction that emitted the event
+    pub transaction_id: Option<AnyOf>,
+    /// Id of the receipt that emitted the event
+    pub receipt_id: Option<AnyOf>,
+    /// Minimum `total_amount`, inclusive, e.g. to track whales
+    pub min_amount: Option<Balance>,
+    /// Maximum `total_amount`, inclusive, e.g. to skip dust
+    pub max_amount: Option<Balance>,
+    /// Excludes events where `donor_id`, `project_id` or `referrer_id` is any
+    /// of these accounts, e.g. known bots
+    pub exclude_account_id: Option<AnyAccountId>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "sqlx", derive(sqlx::FromRow))]
+pub struct PotlockPotProjectDonationEvent {
+    pub transaction_id: TransactionId,
+    pub receipt_id: ReceiptId,
+    pub block_height: BlockHeight,
+    #[cfg_attr(feature = "utoipa", schema(value_type = i64))]
+    #[serde(
+        with = "chrono::serde::ts_nanoseconds",
+        rename = "block_timestamp_nanosec"
+    )]
+    pub timestamp: DateTime<Utc>,
+
+    pub donation_id: DonationId,
+    pub pot_id: AccountId,
+    pub donor_id: AccountId,
+    #[cfg_attr(feature = "sqlx", sqlx(try_from = "bigdecimal::BigDecimal"))]
+    pub total_amount: Balance,
+    #[cfg_attr(feature = "sqlx", sqlx(try_from = "bigdecimal::BigDecimal"))]
+    pub net_amount: Balance,
+    pub message: Option<String>,
+    #[cfg_attr(feature = "utoipa", schema(value_type = i64))]
+    #[serde(with = "chrono::serde::ts_milliseconds")]
+    pub donated_at: DateTime<Utc>,
+    pub project_id: ProjectId,
+    pub referrer_id: Option<AccountId>,
+    #[cfg_attr(feature = "sqlx", sqlx(try_from = "Option<bigdecimal::BigDecimal>"))]
+    pub referrer_fee: OptionalBalance,
+    #[cfg_attr(feature = "sqlx", sqlx(try_from = "bigdecimal::BigDecimal"))]
+    pub protocol_fee: Balance,
+    pub chef_id: Option<AccountId>,
+    #[cfg_attr(feature = "sqlx", sqlx(try_from = "Option<bigdecimal::BigDecimal>"))]
+    pub chef_fee: OptionalBalance,
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[cfg_attr(
+    feature = "utoipa",
+    derive(utoipa::IntoParams),
+    into_params(parameter_in = Query)
+)]
+pub struct PotlockPotProjectDonationFilter {
+    /// Pot account, ends with `.v1.potfactory.potlock.near`
+    pub pot_id: Option<AnyAccountId>,
+    /// Project that received the donation
+    pub project_id: Option<AnyAccountId>,
+    /// Account that donated
+    pub donor_id: Option<AnyAccountId>,
+    /// Referrer of the donation
+    pub referrer_id: Option<AnyAccountId>,
+    /// Hash of the transaction that emitted the event
+    pub transaction_id: Option<AnyOf>,
+    /// Id of the receipt that emitted the event
+    pub receipt_id: Option<AnyOf>,
+    /// Minimum `total_amount`, inclusive, e.g. to track whales
+    pub min_amount: Option<Balance>,
+    /// Maximum `total_amount`, inclusive, e.g. to skip dust
+    pub max_amount: Option<Balance>,
+    /// Excludes events where `donor_id`, `project_id`, `referrer_id` or
+    /// `chef_id` is any of these accounts, e.g. known bots
+    pub exclude_account_id: Option<AnyAccountId>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "sqlx", derive(sqlx::FromRow))]
+pub struct PotlockPotDonationEvent {
+    pub transaction_id: TransactionId,
+    pub receipt_id: ReceiptId,
+    pub block_height: BlockHeight,
+    #[cfg_attr(feature = "utoipa", schema(value_type = i64))]
+    #[serde(
+        with = "chrono::serde::ts_nanoseconds",
+        rename = "block_timestamp_nanosec"
+    )]
+    pub timestamp: DateTime<Utc>,
+
+    pub donation_id: DonationId,
+    pub pot_id: AccountId,
+    pub donor_id: AccountId,
+    #[cfg_attr(feature = "sqlx", sqlx(try_from = "bigdecimal::BigDecimal"))]
+    pub total_amount: Balance,
+    #[cfg_attr(feature = "sqlx", sqlx(try_from = "bigdecimal::BigDecimal"))]
+    pub net_amount: Balance,
+    pub message: Option<String>,
+    #[cfg_attr(feature = "utoipa", schema(value_type = i64))]
+    #[serde(with = "chrono::serde::ts_milliseconds")]
+    pub donated_at: DateTime<Utc>,
+    pub referrer_id: Option<AccountId>,
+    #[cfg_attr(feature = "sqlx", sqlx(try_from = "Option<bigdecimal::BigDecimal>"))]
+    pub referrer_fee: OptionalBalance,
+    #[cfg_attr(feature = "sqlx", sqlx(try_from = "bigdecimal::BigDecimal"))]
+    pub protocol_fee: Balance,
+    pub chef_id: Option<AccountId>,
+    #[cfg_attr(feature = "sqlx", sqlx(try_from = "Option<bigdecimal::BigDecimal>"))]
+    pub chef_fee: OptionalBalance,
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[cfg_attr(
+    feature = "utoipa",
+    derive(utoipa::IntoParams),
+    into_params(parameter_in = Query)
+)]
+pub struct PotlockPotDonationFilter {
+    /// Pot account, ends with `.v1.potfactory.potlock.near`
+    pub pot_id: Option<AnyAccountId>,
+    /// Account that donated
+    pub donor_id: Option<AnyAccountId>,
+    /// Referrer of the donation
+    pub referrer_id: Option<AnyAccountId>,
+    /// Hash of the transaction that emitted the event
+    pub transaction_id: Option<AnyOf>,
+    /// Id of the receipt that emitted the event
+    pub receipt_id: Option<AnyOf>,
+    /// Minimum `total_amount`, inclusive, e.g. to track whales
+    pub min_amount: Option<Balance>,
+    /// Maximum `total_amount`, inclusive, e.g. to skip dust
+    pub max_amount: Option<Balance>,
+    /// Excludes events where `donor_id`, `referrer_id` or `chef_id` is any of
+    /// these accounts, e.g. known bots
+    pub exclude_account_id: Option<AnyAccountId>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "sqlx", derive(sqlx::FromRow))]
+pub struct PotlockPotDeploymentEvent {
+    pub transaction_id: TransactionId,
+    pub receipt_id: ReceiptId,
+    pub block_height: BlockHeight,
+    #[cfg_attr(feature = "utoipa", schema(value_type = i64))]
+    #[serde(
+        with = "chrono::serde::ts_nanoseconds",
+        rename = "block_timestamp_nanosec"
+    )]
+    pub timestamp: DateTime<Utc>,
+
+    pub pot_id: AccountId,
+    pub deployer_id: AccountId,
+    pub owner_id: AccountId,
+    pub chef_id: Option<AccountId>,
+    pub pot_name: String,
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[cfg_attr(
+    feature = "utoipa",
+    derive(utoipa::IntoParams),
+    into_params(parameter_in = Query)
+)]
+pub struct PotlockPotDeploymentFilter {
+    /// Pot account, ends with `.v1.potfactory.potlock.near`
+    pub pot_id: Option<AnyAccountId>,
+    /// Account that deployed the Pot
+    pub deployer_id: Option<AnyAccountId>,
+    /// Hash of the transaction that emitted the event
+    pub transaction_id: Option<AnyOf>,
+    /// Id of the receipt that emitted the event
+    pub receipt_id: Option<AnyOf>,
+    /// Excludes events where `deployer_id`, `owner_id` or `chef_id` is any of
+    /// these accounts, e.g. known bots
+    pub exclude_account_id: Option<AnyAccountId>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "sqlx", derive(sqlx::FromRow))]
+pub struct PotlockPotApplicationEvent {
+    pub transaction_id: TransactionId,
+    pub receipt_id: ReceiptId,
+    pub block_height: BlockHeight,
+    #[cfg_attr(feature = "utoipa", schema(value_type = i64))]
+    #[serde(
+        with = "chrono::serde::ts_nanoseconds",
+        rename = "block_timestamp_nanosec"
+    )]
+    pub timestamp: DateTime<Utc>,
+
+    pub pot_id: AccountId,
+    pub project_id: AccountId,
+    pub message: Option<String>,
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[cfg_attr(
+    feature = "utoipa",
+    derive(utoipa::IntoParams),
+    into_params(parameter_in = Query)
+)]
+pub struct PotlockPotApplicationFilter {
+    /// Pot account, ends with `.v1.potfactory.potlock.near`
+    pub pot_id: Option<AnyAccountId>,
+    /// Project that applied
+    pub project_id: Option<AnyAccountId>,
+    /// Hash of the transaction that emitted the event
+    pub transaction_id: Option<AnyOf>,
+    /// Id of the receipt that emitted the event
+    pub receipt_id: Option<AnyOf>,
+    /// Excludes events where `project_id` is any of these accounts, e.g. known
+    /// bots
+    pub exclude_account_id: Option<AnyAccountId>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "sqlx", derive(sqlx::FromRow))]
+pub struct PotlockPotApplicationReviewEvent {
+    pub transaction_id: TransactionId,
+    pub receipt_id: ReceiptId,
+    pub block_height: BlockHeight,
+    #[cfg_attr(feature = "utoipa", schema(value_type = i64))]
+    #[serde(
+        with = "chrono::serde::ts_nanoseconds",
+        rename = "block_timestamp_nanosec"
+    )]
+    pub timestamp: DateTime<Utc>,
+
+    pub pot_id: AccountId,
+    pub project_id: AccountId,
+    pub reviewer_id: AccountId,
+    pub status: String,
+    pub notes: Option<String>,
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[cfg_attr(
+    feature = "utoipa",
+    derive(utoipa::IntoParams),
+    into_params(parameter_in = Query)
+)]
+pub struct PotlockPotApplicationReviewFilter {
+    /// Pot account, ends with `.v1.potfactory.potlock.near`
+    pub pot_id: Option<AnyAccountId>,
+    /// Project whose application was reviewed
+    pub project_id: Option<AnyAccountId>,
+    /// New status of the application: `Approved`, `Rejected`, `InReview`, or `Pending`
+    pub status: Option<AnyOf>,
+    /// Hash of the transaction that emitted the event
+    pub transaction_id: Option<AnyOf>,
+    /// Id of the receipt that emitted the event
+    pub receipt_id: Option<AnyOf>,
+    /// Excludes events where `project_id` or `reviewer_id` is any of these
+    /// accounts, e.g. known bots
+    pub exclude_account_id: Option<AnyAccountId>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "sqlx", derive(sqlx::FromRow))]
+pub struct PotlockPotPayoutEvent {
+    pub transaction_id: TransactionId,
+    pub receipt_id: ReceiptId,
+    pub block_height: BlockHeight,
+    #[cfg_attr(feature = "utoipa", schema(value_type = i64))]
+    #[serde(
+        with = "chrono::serde::ts_nanoseconds",
+        rename = "block_timestamp_nanosec"
+    )]
+    pub timestamp: DateTime<Utc>,
+
+    pub pot_id: AccountId,
+    pub project_id: AccountId,
+    #[cfg_attr(feature = "sqlx", sqlx(try_from = "bigdecimal::BigDecimal"))]
+    pub amount: Balance,
+    pub admin_id: AccountId,
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[cfg_attr(
+    feature = "utoipa",
+    derive(utoipa::IntoParams),
+    into_params(parameter_in = Query)
+)]
+pub struct PotlockPotPayoutFilter {
+    /// Pot account, ends with `.v1.potfactory.potlock.near`
+    pub pot_id: Option<AnyAccountId>,
+    /// Project that received the payout
+    pub project_id: Option<AnyAccountId>,
+    /// Hash of the transaction that emitted the event
+    pub transaction_id: Option<AnyOf>,
+    /// Id of the receipt that emitted the event
+    pub receipt_id: Option<AnyOf>,
+    /// Excludes events where `project_id` or `admin_id` is any of these
+    /// accounts, e.g. known bots
+    pub exclude_account_id: Option<AnyAccountId>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "sqlx", derive(sqlx::FromRow))]
+pub struct PotlockPotPayoutChallengeEvent {
+    pub transaction_id: TransactionId,
+    pub receipt_id: ReceiptId,
+    pub block_height: BlockHeight,
+    #[cfg_attr(feature = "utoipa", schema(value_type = i64))]
+    #[serde(
+        with = "chrono::serde::ts_nanoseconds",
+        rename = "block_timestamp_nanosec"
+    )]
+    pub timestamp: DateTime<Utc>,
+
+    pub pot_id: AccountId,
+    pub challenger_id: AccountId,
+    pub reason: String,
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[cfg_attr(
+    feature = "utoipa",
+    derive(utoipa::IntoParams),
+    into_params(parameter_in = Query)
+)]
+pub struct PotlockPotPayoutChallengeFilter {
+    /// Pot account, ends with `.v1.potfactory.potlock.near`
+    pub pot_id: Option<AnyAccountId>,
+    /// Account that challenged the payouts
+    pub challenger_id: Option<AnyAccountId>,
+    /// Hash of the transaction that emitted the event
+    pub transaction_id: Option<AnyOf>,
+    /// Id of the receipt that emitted the event
+    pub receipt_id: Option<AnyOf>,
+    /// Excludes events where `challenger_id` is any of these accounts, e.g.
+    /// known bots
+    pub exclude_account_id: Option<AnyAccountId>,
+}
@@ -0,0 +1,44 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::utils::{AnyAccountId, Balance};
+
+pub type AccountId = String;
+pub type BlockHeight = i64;
+
+/// Prices aren't tied to a transaction, they're computed from pool states or
+/// reported by oracles, so there's no transaction or receipt id
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "sqlx", derive(sqlx::FromRow))]
+pub struct PriceChangeEvent {
+    pub token_account_id: AccountId,
+    /// Price of one whole token (adjusted for decimals) in USD, as a decimal
+    /// string
+    #[cfg_attr(feature = "utoipa", schema(value_type = String))]
+    #[cfg_attr(feature = "sqlx", sqlx(try_from = "bigdecimal::BigDecimal"))]
+    pub price_usd: Balance,
+    /// Where the price comes from, e.g. `ref` for Ref Finance pools or
+    /// `pyth` for the Pyth oracle
+    pub source: String,
+    pub block_height: BlockHeight,
+    #[cfg_attr(feature = "utoipa", schema(value_type = i64))]
+    #[serde(
+        with = "chrono::serde::ts_nanoseconds",
+        rename = "block_timestamp_nanosec"
+    )]
+    pub timestamp: DateTime<Utc>,
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[cfg_attr(
+    feature = "utoipa",
+    derive(utoipa::IntoParams),
+    into_params(parameter_in = Query)
+)]
+pub struct PriceChangeFilter {
+    /// Account id of the token contract
+    pub token_account_id: Option<AnyAccountId>,
+    /// Excludes events of any of these token contracts
+    pub exclude_token_account_id: Option<AnyAccountId>,
+}
@@ -0,0 +1,55 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::utils::{AnyAccountId, AnyOf};
+
+pub type TransactionId = String;
+pub type ReceiptId = String;
+pub type AccountId = String;
+pub type BlockHeight = i64;
+
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "sqlx", derive(sqlx::FromRow))]
+pub struct LogEvent {
+    /// Contract that logged the event
+    pub contract_id: AccountId,
+    /// Standard of the event (`standard` in the log), e.g. `nep171`
+    pub event_standard: String,
+    /// Version of the standard (`version` in the log), e.g. `1.0.0`
+    pub event_version: String,
+    /// Name of the event (`event` in the log), e.g. `nft_mint`
+    pub event_name: String,
+    /// `data` of the event as logged, `null` if it has none
+    pub data: Value,
+
+    pub transaction_id: TransactionId,
+    pub receipt_id: ReceiptId,
+    pub block_height: BlockHeight,
+    #[cfg_attr(feature = "utoipa", schema(value_type = i64))]
+    #[serde(
+        with = "chrono::serde::ts_nanoseconds",
+        rename = "block_timestamp_nanosec"
+    )]
+    pub timestamp: DateTime<Utc>,
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[cfg_attr(
+    feature = "utoipa",
+    derive(utoipa::IntoParams),
+    into_params(parameter_in = Query)
+)]
+pub struct LogEventFilter {
+    /// Contract that logged the event
+    pub contract_id: Option<AnyAccountId>,
+    /// Standard of the event (`standard` in the log), e.g. `nep171`
+    pub event_standard: Option<AnyOf>,
+    /// Name of the event (`event` in the log), e.g. `nft_mint`
+    pub event_name: Option<AnyOf>,
+    /// Hash of the transaction that emitted the event
+    pub transaction_id: Option<AnyOf>,
+    /// Id of the receipt that emitted the event
+    pub receipt_id: Option<AnyOf>,
+}
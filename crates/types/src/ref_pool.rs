@@ -0,0 +1,187 @@
+use std::str::FromStr;
+
+use bigdecimal::BigDecimal;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::utils::{Balance, VecBalance};
+
+type AccountId = String;
+
+/// Precision of `c_amounts` of Ref Finance stable swap pools
+const STABLE_SWAP_DECIMALS: i64 = 18;
+/// Precision of `c_amounts` of Ref Finance rated swap pools
+const RATED_SWAP_DECIMALS: i64 = 24;
+
+/// State of a pool after a change. Pools that aren't known, or that can't be
+/// parsed, are returned as stored by the indexer.
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[serde(untagged)]
+pub enum PoolState {
+    Ref(RefPool),
+    Raw(Value),
+}
+
+impl From<Value> for PoolState {
+    fn from(value: Value) -> Self {
+        match serde_json::from_value::<StoredRefPool>(value.clone())
+            .ok()
+            .and_then(StoredRefPool::into_ref_pool)
+        {
+            Some(pool) => PoolState::Ref(pool),
+            None => PoolState::Raw(value),
+        }
+    }
+}
+
+/// Ref Finance pool, with amounts in the token's own decimals for all kinds
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[serde(tag = "type")]
+pub enum RefPool {
+    SimplePool(SimplePool),
+    StableSwapPool(StableSwapPool),
+    RatedSwapPool(StableSwapPool),
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+pub struct SimplePool {
+    pub token_account_ids: Vec<AccountId>,
+    /// Reserves of each token of `token_account_ids`
+    pub amounts: VecBalance,
+    /// Fee of a swap, in basis points
+    pub total_fee: u32,
+    pub exchange_fee: u32,
+    pub referral_fee: u32,
+    pub shares_total_supply: Balance,
+}
+
+/// Stable and rated swap pools, where the amplification factor goes from
+/// `init_amp_factor` to `target_amp_factor` between `init_amp_time` and
+/// `stop_amp_time`
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+pub struct StableSwapPool {
+    pub token_account_ids: Vec<AccountId>,
+    pub token_decimals: Vec<u8>,
+    /// Reserves of each token of `token_account_ids`
+    pub amounts: VecBalance,
+    /// Fee of a swap, in basis points
+    pub total_fee: u32,
+    pub shares_total_supply: Balance,
+    pub init_amp_factor: u64,
+    pub target_amp_factor: u64,
+    /// Nanoseconds
+    pub init_amp_time: u64,
+    /// Nanoseconds
+    pub stop_amp_time: u64,
+}
+
+/// Pool as stored by the indexer, in the format of Ref Finance's contract
+#[derive(Deserialize)]
+// Variant names are the tags of the contract's format
+#[allow(clippy::enum_variant_names)]
+enum StoredRefPool {
+    SimplePool(SimplePool),
+    StableSwapPool(StoredStableSwapPool),
+    RatedSwapPool(StoredStableSwapPool),
+}
+
+#[derive(Deserialize)]
+struct StoredStableSwapPool {
+    token_account_ids: Vec<AccountId>,
+    token_decimals: Vec<u8>,
+    /// Amounts in a precision that's the same for all tokens, so that they
+    /// can be compared
+    c_amounts: VecBalance,
+    total_fee: u32,
+    shares_total_supply: Balance,
+    init_amp_factor: u64,
+    target_amp_factor: u64,
+    init_amp_time: u64,
+    stop_amp_time: u64,
+}
+
+impl StoredRefPool {
+    fn into_ref_pool(self) -> Option<RefPool> {
+        Some(match self {
+            StoredRefPool::SimplePool(pool) => RefPool::SimplePool(pool),
+            StoredRefPool::StableSwapPool(pool) => {
+                RefPool::StableSwapPool(pool.into_pool(STABLE_SWAP_DECIMALS)?)
+            }
+            StoredRefPool::RatedSwapPool(pool) => {
+                RefPool::RatedSwapPool(pool.into_pool(RATED_SWAP_DECIMALS)?)
+            }
+        })
+    }
+}
+
+impl StoredStableSwapPool {
+    fn into_pool(self, comparable_decimals: i64) -> Option<StableSwapPool> {
+        if self.c_amounts.0.len() != self.token_decimals.len() {
+            return None;
+        }
+        // Rounded down, like the contract does when converting back
+        let amounts = self
+            .c_amounts
+            .0
+            .into_iter()
+            .zip(&self.token_decimals)
+            .map(|(amount, &decimals)| {
+                let exponent = i64::from(decimals) - comparable_decimals;
+                let scale = BigDecimal::from_str(&format!("1e{exponent}")).unwrap();
+                (amount * scale).with_scale(0)
+            })
+            .collect();
+        Some(StableSwapPool {
+            token_account_ids: self.token_account_ids,
+            token_decimals: self.token_decimals,
+            amounts: VecBalance(amounts),
+            total_fee: self.total_fee,
+            shares_total_supply: self.shares_total_supply,
+            init_amp_factor: self.init_amp_factor,
+            target_amp_factor: self.target_amp_factor,
+            init_amp_time: self.init_amp_time,
+            stop_amp_time: self.stop_amp_time,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stable_swap_amounts_are_in_token_decimals() {
+        let pool = PoolState::from(serde_json::json!({
+            "StableSwapPool": {
+                "token_account_ids": ["usdt.tether-token.near", "dai.near"],
+                "token_decimals": [6, 18],
+                "c_amounts": ["1234567890123456789", "5000000000000000000"],
+                "total_fee": 5,
+                "shares_total_supply": "6000000000000000000",
+                "init_amp_factor": 240,
+                "target_amp_factor": 240,
+                "init_amp_time": 0,
+                "stop_amp_time": 0,
+            }
+        }));
+        let PoolState::Ref(RefPool::StableSwapPool(pool)) = pool else {
+            panic!("Expected a stable swap pool, got {pool:?}");
+        };
+        assert_eq!(
+            serde_json::to_value(&pool.amounts).unwrap(),
+            serde_json::json!(["1234567", "5000000000000000000"])
+        );
+    }
+
+    #[test]
+    fn unknown_pools_are_kept_as_stored() {
+        let stored = serde_json::json!({"DegenSwapPool": {"token_account_ids": []}});
+        assert!(
+            matches!(PoolState::from(stored.clone()), PoolState::Raw(value) if value == stored)
+        );
+    }
+}
@@ -0,0 +1,307 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::ref_pool::PoolState;
+use crate::utils::{AnyAccountId, AnyOf, Balance, VecBalance};
+
+pub type TransactionId = String;
+pub type ReceiptId = String;
+pub type AccountId = String;
+pub type BlockHeight = i64;
+pub type PoolId = String;
+
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "sqlx", derive(sqlx::FromRow))]
+pub struct TradePoolEvent {
+    pub trader: AccountId,
+    pub block_height: BlockHeight,
+    #[cfg_attr(feature = "utoipa", schema(value_type = i64))]
+    #[serde(
+        with = "chrono::serde::ts_nanoseconds",
+        rename = "block_timestamp_nanosec"
+    )]
+    pub timestamp: DateTime<Utc>,
+    pub transaction_id: TransactionId,
+    pub receipt_id: ReceiptId,
+
+    pub pool: PoolId,
+    pub token_in: AccountId,
+    pub token_out: AccountId,
+    #[cfg_attr(feature = "sqlx", sqlx(try_from = "bigdecimal::BigDecimal"))]
+    pub amount_in: Balance,
+    #[cfg_attr(feature = "sqlx", sqlx(try_from = "bigdecimal::BigDecimal"))]
+    pub amount_out: Balance,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "sqlx", derive(sqlx::FromRow))]
+pub struct TradeLiquidityAddEvent {
+    /// Pool id in format `REF-<number>`
+    pub pool_id: PoolId,
+    /// Account that added the liquidity
+    pub account_id: AccountId,
+    /// Tokens of the pool
+    pub token_ids: Vec<AccountId>,
+    /// Amounts of `token_ids` that were added
+    #[cfg_attr(feature = "sqlx", sqlx(try_from = "Vec<bigdecimal::BigDecimal>"))]
+    pub amounts: VecBalance,
+    /// Pool shares that the account received
+    #[cfg_attr(feature = "sqlx", sqlx(try_from = "bigdecimal::BigDecimal"))]
+    pub shares: Balance,
+
+    pub transaction_id: TransactionId,
+    pub receipt_id: ReceiptId,
+    pub block_height: BlockHeight,
+    #[cfg_attr(feature = "utoipa", schema(value_type = i64))]
+    #[serde(
+        with = "chrono::serde::ts_nanoseconds",
+        rename = "block_timestamp_nanosec"
+    )]
+    pub timestamp: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "sqlx", derive(sqlx::FromRow))]
+pub struct TradeLiquidityRemoveEvent {
+    /// Pool id in format `REF-<number>`
+    pub pool_id: PoolId,
+    /// Account that removed the liquidity
+    pub account_id: AccountId,
+    /// Tokens of the pool
+    pub token_ids: Vec<AccountId>,
+    /// Amounts of `token_ids` that were removed
+    #[cfg_attr(feature = "sqlx", sqlx(try_from = "Vec<bigdecimal::BigDecimal>"))]
+    pub amounts: VecBalance,
+    /// Pool shares that were burned
+    #[cfg_attr(feature = "sqlx", sqlx(try_from = "bigdecimal::BigDecimal"))]
+    pub shares: Balance,
+
+    pub transaction_id: TransactionId,
+    pub receipt_id: ReceiptId,
+    pub block_height: BlockHeight,
+    #[cfg_attr(feature = "utoipa", schema(value_type = i64))]
+    #[serde(
+        with = "chrono::serde::ts_nanoseconds",
+        rename = "block_timestamp_nanosec"
+    )]
+    pub timestamp: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "sqlx", derive(sqlx::FromRow))]
+pub struct TradeWrapEvent {
+    /// Account that wrapped NEAR
+    pub account_id: AccountId,
+    /// Amount of NEAR that was wrapped, in yoctoNEAR
+    #[cfg_attr(feature = "sqlx", sqlx(try_from = "bigdecimal::BigDecimal"))]
+    pub amount: Balance,
+
+    pub transaction_id: TransactionId,
+    pub receipt_id: ReceiptId,
+    pub block_height: BlockHeight,
+    #[cfg_attr(feature = "utoipa", schema(value_type = i64))]
+    #[serde(
+        with = "chrono::serde::ts_nanoseconds",
+        rename = "block_timestamp_nanosec"
+    )]
+    pub timestamp: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "sqlx", derive(sqlx::FromRow))]
+pub struct TradeUnwrapEvent {
+    /// Account that unwrapped wNEAR
+    pub account_id: AccountId,
+    /// Amount of wNEAR that was unwrapped, in yoctoNEAR
+    #[cfg_attr(feature = "sqlx", sqlx(try_from = "bigdecimal::BigDecimal"))]
+    pub amount: Balance,
+
+    pub transaction_id: TransactionId,
+    pub receipt_id: ReceiptId,
+    pub block_height: BlockHeight,
+    #[cfg_attr(feature = "utoipa", schema(value_type = i64))]
+    #[serde(
+        with = "chrono::serde::ts_nanoseconds",
+        rename = "block_timestamp_nanosec"
+    )]
+    pub timestamp: DateTime<Utc>,
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[cfg_attr(
+    feature = "utoipa",
+    derive(utoipa::IntoParams),
+    into_params(parameter_in = Query)
+)]
+pub struct TradePoolFilter {
+    /// Pool id in format `REF-<number>`
+    pub pool_id: Option<AnyOf>,
+    /// Account of the trader
+    pub account_id: Option<AnyAccountId>,
+    /// Hash of the transaction that emitted the event
+    pub transaction_id: Option<AnyOf>,
+    /// Id of the receipt that emitted the event
+    pub receipt_id: Option<AnyOf>,
+    /// Minimum `amount_in`, inclusive, e.g. to track whales
+    pub min_amount: Option<Balance>,
+    /// Maximum `amount_in`, inclusive, e.g. to skip dust
+    pub max_amount: Option<Balance>,
+    /// Excludes events where `trader` is any of these accounts, e.g. known bots
+    pub exclude_account_id: Option<AnyAccountId>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "sqlx", derive(sqlx::FromRow))]
+pub struct TradeSwapEvent {
+    pub trader: AccountId,
+    pub block_height: BlockHeight,
+    #[cfg_attr(feature = "utoipa", schema(value_type = i64))]
+    #[serde(
+        with = "chrono::serde::ts_nanoseconds",
+        rename = "block_timestamp_nanosec"
+    )]
+    pub timestamp: DateTime<Utc>,
+    pub transaction_id: TransactionId,
+    pub receipt_id: ReceiptId,
+
+    #[cfg_attr(feature = "utoipa", schema(value_type = Object))]
+    pub balance_changes: Value, // account_id: String -> balance_change: Balance
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[cfg_attr(
+    feature = "utoipa",
+    derive(utoipa::IntoParams),
+    into_params(parameter_in = Query)
+)]
+pub struct TradeSwapFilter {
+    /// Account of the trader
+    pub account_id: Option<AnyAccountId>,
+    /// Comma-separated list of token contracts whose balances must all be changed by the swap
+    pub involved_token_account_ids: Option<AnyAccountId>,
+    /// Hash of the transaction that emitted the event
+    pub transaction_id: Option<AnyOf>,
+    /// Id of the receipt that emitted the event
+    pub receipt_id: Option<AnyOf>,
+    /// Only swaps where the trader's balance of any of these tokens increased
+    pub token_bought: Option<AnyAccountId>,
+    /// Only swaps where the trader's balance of any of these tokens decreased
+    pub token_sold: Option<AnyAccountId>,
+    /// Excludes events where `trader` is any of these accounts, e.g. known bots
+    pub exclude_account_id: Option<AnyAccountId>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "sqlx", derive(sqlx::FromRow))]
+pub struct TradePoolChangeEvent {
+    pub pool_id: PoolId,
+    pub receipt_id: ReceiptId,
+    #[cfg_attr(feature = "utoipa", schema(value_type = i64))]
+    #[serde(
+        with = "chrono::serde::ts_nanoseconds",
+        rename = "block_timestamp_nanosec"
+    )]
+    pub timestamp: DateTime<Utc>,
+    pub block_height: BlockHeight,
+    #[cfg_attr(feature = "sqlx", sqlx(try_from = "Value"))]
+    pub pool: PoolState,
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[cfg_attr(
+    feature = "utoipa",
+    derive(utoipa::IntoParams),
+    into_params(parameter_in = Query)
+)]
+pub struct TradePoolChangeFilter {
+    /// Pool id in format `REF-<number>`
+    pub pool_id: Option<AnyOf>,
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[cfg_attr(
+    feature = "utoipa",
+    derive(utoipa::IntoParams),
+    into_params(parameter_in = Query)
+)]
+pub struct TradeLiquidityAddFilter {
+    /// Pool id in format `REF-<number>`
+    pub pool_id: Option<AnyOf>,
+    /// Account that added the liquidity
+    pub account_id: Option<AnyAccountId>,
+    /// Only pools with any of these tokens
+    pub token_id: Option<AnyAccountId>,
+    /// Hash of the transaction that emitted the event
+    pub transaction_id: Option<AnyOf>,
+    /// Id of the receipt that emitted the event
+    pub receipt_id: Option<AnyOf>,
+    /// Excludes events where `account_id` is any of these accounts, e.g.
+    /// known bots
+    pub exclude_account_id: Option<AnyAccountId>,
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[cfg_attr(
+    feature = "utoipa",
+    derive(utoipa::IntoParams),
+    into_params(parameter_in = Query)
+)]
+pub struct TradeLiquidityRemoveFilter {
+    /// Pool id in format `REF-<number>`
+    pub pool_id: Option<AnyOf>,
+    /// Account that removed the liquidity
+    pub account_id: Option<AnyAccountId>,
+    /// Only pools with any of these tokens
+    pub token_id: Option<AnyAccountId>,
+    /// Hash of the transaction that emitted the event
+    pub transaction_id: Option<AnyOf>,
+    /// Id of the receipt that emitted the event
+    pub receipt_id: Option<AnyOf>,
+    /// Excludes events where `account_id` is any of these accounts, e.g.
+    /// known bots
+    pub exclude_account_id: Option<AnyAccountId>,
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[cfg_attr(
+    feature = "utoipa",
+    derive(utoipa::IntoParams),
+    into_params(parameter_in = Query)
+)]
+pub struct TradeWrapFilter {
+    /// Account that wrapped NEAR
+    pub account_id: Option<AnyAccountId>,
+    /// Hash of the transaction that emitted the event
+    pub transaction_id: Option<AnyOf>,
+    /// Id of the receipt that emitted the event
+    pub receipt_id: Option<AnyOf>,
+    /// Excludes events where `account_id` is any of these accounts, e.g.
+    /// known bots
+    pub exclude_account_id: Option<AnyAccountId>,
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[cfg_attr(
+    feature = "utoipa",
+    derive(utoipa::IntoParams),
+    into_params(parameter_in = Query)
+)]
+pub struct TradeUnwrapFilter {
+    /// Account that unwrapped wNEAR
+    pub account_id: Option<AnyAccountId>,
+    /// Hash of the transaction that emitted the event
+    pub transaction_id: Option<AnyOf>,
+    /// Id of the receipt that emitted the event
+    pub receipt_id: Option<AnyOf>,
+    /// Excludes events where `account_id` is any of these accounts, e.g.
+    /// known bots
+    pub exclude_account_id: Option<AnyAccountId>,
+}
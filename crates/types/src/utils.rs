@@ -0,0 +1,498 @@
+use std::{ops::Deref, str::FromStr};
+
+use bigdecimal::BigDecimal;
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "utoipa")]
+use utoipa::{
+    openapi::{
+        schema::{ArrayBuilder, Object, ObjectBuilder, SchemaType, Type},
+        RefOr, Schema,
+    },
+    PartialSchema, ToSchema,
+};
+
+/// Parses a comma-separated list query parameter, e.g. `involved_account_ids`
+pub fn split_list(s: &str) -> Vec<String> {
+    s.split(',').map(ToOwned::to_owned).collect()
+}
+
+/// Value of a filter that matches any of the values of a comma-separated
+/// list, e.g. `project_id=a.near,b.near`
+#[derive(Debug, Clone)]
+pub struct AnyOf(pub Vec<String>);
+
+impl Deref for AnyOf {
+    type Target = [String];
+
+    fn deref(&self) -> &[String] {
+        &self.0
+    }
+}
+
+impl Serialize for AnyOf {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        self.0.join(",").serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for AnyOf {
+    fn deserialize<D>(deserializer: D) -> Result<AnyOf, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        Ok(AnyOf(split_list(&String::deserialize(deserializer)?)))
+    }
+}
+
+/// Value of a filter on a numeric id that matches any of the ids of a
+/// comma-separated list, e.g. `meme_id=1,2`. Ids that aren't numbers are
+/// rejected instead of matching no events.
+#[derive(Debug, Clone)]
+pub struct AnyId(pub Vec<i64>);
+
+impl Deref for AnyId {
+    type Target = [i64];
+
+    fn deref(&self) -> &[i64] {
+        &self.0
+    }
+}
+
+impl Serialize for AnyId {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        self.0
+            .iter()
+            .map(i64::to_string)
+            .collect::<Vec<_>>()
+            .join(",")
+            .serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for AnyId {
+    fn deserialize<D>(deserializer: D) -> Result<AnyId, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        split_list(&String::deserialize(deserializer)?)
+            .into_iter()
+            .map(|id| {
+                id.parse()
+                    .map_err(|_| serde::de::Error::custom(format!("`{id}` is not a number")))
+            })
+            .collect::<Result<_, _>>()
+            .map(AnyId)
+    }
+}
+
+/// NEAR account id that's validated when parsed from a filter, so that a
+/// malformed id is rejected instead of matching no events
+#[derive(Debug, Clone)]
+pub struct ValidAccountId(String);
+
+impl ValidAccountId {
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    pub fn into_inner(self) -> String {
+        self.0
+    }
+}
+
+impl Deref for ValidAccountId {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl FromStr for ValidAccountId {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if is_valid_account_id(s) {
+            Ok(ValidAccountId(s.to_owned()))
+        } else {
+            Err(format!("`{s}` is not a valid NEAR account id"))
+        }
+    }
+}
+
+impl Serialize for ValidAccountId {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        self.0.serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for ValidAccountId {
+    fn deserialize<D>(deserializer: D) -> Result<ValidAccountId, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(serde::de::Error::custom)
+    }
+}
+
+/// Account ids are 2 to 64 characters long, and consist of parts separated
+/// by `.`, e.g. `sub.account.near`. Parts are lowercase letters and digits,
+/// which can be separated by a single `-` or `_`. Implicit accounts, 64 hex
+/// characters or `0x` and 40 hex characters, follow the same rules.
+pub fn is_valid_account_id(account_id: &str) -> bool {
+    (2..=64).contains(&account_id.len())
+        && account_id.split('.').all(|part| {
+            part.split(['-', '_']).all(|word| {
+                !word.is_empty()
+                    && word
+                        .bytes()
+                        .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit())
+            })
+        })
+}
+
+/// Same as `AnyOf`, for filters by account, where every value must be a
+/// valid NEAR account id
+#[derive(Debug, Clone)]
+pub struct AnyAccountId(pub Vec<String>);
+
+impl Deref for AnyAccountId {
+    type Target = [String];
+
+    fn deref(&self) -> &[String] {
+        &self.0
+    }
+}
+
+impl Serialize for AnyAccountId {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        self.0.join(",").serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for AnyAccountId {
+    fn deserialize<D>(deserializer: D) -> Result<AnyAccountId, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        split_list(&String::deserialize(deserializer)?)
+            .into_iter()
+            .map(|account_id| ValidAccountId::from_str(&account_id).map(|_| account_id))
+            .collect::<Result<_, _>>()
+            .map(AnyAccountId)
+            .map_err(serde::de::Error::custom)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Balance(pub BigDecimal);
+
+impl From<BigDecimal> for Balance {
+    fn from(value: BigDecimal) -> Self {
+        Balance(value)
+    }
+}
+
+impl Serialize for Balance {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        self.0.to_string().serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Balance {
+    fn deserialize<D>(deserializer: D) -> Result<Balance, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let s = String::deserialize(deserializer)?;
+        Ok(Balance(
+            BigDecimal::from_str(&s).map_err(serde::de::Error::custom)?,
+        ))
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct OptionalBalance(pub Option<BigDecimal>);
+
+impl From<Option<BigDecimal>> for OptionalBalance {
+    fn from(value: Option<BigDecimal>) -> Self {
+        OptionalBalance(value)
+    }
+}
+
+impl Serialize for OptionalBalance {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        self.0.as_ref().map(|v| v.to_string()).serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for OptionalBalance {
+    fn deserialize<D>(deserializer: D) -> Result<OptionalBalance, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let s = Option::<String>::deserialize(deserializer)?;
+        Ok(OptionalBalance(
+            s.map(|s| BigDecimal::from_str(&s).map_err(serde::de::Error::custom))
+                .transpose()?,
+        ))
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct VecBalance(pub Vec<BigDecimal>);
+
+impl From<Vec<BigDecimal>> for VecBalance {
+    fn from(value: Vec<BigDecimal>) -> Self {
+        VecBalance(value)
+    }
+}
+
+impl Serialize for VecBalance {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        self.0
+            .iter()
+            .map(|v| v.to_string())
+            .collect::<Vec<String>>()
+            .serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for VecBalance {
+    fn deserialize<D>(deserializer: D) -> Result<VecBalance, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let s = Vec::<String>::deserialize(deserializer)?;
+        Ok(VecBalance(
+            s.into_iter()
+                .map(|s| BigDecimal::from_str(&s).map_err(serde::de::Error::custom))
+                .collect::<Result<Vec<BigDecimal>, _>>()?,
+        ))
+    }
+}
+
+/// Description of balances in the schema, which tells them apart from other
+/// strings
+#[cfg(feature = "utoipa")]
+pub const BALANCE_DESCRIPTION: &str = "Integer amount as a decimal string";
+
+/// Balances are serialized as strings, since they don't fit in JSON numbers
+#[cfg(feature = "utoipa")]
+fn balance_schema() -> Object {
+    ObjectBuilder::new()
+        .schema_type(Type::String)
+        .description(Some(BALANCE_DESCRIPTION))
+        .examples([serde_json::json!("1000000000000000000000000")])
+        .build()
+}
+
+#[cfg(feature = "utoipa")]
+impl PartialSchema for Balance {
+    fn schema() -> RefOr<Schema> {
+        balance_schema().into()
+    }
+}
+
+#[cfg(feature = "utoipa")]
+impl ToSchema for Balance {}
+
+#[cfg(feature = "utoipa")]
+impl PartialSchema for AnyOf {
+    fn schema() -> RefOr<Schema> {
+        ObjectBuilder::new()
+            .schema_type(Type::String)
+            .description(Some(
+                "A value, or a comma-separated list of values to match any of",
+            ))
+            .build()
+            .into()
+    }
+}
+
+#[cfg(feature = "utoipa")]
+impl ToSchema for AnyOf {}
+
+#[cfg(feature = "utoipa")]
+impl PartialSchema for AnyId {
+    fn schema() -> RefOr<Schema> {
+        ObjectBuilder::new()
+            .schema_type(Type::String)
+            .description(Some(
+                "A number, or a comma-separated list of numbers to match any of",
+            ))
+            .examples([serde_json::json!("1,2")])
+            .build()
+            .into()
+    }
+}
+
+#[cfg(feature = "utoipa")]
+impl ToSchema for AnyId {}
+
+#[cfg(feature = "utoipa")]
+impl PartialSchema for AnyAccountId {
+    fn schema() -> RefOr<Schema> {
+        ObjectBuilder::new()
+            .schema_type(Type::String)
+            .description(Some(
+                "An account id, or a comma-separated list of account ids to match any of",
+            ))
+            .examples([serde_json::json!("alice.near,bob.tg")])
+            .build()
+            .into()
+    }
+}
+
+#[cfg(feature = "utoipa")]
+impl ToSchema for AnyAccountId {}
+
+#[cfg(feature = "utoipa")]
+impl PartialSchema for ValidAccountId {
+    fn schema() -> RefOr<Schema> {
+        ObjectBuilder::new()
+            .schema_type(Type::String)
+            .description(Some("NEAR account id"))
+            .min_length(Some(2))
+            .max_length(Some(64))
+            .examples([serde_json::json!("alice.near")])
+            .build()
+            .into()
+    }
+}
+
+#[cfg(feature = "utoipa")]
+impl ToSchema for ValidAccountId {}
+
+#[cfg(feature = "utoipa")]
+impl PartialSchema for OptionalBalance {
+    fn schema() -> RefOr<Schema> {
+        let mut schema = balance_schema();
+        schema.schema_type = SchemaType::from_iter([Type::String, Type::Null]);
+        schema.into()
+    }
+}
+
+#[cfg(feature = "utoipa")]
+impl ToSchema for OptionalBalance {}
+
+#[cfg(feature = "utoipa")]
+impl PartialSchema for VecBalance {
+    fn schema() -> RefOr<Schema> {
+        ArrayBuilder::new().items(balance_schema()).build().into()
+    }
+}
+
+#[cfg(feature = "utoipa")]
+impl ToSchema for VecBalance {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    /// Decimal strings covering yocto amounts, values that don't fit in u128,
+    /// negative balance deltas and fractional parts.
+    fn big_decimal() -> impl Strategy<Value = BigDecimal> {
+        prop_oneof![
+            any::<u128>().prop_map(|v| v.to_string()),
+            "-?[1-9][0-9]{39,80}",
+            "-?(0|[1-9][0-9]{0,40})\\.[0-9]{1,30}",
+        ]
+        .prop_map(|s| BigDecimal::from_str(&s).unwrap())
+    }
+
+    proptest! {
+        #[test]
+        fn balance_roundtrip(value in big_decimal()) {
+            let json = serde_json::to_string(&Balance(value.clone())).unwrap();
+            prop_assert_eq!(&json, &format!("\"{value}\""));
+            let parsed: Balance = serde_json::from_str(&json).unwrap();
+            prop_assert_eq!(parsed.0, value);
+        }
+
+        #[test]
+        fn optional_balance_roundtrip(value in proptest::option::of(big_decimal())) {
+            let json = serde_json::to_string(&OptionalBalance(value.clone())).unwrap();
+            if value.is_none() {
+                prop_assert_eq!(&json, "null");
+            }
+            let parsed: OptionalBalance = serde_json::from_str(&json).unwrap();
+            prop_assert_eq!(parsed.0, value);
+        }
+
+        #[test]
+        fn vec_balance_roundtrip(values in proptest::collection::vec(big_decimal(), 0..16)) {
+            let json = serde_json::to_string(&VecBalance(values.clone())).unwrap();
+            let parsed: VecBalance = serde_json::from_str(&json).unwrap();
+            prop_assert_eq!(parsed.0, values);
+        }
+    }
+
+    #[test]
+    fn account_id_validation() {
+        for valid in [
+            "near",
+            "alice.near",
+            "v1.potfactory.potlock.near",
+            "wrap_token-v2.near",
+            "0x1234567890abcdef1234567890abcdef12345678",
+            "98793cd91a3f870fb126f66285808c7e094afcfc4eda8a970f6648cdf0dbd6de",
+        ] {
+            assert!(is_valid_account_id(valid), "{valid} should be valid");
+        }
+        for invalid in [
+            "",
+            "a",
+            "Alice.near",
+            "alice..near",
+            ".near",
+            "alice.",
+            "alice-.near",
+            "al--ice.near",
+            "alice near",
+            "alice@near",
+            "a".repeat(65).as_str(),
+        ] {
+            assert!(!is_valid_account_id(invalid), "{invalid} should be invalid");
+        }
+    }
+
+    #[test]
+    fn any_account_id_rejects_malformed_ids() {
+        let parsed: AnyAccountId = serde_json::from_str("\"a.near,b.near\"").unwrap();
+        assert_eq!(&*parsed, ["a.near", "b.near"]);
+        assert!(serde_json::from_str::<AnyAccountId>("\"a.near,B.near\"").is_err());
+        assert!(serde_json::from_str::<AnyAccountId>("\"a.near,\"").is_err());
+    }
+
+    #[test]
+    fn balance_rejects_non_numeric_strings() {
+        assert!(serde_json::from_str::<Balance>("\"1e\"").is_err());
+        assert!(serde_json::from_str::<Balance>("1000").is_err());
+        assert!(serde_json::from_str::<VecBalance>("[\"1\", \"abc\"]").is_err());
+    }
+}
@@ -0,0 +1,12 @@
+CREATE TABLE IF NOT EXISTS digest_subscriptions (
+    id BIGSERIAL PRIMARY KEY,
+    kind TEXT NOT NULL,
+    filter_id TEXT NOT NULL,
+    schedule TEXT NOT NULL,
+    webhook_url TEXT NOT NULL,
+    token TEXT NOT NULL DEFAULT gen_random_uuid()::TEXT,
+    next_run_at TIMESTAMPTZ NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE INDEX IF NOT EXISTS digest_subscriptions_next_run_at_idx ON digest_subscriptions (next_run_at);
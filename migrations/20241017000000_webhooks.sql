@@ -0,0 +1,15 @@
+CREATE TABLE IF NOT EXISTS webhooks (
+    id BIGSERIAL PRIMARY KEY,
+    event_type TEXT NOT NULL,
+    filter TEXT NOT NULL,
+    url TEXT NOT NULL,
+    secret TEXT NOT NULL,
+    token TEXT NOT NULL DEFAULT gen_random_uuid()::TEXT,
+    cursor_nanosec BIGINT NOT NULL,
+    failures INT NOT NULL DEFAULT 0,
+    last_error TEXT,
+    next_attempt_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE INDEX IF NOT EXISTS webhooks_next_attempt_at_idx ON webhooks (next_attempt_at);
@@ -0,0 +1,8 @@
+ALTER TABLE digest_subscriptions ADD COLUMN IF NOT EXISTS failures INT NOT NULL DEFAULT 0;
+ALTER TABLE digest_subscriptions ADD COLUMN IF NOT EXISTS last_error TEXT;
+ALTER TABLE digest_subscriptions ADD COLUMN IF NOT EXISTS next_attempt_at TIMESTAMPTZ;
+UPDATE digest_subscriptions SET next_attempt_at = next_run_at WHERE next_attempt_at IS NULL;
+ALTER TABLE digest_subscriptions ALTER COLUMN next_attempt_at SET NOT NULL;
+
+DROP INDEX IF EXISTS digest_subscriptions_next_run_at_idx;
+CREATE INDEX IF NOT EXISTS digest_subscriptions_next_attempt_at_idx ON digest_subscriptions (next_attempt_at);
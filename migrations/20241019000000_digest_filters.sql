@@ -0,0 +1,17 @@
+ALTER TABLE digest_subscriptions ADD COLUMN IF NOT EXISTS event_type TEXT;
+ALTER TABLE digest_subscriptions ADD COLUMN IF NOT EXISTS filter TEXT;
+
+-- Digests of a kind and an id become the same filter of the kind's endpoint
+UPDATE digest_subscriptions
+SET event_type = kind,
+    filter = CASE kind
+        WHEN 'nft_transfer' THEN 'contract_id='
+        WHEN 'potlock_donation' THEN 'project_id='
+        WHEN 'trade_pool' THEN 'pool_id='
+    END || replace(replace(replace(filter_id, '%', '%25'), '&', '%26'), '+', '%2B')
+WHERE event_type IS NULL;
+
+ALTER TABLE digest_subscriptions ALTER COLUMN event_type SET NOT NULL;
+ALTER TABLE digest_subscriptions ALTER COLUMN filter SET NOT NULL;
+ALTER TABLE digest_subscriptions DROP COLUMN IF EXISTS kind;
+ALTER TABLE digest_subscriptions DROP COLUMN IF EXISTS filter_id;
@@ -0,0 +1,169 @@
+use chrono::prelude::{DateTime, Utc};
+pub use inteat_events_api_types::account::{
+    AccountCreateEvent, AccountCreateFilter, AccountDeleteEvent, AccountDeleteFilter,
+    KeyAddedEvent, KeyAddedFilter, KeyDeletedEvent, KeyDeletedFilter,
+};
+
+use crate::endpoint::{event_endpoint, EventEndpoint};
+use crate::event_query::EventQuery;
+use crate::meta::EventTable;
+
+impl EventEndpoint for AccountCreateEvent {
+    const TABLE: EventTable = EventTable::AccountCreate;
+    const SUM_FIELDS: &'static [(&'static str, &'static str)] =
+        &[("initial_balance", "initial_balance")];
+    type Filter = AccountCreateFilter;
+
+    fn query(filter: &AccountCreateFilter) -> EventQuery<Self> {
+        EventQuery::new(
+            "account_create",
+            "account_id, creator_id, initial_balance, transaction_id, receipt_id, block_height, timestamp",
+        )
+        .filter("account_id = ANY({})", filter.account_id.as_deref())
+        .filter("creator_id = ANY({})", filter.creator_id.as_deref())
+        .filter(
+            "EXISTS (SELECT FROM unnest({}) s WHERE right(account_id, length(s)) = s)",
+            filter.suffix.as_deref(),
+        )
+        .filter("transaction_id = ANY({})", filter.transaction_id.as_deref())
+        .filter("receipt_id = ANY({})", filter.receipt_id.as_deref())
+        .filter("creator_id <> ALL({})", filter.exclude_account_id.as_deref())
+    }
+
+    fn timestamp(&self) -> DateTime<Utc> {
+        self.timestamp
+    }
+
+    fn receipt_id(&self) -> &str {
+        &self.receipt_id
+    }
+
+    fn transaction(&self) -> (&str, &str) {
+        (&self.transaction_id, &self.creator_id)
+    }
+}
+
+event_endpoint! {
+    /// Account creation events
+    account_create(AccountCreateEvent, AccountCreateFilter),
+    route = "/account_create",
+    scope = "/v0/account",
+    tag = "Account",
+}
+
+impl EventEndpoint for AccountDeleteEvent {
+    const TABLE: EventTable = EventTable::AccountDelete;
+    type Filter = AccountDeleteFilter;
+
+    fn query(filter: &AccountDeleteFilter) -> EventQuery<Self> {
+        EventQuery::new(
+            "account_delete",
+            "account_id, beneficiary_id, transaction_id, receipt_id, block_height, timestamp",
+        )
+        .filter("account_id = ANY({})", filter.account_id.as_deref())
+        .filter("beneficiary_id = ANY({})", filter.beneficiary_id.as_deref())
+        .filter(
+            "EXISTS (SELECT FROM unnest({}) s WHERE right(account_id, length(s)) = s)",
+            filter.suffix.as_deref(),
+        )
+        .filter("transaction_id = ANY({})", filter.transaction_id.as_deref())
+        .filter("receipt_id = ANY({})", filter.receipt_id.as_deref())
+        .filter(
+            "account_id <> ALL({})",
+            filter.exclude_account_id.as_deref(),
+        )
+    }
+
+    fn timestamp(&self) -> DateTime<Utc> {
+        self.timestamp
+    }
+
+    fn receipt_id(&self) -> &str {
+        &self.receipt_id
+    }
+
+    fn transaction(&self) -> (&str, &str) {
+        (&self.transaction_id, &self.account_id)
+    }
+}
+
+event_endpoint! {
+    /// Account deletion events
+    account_delete(AccountDeleteEvent, AccountDeleteFilter),
+    route = "/account_delete",
+    scope = "/v0/account",
+    tag = "Account",
+}
+
+impl EventEndpoint for KeyAddedEvent {
+    const TABLE: EventTable = EventTable::KeyAdded;
+    type Filter = KeyAddedFilter;
+
+    fn query(filter: &KeyAddedFilter) -> EventQuery<Self> {
+        EventQuery::new(
+            "key_added",
+            "account_id, public_key, full_access, receiver_id, method_names, allowance, transaction_id, receipt_id, block_height, timestamp",
+        )
+        .filter("account_id = ANY({})", filter.account_id.as_deref())
+        .filter("public_key = ANY({})", filter.public_key.as_deref())
+        .filter("full_access = {}", filter.full_access)
+        .filter("transaction_id = ANY({})", filter.transaction_id.as_deref())
+        .filter("receipt_id = ANY({})", filter.receipt_id.as_deref())
+    }
+
+    fn timestamp(&self) -> DateTime<Utc> {
+        self.timestamp
+    }
+
+    fn receipt_id(&self) -> &str {
+        &self.receipt_id
+    }
+
+    fn transaction(&self) -> (&str, &str) {
+        (&self.transaction_id, &self.account_id)
+    }
+}
+
+event_endpoint! {
+    /// Access key addition events
+    key_added(KeyAddedEvent, KeyAddedFilter),
+    route = "/key_added",
+    scope = "/v0/account",
+    tag = "Account",
+}
+
+impl EventEndpoint for KeyDeletedEvent {
+    const TABLE: EventTable = EventTable::KeyDeleted;
+    type Filter = KeyDeletedFilter;
+
+    fn query(filter: &KeyDeletedFilter) -> EventQuery<Self> {
+        EventQuery::new(
+            "key_deleted",
+            "account_id, public_key, transaction_id, receipt_id, block_height, timestamp",
+        )
+        .filter("account_id = ANY({})", filter.account_id.as_deref())
+        .filter("public_key = ANY({})", filter.public_key.as_deref())
+        .filter("transaction_id = ANY({})", filter.transaction_id.as_deref())
+        .filter("receipt_id = ANY({})", filter.receipt_id.as_deref())
+    }
+
+    fn timestamp(&self) -> DateTime<Utc> {
+        self.timestamp
+    }
+
+    fn receipt_id(&self) -> &str {
+        &self.receipt_id
+    }
+
+    fn transaction(&self) -> (&str, &str) {
+        (&self.transaction_id, &self.account_id)
+    }
+}
+
+event_endpoint! {
+    /// Access key deletion events
+    key_deleted(KeyDeletedEvent, KeyDeletedFilter),
+    route = "/key_deleted",
+    scope = "/v0/account",
+    tag = "Account",
+}
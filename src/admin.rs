@@ -0,0 +1,201 @@
+use actix_web::{
+    body::{EitherBody, MessageBody},
+    dev::{ServiceFactory, ServiceRequest, ServiceResponse},
+    get,
+    http::header,
+    middleware::{self, Next},
+    post, web, Error, HttpRequest, HttpResponse, Responder, Scope,
+};
+use serde::{de::DeserializeOwned, Deserialize};
+use serde_json::{json, Value};
+use sqlx::{postgres::PgArguments, query::Query, Decode, PgPool, Postgres, Row, Type};
+use subtle::ConstantTimeEq;
+
+use crate::{
+    meta::EventTable,
+    registry::{self, with_event_type},
+    toggles::EndpointToggles,
+    AppState, PaginationInfo,
+};
+
+/// Bearer token required for everything under `/admin`, from `ADMIN_TOKEN`
+pub struct AdminToken(pub String);
+
+pub fn scope() -> Scope<
+    impl ServiceFactory<
+        ServiceRequest,
+        Config = (),
+        Response = ServiceResponse<impl MessageBody>,
+        Error = Error,
+        InitError = (),
+    >,
+> {
+    web::scope("/admin")
+        .service(list_disabled_endpoints)
+        .service(disable_endpoint)
+        .service(enable_endpoint)
+        .service(reconnect_database)
+        .service(explain)
+        .wrap(middleware::from_fn(require_admin_token))
+}
+
+async fn require_admin_token(
+    token: web::Data<AdminToken>,
+    req: ServiceRequest,
+    next: Next<impl MessageBody>,
+) -> Result<ServiceResponse<EitherBody<impl MessageBody>>, Error> {
+    // Compared in constant time, so that the token can't be guessed byte by
+    // byte from response times
+    let authorized = req
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .is_some_and(|value| bool::from(value.as_bytes().ct_eq(token.0.as_bytes())));
+    if !authorized {
+        return Ok(req
+            .into_response(HttpResponse::Unauthorized().finish())
+            .map_into_right_body());
+    }
+    next.call(req)
+        .await
+        .map(ServiceResponse::map_into_left_body)
+}
+
+#[derive(Deserialize)]
+struct EndpointToggle {
+    endpoint: String,
+}
+
+#[get("/endpoints")]
+async fn list_disabled_endpoints(toggles: web::Data<EndpointToggles>) -> impl Responder {
+    HttpResponse::Ok().json(json!({ "disabled": toggles.disabled() }))
+}
+
+#[post("/endpoints/disable")]
+async fn disable_endpoint(
+    toggles: web::Data<EndpointToggles>,
+    body: web::Json<EndpointToggle>,
+) -> impl Responder {
+    log::warn!("Disabling endpoint {}", body.endpoint);
+    toggles.disable(body.into_inner().endpoint);
+    HttpResponse::Ok().json(json!({ "disabled": toggles.disabled() }))
+}
+
+#[post("/endpoints/enable")]
+async fn enable_endpoint(
+    toggles: web::Data<EndpointToggles>,
+    body: web::Json<EndpointToggle>,
+) -> impl Responder {
+    log::warn!("Enabling endpoint {}", body.endpoint);
+    toggles.enable(&body.endpoint);
+    HttpResponse::Ok().json(json!({ "disabled": toggles.disabled() }))
+}
+
+#[post("/database/reconnect")]
+async fn reconnect_database(state: web::Data<AppState>) -> impl Responder {
+    dotenvy::dotenv_override().ok();
+    match state.reconnect().await {
+        Ok(()) => {
+            log::info!("Reconnected to Postgres");
+            HttpResponse::Ok().finish()
+        }
+        Err(err) => {
+            log::error!("Failed to reconnect to Postgres: {err}");
+            HttpResponse::InternalServerError().body(err.to_string())
+        }
+    }
+}
+
+/// Failure to run an endpoint's query from raw query parameters
+pub enum QueryError {
+    BadQuery(String),
+    Database(sqlx::Error),
+}
+
+/// Query of an event endpoint, wrapped to return a single value instead of
+/// events
+#[derive(Clone, Copy)]
+pub(crate) enum Summary {
+    /// Plan of the query of a page, as the endpoint would run it
+    Plan,
+    /// Number of events in the whole time range, not just in a page
+    Count,
+}
+
+impl Summary {
+    pub(crate) fn sql(self, sql: &str) -> String {
+        match self {
+            Summary::Plan => format!("EXPLAIN (ANALYZE, BUFFERS, FORMAT JSON) {sql}"),
+            Summary::Count => format!("SELECT COUNT(*) FROM ({sql}) events"),
+        }
+    }
+
+    /// `blocks` of the query, `None` for all blocks
+    pub(crate) fn blocks(self, pagination: &PaginationInfo) -> Option<i64> {
+        match self {
+            Summary::Plan => Some(pagination.blocks),
+            Summary::Count => None,
+        }
+    }
+
+    /// Row limit of the query, `None` for all rows
+    pub(crate) fn limit(self, pagination: &PaginationInfo) -> Option<i64> {
+        match self {
+            Summary::Plan => Some(pagination.fetch_limit()),
+            Summary::Count => None,
+        }
+    }
+}
+
+/// Type of the single value returned by a summary, e.g. the plan as JSON
+pub(crate) trait SummaryValue:
+    for<'r> Decode<'r, Postgres> + Type<Postgres> + Send + Unpin
+{
+}
+
+impl<T> SummaryValue for T where T: for<'r> Decode<'r, Postgres> + Type<Postgres> + Send + Unpin {}
+
+/// Parses query parameters the same way the endpoint would
+pub(crate) fn parse_query<T: DeserializeOwned>(query: &str) -> Result<T, QueryError> {
+    web::Query::<T>::from_query(query)
+        .map(web::Query::into_inner)
+        .map_err(|err| QueryError::BadQuery(err.to_string()))
+}
+
+pub(crate) async fn fetch_summary<T: SummaryValue>(
+    query: Query<'_, Postgres, PgArguments>,
+    pg_pool: &PgPool,
+) -> Result<T, QueryError> {
+    query
+        .fetch_one(pg_pool)
+        .await
+        .and_then(|row| row.try_get(0))
+        .map_err(QueryError::Database)
+}
+
+/// Runs `EXPLAIN (ANALYZE, BUFFERS)` for an event endpoint with the given
+/// query parameters, e.g. `/admin/explain/nft_transfer?token_account_id=x.near`
+#[get("/explain/{endpoint}")]
+async fn explain(
+    state: web::Data<AppState>,
+    endpoint: web::Path<String>,
+    req: HttpRequest,
+) -> impl Responder {
+    let pg_pool = state.pg_pool();
+    let query = req.query_string();
+    let Some(table) = EventTable::ALL
+        .into_iter()
+        .find(|table| table.as_str() == endpoint.as_str())
+    else {
+        return HttpResponse::NotFound().body(format!("Unknown endpoint {endpoint}"));
+    };
+    let plan: Result<Value, QueryError> = with_event_type!(table, |E| {
+        registry::summarize::<E, _>(&pg_pool, query, Summary::Plan).await
+    });
+    match plan {
+        Ok(plan) => HttpResponse::Ok().json(plan),
+        Err(QueryError::BadQuery(err)) => HttpResponse::BadRequest().body(err),
+        Err(QueryError::Database(err)) => HttpResponse::InternalServerError().body(err.to_string()),
+    }
+}
@@ -0,0 +1,130 @@
+use actix_web::HttpResponse;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{postgres::PgArguments, query::Query, types::BigDecimal, Postgres, Row};
+
+use utoipa::IntoParams;
+
+use crate::{cancel::CancelOnDrop, error::ApiError, utils::Balance, AppState};
+
+/// Aggregation covers more blocks than a page of raw events, since only one
+/// row per bucket is returned
+pub(crate) const MAX_BLOCKS_PER_AGGREGATE: i64 = 100_000;
+
+/// `aggregate=count` or `aggregate=sum:<field>` with `bucket=<n><s|m|h|d|w>`
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub(crate) struct AggregationParams {
+    /// `count` or `sum:<field>`. Returns one row per bucket instead of events.
+    aggregate: Option<String>,
+    /// Bucket size, e.g. `5m` or `1d`. Required with `aggregate`.
+    bucket: Option<String>,
+}
+
+pub(crate) struct Aggregation {
+    value_sql: String,
+    bucket_secs: i64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct AggregateBucket {
+    #[serde(
+        with = "chrono::serde::ts_nanoseconds",
+        rename = "bucket_start_nanosec"
+    )]
+    pub bucket: DateTime<Utc>,
+    pub value: Balance,
+}
+
+impl AggregationParams {
+    /// `None` if raw events were requested. `sum_fields` maps field names
+    /// accepted in `sum:<field>` to SQL expressions over the endpoint's columns.
+    pub(crate) fn parse(
+        &self,
+        sum_fields: &[(&str, &'static str)],
+    ) -> Result<Option<Aggregation>, String> {
+        let Some(aggregate) = self.aggregate.as_deref() else {
+            return Ok(None);
+        };
+        let value_sql = if aggregate == "count" {
+            "COUNT(*)".to_string()
+        } else if let Some(field) = aggregate.strip_prefix("sum:") {
+            sum_fields
+                .iter()
+                .find(|(name, _)| *name == field)
+                .map(|(_, sql)| format!("SUM({sql})"))
+                .ok_or_else(|| {
+                    if sum_fields.is_empty() {
+                        return "This endpoint only supports aggregate=count".to_string();
+                    }
+                    let fields = sum_fields
+                        .iter()
+                        .map(|(name, _)| *name)
+                        .collect::<Vec<_>>()
+                        .join(", ");
+                    format!("Can't sum {field}, supported fields on this endpoint: {fields}")
+                })?
+        } else {
+            return Err("aggregate must be count or sum:<field>".to_string());
+        };
+        let bucket = self
+            .bucket
+            .as_deref()
+            .ok_or("bucket is required with aggregate, e.g. bucket=1h")?;
+        let bucket_secs = parse_bucket(bucket)
+            .ok_or("bucket must be a positive number followed by s, m, h, d, or w")?;
+        Ok(Some(Aggregation {
+            value_sql,
+            bucket_secs,
+        }))
+    }
+}
+
+fn parse_bucket(bucket: &str) -> Option<i64> {
+    let unit_secs = match bucket.chars().last()? {
+        's' => 1,
+        'm' => 60,
+        'h' => 60 * 60,
+        'd' => 24 * 60 * 60,
+        'w' => 7 * 24 * 60 * 60,
+        _ => return None,
+    };
+    let count: i64 = bucket[..bucket.len() - 1].parse().ok()?;
+    if count <= 0 {
+        return None;
+    }
+    count.checked_mul(unit_secs)
+}
+
+impl Aggregation {
+    /// Wraps an endpoint's events query, so it takes the same parameters.
+    /// Buckets are aligned to the unix epoch.
+    pub(crate) fn sql(&self, events_sql: &str) -> String {
+        format!(
+            "SELECT date_bin(make_interval(secs => {}), timestamp, TIMESTAMPTZ 'epoch') as bucket, \
+            COALESCE({}, 0)::NUMERIC as value \
+            FROM ({events_sql}) events \
+            GROUP BY 1 \
+            ORDER BY 1 ASC",
+            self.bucket_secs, self.value_sql,
+        )
+    }
+}
+
+pub(crate) async fn respond(
+    query: Query<'_, Postgres, PgArguments>,
+    state: &AppState,
+) -> Result<HttpResponse, ApiError> {
+    let _permit = state.heavy_queries().try_acquire()?;
+    let mut connection = CancelOnDrop::acquire(&state.pg_pool()).await?;
+    let rows = query.fetch_all(&mut *connection).await?;
+    connection.finish(rows.len());
+    let buckets = rows
+        .into_iter()
+        .map(|row| AggregateBucket {
+            bucket: row.get("bucket"),
+            value: Balance(row.get::<BigDecimal, _>("value")),
+        })
+        .collect::<Vec<_>>();
+    Ok(HttpResponse::Ok().json(buckets))
+}
@@ -0,0 +1,117 @@
+use std::collections::{BTreeMap, HashSet};
+
+use actix_web::{post, web, HttpRequest, HttpResponse};
+use futures_util::future::join_all;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::{
+    admin::QueryError,
+    error::{ApiError, ErrorDetails},
+    error_reporting, live,
+    meta::EventTable,
+    request_id, AppState,
+};
+
+/// Each query in a batch runs a database query, and they all run at once
+const MAX_QUERIES_PER_BATCH: usize = 10;
+
+#[derive(Deserialize)]
+struct BatchQuery {
+    /// Key of the result in the response
+    id: String,
+    event_type: EventTable,
+    /// Same filters as the event type's endpoint accepts
+    #[serde(default)]
+    filter: BTreeMap<String, String>,
+    #[serde(default)]
+    start_block_timestamp_nanosec: i64,
+    #[serde(default = "default_blocks")]
+    blocks: i64,
+}
+
+fn default_blocks() -> i64 {
+    10
+}
+
+#[derive(Serialize)]
+#[serde(untagged)]
+enum BatchResult {
+    Events { events: Vec<Value> },
+    Error { error: ErrorDetails },
+}
+
+/// Runs several event queries in one round trip, e.g. everything a page needs
+/// on load. Takes a JSON array of `{"id", "event_type", "filter",
+/// "start_block_timestamp_nanosec", "blocks"}` and responds with results
+/// keyed by `id`. Queries run concurrently, and a failed query doesn't fail
+/// the others.
+#[post("/query")]
+pub async fn batch_query(
+    state: web::Data<AppState>,
+    queries: web::Json<Vec<BatchQuery>>,
+    req: HttpRequest,
+) -> Result<HttpResponse, ApiError> {
+    let queries = queries.into_inner();
+    if queries.len() > MAX_QUERIES_PER_BATCH {
+        return Err(ApiError::Validation(format!(
+            "At most {MAX_QUERIES_PER_BATCH} queries are allowed per batch"
+        )));
+    }
+    let mut ids = HashSet::new();
+    if let Some(query) = queries.iter().find(|query| !ids.insert(&query.id)) {
+        return Err(ApiError::Validation(format!(
+            "Query id {} is used more than once",
+            query.id
+        )));
+    }
+
+    let results = join_all(queries.iter().map(|query| run(&state, query))).await;
+    Ok(HttpResponse::Ok().json(
+        queries
+            .iter()
+            .map(|query| &query.id)
+            .zip(results.into_iter().map(|result| match result {
+                Ok(events) => BatchResult::Events { events },
+                Err(err) => {
+                    if let ApiError::Database(err) = &err {
+                        error_reporting::report_query_error(err);
+                    }
+                    BatchResult::Error {
+                        error: ErrorDetails {
+                            code: err.code(),
+                            message: err.to_string(),
+                            request_id: request_id::of(&req).map(|id| id.to_string()),
+                        },
+                    }
+                }
+            }))
+            .collect::<BTreeMap<_, _>>(),
+    ))
+}
+
+async fn run(state: &AppState, query: &BatchQuery) -> Result<Vec<Value>, ApiError> {
+    let max_blocks = state.block_limits().of(query.event_type);
+    if !(1..=max_blocks).contains(&query.blocks) {
+        return Err(ApiError::Validation(format!(
+            "blocks must be between 1 and {max_blocks}"
+        )));
+    }
+    // Filters are parsed by the endpoint's filter struct, the same way as
+    // query parameters of the endpoint
+    let filter = serde_urlencoded::to_string(&query.filter)
+        .map_err(|err| ApiError::BadFilter(err.to_string()))?;
+    let events = live::fetch_events(
+        &state.pg_pool(),
+        query.event_type,
+        &filter,
+        query.start_block_timestamp_nanosec,
+        query.blocks,
+    )
+    .await
+    .map_err(|err| match err {
+        QueryError::BadQuery(message) => ApiError::BadFilter(message),
+        QueryError::Database(err) => ApiError::Database(err),
+    })?;
+    Ok(events.into_iter().map(|event| event.event).collect())
+}
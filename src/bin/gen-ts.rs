@@ -0,0 +1,6 @@
+//! Prints TypeScript definitions of all events, e.g.
+//! `cargo run --bin gen-ts > events.ts`
+
+fn main() {
+    print!("{}", events_api_http_server::typescript::definitions());
+}
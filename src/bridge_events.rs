@@ -0,0 +1,84 @@
+use chrono::prelude::{DateTime, Utc};
+pub use inteat_events_api_types::bridge::{
+    BridgeDepositEvent, BridgeDepositFilter, BridgeWithdrawEvent, BridgeWithdrawFilter,
+};
+
+use crate::endpoint::{event_endpoint, EventEndpoint};
+use crate::event_query::EventQuery;
+use crate::meta::EventTable;
+
+impl EventEndpoint for BridgeDepositEvent {
+    const TABLE: EventTable = EventTable::BridgeDeposit;
+    const SUM_FIELDS: &'static [(&'static str, &'static str)] = &[("amount", "amount")];
+    type Filter = BridgeDepositFilter;
+
+    fn query(filter: &BridgeDepositFilter) -> EventQuery<Self> {
+        EventQuery::new(
+            "bridge_deposit",
+            "account_id, origin_chain, sender, token_id, amount, transaction_id, receipt_id, block_height, timestamp",
+        )
+        .filter("account_id = ANY({})", filter.account_id.as_deref())
+        .filter("token_id = ANY({})", filter.token_id.as_deref())
+        .filter("origin_chain = ANY({})", filter.origin_chain.as_deref())
+        .filter("transaction_id = ANY({})", filter.transaction_id.as_deref())
+        .filter("receipt_id = ANY({})", filter.receipt_id.as_deref())
+    }
+
+    fn timestamp(&self) -> DateTime<Utc> {
+        self.timestamp
+    }
+
+    fn receipt_id(&self) -> &str {
+        &self.receipt_id
+    }
+
+    fn transaction(&self) -> (&str, &str) {
+        (&self.transaction_id, &self.account_id)
+    }
+}
+
+event_endpoint! {
+    /// Bridge deposit events, tokens bridged to NEAR
+    bridge_deposit(BridgeDepositEvent, BridgeDepositFilter),
+    route = "/bridge_deposit",
+    scope = "/v0/bridge",
+    tag = "Bridge",
+}
+
+impl EventEndpoint for BridgeWithdrawEvent {
+    const TABLE: EventTable = EventTable::BridgeWithdraw;
+    const SUM_FIELDS: &'static [(&'static str, &'static str)] = &[("amount", "amount")];
+    type Filter = BridgeWithdrawFilter;
+
+    fn query(filter: &BridgeWithdrawFilter) -> EventQuery<Self> {
+        EventQuery::new(
+            "bridge_withdraw",
+            "account_id, destination_chain, recipient, token_id, amount, transaction_id, receipt_id, block_height, timestamp",
+        )
+        .filter("account_id = ANY({})", filter.account_id.as_deref())
+        .filter("token_id = ANY({})", filter.token_id.as_deref())
+        .filter("destination_chain = ANY({})", filter.destination_chain.as_deref())
+        .filter("transaction_id = ANY({})", filter.transaction_id.as_deref())
+        .filter("receipt_id = ANY({})", filter.receipt_id.as_deref())
+    }
+
+    fn timestamp(&self) -> DateTime<Utc> {
+        self.timestamp
+    }
+
+    fn receipt_id(&self) -> &str {
+        &self.receipt_id
+    }
+
+    fn transaction(&self) -> (&str, &str) {
+        (&self.transaction_id, &self.account_id)
+    }
+}
+
+event_endpoint! {
+    /// Bridge withdrawal events, tokens bridged from NEAR
+    bridge_withdraw(BridgeWithdrawEvent, BridgeWithdrawFilter),
+    route = "/bridge_withdraw",
+    scope = "/v0/bridge",
+    tag = "Bridge",
+}
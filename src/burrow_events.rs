@@ -0,0 +1,222 @@
+use chrono::prelude::{DateTime, Utc};
+pub use inteat_events_api_types::burrow::{
+    BurrowBorrowEvent, BurrowBorrowFilter, BurrowDepositEvent, BurrowDepositFilter,
+    BurrowLiquidationEvent, BurrowLiquidationFilter, BurrowRepayEvent, BurrowRepayFilter,
+    BurrowWithdrawEvent, BurrowWithdrawFilter,
+};
+
+use crate::endpoint::{event_endpoint, EventEndpoint};
+use crate::event_query::EventQuery;
+use crate::meta::EventTable;
+
+impl EventEndpoint for BurrowDepositEvent {
+    const TABLE: EventTable = EventTable::BurrowDeposit;
+    const SUM_FIELDS: &'static [(&'static str, &'static str)] = &[("amount", "amount")];
+    type Filter = BurrowDepositFilter;
+
+    fn query(filter: &BurrowDepositFilter) -> EventQuery<Self> {
+        EventQuery::new(
+            "burrow_deposit",
+            "account_id, token_id, amount, transaction_id, receipt_id, block_height, timestamp",
+        )
+        .filter("account_id = ANY({})", filter.account_id.as_deref())
+        .filter("token_id = ANY({})", filter.token_id.as_deref())
+        .filter("transaction_id = ANY({})", filter.transaction_id.as_deref())
+        .filter("receipt_id = ANY({})", filter.receipt_id.as_deref())
+        .filter(
+            "account_id <> ALL({})",
+            filter.exclude_account_id.as_deref(),
+        )
+    }
+
+    fn timestamp(&self) -> DateTime<Utc> {
+        self.timestamp
+    }
+
+    fn receipt_id(&self) -> &str {
+        &self.receipt_id
+    }
+
+    fn transaction(&self) -> (&str, &str) {
+        (&self.transaction_id, &self.account_id)
+    }
+}
+
+event_endpoint! {
+    /// Burrow deposit events, supplying tokens to the lending pool
+    burrow_deposit(BurrowDepositEvent, BurrowDepositFilter),
+    route = "/burrow_deposit",
+    scope = "/v0/burrow",
+    tag = "Burrow",
+}
+
+impl EventEndpoint for BurrowWithdrawEvent {
+    const TABLE: EventTable = EventTable::BurrowWithdraw;
+    const SUM_FIELDS: &'static [(&'static str, &'static str)] = &[("amount", "amount")];
+    type Filter = BurrowWithdrawFilter;
+
+    fn query(filter: &BurrowWithdrawFilter) -> EventQuery<Self> {
+        EventQuery::new(
+            "burrow_withdraw",
+            "account_id, token_id, amount, transaction_id, receipt_id, block_height, timestamp",
+        )
+        .filter("account_id = ANY({})", filter.account_id.as_deref())
+        .filter("token_id = ANY({})", filter.token_id.as_deref())
+        .filter("transaction_id = ANY({})", filter.transaction_id.as_deref())
+        .filter("receipt_id = ANY({})", filter.receipt_id.as_deref())
+        .filter(
+            "account_id <> ALL({})",
+            filter.exclude_account_id.as_deref(),
+        )
+    }
+
+    fn timestamp(&self) -> DateTime<Utc> {
+        self.timestamp
+    }
+
+    fn receipt_id(&self) -> &str {
+        &self.receipt_id
+    }
+
+    fn transaction(&self) -> (&str, &str) {
+        (&self.transaction_id, &self.account_id)
+    }
+}
+
+event_endpoint! {
+    /// Burrow withdrawal events
+    burrow_withdraw(BurrowWithdrawEvent, BurrowWithdrawFilter),
+    route = "/burrow_withdraw",
+    scope = "/v0/burrow",
+    tag = "Burrow",
+}
+
+impl EventEndpoint for BurrowBorrowEvent {
+    const TABLE: EventTable = EventTable::BurrowBorrow;
+    const SUM_FIELDS: &'static [(&'static str, &'static str)] = &[("amount", "amount")];
+    type Filter = BurrowBorrowFilter;
+
+    fn query(filter: &BurrowBorrowFilter) -> EventQuery<Self> {
+        EventQuery::new(
+            "burrow_borrow",
+            "account_id, token_id, amount, transaction_id, receipt_id, block_height, timestamp",
+        )
+        .filter("account_id = ANY({})", filter.account_id.as_deref())
+        .filter("token_id = ANY({})", filter.token_id.as_deref())
+        .filter("transaction_id = ANY({})", filter.transaction_id.as_deref())
+        .filter("receipt_id = ANY({})", filter.receipt_id.as_deref())
+        .filter(
+            "account_id <> ALL({})",
+            filter.exclude_account_id.as_deref(),
+        )
+    }
+
+    fn timestamp(&self) -> DateTime<Utc> {
+        self.timestamp
+    }
+
+    fn receipt_id(&self) -> &str {
+        &self.receipt_id
+    }
+
+    fn transaction(&self) -> (&str, &str) {
+        (&self.transaction_id, &self.account_id)
+    }
+}
+
+event_endpoint! {
+    /// Burrow borrow events
+    burrow_borrow(BurrowBorrowEvent, BurrowBorrowFilter),
+    route = "/burrow_borrow",
+    scope = "/v0/burrow",
+    tag = "Burrow",
+}
+
+impl EventEndpoint for BurrowRepayEvent {
+    const TABLE: EventTable = EventTable::BurrowRepay;
+    const SUM_FIELDS: &'static [(&'static str, &'static str)] = &[("amount", "amount")];
+    type Filter = BurrowRepayFilter;
+
+    fn query(filter: &BurrowRepayFilter) -> EventQuery<Self> {
+        EventQuery::new(
+            "burrow_repay",
+            "account_id, token_id, amount, transaction_id, receipt_id, block_height, timestamp",
+        )
+        .filter("account_id = ANY({})", filter.account_id.as_deref())
+        .filter("token_id = ANY({})", filter.token_id.as_deref())
+        .filter("transaction_id = ANY({})", filter.transaction_id.as_deref())
+        .filter("receipt_id = ANY({})", filter.receipt_id.as_deref())
+        .filter(
+            "account_id <> ALL({})",
+            filter.exclude_account_id.as_deref(),
+        )
+    }
+
+    fn timestamp(&self) -> DateTime<Utc> {
+        self.timestamp
+    }
+
+    fn receipt_id(&self) -> &str {
+        &self.receipt_id
+    }
+
+    fn transaction(&self) -> (&str, &str) {
+        (&self.transaction_id, &self.account_id)
+    }
+}
+
+event_endpoint! {
+    /// Burrow repayment events
+    burrow_repay(BurrowRepayEvent, BurrowRepayFilter),
+    route = "/burrow_repay",
+    scope = "/v0/burrow",
+    tag = "Burrow",
+}
+
+impl EventEndpoint for BurrowLiquidationEvent {
+    const TABLE: EventTable = EventTable::BurrowLiquidation;
+    const SUM_FIELDS: &'static [(&'static str, &'static str)] = &[
+        ("collateral_sum", "collateral_sum"),
+        ("repaid_sum", "repaid_sum"),
+    ];
+    type Filter = BurrowLiquidationFilter;
+
+    fn query(filter: &BurrowLiquidationFilter) -> EventQuery<Self> {
+        EventQuery::new(
+            "burrow_liquidation",
+            "account_id, liquidator_id, collateral_token_ids, repaid_token_ids, collateral_sum, repaid_sum, transaction_id, receipt_id, block_height, timestamp",
+        )
+        .filter("account_id = ANY({})", filter.account_id.as_deref())
+        .filter("liquidator_id = ANY({})", filter.liquidator_id.as_deref())
+        .filter(
+            "collateral_token_ids && {} OR repaid_token_ids && {}",
+            filter.token_id.as_deref(),
+        )
+        .filter("transaction_id = ANY({})", filter.transaction_id.as_deref())
+        .filter("receipt_id = ANY({})", filter.receipt_id.as_deref())
+        .filter(
+            "NOT (ARRAY[account_id, liquidator_id] && {})",
+            filter.exclude_account_id.as_deref(),
+        )
+    }
+
+    fn timestamp(&self) -> DateTime<Utc> {
+        self.timestamp
+    }
+
+    fn receipt_id(&self) -> &str {
+        &self.receipt_id
+    }
+
+    fn transaction(&self) -> (&str, &str) {
+        (&self.transaction_id, &self.liquidator_id)
+    }
+}
+
+event_endpoint! {
+    /// Burrow liquidation events
+    burrow_liquidation(BurrowLiquidationEvent, BurrowLiquidationFilter),
+    route = "/burrow_liquidation",
+    scope = "/v0/burrow",
+    tag = "Burrow",
+}
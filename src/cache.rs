@@ -0,0 +1,421 @@
+use std::{sync::RwLock, time::Duration};
+
+use actix_web::{
+    body::{self, BoxBody, EitherBody, MessageBody},
+    dev::{ServiceRequest, ServiceResponse},
+    http::{header, Method, StatusCode},
+    middleware::Next,
+    web::{self, Bytes},
+    Error, HttpResponse,
+};
+use chrono::Utc;
+use redis::{
+    aio::{ConnectionManager, ConnectionManagerConfig},
+    AsyncCommands,
+};
+
+use crate::cursor;
+
+/// Pages that can still get new events are cached only for a short time, so
+/// that dashboards polling the same query share one database query
+const DEFAULT_RECENT_TTL_SECS: u64 = 3;
+
+/// Pages that end long enough ago don't change anymore
+const DEFAULT_HISTORICAL_TTL_SECS: u64 = 24 * 60 * 60;
+
+/// Events older than this are considered final, with a margin for indexer lag
+const HISTORICAL_AFTER: Duration = Duration::from_secs(60 * 60);
+
+/// A slow or unavailable Redis shouldn't slow down requests, they're just not
+/// cached then
+const REDIS_TIMEOUT: Duration = Duration::from_millis(500);
+
+/// Live streams aren't cached, and neither are webhooks, which belong to
+/// whoever has their token
+const UNCACHED_PATHS: &[&str] = &["/v0/ws", "/v0/sse/", "/v0/graphql", "/v0/webhooks"];
+
+/// Streamed responses, like NDJSON, aren't cached, since they'd have to be
+/// buffered
+const CACHED_CONTENT_TYPES: &[&str] = &["application/json", "application/msgpack", "text/csv"];
+
+const KEY_PREFIX: &str = "events-api:";
+
+/// Responses of `/v0` endpoints cached in Redis, enabled with `REDIS_URL`
+pub struct ResponseCache {
+    connection: ConnectionManager,
+    /// Replaced when the config is reloaded
+    ttls: RwLock<CacheTtls>,
+}
+
+#[derive(Clone, Copy)]
+struct CacheTtls {
+    /// `CACHE_RECENT_TTL_SECS`
+    recent: Duration,
+    /// `CACHE_HISTORICAL_TTL_SECS`
+    historical: Duration,
+}
+
+impl CacheTtls {
+    fn from_env() -> Self {
+        fn parse(var: &str, default: u64) -> Duration {
+            Duration::from_secs(std::env::var(var).map_or(default, |value| {
+                value
+                    .parse()
+                    .ok()
+                    .filter(|&secs| secs > 0)
+                    .unwrap_or_else(|| panic!("{var} must be a positive number of seconds"))
+            }))
+        }
+        Self {
+            recent: parse("CACHE_RECENT_TTL_SECS", DEFAULT_RECENT_TTL_SECS),
+            historical: parse("CACHE_HISTORICAL_TTL_SECS", DEFAULT_HISTORICAL_TTL_SECS),
+        }
+    }
+}
+
+impl ResponseCache {
+    /// `None` if `REDIS_URL` is not set
+    pub async fn from_env() -> Option<Self> {
+        let redis_url = std::env::var("REDIS_URL").ok()?;
+        let client = redis::Client::open(redis_url).expect("REDIS_URL must be a Redis URL");
+        let connection = ConnectionManager::new_with_config(
+            client,
+            ConnectionManagerConfig::new()
+                .set_connection_timeout(REDIS_TIMEOUT)
+                .set_response_timeout(REDIS_TIMEOUT),
+        )
+        .await
+        .expect("Failed to connect to Redis");
+        Some(Self {
+            connection,
+            ttls: RwLock::new(CacheTtls::from_env()),
+        })
+    }
+
+    /// Applies changed environment variables
+    pub fn reload(&self) {
+        *self.ttls.write().unwrap() = CacheTtls::from_env();
+    }
+
+    /// Content type and body of a cached response
+    async fn get(&self, key: &str) -> Option<(String, Bytes)> {
+        let cached: Option<Vec<u8>> = self
+            .connection
+            .clone()
+            .get(key)
+            .await
+            .map_err(|err| log::warn!("Failed to read from Redis: {err}"))
+            .ok()?;
+        let cached = cached?;
+        let separator = cached.iter().position(|&byte| byte == b'\n')?;
+        let content_type = String::from_utf8(cached[..separator].to_vec()).ok()?;
+        Some((
+            content_type,
+            Bytes::copy_from_slice(&cached[separator + 1..]),
+        ))
+    }
+
+    async fn set(&self, key: &str, content_type: &str, body: &[u8], ttl: Duration) {
+        let mut value = Vec::with_capacity(content_type.len() + 1 + body.len());
+        value.extend_from_slice(content_type.as_bytes());
+        value.push(b'\n');
+        value.extend_from_slice(body);
+        let result: Result<(), _> = self
+            .connection
+            .clone()
+            .set_ex(key, value, ttl.as_secs())
+            .await;
+        if let Err(err) = result {
+            log::warn!("Failed to write to Redis: {err}");
+        }
+    }
+}
+
+/// Key of a request and how long its response can be cached, `None` if it
+/// shouldn't be cached. Query parameters are sorted, so that the same query
+/// written differently is cached once. Authorized requests aren't cached, the
+/// key doesn't include who made them.
+fn cache_key(req: &ServiceRequest, ttls: CacheTtls) -> Option<(String, Duration)> {
+    let path = req.path();
+    if req.method() != Method::GET
+        || req.headers().contains_key(header::AUTHORIZATION)
+        || !path.starts_with("/v0/")
+        || UNCACHED_PATHS
+            .iter()
+            .any(|uncached| path.starts_with(uncached))
+    {
+        return None;
+    }
+    let mut params =
+        serde_urlencoded::from_str::<Vec<(String, String)>>(req.query_string()).ok()?;
+    params.sort();
+    let param = |name: &str| {
+        params
+            .iter()
+            .find(|(param, _)| param == name)
+            .map(|(_, value)| value.as_str())
+    };
+
+    let end_block_timestamp_nanosec = match param("end_block_timestamp_nanosec") {
+        Some(end) => end.parse::<i64>().ok(),
+        // Cursors of descending pages are the end of the next page
+        None if param("order") == Some("desc") => {
+            param("cursor").and_then(|cursor| cursor::decode(cursor).ok().map(|(block, _)| block))
+        }
+        None => None,
+    };
+    let historical_before =
+        Utc::now().timestamp_nanos_opt()? - i64::try_from(HISTORICAL_AFTER.as_nanos()).ok()?;
+    let ttl = match end_block_timestamp_nanosec {
+        Some(end) if end <= historical_before => ttls.historical,
+        _ => ttls.recent,
+    };
+
+    let query = serde_urlencoded::to_string(&params).ok()?;
+    Some((format!("{KEY_PREFIX}{path}?{query}"), ttl))
+}
+
+fn cached_content_type(res: &ServiceResponse<impl MessageBody>) -> Option<String> {
+    if res.status() != StatusCode::OK {
+        return None;
+    }
+    let content_type = res.headers().get(header::CONTENT_TYPE)?.to_str().ok()?;
+    CACHED_CONTENT_TYPES
+        .iter()
+        .any(|cached| content_type.starts_with(cached))
+        .then(|| content_type.to_owned())
+}
+
+/// Serves responses from the cache, and caches successful ones. Responses
+/// have an `X-Cache: HIT` or `X-Cache: MISS` header when caching is enabled.
+pub async fn cache_responses(
+    cache: Option<web::Data<ResponseCache>>,
+    req: ServiceRequest,
+    next: Next<impl MessageBody>,
+) -> Result<ServiceResponse<EitherBody<impl MessageBody, BoxBody>>, Error> {
+    let Some((cache, (key, ttl))) = cache.and_then(|cache| {
+        let ttls = *cache.ttls.read().unwrap();
+        Some((cache, cache_key(&req, ttls)?))
+    }) else {
+        return next
+            .call(req)
+            .await
+            .map(ServiceResponse::map_into_left_body);
+    };
+
+    if let Some((content_type, body)) = cache.get(&key).await {
+        return Ok(req
+            .into_response(
+                HttpResponse::Ok()
+                    .content_type(content_type)
+                    .insert_header(("X-Cache", "HIT"))
+                    .body(body),
+            )
+            .map_into_right_body());
+    }
+
+    let res = next.call(req).await?;
+    let Some(content_type) = cached_content_type(&res) else {
+        return Ok(res.map_into_left_body());
+    };
+    let (req, res) = res.into_parts();
+    let (mut res, body) = res.into_parts();
+    let body = body::to_bytes(body)
+        .await
+        .map_err(|err| actix_web::error::ErrorInternalServerError(err.into().to_string()))?;
+    cache.set(&key, &content_type, &body, ttl).await;
+    res.headers_mut().insert(
+        header::HeaderName::from_static("x-cache"),
+        header::HeaderValue::from_static("MISS"),
+    );
+    Ok(ServiceResponse::new(req, res.set_body(body).map_into_boxed_body()).map_into_right_body())
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::{
+        body::BoxBody,
+        middleware,
+        test::{call_service, init_service, read_body, TestRequest},
+        App, HttpRequest,
+    };
+
+    use super::*;
+    use crate::ndjson;
+
+    const TTLS: CacheTtls = CacheTtls {
+        recent: Duration::from_secs(3),
+        historical: Duration::from_secs(86_400),
+    };
+
+    fn key(req: TestRequest) -> Option<(String, Duration)> {
+        cache_key(&req.to_srv_request(), TTLS)
+    }
+
+    #[test]
+    fn keys_have_sorted_params() {
+        let key_of = |uri| key(TestRequest::get().uri(uri)).unwrap().0;
+        assert_eq!(
+            key_of("/v0/nft/nft_transfer?token_id=1&blocks=10&account_id=a.near"),
+            "events-api:/v0/nft/nft_transfer?account_id=a.near&blocks=10&token_id=1"
+        );
+        assert_eq!(
+            key_of("/v0/nft/nft_transfer?blocks=10&account_id=a.near&token_id=1"),
+            key_of("/v0/nft/nft_transfer?token_id=1&blocks=10&account_id=a.near")
+        );
+    }
+
+    #[actix_web::test]
+    async fn keys_include_the_negotiated_format() {
+        let app = init_service(
+            App::new()
+                .wrap(middleware::from_fn(
+                    |req: ServiceRequest, _: Next<BoxBody>| async {
+                        let (key, _) = cache_key(&req, TTLS).unwrap();
+                        Ok(req.into_response(HttpResponse::Ok().body(key)))
+                    },
+                ))
+                .wrap(middleware::from_fn(cursor::negotiate_format)),
+        )
+        .await;
+        let key_of = |req: TestRequest| {
+            let app = &app;
+            async move {
+                let res = call_service(app, req.to_request()).await;
+                read_body(res).await
+            }
+        };
+
+        let negotiated = key_of(
+            TestRequest::get()
+                .uri("/v0/ft/ft_transfer?blocks=5")
+                .insert_header((header::ACCEPT, "application/msgpack")),
+        )
+        .await;
+        assert_eq!(
+            negotiated,
+            "events-api:/v0/ft/ft_transfer?blocks=5&format=msgpack"
+        );
+        let explicit =
+            key_of(TestRequest::get().uri("/v0/ft/ft_transfer?format=msgpack&blocks=5")).await;
+        assert_eq!(negotiated, explicit);
+        let json = key_of(TestRequest::get().uri("/v0/ft/ft_transfer?blocks=5")).await;
+        assert_ne!(negotiated, json);
+    }
+
+    #[test]
+    fn pages_that_ended_long_ago_are_cached_longer() {
+        let now = Utc::now().timestamp_nanos_opt().unwrap();
+        let day_ago = now - 86_400_000_000_000;
+        let ttl_of = |uri: String| key(TestRequest::get().uri(&uri)).unwrap().1;
+
+        assert_eq!(
+            ttl_of(format!(
+                "/v0/ft/ft_transfer?end_block_timestamp_nanosec={day_ago}"
+            )),
+            TTLS.historical
+        );
+        assert_eq!(
+            ttl_of(format!(
+                "/v0/ft/ft_transfer?end_block_timestamp_nanosec={now}"
+            )),
+            TTLS.recent
+        );
+        assert_eq!(
+            ttl_of("/v0/ft/ft_transfer?start_block_timestamp_nanosec=0".to_owned()),
+            TTLS.recent
+        );
+        // The cursor is the end of descending pages
+        let cursor = cursor::encode(day_ago);
+        assert_eq!(
+            ttl_of(format!("/v0/ft/ft_transfer?order=desc&cursor={cursor}")),
+            TTLS.historical
+        );
+        assert_eq!(
+            ttl_of(format!("/v0/ft/ft_transfer?cursor={cursor}")),
+            TTLS.recent
+        );
+    }
+
+    #[test]
+    fn streams_and_other_requests_are_not_cached() {
+        for uri in [
+            "/v0/ws",
+            "/v0/sse/nft_transfer",
+            "/v0/graphql",
+            "/v0/webhooks/1",
+            "/healthz",
+        ] {
+            assert!(key(TestRequest::get().uri(uri)).is_none(), "{uri}");
+        }
+        assert!(key(TestRequest::post().uri("/v0/batch")).is_none());
+        assert!(key(TestRequest::get()
+            .uri("/v0/ft/ft_transfer")
+            .insert_header((header::AUTHORIZATION, "Bearer token")))
+        .is_none());
+        assert!(key(TestRequest::get().uri("/v0/ft/ft_transfer")).is_some());
+    }
+
+    #[test]
+    fn only_successful_responses_of_buffered_formats_are_cached() {
+        let content_type_of =
+            |res: HttpResponse| cached_content_type(&TestRequest::get().to_srv_response(res));
+        assert_eq!(
+            content_type_of(HttpResponse::Ok().json(Vec::<()>::new())).as_deref(),
+            Some("application/json")
+        );
+        assert_eq!(
+            content_type_of(HttpResponse::NotFound().json(Vec::<()>::new())),
+            None
+        );
+        assert_eq!(
+            content_type_of(HttpResponse::ServiceUnavailable().json(Vec::<()>::new())),
+            None
+        );
+        assert_eq!(
+            content_type_of(
+                HttpResponse::Ok()
+                    .content_type(ndjson::CONTENT_TYPE)
+                    .finish()
+            ),
+            None
+        );
+    }
+
+    #[actix_web::test]
+    #[ignore = "needs REDIS_URL"]
+    async fn authorized_responses_are_not_served_to_others() {
+        let cache = ResponseCache::from_env()
+            .await
+            .expect("REDIS_URL is not set");
+        let app = init_service(
+            App::new()
+                .app_data(web::Data::new(cache))
+                .route(
+                    "/v0/webhooks/{id}",
+                    web::get().to(|req: HttpRequest| async move {
+                        match req.headers().get(header::AUTHORIZATION) {
+                            Some(token) if token == "Bearer token" => {
+                                HttpResponse::Ok().json(["https://example.com/hook"])
+                            }
+                            _ => HttpResponse::Unauthorized().finish(),
+                        }
+                    }),
+                )
+                .wrap(middleware::from_fn(cache_responses)),
+        )
+        .await;
+
+        let req = TestRequest::get()
+            .uri("/v0/webhooks/1")
+            .insert_header((header::AUTHORIZATION, "Bearer token"))
+            .to_request();
+        let res = call_service(&app, req).await;
+        assert_eq!(res.status(), StatusCode::OK);
+        assert!(!res.headers().contains_key("x-cache"));
+
+        let req = TestRequest::get().uri("/v0/webhooks/1").to_request();
+        let res = call_service(&app, req).await;
+        assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+    }
+}
@@ -0,0 +1,86 @@
+use std::{
+    ops::{Deref, DerefMut},
+    time::Instant,
+};
+
+use sqlx::{pool::PoolConnection, PgConnection, PgPool, Postgres};
+
+use crate::access_log;
+
+/// Connection for one query that's cancelled in Postgres if it's dropped
+/// before `finish`, e.g. because the client disconnected and actix dropped
+/// the handler. Without it, the query keeps running on the database after
+/// nobody waits for it.
+pub(crate) struct CancelOnDrop {
+    /// Only taken in `drop`
+    connection: Option<PoolConnection<Postgres>>,
+    pg_pool: PgPool,
+    backend_pid: i32,
+    acquired: Instant,
+    finished: bool,
+}
+
+impl CancelOnDrop {
+    pub(crate) async fn acquire(pg_pool: &PgPool) -> Result<Self, sqlx::Error> {
+        let mut connection = pg_pool.acquire().await?;
+        let backend_pid = sqlx::query_scalar("SELECT pg_backend_pid()")
+            .fetch_one(&mut *connection)
+            .await?;
+        Ok(Self {
+            connection: Some(connection),
+            pg_pool: pg_pool.clone(),
+            backend_pid,
+            acquired: Instant::now(),
+            finished: false,
+        })
+    }
+
+    /// Returns the connection to the pool, and counts the `rows` and the
+    /// time since acquiring in the access log
+    pub(crate) fn finish(mut self, rows: usize) {
+        access_log::record_query(rows, self.acquired.elapsed());
+        self.finished = true;
+    }
+}
+
+impl Deref for CancelOnDrop {
+    type Target = PgConnection;
+
+    fn deref(&self) -> &PgConnection {
+        self.connection
+            .as_ref()
+            .expect("connection is only taken in drop")
+    }
+}
+
+impl DerefMut for CancelOnDrop {
+    fn deref_mut(&mut self) -> &mut PgConnection {
+        self.connection
+            .as_mut()
+            .expect("connection is only taken in drop")
+    }
+}
+
+impl Drop for CancelOnDrop {
+    fn drop(&mut self) {
+        if self.finished {
+            return;
+        }
+        // The connection may be in the middle of a query, so it's detached
+        // from the pool and closed instead of being reused, and the
+        // cancellation can't hit a later query
+        let connection = self.connection.take().map(PoolConnection::detach);
+        let pg_pool = self.pg_pool.clone();
+        let backend_pid = self.backend_pid;
+        tokio::spawn(async move {
+            if let Err(err) = sqlx::query("SELECT pg_cancel_backend($1)")
+                .bind(backend_pid)
+                .execute(&pg_pool)
+                .await
+            {
+                log::warn!("Failed to cancel query of backend {backend_pid}: {err}");
+            }
+            drop(connection);
+        });
+    }
+}
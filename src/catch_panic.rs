@@ -0,0 +1,88 @@
+use std::panic::AssertUnwindSafe;
+
+use actix_web::{
+    body::MessageBody,
+    dev::{ServiceRequest, ServiceResponse},
+    middleware::Next,
+    Error,
+};
+use futures_util::FutureExt;
+
+use crate::error::ApiError;
+
+/// Catches panics in handlers (e.g. unexpected NULLs while decoding rows)
+/// and turns them into an `internal_error` response instead of a dropped
+/// connection. The request was moved into the handler, and it can't be
+/// cloned here because routing needs exclusive access to it, so the error is
+/// returned as `Err`. Outer middlewares add their headers to it with
+/// response mappers, the same way as to any other error.
+pub async fn catch_panic(
+    req: ServiceRequest,
+    next: Next<impl MessageBody>,
+) -> Result<ServiceResponse<impl MessageBody>, Error> {
+    let method = req.method().clone();
+    let uri = req.uri().clone();
+    match AssertUnwindSafe(next.call(req)).catch_unwind().await {
+        Ok(res) => res,
+        Err(panic) => {
+            let message = panic
+                .downcast_ref::<&str>()
+                .copied()
+                .or_else(|| panic.downcast_ref::<String>().map(String::as_str))
+                .unwrap_or("unknown panic");
+            log::error!("Handler panicked on {method} {uri}: {message}");
+            Err(ApiError::Internal.into())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::{
+        dev::Service,
+        http::{header, StatusCode},
+        middleware, test, web, App, HttpResponse,
+    };
+    use serde_json::Value;
+
+    use super::*;
+    use crate::{
+        cors::CorsConfig,
+        request_id::{assign_request_id, REQUEST_ID_HEADER},
+    };
+
+    #[actix_web::test]
+    async fn panics_respond_with_internal_error_and_headers_of_outer_middlewares() {
+        let app = test::init_service(
+            App::new()
+                .route(
+                    "/panic",
+                    web::get().to(|| async { panic!("unexpected NULL") as HttpResponse }),
+                )
+                .wrap(middleware::from_fn(catch_panic))
+                .wrap(CorsConfig::from_env().cors())
+                .wrap(middleware::from_fn(assign_request_id)),
+        )
+        .await;
+        let req = test::TestRequest::get()
+            .uri("/panic")
+            .insert_header((REQUEST_ID_HEADER, "panicked"))
+            .insert_header((header::ORIGIN, "https://example.com"))
+            .to_request();
+        // The server turns the error into a response the same way
+        let Err(err) = app.call(req).await else {
+            panic!("panics should be returned as errors");
+        };
+        let res = err.error_response();
+
+        assert_eq!(res.status(), StatusCode::INTERNAL_SERVER_ERROR);
+        assert_eq!(res.headers().get(REQUEST_ID_HEADER).unwrap(), "panicked");
+        assert!(res
+            .headers()
+            .contains_key(header::ACCESS_CONTROL_ALLOW_ORIGIN));
+        let body = actix_web::body::to_bytes(res.into_body()).await.unwrap();
+        let body: Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body["error"]["code"], "internal_error");
+        assert_eq!(body["error"]["request_id"], "panicked");
+    }
+}
@@ -0,0 +1,141 @@
+use std::io::Write;
+
+use actix_web::{
+    body::{self, EitherBody, MessageBody},
+    dev::{ServiceRequest, ServiceResponse},
+    http::header::{self, HeaderMap, HeaderValue},
+    middleware::Next,
+    web::{self, Bytes},
+    Error,
+};
+
+/// Compressing small responses isn't worth the CPU time and headers
+const MIN_COMPRESSED_SIZE: usize = 1024;
+
+/// Streaming responses, like NDJSON and SSE, aren't compressed, since they'd
+/// have to be buffered
+const COMPRESSED_CONTENT_TYPES: &[&str] = &[
+    "application/json",
+    "application/msgpack",
+    "text/csv",
+    "text/html",
+];
+
+/// Compression of responses with gzip or brotli, configured with environment
+/// variables. Event payloads are repetitive, so they compress well.
+pub struct ResponseCompression {
+    /// `COMPRESSION_LEVEL` from 1 (fastest) to 9 (smallest), or 0 to disable
+    /// compression. Defaults to 5.
+    level: u32,
+}
+
+#[derive(Clone, Copy)]
+enum Encoding {
+    Brotli,
+    Gzip,
+}
+
+impl ResponseCompression {
+    pub fn from_env() -> Self {
+        let level = std::env::var("COMPRESSION_LEVEL")
+            .ok()
+            .map(|level| {
+                level
+                    .parse()
+                    .ok()
+                    .filter(|level| (0..=9).contains(level))
+                    .expect("COMPRESSION_LEVEL must be a number from 0 to 9")
+            })
+            .unwrap_or(5);
+        Self { level }
+    }
+
+    fn compress(&self, encoding: Encoding, body: &[u8]) -> std::io::Result<Vec<u8>> {
+        match encoding {
+            Encoding::Brotli => {
+                let mut encoder = brotli::CompressorWriter::new(Vec::new(), 4096, self.level, 22);
+                encoder.write_all(body)?;
+                Ok(encoder.into_inner())
+            }
+            Encoding::Gzip => {
+                let mut encoder =
+                    flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::new(self.level));
+                encoder.write_all(body)?;
+                encoder.finish()
+            }
+        }
+    }
+}
+
+/// Brotli is preferred over gzip if the client accepts both
+fn accepted_encoding(headers: &HeaderMap) -> Option<Encoding> {
+    let accepted = headers
+        .get_all(header::ACCEPT_ENCODING)
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .filter_map(|coding| {
+            let mut parts = coding.split(';').map(str::trim);
+            let name = parts.next()?;
+            let rejected = parts.any(|param| {
+                param.strip_prefix("q=").and_then(|q| q.parse::<f32>().ok()) == Some(0.0)
+            });
+            (!rejected).then_some(name)
+        })
+        .collect::<Vec<_>>();
+    if accepted.iter().any(|name| name.eq_ignore_ascii_case("br")) {
+        Some(Encoding::Brotli)
+    } else if accepted
+        .iter()
+        .any(|name| name.eq_ignore_ascii_case("gzip"))
+    {
+        Some(Encoding::Gzip)
+    } else {
+        None
+    }
+}
+
+fn is_compressible(headers: &HeaderMap) -> bool {
+    !headers.contains_key(header::CONTENT_ENCODING)
+        && headers
+            .get(header::CONTENT_TYPE)
+            .and_then(|content_type| content_type.to_str().ok())
+            .is_some_and(|content_type| {
+                COMPRESSED_CONTENT_TYPES
+                    .iter()
+                    .any(|compressed| content_type.starts_with(compressed))
+            })
+}
+
+/// Compresses buffered responses with the best encoding the client accepts
+pub async fn compress_responses(
+    compression: web::Data<ResponseCompression>,
+    req: ServiceRequest,
+    next: Next<impl MessageBody>,
+) -> Result<ServiceResponse<EitherBody<impl MessageBody, Bytes>>, Error> {
+    let encoding = accepted_encoding(req.headers()).filter(|_| compression.level > 0);
+    let res = next.call(req).await?;
+    let Some(encoding) = encoding.filter(|_| is_compressible(res.headers())) else {
+        return Ok(res.map_into_left_body());
+    };
+
+    let (req, res) = res.into_parts();
+    let (mut res, body) = res.into_parts();
+    let body = body::to_bytes(body)
+        .await
+        .map_err(|err| actix_web::error::ErrorInternalServerError(err.into().to_string()))?;
+    // Responses vary by `Accept-Encoding` even if this one isn't compressed
+    res.headers_mut()
+        .append(header::VARY, HeaderValue::from_static("accept-encoding"));
+    if body.len() < MIN_COMPRESSED_SIZE {
+        return Ok(ServiceResponse::new(req, res.set_body(body)).map_into_right_body());
+    }
+    let compressed = compression.compress(encoding, &body)?;
+    res.headers_mut().insert(
+        header::CONTENT_ENCODING,
+        HeaderValue::from_static(match encoding {
+            Encoding::Brotli => "br",
+            Encoding::Gzip => "gzip",
+        }),
+    );
+    Ok(ServiceResponse::new(req, res.set_body(Bytes::from(compressed))).map_into_right_body())
+}
@@ -0,0 +1,140 @@
+use std::{collections::BTreeMap, sync::Mutex};
+
+use toml::{Table, Value};
+
+/// Variables set from the config file, with their values, so that a reload
+/// can tell them apart from variables of the environment
+static FROM_FILE: Mutex<BTreeMap<String, String>> = Mutex::new(BTreeMap::new());
+
+const DEFAULT_CONFIG_FILE: &str = "config.toml";
+
+/// Loads `CONFIG_FILE`, or `config.toml` if it exists. Every setting can be
+/// set in the file instead of an environment variable, with the variable's
+/// name in lowercase, e.g. `max_blocks_per_request = 100`, or in a table
+/// named after its prefix, e.g. `per_minute = 60` in `[rate_limit]`.
+/// Environment variables take precedence over the file.
+///
+/// Settings are applied as environment variables, so they're read the same
+/// way as if they were set in the environment.
+pub fn load() {
+    if let Err(err) = reload() {
+        panic!("{err}");
+    }
+}
+
+/// Reads the config file again, e.g. on `SIGHUP`. Settings removed from the
+/// file are unset. If the file can't be read or parsed, the previous settings
+/// are kept.
+pub fn reload() -> Result<(), String> {
+    let (path, required) = match std::env::var("CONFIG_FILE") {
+        Ok(path) => (path, true),
+        Err(_) => (DEFAULT_CONFIG_FILE.to_owned(), false),
+    };
+    let contents = match std::fs::read_to_string(&path) {
+        Ok(contents) => contents,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound && !required => String::new(),
+        Err(err) => return Err(format!("Failed to read {path}: {err}")),
+    };
+    let table = contents
+        .parse::<Table>()
+        .map_err(|err| format!("Failed to parse {path}: {err}"))?;
+    let mut settings = BTreeMap::new();
+    flatten("", table, &mut settings)?;
+
+    let mut from_file = FROM_FILE.lock().unwrap();
+    // Variables that changed since they were set, e.g. by `.env`, were
+    // overridden and aren't the file's anymore
+    from_file.retain(|var, value| std::env::var(var).as_deref() == Ok(value.as_str()));
+    for var in from_file.keys() {
+        if !settings.contains_key(var) {
+            std::env::remove_var(var);
+        }
+    }
+    from_file.retain(|var, _| settings.contains_key(var));
+    for (var, value) in settings {
+        if std::env::var_os(&var).is_some() && !from_file.contains_key(&var) {
+            continue;
+        }
+        std::env::set_var(&var, &value);
+        from_file.insert(var, value);
+    }
+    Ok(())
+}
+
+/// Names of variables are keys of nested tables joined with `_`, in
+/// uppercase. Arrays become comma-separated lists, and `false` leaves
+/// flags like `enable_webhooks` unset.
+fn flatten(
+    prefix: &str,
+    table: Table,
+    settings: &mut BTreeMap<String, String>,
+) -> Result<(), String> {
+    for (key, value) in table {
+        let var = format!("{prefix}{}", key.to_uppercase());
+        let value = match value {
+            Value::Table(table) => {
+                flatten(&format!("{var}_"), table, settings)?;
+                continue;
+            }
+            Value::Boolean(false) => continue,
+            Value::Array(values) => values
+                .into_iter()
+                .map(|value| scalar(&var, value))
+                .collect::<Result<Vec<_>, _>>()?
+                .join(","),
+            value => scalar(&var, value)?,
+        };
+        settings.insert(var, value);
+    }
+    Ok(())
+}
+
+fn scalar(var: &str, value: Value) -> Result<String, String> {
+    match value {
+        Value::String(value) => Ok(value),
+        Value::Integer(value) => Ok(value.to_string()),
+        Value::Float(value) => Ok(value.to_string()),
+        Value::Boolean(value) => Ok(value.to_string()),
+        Value::Datetime(value) => Ok(value.to_string()),
+        Value::Array(_) | Value::Table(_) => Err(format!(
+            "{} in the config file must be a string, number, or list of them",
+            var.to_lowercase()
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn flattens_tables_into_variables() {
+        let table = r#"
+            bind_address = ["0.0.0.0:8080", "[::]:8080"]
+            max_blocks_per_request = 100
+            enable_webhooks = true
+            enable_digests = false
+
+            [rate_limit]
+            per_minute = 60
+            trust_forwarded = true
+        "#
+        .parse::<Table>()
+        .unwrap();
+        let mut settings = BTreeMap::new();
+        flatten("", table, &mut settings).unwrap();
+        assert_eq!(
+            settings,
+            BTreeMap::from(
+                [
+                    ("BIND_ADDRESS", "0.0.0.0:8080,[::]:8080"),
+                    ("ENABLE_WEBHOOKS", "true"),
+                    ("MAX_BLOCKS_PER_REQUEST", "100"),
+                    ("RATE_LIMIT_PER_MINUTE", "60"),
+                    ("RATE_LIMIT_TRUST_FORWARDED", "true"),
+                ]
+                .map(|(var, value)| (var.to_owned(), value.to_owned()))
+            )
+        );
+    }
+}
@@ -0,0 +1,239 @@
+use std::collections::{BTreeMap, HashMap};
+
+use actix_web::{get, web, HttpResponse, Responder};
+use chrono::{DateTime, TimeDelta, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sqlx::PgPool;
+use utoipa::{IntoParams, ToSchema};
+
+use crate::{
+    cursor, error_reporting,
+    meta::EventTable,
+    registry::{self, with_event_type},
+    utils::split_list,
+    AppState, PaginationInfo, PaginationParams,
+};
+
+/// Receipts of a transaction are usually executed within a few blocks, so
+/// events this close to the requested blocks are also looked at
+const CORRELATION_WINDOW: TimeDelta = TimeDelta::minutes(1);
+
+/// An event of a correlated group, serialized as returned by its endpoint
+pub(crate) struct CorrelatedEvent {
+    pub(crate) transaction_id: String,
+    pub(crate) timestamp: DateTime<Utc>,
+    pub(crate) event: Value,
+}
+
+pub(crate) fn correlated<T: Serialize>(
+    events: Vec<T>,
+    key: impl Fn(&T) -> (String, DateTime<Utc>),
+) -> Vec<CorrelatedEvent> {
+    events
+        .into_iter()
+        .map(|event| {
+            let (transaction_id, timestamp) = key(&event);
+            CorrelatedEvent {
+                transaction_id,
+                timestamp,
+                event: serde_json::to_value(event).unwrap(),
+            }
+        })
+        .collect()
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct CorrelatedGroup {
+    pub transaction_id: String,
+    /// Timestamp of the first event of the transaction
+    #[serde(
+        with = "chrono::serde::ts_nanoseconds",
+        rename = "block_timestamp_nanosec"
+    )]
+    #[schema(value_type = i64)]
+    pub timestamp: DateTime<Utc>,
+    /// Events by type, e.g. `nft_transfer`
+    pub events: BTreeMap<&'static str, Vec<Value>>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct CorrelatedResponse {
+    pub groups: Vec<CorrelatedGroup>,
+    /// `None` if there were no events after `start_block_timestamp_nanosec`
+    pub next_start_block_timestamp_nanosec: Option<i64>,
+    /// Same as `next_start_block_timestamp_nanosec`, to be passed as `cursor`
+    pub cursor: Option<String>,
+}
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+struct CorrelatedFilter {
+    /// Comma-separated event types with a transaction id, at least 2, e.g.
+    /// `nft_sale,trade_swap`
+    types: String,
+}
+
+/// Groups of events of different types that belong to the same transaction,
+/// e.g. an NFT sale and the swap that paid for it
+#[utoipa::path(
+    get,
+    path = "/v0/correlated",
+    tag = "Lookup",
+    params(PaginationParams, CorrelatedFilter),
+    responses(
+        (status = 200, body = CorrelatedResponse),
+        (status = 400, description = "Unknown event types, or fewer than 2"),
+    )
+)]
+#[get("/correlated")]
+pub async fn correlated_events(
+    state: web::Data<AppState>,
+    pagination: web::Query<PaginationInfo>,
+    filter: web::Query<CorrelatedFilter>,
+) -> impl Responder {
+    let max_blocks = state.block_limits().default_limit();
+    if pagination.blocks > max_blocks {
+        return HttpResponse::BadRequest().body(format!(
+            "Blocks per request must be less or equal to {max_blocks}"
+        ));
+    }
+
+    let mut types = Vec::new();
+    for event_type in split_list(&filter.types) {
+        let Ok(table) = serde_json::from_value::<EventTable>(Value::String(event_type.clone()))
+        else {
+            return HttpResponse::BadRequest().body(format!("Unknown event type {event_type}"));
+        };
+        if !table.has_transaction_id() {
+            return HttpResponse::BadRequest()
+                .body(format!("{event_type} events don't have a transaction id"));
+        }
+        if !types.contains(&table) {
+            types.push(table);
+        }
+    }
+    if types.len() < 2 {
+        return HttpResponse::BadRequest().body("types must contain at least 2 event types");
+    }
+
+    let pg_pool = state.pg_pool();
+    match fetch_groups(&pg_pool, &types, &pagination).await {
+        Ok(res) => HttpResponse::Ok().json(res),
+        Err(err) => {
+            error_reporting::report_query_error(&err);
+            HttpResponse::InternalServerError().finish()
+        }
+    }
+}
+
+async fn fetch_groups(
+    pg_pool: &PgPool,
+    types: &[EventTable],
+    pagination: &PaginationInfo,
+) -> Result<CorrelatedResponse, sqlx::Error> {
+    // Table names come from `EventTable`, so it's safe to format them in
+    let timestamps = types
+        .iter()
+        .map(|table| format!("SELECT timestamp FROM {}", table.as_str()))
+        .collect::<Vec<_>>()
+        .join(" UNION ALL ");
+    let (window_start, window_end): (Option<DateTime<Utc>>, Option<DateTime<Utc>>) =
+        sqlx::query_as(&format!(
+            r#"
+            WITH blocks AS (
+                SELECT DISTINCT timestamp as t
+                FROM ({timestamps}) events
+                WHERE timestamp >= $1
+                    AND ($3::TIMESTAMPTZ IS NULL OR timestamp < $3)
+                ORDER BY t
+                LIMIT $2
+            )
+            SELECT MIN(t), MAX(t) FROM blocks
+            "#
+        ))
+        .bind(pagination.start())
+        .bind(pagination.blocks)
+        .bind(pagination.end())
+        .fetch_one(pg_pool)
+        .await?;
+    let (Some(window_start), Some(window_end)) = (window_start, window_end) else {
+        return Ok(CorrelatedResponse {
+            groups: Vec::new(),
+            next_start_block_timestamp_nanosec: None,
+            cursor: None,
+        });
+    };
+    let next_start_block_timestamp_nanosec = window_end.timestamp_nanos_opt().map(|t| t + 1);
+    let from = window_start - CORRELATION_WINDOW;
+    let to = window_end + CORRELATION_WINDOW;
+
+    // A transaction belongs to the page of its first event, so that it's
+    // returned once even if its events span multiple pages
+    let events = types
+        .iter()
+        .map(|table| {
+            format!(
+                "SELECT '{table}' as event_type, transaction_id, timestamp FROM {table} WHERE timestamp BETWEEN $1 AND $2",
+                table = table.as_str()
+            )
+        })
+        .collect::<Vec<_>>()
+        .join(" UNION ALL ");
+    let transaction_ids: Vec<String> = sqlx::query_scalar(&format!(
+        r#"
+        SELECT transaction_id
+        FROM ({events}) events
+        GROUP BY transaction_id
+        HAVING MIN(timestamp) BETWEEN $3 AND $4 AND COUNT(DISTINCT event_type) >= 2
+        "#
+    ))
+    .bind(from)
+    .bind(to)
+    .bind(window_start)
+    .bind(window_end)
+    .fetch_all(pg_pool)
+    .await?;
+
+    let mut groups = HashMap::<String, CorrelatedGroup>::new();
+    for &table in types {
+        let events = fetch_events(pg_pool, table, &transaction_ids, from, to).await?;
+        for event in events {
+            let group = groups
+                .entry(event.transaction_id.clone())
+                .or_insert_with(|| CorrelatedGroup {
+                    transaction_id: event.transaction_id,
+                    timestamp: event.timestamp,
+                    events: BTreeMap::new(),
+                });
+            group.timestamp = group.timestamp.min(event.timestamp);
+            group
+                .events
+                .entry(table.as_str())
+                .or_default()
+                .push(event.event);
+        }
+    }
+
+    let mut groups = groups.into_values().collect::<Vec<_>>();
+    groups.sort_by(|a, b| (a.timestamp, &a.transaction_id).cmp(&(b.timestamp, &b.transaction_id)));
+    Ok(CorrelatedResponse {
+        groups,
+        next_start_block_timestamp_nanosec,
+        cursor: next_start_block_timestamp_nanosec.map(cursor::encode),
+    })
+}
+
+/// Events of a table that belong to any of the transactions, between `from`
+/// and `to` inclusive. The table must have a transaction id.
+pub(crate) async fn fetch_events(
+    pg_pool: &PgPool,
+    table: EventTable,
+    transaction_ids: &[String],
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+) -> Result<Vec<CorrelatedEvent>, sqlx::Error> {
+    with_event_type!(table, |E| {
+        registry::correlated_events::<E>(pg_pool, transaction_ids, from, to).await
+    })
+}
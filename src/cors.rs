@@ -0,0 +1,77 @@
+use actix_cors::Cors;
+use actix_web::http::header::{self, HeaderName};
+
+use crate::request_id;
+
+/// CORS policy, configured with environment variables. By default, any
+/// origin can read responses, but browsers don't send cookies or other
+/// credentials with requests.
+#[derive(Clone)]
+pub struct CorsConfig {
+    /// `CORS_ALLOWED_ORIGINS`, comma-separated, e.g.
+    /// `https://app.example.com,https://example.com`. `*` or unset allows
+    /// any origin.
+    allowed_origins: Option<Vec<String>>,
+    /// `CORS_ALLOWED_HEADERS`, comma-separated headers that requests can have
+    /// in addition to `Content-Type`, e.g. `Authorization`
+    allowed_headers: Vec<HeaderName>,
+    /// `CORS_SUPPORTS_CREDENTIALS`, requires `CORS_ALLOWED_ORIGINS`, since
+    /// any site could make requests with the user's credentials otherwise
+    supports_credentials: bool,
+}
+
+impl CorsConfig {
+    pub fn from_env() -> Self {
+        let list = |var: &str| {
+            std::env::var(var)
+                .unwrap_or_default()
+                .split(',')
+                .map(str::trim)
+                .filter(|item| !item.is_empty())
+                .map(ToOwned::to_owned)
+                .collect::<Vec<_>>()
+        };
+        let allowed_origins = list("CORS_ALLOWED_ORIGINS");
+        let allowed_origins = (!allowed_origins.is_empty()
+            && !allowed_origins.iter().any(|origin| origin == "*"))
+        .then_some(allowed_origins);
+        let allowed_headers = list("CORS_ALLOWED_HEADERS")
+            .into_iter()
+            .map(|name| {
+                HeaderName::try_from(name.as_str()).unwrap_or_else(|_| {
+                    panic!("CORS_ALLOWED_HEADERS must be header names, `{name}` isn't one")
+                })
+            })
+            .collect();
+        let supports_credentials = std::env::var("CORS_SUPPORTS_CREDENTIALS").is_ok();
+        if supports_credentials && allowed_origins.is_none() {
+            panic!("CORS_SUPPORTS_CREDENTIALS requires CORS_ALLOWED_ORIGINS to list origins");
+        }
+        Self {
+            allowed_origins,
+            allowed_headers,
+            supports_credentials,
+        }
+    }
+
+    /// Middleware of a worker
+    pub fn cors(&self) -> Cors {
+        let mut cors = Cors::default()
+            // POST is only used by GraphQL and batch queries, which are sent as JSON
+            .allowed_methods(vec!["GET", "POST"])
+            .allowed_header(header::CONTENT_TYPE)
+            .allowed_headers(self.allowed_headers.clone())
+            .expose_headers([request_id::REQUEST_ID_HEADER])
+            .max_age(3600);
+        cors = match &self.allowed_origins {
+            Some(origins) => origins
+                .iter()
+                .fold(cors, |cors, origin| cors.allowed_origin(origin)),
+            None => cors.allow_any_origin(),
+        };
+        if self.supports_credentials {
+            cors = cors.supports_credentials();
+        }
+        cors
+    }
+}
@@ -0,0 +1,64 @@
+use actix_web::{get, web, HttpRequest, HttpResponse};
+use serde::Serialize;
+use utoipa::ToSchema;
+
+use crate::{
+    admin::{QueryError, Summary},
+    error::{ApiError, ErrorResponse},
+    meta::EventTable,
+    registry::{self, with_event_type, EventType},
+    AppState,
+};
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct EventCount {
+    pub count: i64,
+}
+
+/// Number of events of an event endpoint with the same filters, e.g.
+/// `/v0/nft/nft_mint/count?token_account_id=x.near`, for dashboards that would
+/// otherwise paginate through all events to count them. The count is over the
+/// whole time range between `start_block_timestamp_nanosec` and
+/// `end_block_timestamp_nanosec`, `blocks` and `limit` are ignored.
+#[utoipa::path(
+    get,
+    path = "/v0/{category}/{endpoint}/count",
+    tag = "Count",
+    params(
+        ("category" = String, Path, description = "Category of the endpoint, e.g. `nft`"),
+        ("endpoint" = String, Path, description = "Event type, e.g. `nft_mint`"),
+    ),
+    responses(
+        (status = 200, body = EventCount),
+        (status = 400, body = ErrorResponse),
+        (status = 404, body = ErrorResponse),
+        (status = 503, body = ErrorResponse),
+    )
+)]
+#[get("/{category}/{endpoint}/count")]
+pub async fn count(
+    state: web::Data<AppState>,
+    path: web::Path<(String, String)>,
+    req: HttpRequest,
+) -> Result<HttpResponse, ApiError> {
+    let _permit = state.heavy_queries().try_acquire()?;
+    let (category, endpoint) = path.into_inner();
+    let pg_pool = state.pg_pool();
+    let query = req.query_string();
+    let unknown = || ApiError::NotFound(format!("Unknown endpoint /v0/{category}/{endpoint}"));
+    let table = EventTable::ALL
+        .into_iter()
+        .find(|table| table.as_str() == endpoint)
+        .ok_or_else(unknown)?;
+    if with_event_type!(table, |E| E::SCOPE) != format!("/v0/{category}") {
+        return Err(unknown());
+    }
+    let count = with_event_type!(table, |E| {
+        registry::summarize::<E, _>(&pg_pool, query, Summary::Count).await
+    });
+    match count {
+        Ok(count) => Ok(HttpResponse::Ok().json(EventCount { count })),
+        Err(QueryError::BadQuery(message)) => Err(ApiError::BadFilter(message)),
+        Err(QueryError::Database(err)) => Err(ApiError::Database(err)),
+    }
+}
@@ -0,0 +1,49 @@
+use indexmap::{IndexMap, IndexSet};
+use serde::Serialize;
+use serde_json::Value;
+
+pub(crate) const CONTENT_TYPE: &str = "text/csv; charset=utf-8";
+
+/// Events as CSV with a header row, for spreadsheets. Columns are fields of
+/// the events in their original order, with fields of all event types when
+/// types are mixed. Lists of plain values, like `token_ids`, are joined with
+/// `;`, and objects, like `balance_changes`, are JSON-encoded.
+pub fn to_csv<T: Serialize>(events: &[T]) -> Vec<u8> {
+    // Maps of `serde_json` are sorted, so fields are read into an `IndexMap`
+    // to keep them in the order of the struct
+    let rows = events
+        .iter()
+        .map(|event| {
+            serde_json::from_str::<IndexMap<String, Value>>(&serde_json::to_string(event).unwrap())
+                .unwrap()
+        })
+        .collect::<Vec<_>>();
+    let mut columns = IndexSet::new();
+    for row in &rows {
+        columns.extend(row.keys());
+    }
+
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    if !rows.is_empty() {
+        writer.write_record(&columns).unwrap();
+    }
+    for row in &rows {
+        writer
+            .write_record(columns.iter().map(|&column| cell(row.get(column))))
+            .unwrap();
+    }
+    writer.into_inner().unwrap()
+}
+
+fn cell(value: Option<&Value>) -> String {
+    match value {
+        None | Some(Value::Null) => String::new(),
+        Some(Value::String(value)) => value.clone(),
+        Some(Value::Array(values)) if values.iter().all(|value| !value.is_object()) => values
+            .iter()
+            .map(|value| cell(Some(value)))
+            .collect::<Vec<_>>()
+            .join(";"),
+        Some(value) => value.to_string(),
+    }
+}
@@ -0,0 +1,260 @@
+use actix_web::{
+    body::MessageBody,
+    dev::{ServiceRequest, ServiceResponse},
+    http::{header, Uri},
+    middleware::Next,
+    Error, HttpResponse,
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+pub use inteat_events_api_types::EventsPage;
+
+use crate::{csv_format, ndjson, Order};
+
+/// Cursors are opaque to clients, so that the position they encode can change
+/// without breaking them. Currently it's the timestamp the next page starts
+/// from, or ends before with `order=desc`.
+pub(crate) fn encode(block_timestamp_nanosec: i64) -> String {
+    format!("{:016x}", block_timestamp_nanosec)
+}
+
+/// Cursor of a page that ended inside a block, followed by the tie breaker of
+/// its last event, e.g. the receipt id
+pub(crate) fn encode_inside_block(block_timestamp_nanosec: i64, tie_breaker: &str) -> String {
+    format!(
+        "{}{}",
+        encode(block_timestamp_nanosec),
+        hex::encode(tie_breaker)
+    )
+}
+
+/// Timestamp of the block, and the tie breaker if the cursor is inside it
+pub(crate) fn decode(cursor: &str) -> Result<(i64, Option<String>), String> {
+    let invalid = || "Invalid cursor".to_owned();
+    let (block, tie_breaker) = cursor.split_at_checked(16).ok_or_else(invalid)?;
+    let block = u64::from_str_radix(block, 16)
+        .ok()
+        .and_then(|block| i64::try_from(block).ok())
+        .ok_or_else(invalid)?;
+    if tie_breaker.is_empty() {
+        return Ok((block, None));
+    }
+    let tie_breaker = hex::decode(tie_breaker)
+        .ok()
+        .and_then(|tie_breaker| String::from_utf8(tie_breaker).ok())
+        .ok_or_else(invalid)?;
+    Ok((block, Some(tie_breaker)))
+}
+
+/// Timestamp of a cursor that's never inside a block, like that of `/v1`
+pub(crate) fn decode_block(cursor: &str) -> Result<i64, String> {
+    match decode(cursor)? {
+        (block, None) => Ok(block),
+        (_, Some(_)) => Err("Invalid cursor".to_owned()),
+    }
+}
+
+#[derive(Deserialize, ToSchema, Default, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum ResponseFormat {
+    /// `{"events": [...], "cursor": "..."}`
+    #[default]
+    Envelope,
+    /// Just the events, as returned before cursors were added
+    Legacy,
+    /// One event per line, streamed as it's read from the database. There's
+    /// no cursor, the next page starts right after the last event's block.
+    Ndjson,
+    /// A table with a row per event, also without a cursor
+    Csv,
+    /// Same as `envelope`, encoded as MessagePack, which is smaller and faster
+    /// to parse than JSON
+    Msgpack,
+}
+
+const MSGPACK_CONTENT_TYPE: &str = "application/msgpack";
+
+/// Media types that can be requested with `Accept` instead of `format`
+const ACCEPTED_FORMATS: &[(&str, &str)] = &[
+    (ndjson::CONTENT_TYPE, "ndjson"),
+    (MSGPACK_CONTENT_TYPE, "msgpack"),
+    ("application/x-msgpack", "msgpack"),
+];
+
+/// Response of an event endpoint
+pub(crate) struct Page {
+    format: ResponseFormat,
+    cursor: Option<String>,
+}
+
+impl Page {
+    /// Pages always contain whole blocks, so the next page starts right after
+    /// the latest block of this one, or right before the earliest one when
+    /// going back in time
+    pub(crate) fn new<T>(
+        format: ResponseFormat,
+        order: Order,
+        events: &[T],
+        timestamp: impl Fn(&T) -> DateTime<Utc>,
+    ) -> Self {
+        let timestamps = events.iter().map(timestamp);
+        let next = match order {
+            Order::Asc => timestamps
+                .max()
+                .and_then(|t| t.timestamp_nanos_opt())
+                .map(|t| t + 1),
+            Order::Desc => timestamps.min().and_then(|t| t.timestamp_nanos_opt()),
+        };
+        Self {
+            format,
+            cursor: next.map(encode),
+        }
+    }
+
+    /// Page that ended inside a block, which the next page continues after
+    /// the last event
+    pub(crate) fn inside_block(
+        format: ResponseFormat,
+        block_timestamp_nanosec: i64,
+        tie_breaker: &str,
+    ) -> Self {
+        Self {
+            format,
+            cursor: Some(encode_inside_block(block_timestamp_nanosec, tie_breaker)),
+        }
+    }
+
+    #[cfg(test)]
+    pub(crate) fn cursor(&self) -> Option<&str> {
+        self.cursor.as_deref()
+    }
+
+    pub(crate) fn respond<T: Serialize>(self, events: Vec<T>) -> HttpResponse {
+        match self.format {
+            ResponseFormat::Envelope => HttpResponse::Ok().json(EventsPage {
+                events,
+                cursor: self.cursor,
+            }),
+            ResponseFormat::Legacy => HttpResponse::Ok().json(events),
+            ResponseFormat::Ndjson => HttpResponse::Ok()
+                .content_type(ndjson::CONTENT_TYPE)
+                .body(events.iter().flat_map(ndjson::line).collect::<Vec<_>>()),
+            ResponseFormat::Csv => HttpResponse::Ok()
+                .content_type(csv_format::CONTENT_TYPE)
+                .body(csv_format::to_csv(&events)),
+            // Fields are encoded with their names, so that the structure is
+            // the same as of JSON
+            ResponseFormat::Msgpack => HttpResponse::Ok().content_type(MSGPACK_CONTENT_TYPE).body(
+                rmp_serde::to_vec_named(&EventsPage {
+                    events,
+                    cursor: self.cursor,
+                })
+                .unwrap(),
+            ),
+        }
+    }
+}
+
+/// `Accept: application/x-ndjson` is the same as `format=ndjson`, and
+/// `Accept: application/msgpack` as `format=msgpack`, for clients that
+/// negotiate the format with headers. An explicit `format` takes precedence.
+/// CSV responses are downloaded as a file named after the endpoint, e.g.
+/// `nft_transfer.csv`.
+pub async fn negotiate_format(
+    mut req: ServiceRequest,
+    next: Next<impl MessageBody>,
+) -> Result<ServiceResponse<impl MessageBody>, Error> {
+    let accepted_format = req
+        .headers()
+        .get(header::ACCEPT)
+        .and_then(|accept| accept.to_str().ok())
+        .and_then(|accept| {
+            // Media types are picked in the order they're listed, so JSON is
+            // kept if it's listed first
+            accept
+                .split(',')
+                .map(|media_type| media_type.split(';').next().unwrap_or_default().trim())
+                .find_map(|media_type| {
+                    if media_type.eq_ignore_ascii_case("application/json") {
+                        return Some(None);
+                    }
+                    ACCEPTED_FORMATS
+                        .iter()
+                        .find(|(accepted, _)| media_type.eq_ignore_ascii_case(accepted))
+                        .map(|(_, format)| Some(*format))
+                })
+                .flatten()
+        });
+    let has_format = serde_urlencoded::from_str::<Vec<(String, String)>>(req.query_string())
+        .is_ok_and(|params| params.iter().any(|(name, _)| name == "format"));
+    if let Some(format) = accepted_format.filter(|_| !has_format) {
+        let uri = req.uri();
+        let path_and_query = match uri.query() {
+            Some(query) => format!("{}?{query}&format={format}", uri.path()),
+            None => format!("{}?format={format}", uri.path()),
+        };
+        let mut parts = uri.clone().into_parts();
+        parts.path_and_query = path_and_query.parse().ok();
+        if let Ok(uri) = Uri::from_parts(parts) {
+            req.head_mut().uri = uri;
+        }
+    }
+    let mut res = next.call(req).await?;
+    let is_csv = res
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .is_some_and(|content_type| content_type == csv_format::CONTENT_TYPE);
+    if is_csv {
+        let name = res.request().path().rsplit('/').next().unwrap_or("events");
+        if let Ok(disposition) =
+            header::HeaderValue::from_str(&format!("attachment; filename=\"{name}.csv\""))
+        {
+            res.headers_mut()
+                .insert(header::CONTENT_DISPOSITION, disposition);
+        }
+    }
+    Ok(res)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn block_cursors_decode_without_tie_breaker() {
+        assert_eq!(decode("17979cfe362a0000"), Ok((1700000000000000000, None)));
+        assert_eq!(decode_block("17979cfe362a0000"), Ok(1700000000000000000));
+        assert_eq!(decode(&encode(0)), Ok((0, None)));
+    }
+
+    #[test]
+    fn cursors_inside_blocks_keep_the_tie_breaker() {
+        for tie_breaker in [
+            "7SgRbGUGnkYkDWT9fSLqUTuSbUK6hEVyF6WJ3fVnXFZx",
+            "wrap.near",
+            "a-b_c",
+        ] {
+            let cursor = encode_inside_block(1700000000000000000, tie_breaker);
+            assert_eq!(
+                decode(&cursor),
+                Ok((1700000000000000000, Some(tie_breaker.to_owned())))
+            );
+            assert!(decode_block(&cursor).is_err());
+        }
+    }
+
+    #[test]
+    fn rejects_malformed_cursors() {
+        for cursor in [
+            "",
+            "17979cfe362a000",
+            "17979cfe362a000g",
+            "ffffffffffffffff",
+            "17979cfe362a00007",
+        ] {
+            assert!(decode(cursor).is_err(), "{cursor}");
+        }
+    }
+}
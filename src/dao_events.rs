@@ -0,0 +1,123 @@
+use chrono::prelude::{DateTime, Utc};
+pub use inteat_events_api_types::dao::{
+    DaoProposalEvent, DaoProposalExecuteEvent, DaoProposalExecuteFilter, DaoProposalFilter,
+    DaoVoteEvent, DaoVoteFilter,
+};
+
+use crate::endpoint::{event_endpoint, EventEndpoint};
+use crate::event_query::EventQuery;
+use crate::meta::EventTable;
+
+impl EventEndpoint for DaoProposalEvent {
+    const TABLE: EventTable = EventTable::DaoProposal;
+    type Filter = DaoProposalFilter;
+
+    fn query(filter: &DaoProposalFilter) -> EventQuery<Self> {
+        EventQuery::new(
+            "dao_proposal",
+            "dao_id, proposal_id, proposer, proposal_kind, description, transaction_id, receipt_id, block_height, timestamp",
+        )
+        .filter("dao_id = ANY({})", filter.dao_id.as_deref())
+        .filter("proposal_id = ANY({})", filter.proposal_id.as_deref())
+        .filter("proposer = ANY({})", filter.proposer.as_deref())
+        .filter("proposal_kind = ANY({})", filter.proposal_kind.as_deref())
+        .filter("transaction_id = ANY({})", filter.transaction_id.as_deref())
+        .filter("receipt_id = ANY({})", filter.receipt_id.as_deref())
+    }
+
+    fn timestamp(&self) -> DateTime<Utc> {
+        self.timestamp
+    }
+
+    fn receipt_id(&self) -> &str {
+        &self.receipt_id
+    }
+
+    fn transaction(&self) -> (&str, &str) {
+        (&self.transaction_id, &self.proposer)
+    }
+}
+
+event_endpoint! {
+    /// DAO proposal creation events
+    dao_proposal(DaoProposalEvent, DaoProposalFilter),
+    route = "/dao_proposal",
+    scope = "/v0/dao",
+    tag = "DAO",
+}
+
+impl EventEndpoint for DaoVoteEvent {
+    const TABLE: EventTable = EventTable::DaoVote;
+    type Filter = DaoVoteFilter;
+
+    fn query(filter: &DaoVoteFilter) -> EventQuery<Self> {
+        EventQuery::new(
+            "dao_vote",
+            "dao_id, proposal_id, account_id, vote, proposal_kind, transaction_id, receipt_id, block_height, timestamp",
+        )
+        .filter("dao_id = ANY({})", filter.dao_id.as_deref())
+        .filter("proposal_id = ANY({})", filter.proposal_id.as_deref())
+        .filter("account_id = ANY({})", filter.account_id.as_deref())
+        .filter("proposal_kind = ANY({})", filter.proposal_kind.as_deref())
+        .filter("transaction_id = ANY({})", filter.transaction_id.as_deref())
+        .filter("receipt_id = ANY({})", filter.receipt_id.as_deref())
+    }
+
+    fn timestamp(&self) -> DateTime<Utc> {
+        self.timestamp
+    }
+
+    fn receipt_id(&self) -> &str {
+        &self.receipt_id
+    }
+
+    fn transaction(&self) -> (&str, &str) {
+        (&self.transaction_id, &self.account_id)
+    }
+}
+
+event_endpoint! {
+    /// DAO vote events
+    dao_vote(DaoVoteEvent, DaoVoteFilter),
+    route = "/dao_vote",
+    scope = "/v0/dao",
+    tag = "DAO",
+}
+
+impl EventEndpoint for DaoProposalExecuteEvent {
+    const TABLE: EventTable = EventTable::DaoProposalExecute;
+    type Filter = DaoProposalExecuteFilter;
+
+    fn query(filter: &DaoProposalExecuteFilter) -> EventQuery<Self> {
+        EventQuery::new(
+            "dao_proposal_execute",
+            "dao_id, proposal_id, proposer, proposal_kind, account_id, success, transaction_id, receipt_id, block_height, timestamp",
+        )
+        .filter("dao_id = ANY({})", filter.dao_id.as_deref())
+        .filter("proposal_id = ANY({})", filter.proposal_id.as_deref())
+        .filter("proposer = ANY({})", filter.proposer.as_deref())
+        .filter("proposal_kind = ANY({})", filter.proposal_kind.as_deref())
+        .filter("transaction_id = ANY({})", filter.transaction_id.as_deref())
+        .filter("receipt_id = ANY({})", filter.receipt_id.as_deref())
+    }
+
+    fn timestamp(&self) -> DateTime<Utc> {
+        self.timestamp
+    }
+
+    fn receipt_id(&self) -> &str {
+        &self.receipt_id
+    }
+
+    fn transaction(&self) -> (&str, &str) {
+        (&self.transaction_id, &self.account_id)
+    }
+}
+
+event_endpoint! {
+    /// DAO proposal execution events
+    dao_proposal_execute(DaoProposalExecuteEvent, DaoProposalExecuteFilter),
+    route = "/dao_proposal_execute",
+    scope = "/v0/dao",
+    tag = "DAO",
+}
@@ -0,0 +1,472 @@
+use std::{collections::BTreeMap, time::Duration};
+
+use actix_web::{delete, post, web, HttpRequest, HttpResponse, Scope};
+use chrono::{DateTime, DurationRound, TimeDelta, Utc};
+use futures_util::StreamExt;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use utoipa::ToSchema;
+
+use crate::{
+    admin::{QueryError, Summary},
+    count::EventCount,
+    error::{ApiError, ErrorResponse},
+    error_reporting,
+    meta::EventTable,
+    registry::{self, with_event_type, EventType},
+    webhooks::{bearer_token, retry_delay, LEASE},
+    AppState,
+};
+
+const SCHEDULER_INTERVAL: Duration = Duration::from_secs(60);
+const WEBHOOK_TIMEOUT: Duration = Duration::from_secs(10);
+/// Digests claimed by one tick, leased like webhooks
+const DELIVERIES_PER_TICK: i64 = 100;
+const CONCURRENT_DELIVERIES: usize = 16;
+/// Failed deliveries are retried with the backoff of webhooks, and after this
+/// many the digest of the period is skipped
+const MAX_FAILURES: i32 = 10;
+
+/// Raw events of the digest period can be fetched from this endpoint
+fn events_url(
+    public_url: &str,
+    event_type: EventTable,
+    filter: &str,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+) -> String {
+    let endpoint = with_event_type!(event_type, |E| E::endpoint());
+    format!(
+        "{public_url}{endpoint}?{}",
+        period_query(filter, start, end)
+    )
+}
+
+/// Filters of a digest, limited to the events of the period
+fn period_query(filter: &str, start: DateTime<Utc>, end: DateTime<Utc>) -> String {
+    let period = serde_urlencoded::to_string([
+        (
+            "start_block_timestamp_nanosec",
+            start.timestamp_nanos_opt().unwrap_or_default(),
+        ),
+        (
+            "end_block_timestamp_nanosec",
+            end.timestamp_nanos_opt().unwrap_or_default(),
+        ),
+    ])
+    .unwrap();
+    format!("{period}&{filter}")
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, sqlx::Type, ToSchema)]
+#[serde(rename_all = "snake_case")]
+#[sqlx(type_name = "TEXT", rename_all = "snake_case")]
+pub enum DigestSchedule {
+    Hourly,
+    Daily,
+    Weekly,
+}
+
+impl DigestSchedule {
+    fn interval(&self) -> TimeDelta {
+        match self {
+            DigestSchedule::Hourly => TimeDelta::hours(1),
+            DigestSchedule::Daily => TimeDelta::days(1),
+            DigestSchedule::Weekly => TimeDelta::weeks(1),
+        }
+    }
+
+    /// End of the current period, when the first digest is delivered
+    fn first_run_at(&self, now: DateTime<Utc>) -> DateTime<Utc> {
+        now.duration_trunc(self.interval()).unwrap() + self.interval()
+    }
+}
+
+struct DigestSubscription {
+    id: i64,
+    event_type: String,
+    filter: String,
+    schedule: DigestSchedule,
+    webhook_url: String,
+    next_run_at: DateTime<Utc>,
+    failures: i32,
+}
+
+#[derive(Serialize)]
+struct DigestPayload {
+    subscription_id: i64,
+    event_type: EventTable,
+    filter: BTreeMap<String, String>,
+    #[serde(with = "chrono::serde::ts_nanoseconds")]
+    period_start_nanosec: DateTime<Utc>,
+    #[serde(with = "chrono::serde::ts_nanoseconds")]
+    period_end_nanosec: DateTime<Utc>,
+    summary: EventCount,
+    events_url: String,
+}
+
+pub fn scope() -> Scope {
+    web::scope("/digests")
+        .service(create_digest)
+        .service(delete_digest)
+}
+
+/// Runs database migrations needed for digests and starts delivering them
+pub async fn start(state: web::Data<AppState>) {
+    sqlx::migrate!()
+        .run(&state.primary_pg_pool())
+        .await
+        .expect("Failed to run digest migrations");
+    let public_url =
+        std::env::var("PUBLIC_URL").unwrap_or("https://events.intear.tech".to_string());
+    tokio::spawn(async move {
+        let client = reqwest::Client::builder()
+            .timeout(WEBHOOK_TIMEOUT)
+            .build()
+            .unwrap();
+        let mut interval = tokio::time::interval(SCHEDULER_INTERVAL);
+        loop {
+            interval.tick().await;
+            if let Err(err) = deliver_due_digests(&state, &client, &public_url).await {
+                log::error!("Failed to deliver digests: {err}");
+            }
+        }
+    });
+}
+
+async fn deliver_due_digests(
+    state: &AppState,
+    client: &reqwest::Client,
+    public_url: &str,
+) -> Result<(), sqlx::Error> {
+    let subscriptions = sqlx::query_as!(
+        DigestSubscription,
+        r#"
+        UPDATE digest_subscriptions SET next_attempt_at = $1
+        WHERE id IN (
+            SELECT id FROM digest_subscriptions
+            WHERE next_attempt_at <= now()
+            ORDER BY next_attempt_at
+            LIMIT $2
+            FOR UPDATE SKIP LOCKED
+        )
+        RETURNING id, event_type, filter, schedule as "schedule: DigestSchedule", webhook_url, next_run_at, failures
+        "#,
+        Utc::now() + LEASE,
+        DELIVERIES_PER_TICK,
+    )
+    .fetch_all(&state.primary_pg_pool())
+    .await?;
+
+    futures_util::stream::iter(subscriptions)
+        .for_each_concurrent(CONCURRENT_DELIVERIES, |subscription| async move {
+            if let Err(err) = deliver(state, client, public_url, &subscription).await {
+                log::error!("Failed to deliver digest {}: {err}", subscription.id);
+            }
+        })
+        .await;
+    Ok(())
+}
+
+/// Sends the digest of the period that ends at `next_run_at`, and schedules
+/// the next one after the receiver responds with a 2xx status
+async fn deliver(
+    state: &AppState,
+    client: &reqwest::Client,
+    public_url: &str,
+    subscription: &DigestSubscription,
+) -> Result<(), sqlx::Error> {
+    let Some(event_type) = EventTable::ALL
+        .into_iter()
+        .find(|table| table.as_str() == subscription.event_type)
+    else {
+        return failed(state, subscription, "Unknown event type").await;
+    };
+    let period_end = subscription.next_run_at;
+    let period_start = period_end - subscription.schedule.interval();
+    // Counted the same way as by the count of the event type's endpoint
+    let query = period_query(&subscription.filter, period_start, period_end);
+    let pg_pool = state.pg_pool();
+    let count = with_event_type!(event_type, |E| {
+        registry::summarize::<E, _>(&pg_pool, &query, Summary::Count).await
+    });
+    let count = match count {
+        Ok(count) => count,
+        Err(QueryError::BadQuery(message)) => return failed(state, subscription, &message).await,
+        Err(QueryError::Database(err)) => {
+            error_reporting::report_query_error(&err);
+            return failed(state, subscription, &err.to_string()).await;
+        }
+    };
+    let payload = DigestPayload {
+        subscription_id: subscription.id,
+        event_type,
+        filter: serde_urlencoded::from_str(&subscription.filter).unwrap_or_default(),
+        period_start_nanosec: period_start,
+        period_end_nanosec: period_end,
+        summary: EventCount { count },
+        events_url: events_url(
+            public_url,
+            event_type,
+            &subscription.filter,
+            period_start,
+            period_end,
+        ),
+    };
+    let delivery = client
+        .post(&subscription.webhook_url)
+        .json(&payload)
+        .send()
+        .await
+        .and_then(|res| res.error_for_status());
+    if let Err(err) = delivery {
+        log::warn!(
+            "Failed to deliver digest {} to {}: {err}",
+            subscription.id,
+            subscription.webhook_url
+        );
+        return failed(state, subscription, &err.to_string()).await;
+    }
+    next_period(state, subscription, None).await
+}
+
+/// Schedules the digest of the next period, which is due right away if the
+/// scheduler is behind
+async fn next_period(
+    state: &AppState,
+    subscription: &DigestSubscription,
+    error: Option<&str>,
+) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        r#"
+        UPDATE digest_subscriptions
+        SET next_run_at = $2, next_attempt_at = $2, failures = 0, last_error = $3
+        WHERE id = $1
+        "#,
+        subscription.id,
+        subscription.next_run_at + subscription.schedule.interval(),
+        error,
+    )
+    .execute(&state.primary_pg_pool())
+    .await?;
+    Ok(())
+}
+
+/// Schedules a retry with exponential backoff, or skips the period after
+/// `MAX_FAILURES`
+async fn failed(
+    state: &AppState,
+    subscription: &DigestSubscription,
+    error: &str,
+) -> Result<(), sqlx::Error> {
+    let failures = subscription.failures + 1;
+    if failures >= MAX_FAILURES {
+        log::warn!(
+            "Skipping digest {} of the period ending at {} after {failures} failures",
+            subscription.id,
+            subscription.next_run_at,
+        );
+        return next_period(state, subscription, Some(error)).await;
+    }
+    sqlx::query!(
+        r#"
+        UPDATE digest_subscriptions SET failures = $2, last_error = $3, next_attempt_at = $4
+        WHERE id = $1
+        "#,
+        subscription.id,
+        failures,
+        error,
+        Utc::now() + retry_delay(failures),
+    )
+    .execute(&state.primary_pg_pool())
+    .await?;
+    Ok(())
+}
+
+#[derive(Deserialize, ToSchema)]
+struct CreateDigest {
+    event_type: EventTable,
+    /// Same filters as the event type's endpoint accepts, at least one
+    filter: BTreeMap<String, String>,
+    schedule: DigestSchedule,
+    webhook_url: String,
+}
+
+/// Number of events matching the filters in each period is delivered to the
+/// webhook at the end of the period
+#[utoipa::path(
+    post,
+    path = "/v0/digests",
+    tag = "Digests",
+    request_body = CreateDigest,
+    responses(
+        (status = 201, description = "`id` of the subscription, the `token` to delete it, and when the first digest is delivered"),
+        (status = 400, body = ErrorResponse),
+    )
+)]
+#[post("")]
+pub async fn create_digest(
+    state: web::Data<AppState>,
+    body: web::Json<CreateDigest>,
+) -> Result<HttpResponse, ApiError> {
+    if !body.webhook_url.starts_with("https://") {
+        return Err(ApiError::Validation(
+            "webhook_url must be an https:// URL".to_string(),
+        ));
+    }
+    // Digests of all events of a type would count whole tables every period
+    if body.filter.values().all(|value| value.is_empty()) {
+        return Err(ApiError::BadFilter(
+            "filter must have at least one filter of the event type".to_string(),
+        ));
+    }
+    let filter = serde_urlencoded::to_string(&body.filter)
+        .map_err(|err| ApiError::BadFilter(err.to_string()))?;
+    with_event_type!(body.event_type, |E| registry::check_filter::<E>(&filter)).map_err(|err| {
+        match err {
+            QueryError::BadQuery(message) => ApiError::BadFilter(message),
+            QueryError::Database(err) => ApiError::Database(err),
+        }
+    })?;
+
+    let res = sqlx::query!(
+        r#"
+        INSERT INTO digest_subscriptions (event_type, filter, schedule, webhook_url, next_run_at, next_attempt_at)
+        VALUES ($1, $2, $3, $4, $5, $5)
+        RETURNING id, token, next_run_at
+        "#,
+        body.event_type.as_str(),
+        filter,
+        body.schedule as DigestSchedule,
+        body.webhook_url,
+        body.schedule.first_run_at(Utc::now()),
+    )
+    .fetch_one(&state.primary_pg_pool())
+    .await?;
+    Ok(HttpResponse::Created().json(json!({
+        "id": res.id,
+        "token": res.token,
+        "next_run_at_nanosec": res.next_run_at.timestamp_nanos_opt(),
+    })))
+}
+
+/// Requires `Authorization: Bearer <token>` with the token returned on creation
+#[utoipa::path(
+    delete,
+    path = "/v0/digests/{id}",
+    tag = "Digests",
+    params(("id" = i64, Path)),
+    security(("bearer" = [])),
+    responses(
+        (status = 204),
+        (status = 401, body = ErrorResponse),
+        (status = 404, body = ErrorResponse),
+    )
+)]
+#[delete("/{id}")]
+pub async fn delete_digest(
+    state: web::Data<AppState>,
+    id: web::Path<i64>,
+    req: HttpRequest,
+) -> Result<HttpResponse, ApiError> {
+    let Some(token) = bearer_token(&req) else {
+        return Err(ApiError::Unauthorized);
+    };
+    let res = sqlx::query!(
+        "DELETE FROM digest_subscriptions WHERE id = $1 AND token = $2",
+        id.into_inner(),
+        token,
+    )
+    .execute(&state.primary_pg_pool())
+    .await?;
+    if res.rows_affected() == 0 {
+        return Err(ApiError::NotFound("Digest not found".to_string()));
+    }
+    Ok(HttpResponse::NoContent().finish())
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::{
+        http::StatusCode,
+        test::{call_service, init_service, read_body_json, TestRequest},
+        App,
+    };
+    use serde_json::Value;
+    use sqlx::postgres::PgPoolOptions;
+
+    use super::*;
+    use crate::limits::{BlockLimits, HeavyQueries};
+
+    #[actix_web::test]
+    async fn invalid_digests_are_rejected_before_reaching_the_database() {
+        let pg_pool = PgPoolOptions::new()
+            .connect_lazy("postgres://localhost/events")
+            .unwrap();
+        let state = AppState::new(
+            pg_pool,
+            Vec::new(),
+            BlockLimits::from_env(),
+            HeavyQueries::from_env(),
+        );
+        let app = init_service(
+            App::new()
+                .app_data(web::Data::new(state))
+                .service(web::scope("/v0").service(scope())),
+        )
+        .await;
+
+        let digest = |filter: Value, webhook_url: &str| {
+            json!({
+                "event_type": "nft_transfer",
+                "filter": filter,
+                "schedule": "daily",
+                "webhook_url": webhook_url,
+            })
+        };
+        let https = "https://example.com";
+        for (body, code) in [
+            (
+                digest(json!({ "contract_id": "x.near" }), "http://example.com"),
+                "validation_failed",
+            ),
+            (digest(json!({}), https), "bad_filter"),
+            (
+                digest(json!({ "contractid": "x.near" }), https),
+                "bad_filter",
+            ),
+            (
+                digest(json!({ "contract_id": "x.near", "blocks": "5" }), https),
+                "bad_filter",
+            ),
+        ] {
+            let req = TestRequest::post()
+                .uri("/v0/digests")
+                .set_json(&body)
+                .to_request();
+            let res = call_service(&app, req).await;
+            assert_eq!(res.status(), StatusCode::BAD_REQUEST, "{body}");
+            let error: Value = read_body_json(res).await;
+            assert_eq!(error["error"]["code"], code, "{body}");
+        }
+
+        let req = TestRequest::delete().uri("/v0/digests/1").to_request();
+        let res = call_service(&app, req).await;
+        assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+    }
+
+    #[test]
+    fn events_url_covers_the_period() {
+        let start = DateTime::from_timestamp(1_700_000_000, 0).unwrap();
+        assert_eq!(
+            events_url(
+                "https://events.intear.tech",
+                EventTable::NftTransfer,
+                "contract_id=x.near",
+                start,
+                start + DigestSchedule::Hourly.interval(),
+            ),
+            "https://events.intear.tech/v0/nft/nft_transfer?start_block_timestamp_nanosec=1700000000000000000&end_block_timestamp_nanosec=1700003600000000000&contract_id=x.near"
+        );
+    }
+}
@@ -0,0 +1,155 @@
+use actix_web::{web, HttpResponse};
+use chrono::{DateTime, Utc};
+use serde::{de::DeserializeOwned, Serialize};
+use sqlx::{postgres::PgRow, FromRow};
+
+use crate::{
+    aggregate::{AggregationParams, MAX_BLOCKS_PER_AGGREGATE},
+    cursor::ResponseFormat,
+    enrich::{self, EnrichParams, TxStatusClient},
+    error::ApiError,
+    event_query::EventQuery,
+    meta::EventTable,
+    AppState, Ordering, PaginationInfo,
+};
+
+/// Event type with a paginated endpoint. The handler, with its route and
+/// OpenAPI schema, is generated by `event_endpoint!`, and the same query
+/// serves summaries and live subscriptions.
+pub(crate) trait EventEndpoint:
+    for<'r> FromRow<'r, PgRow> + Serialize + Send + Unpin + 'static
+{
+    const TABLE: EventTable;
+    /// Fields accepted in `aggregate=sum:<field>`, with their SQL expressions
+    const SUM_FIELDS: &'static [(&'static str, &'static str)] = &[];
+    type Filter: DeserializeOwned + Default;
+
+    fn query(filter: &Self::Filter) -> EventQuery<Self>;
+
+    fn timestamp(&self) -> DateTime<Utc>;
+
+    /// Sorts events of the same block, so that a page can end inside a block
+    fn receipt_id(&self) -> &str;
+
+    /// Transaction of the event and its signer, for `enrich=tx_status`
+    fn transaction(&self) -> (&str, &str);
+}
+
+/// Scope of the routes of an event type, e.g. `/v0/nft`, implemented by
+/// `event_endpoint!`
+pub(crate) trait EndpointScope {
+    const SCOPE: &'static str;
+}
+
+/// Defines the handler of an event endpoint, e.g.
+///
+/// ```ignore
+/// event_endpoint! {
+///     /// NFT mint events
+///     nft_mint(NftMintEvent, NftMintFilter),
+///     route = "/nft_mint",
+///     scope = "/v0/nft",
+///     tag = "NFT",
+/// }
+/// ```
+///
+/// Query parameter structs that are documented in addition to the filter,
+/// e.g. `Ordering`, follow the filter.
+macro_rules! event_endpoint {
+    (
+        $(#[doc = $doc:literal])*
+        $name:ident($event:ident, $filter:ident $(, $params:ident)*),
+        route = $route:literal,
+        scope = $scope:literal,
+        tag = $tag:literal $(,)?
+    ) => {
+        $(#[doc = $doc])*
+        #[utoipa::path(
+            get,
+            context_path = $scope,
+            path = $route,
+            tag = $tag,
+            params(
+                crate::PaginationParams,
+                $filter,
+                $($params,)*
+                crate::aggregate::AggregationParams,
+                crate::enrich::EnrichParams,
+            ),
+            responses(
+                (status = 200, body = crate::cursor::EventsPage<$event>),
+                (status = 400, body = crate::error::ErrorResponse),
+                (status = 429, body = crate::error::ErrorResponse),
+                (status = 500, body = crate::error::ErrorResponse),
+            )
+        )]
+        #[actix_web::get($route)]
+        pub async fn $name(
+            state: actix_web::web::Data<crate::AppState>,
+            pagination: actix_web::web::Query<crate::PaginationInfo>,
+            filter: actix_web::web::Query<$filter>,
+            ordering: actix_web::web::Query<crate::Ordering>,
+            aggregation: actix_web::web::Query<crate::aggregate::AggregationParams>,
+            enrich: actix_web::web::Query<crate::enrich::EnrichParams>,
+            tx_status: Option<actix_web::web::Data<crate::enrich::TxStatusClient>>,
+        ) -> Result<actix_web::HttpResponse, crate::error::ApiError> {
+            crate::endpoint::respond::<$event>(
+                &state,
+                &pagination,
+                &filter,
+                &ordering,
+                &aggregation,
+                &enrich,
+                tx_status.as_ref(),
+            )
+            .await
+        }
+
+        impl crate::endpoint::EndpointScope for $event {
+            const SCOPE: &'static str = $scope;
+        }
+    };
+}
+
+pub(crate) use event_endpoint;
+
+/// Body of handlers generated by `event_endpoint!`
+pub(crate) async fn respond<E: EventEndpoint>(
+    state: &AppState,
+    pagination: &PaginationInfo,
+    filter: &E::Filter,
+    ordering: &Ordering,
+    aggregation: &AggregationParams,
+    enrich: &EnrichParams,
+    tx_status: Option<&web::Data<TxStatusClient>>,
+) -> Result<HttpResponse, ApiError> {
+    let aggregation = aggregation
+        .parse(E::SUM_FIELDS)
+        .map_err(ApiError::Validation)?;
+    let max_blocks = if aggregation.is_some() {
+        MAX_BLOCKS_PER_AGGREGATE
+    } else {
+        state.block_limits().of(E::TABLE)
+    };
+    if pagination.blocks > max_blocks {
+        return Err(ApiError::Validation(format!(
+            "Blocks per request must be less or equal to {max_blocks}"
+        )));
+    }
+
+    let tx_status = enrich.parse(tx_status).map_err(ApiError::Validation)?;
+
+    let query = E::query(filter).ordering(ordering);
+    if let Some(aggregation) = aggregation {
+        return query.aggregate(&aggregation, pagination, state).await;
+    }
+    let query = query.paginate(pagination);
+    if pagination.format == ResponseFormat::Ndjson && tx_status.is_none() {
+        return query
+            .ndjson(state.pg_pool(), pagination, E::timestamp)
+            .await;
+    }
+    let mut res = query.fetch_all(&state.pg_pool()).await?;
+    let page = pagination.page(&mut res, E::timestamp, E::receipt_id)?;
+    Ok(enrich::respond(page, tx_status, res, E::transaction).await)
+}
@@ -0,0 +1,157 @@
+use std::{collections::HashMap, sync::RwLock, time::Duration};
+
+use actix_web::{web, HttpResponse};
+use futures_util::{stream, StreamExt};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use utoipa::IntoParams;
+
+use crate::cursor::Page;
+
+const RPC_TIMEOUT: Duration = Duration::from_secs(10);
+const RPC_CONCURRENCY: usize = 10;
+/// The cache is cleared when it grows past this size
+const MAX_CACHED_STATUSES: usize = 100_000;
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub(crate) struct EnrichParams {
+    /// `tx_status` adds the outcome of each event's transaction
+    enrich: Option<String>,
+}
+
+impl EnrichParams {
+    /// The client to enrich events with, `None` if enrichment wasn't requested
+    pub(crate) fn parse<'a>(
+        &self,
+        tx_status: Option<&'a web::Data<TxStatusClient>>,
+    ) -> Result<Option<&'a TxStatusClient>, String> {
+        match self.enrich.as_deref() {
+            None => Ok(None),
+            Some("tx_status") => tx_status
+                .map(|client| Some(client.get_ref()))
+                .ok_or_else(|| "enrich=tx_status is not available on this server".to_owned()),
+            Some(_) => Err("enrich must be tx_status".to_owned()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TxStatus {
+    /// Final execution status of the transaction, `false` if it failed
+    pub success: bool,
+    pub gas_burnt: u64,
+}
+
+/// Fetches final transaction outcomes from a NEAR RPC. Outcomes never change
+/// once final, so they're cached for all workers.
+pub struct TxStatusClient {
+    rpc_url: String,
+    client: reqwest::Client,
+    cache: RwLock<HashMap<String, TxStatus>>,
+}
+
+impl TxStatusClient {
+    /// `None` if `NEAR_RPC_URL` is not set
+    pub fn from_env() -> Option<Self> {
+        let rpc_url = std::env::var("NEAR_RPC_URL").ok()?;
+        Some(Self {
+            rpc_url,
+            client: reqwest::Client::builder()
+                .timeout(RPC_TIMEOUT)
+                .build()
+                .unwrap(),
+            cache: RwLock::new(HashMap::new()),
+        })
+    }
+
+    /// `None` if the transaction isn't final yet or the RPC request failed.
+    /// The RPC uses `sender_id` only to find the transaction's shard, so nodes
+    /// that track all shards find it with any account involved in it.
+    async fn status(&self, transaction_id: &str, sender_id: &str) -> Option<TxStatus> {
+        if let Some(status) = self.cache.read().unwrap().get(transaction_id) {
+            return Some(status.clone());
+        }
+        let response: Value = self
+            .client
+            .post(&self.rpc_url)
+            .json(&json!({
+                "jsonrpc": "2.0",
+                "id": "events-api",
+                "method": "tx",
+                "params": {
+                    "tx_hash": transaction_id,
+                    "sender_account_id": sender_id,
+                    "wait_until": "NONE",
+                },
+            }))
+            .send()
+            .await
+            .and_then(|res| res.error_for_status())
+            .inspect_err(|err| log::warn!("Failed to fetch status of {transaction_id}: {err}"))
+            .ok()?
+            .json()
+            .await
+            .ok()?;
+        let status = parse_outcome(response.get("result")?)?;
+        let mut cache = self.cache.write().unwrap();
+        if cache.len() >= MAX_CACHED_STATUSES {
+            cache.clear();
+        }
+        cache.insert(transaction_id.to_owned(), status.clone());
+        Some(status)
+    }
+}
+
+fn parse_outcome(result: &Value) -> Option<TxStatus> {
+    let success = match result.get("status")? {
+        Value::Object(status) if status.contains_key("SuccessValue") => true,
+        Value::Object(status) if status.contains_key("Failure") => false,
+        // NotStarted, Started, or SuccessReceiptId of a transaction that's
+        // still being executed
+        _ => return None,
+    };
+    let gas_burnt = std::iter::once(result.get("transaction_outcome")?)
+        .chain(result.get("receipts_outcome")?.as_array()?)
+        .map(|outcome| outcome["outcome"]["gas_burnt"].as_u64())
+        .sum::<Option<u64>>()?;
+    Some(TxStatus { success, gas_burnt })
+}
+
+/// Responds with the page of events, each with a `tx_status` field if
+/// `tx_status` is set. `tx` returns the transaction id and sender of an event.
+pub(crate) async fn respond<T: Serialize>(
+    page: Page,
+    tx_status: Option<&TxStatusClient>,
+    events: Vec<T>,
+    tx: impl Fn(&T) -> (&str, &str),
+) -> HttpResponse {
+    let Some(client) = tx_status else {
+        return page.respond(events);
+    };
+
+    let mut transactions = events.iter().map(&tx).collect::<Vec<_>>();
+    transactions.sort_unstable();
+    transactions.dedup_by_key(|(transaction_id, _)| *transaction_id);
+    let statuses = stream::iter(transactions)
+        .map(|(transaction_id, sender_id)| async move {
+            (
+                transaction_id,
+                client.status(transaction_id, sender_id).await,
+            )
+        })
+        .buffer_unordered(RPC_CONCURRENCY)
+        .collect::<HashMap<_, _>>()
+        .await;
+
+    let events = events
+        .iter()
+        .map(|event| {
+            let mut value = serde_json::to_value(event).unwrap();
+            value["tx_status"] = json!(statuses[tx(event).0]);
+            value
+        })
+        .collect::<Vec<_>>();
+    page.respond(events)
+}
@@ -0,0 +1,176 @@
+use std::fmt;
+
+use actix_web::{http::StatusCode, web, HttpResponse, ResponseError};
+use serde::Serialize;
+use utoipa::ToSchema;
+
+use crate::error_reporting;
+
+/// `Retry-After` of `overloaded` responses, heavy queries take seconds
+const OVERLOADED_RETRY_AFTER_SECS: u64 = 5;
+
+/// Error of an API endpoint, returned as
+/// `{"error": {"code": "...", "message": "..."}}`
+#[derive(Debug)]
+pub enum ApiError {
+    /// Query parameters that couldn't be parsed, e.g. a misspelled `order`,
+    /// or that the endpoint doesn't accept
+    BadFilter(String),
+    /// Query parameters that were parsed but aren't allowed, e.g. too many
+    /// blocks per request
+    Validation(String),
+    /// Nothing was found for an id in the path, e.g. an unknown transaction
+    NotFound(String),
+    /// The `Authorization: Bearer <token>` header of a resource that needs
+    /// one, e.g. a webhook, is missing
+    Unauthorized,
+    /// Path and query string are longer than `MAX_URL_LENGTH`
+    UriTooLong { max_url_length: usize },
+    /// Headers are larger than `MAX_HEADER_SIZE` in total
+    HeadersTooLarge { max_header_size: usize },
+    /// The client made too many requests, see `RATE_LIMIT_PER_MINUTE`
+    RateLimited { retry_after_secs: u64 },
+    /// Too many heavy queries are running, the client should retry later
+    Overloaded,
+    /// The endpoint with this route pattern was turned off by an admin, see
+    /// `DISABLED_ENDPOINTS`
+    EndpointDisabled(String),
+    /// A handler panicked, the panic is only logged
+    Internal,
+    /// Details are only logged, they may contain parts of queries. Queries
+    /// cancelled by the statement timeout are reported as `query_timeout`, and
+    /// timeouts waiting for a connection of the pool as `overloaded`.
+    Database(sqlx::Error),
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct ErrorResponse {
+    pub error: ErrorDetails,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct ErrorDetails {
+    /// `bad_filter`, `validation_failed`, `not_found`, `unauthorized`,
+    /// `uri_too_long`, `headers_too_large`, `rate_limited`, `overloaded`,
+    /// `endpoint_disabled`, `query_timeout`, `database_error`, or
+    /// `internal_error`
+    pub code: &'static str,
+    pub message: String,
+    /// Same as the `X-Request-Id` response header, added to all errors. Quote
+    /// it when reporting an error.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub request_id: Option<String>,
+}
+
+impl ApiError {
+    pub fn code(&self) -> &'static str {
+        match self {
+            ApiError::BadFilter(_) => "bad_filter",
+            ApiError::Validation(_) => "validation_failed",
+            ApiError::NotFound(_) => "not_found",
+            ApiError::Unauthorized => "unauthorized",
+            ApiError::UriTooLong { .. } => "uri_too_long",
+            ApiError::HeadersTooLarge { .. } => "headers_too_large",
+            ApiError::RateLimited { .. } => "rate_limited",
+            ApiError::Overloaded | ApiError::Database(sqlx::Error::PoolTimedOut) => "overloaded",
+            ApiError::EndpointDisabled(_) => "endpoint_disabled",
+            ApiError::Database(err) if is_timeout(err) => "query_timeout",
+            ApiError::Database(_) => "database_error",
+            ApiError::Internal => "internal_error",
+        }
+    }
+}
+
+impl fmt::Display for ApiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ApiError::BadFilter(message)
+            | ApiError::Validation(message)
+            | ApiError::NotFound(message) => f.write_str(message),
+            ApiError::Unauthorized => {
+                f.write_str("Authorization: Bearer <token> header is required")
+            }
+            ApiError::UriTooLong { max_url_length } => {
+                write!(
+                    f,
+                    "URL length must be less or equal to {max_url_length} bytes"
+                )
+            }
+            ApiError::HeadersTooLarge { max_header_size } => write!(
+                f,
+                "Total size of headers must be less or equal to {max_header_size} bytes"
+            ),
+            ApiError::RateLimited { retry_after_secs } => {
+                write!(f, "Too many requests, retry after {retry_after_secs}s")
+            }
+            ApiError::Overloaded | ApiError::Database(sqlx::Error::PoolTimedOut) => {
+                f.write_str("The server is busy, try again later")
+            }
+            ApiError::EndpointDisabled(endpoint) => {
+                write!(f, "{endpoint} is temporarily disabled, try again later")
+            }
+            ApiError::Database(err) if is_timeout(err) => f.write_str(
+                "The query took too long, request fewer blocks or use more specific filters",
+            ),
+            ApiError::Database(_) | ApiError::Internal => f.write_str("Internal server error"),
+        }
+    }
+}
+
+impl From<sqlx::Error> for ApiError {
+    fn from(err: sqlx::Error) -> Self {
+        ApiError::Database(err)
+    }
+}
+
+impl ResponseError for ApiError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            ApiError::BadFilter(_) | ApiError::Validation(_) => StatusCode::BAD_REQUEST,
+            ApiError::NotFound(_) => StatusCode::NOT_FOUND,
+            ApiError::Unauthorized => StatusCode::UNAUTHORIZED,
+            ApiError::UriTooLong { .. } => StatusCode::URI_TOO_LONG,
+            ApiError::HeadersTooLarge { .. } => StatusCode::REQUEST_HEADER_FIELDS_TOO_LARGE,
+            ApiError::RateLimited { .. } => StatusCode::TOO_MANY_REQUESTS,
+            ApiError::Overloaded
+            | ApiError::Database(sqlx::Error::PoolTimedOut)
+            | ApiError::EndpointDisabled(_) => StatusCode::SERVICE_UNAVAILABLE,
+            ApiError::Database(err) if is_timeout(err) => StatusCode::GATEWAY_TIMEOUT,
+            ApiError::Database(_) | ApiError::Internal => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        if let ApiError::Database(err) = self {
+            error_reporting::report_query_error(err);
+        }
+        let mut res = HttpResponse::build(self.status_code());
+        if let ApiError::RateLimited { retry_after_secs } = self {
+            res.insert_header(("Retry-After", retry_after_secs.to_string()));
+        } else if let ApiError::Overloaded | ApiError::Database(sqlx::Error::PoolTimedOut) = self {
+            res.insert_header(("Retry-After", OVERLOADED_RETRY_AFTER_SECS.to_string()));
+        } else if let ApiError::Unauthorized = self {
+            res.insert_header(("WWW-Authenticate", "Bearer"));
+        }
+        res.json(ErrorResponse {
+            error: ErrorDetails {
+                code: self.code(),
+                message: self.to_string(),
+                request_id: None,
+            },
+        })
+    }
+}
+
+/// Whether Postgres cancelled the query, which happens when it runs longer
+/// than `statement_timeout`
+pub(crate) fn is_timeout(err: &sqlx::Error) -> bool {
+    // query_canceled
+    matches!(err, sqlx::Error::Database(err) if err.code().as_deref() == Some("57014"))
+}
+
+/// Makes query strings that can't be parsed respond with `bad_filter` instead
+/// of a plain text error
+pub fn query_config() -> web::QueryConfig {
+    web::QueryConfig::default().error_handler(|err, _| ApiError::BadFilter(err.to_string()).into())
+}
@@ -0,0 +1,84 @@
+use std::collections::BTreeMap;
+
+use actix_web::{
+    body::MessageBody,
+    dev::{ServiceRequest, ServiceResponse},
+    middleware::Next,
+    Error,
+};
+use sentry::{ClientInitGuard, Hub, SentryFutureExt};
+use serde_json::{Map, Value};
+
+use crate::{error::is_timeout, request_id};
+
+/// Values of these query parameters are never sent to Sentry
+const SENSITIVE_PARAMS: &[&str] = &["token", "secret", "password", "api_key", "key"];
+
+/// Longer parameter values, e.g. long lists of accounts, are cut
+const MAX_PARAM_LENGTH: usize = 200;
+
+/// Reports errors to Sentry if `SENTRY_DSN` is set, tagged with
+/// `SENTRY_ENVIRONMENT` if it's set too. Reporting stops when the guard is
+/// dropped, after sending errors that are still queued.
+pub fn init() -> Option<ClientInitGuard> {
+    let dsn = std::env::var("SENTRY_DSN").ok()?;
+    Some(sentry::init((
+        dsn,
+        sentry::ClientOptions {
+            release: sentry::release_name!(),
+            environment: std::env::var("SENTRY_ENVIRONMENT").ok().map(Into::into),
+            ..Default::default()
+        },
+    )))
+}
+
+/// Logs a failed query, and reports it with the request that ran it. Queries
+/// that timed out or couldn't get a connection are only logged, they're
+/// caused by load rather than broken queries.
+pub fn report_query_error(err: &sqlx::Error) {
+    log::error!("Database error: {err}");
+    if !matches!(err, sqlx::Error::PoolTimedOut) && !is_timeout(err) {
+        sentry::capture_error(err);
+    }
+}
+
+/// Errors and panics of a request are reported with its route, query
+/// parameters, and request id
+pub async fn scope_errors(
+    req: ServiceRequest,
+    next: Next<impl MessageBody>,
+) -> Result<ServiceResponse<impl MessageBody>, Error> {
+    if Hub::main().client().is_none() {
+        return next.call(req).await;
+    }
+    let hub = Hub::new_from_top(Hub::main());
+    let route = req.match_pattern().unwrap_or_else(|| req.path().to_owned());
+    let params = sanitize_params(req.query_string());
+    let request_id = request_id::of(&req);
+    hub.configure_scope(|scope| {
+        scope.set_transaction(Some(&format!("{} {route}", req.method())));
+        scope.set_tag("route", route);
+        if let Some(request_id) = request_id {
+            scope.set_tag("request_id", request_id);
+        }
+        scope.set_extra("params", Value::Object(params));
+    });
+    next.call(req).bind_hub(hub).await
+}
+
+fn sanitize_params(query: &str) -> Map<String, Value> {
+    let params = serde_urlencoded::from_str::<BTreeMap<String, String>>(query).unwrap_or_default();
+    params
+        .into_iter()
+        .map(|(name, value)| {
+            let value = if SENSITIVE_PARAMS.contains(&name.as_str()) {
+                "[redacted]".to_owned()
+            } else if let Some((end, _)) = value.char_indices().nth(MAX_PARAM_LENGTH) {
+                format!("{}...", &value[..end])
+            } else {
+                value
+            };
+            (name, Value::String(value))
+        })
+        .collect()
+}
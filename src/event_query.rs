@@ -0,0 +1,541 @@
+use std::marker::PhantomData;
+
+use actix_web::HttpResponse;
+use chrono::{DateTime, TimeDelta, Utc};
+use serde::Serialize;
+use sqlx::{
+    postgres::{PgArguments, PgRow},
+    Arguments, Encode, FromRow, PgPool, Postgres, Type, TypeInfo,
+};
+use tracing::{field, Instrument, Span};
+
+use crate::{
+    admin::{fetch_summary, QueryError, Summary, SummaryValue},
+    aggregate::{self, Aggregation},
+    cancel::CancelOnDrop,
+    error::ApiError,
+    ndjson,
+    utils::nanosec_to_timestamp,
+    AppState, Ordering, PaginationInfo, Sampling,
+};
+
+/// Paginated query of an event table: events of the first `blocks` blocks
+/// with matching events after the start, or before the end with `desc`.
+///
+/// Each event type defines its table, columns, and filters once, and the same
+/// query is used for pages, aggregates, summaries, and live subscriptions.
+/// Parameters of filters come first, in the order they were added, so each
+/// condition gets its own number.
+///
+/// Pages are read in the order of the `(timestamp, tie_breaker)` index of the
+/// table, from the position of the cursor, and blocks are counted as they're
+/// read, so a page only reads its own events however deep it is.
+pub(crate) struct EventQuery<T> {
+    table: &'static str,
+    columns: &'static str,
+    filters: Vec<String>,
+    /// Conditions of filters with a value, for tracing
+    set_filters: Vec<String>,
+    arguments: PgArguments,
+    start: DateTime<Utc>,
+    /// Exclusive
+    end: Option<DateTime<Utc>>,
+    /// Block and tie breaker of the last event of the previous page, if it
+    /// ended inside the block
+    after: Option<(DateTime<Utc>, String)>,
+    /// `None` for all blocks
+    blocks: Option<i64>,
+    order: &'static str,
+    /// `None` for all rows
+    limit: Option<i64>,
+    /// Only every n-th block with events, `None` if the endpoint doesn't
+    /// support sampling
+    sample_every_n_blocks: Option<i64>,
+    /// Column that `order_by=amount` sorts by, `None` if the endpoint only
+    /// sorts by timestamp
+    amount_column: Option<&'static str>,
+    order_by: &'static str,
+    amount_order: &'static str,
+    /// Sorts events of the same block, so that pages are stable
+    tie_breaker: &'static str,
+    event: PhantomData<fn() -> T>,
+}
+
+impl<T> EventQuery<T>
+where
+    T: for<'r> FromRow<'r, PgRow> + Send + Unpin + 'static,
+{
+    /// `columns` are the select list, with the names of `T`'s fields, including
+    /// `timestamp` and the tie breaker that pages are sorted by
+    pub(crate) fn new(table: &'static str, columns: &'static str) -> Self {
+        Self {
+            table,
+            columns,
+            filters: Vec::new(),
+            set_filters: Vec::new(),
+            arguments: PgArguments::default(),
+            start: DateTime::UNIX_EPOCH,
+            end: None,
+            after: None,
+            blocks: None,
+            order: "asc",
+            limit: None,
+            sample_every_n_blocks: None,
+            amount_column: None,
+            order_by: "timestamp",
+            amount_order: "asc",
+            tie_breaker: "receipt_id",
+            event: PhantomData,
+        }
+    }
+
+    /// Adds a condition that applies if `value` is set, with `{}` standing for
+    /// the value, e.g. `contract_id = ANY({})`
+    pub(crate) fn filter<'q, V>(mut self, condition: &str, value: Option<V>) -> Self
+    where
+        V: Encode<'q, Postgres> + Type<Postgres> + Send + 'q,
+    {
+        let parameter = format!("${}", self.filters.len() + 1);
+        if value.is_some() {
+            self.set_filters.push(condition.to_owned());
+        }
+        self.filters.push(format!(
+            "({parameter}::{} IS NULL OR {})",
+            V::type_info().name(),
+            condition.replace("{}", &parameter),
+        ));
+        self.arguments.add(value);
+        self
+    }
+
+    pub(crate) fn tie_breaker(mut self, column: &'static str) -> Self {
+        self.tie_breaker = column;
+        self
+    }
+
+    pub(crate) fn sample(mut self, sampling: &Sampling) -> Self {
+        self.sample_every_n_blocks = Some(sampling.sample_every_n_blocks);
+        self
+    }
+
+    /// Column that events of a page are sorted by with `order_by=amount`
+    pub(crate) fn amount_column(mut self, column: &'static str) -> Self {
+        self.amount_column = Some(column);
+        self
+    }
+
+    pub(crate) fn ordering(mut self, ordering: &Ordering) -> Self {
+        self.order_by = ordering.order_by();
+        self.amount_order = ordering.order();
+        self
+    }
+
+    /// A page of up to `limit` events, with the row that tells if the last
+    /// block fits
+    pub(crate) fn paginate(mut self, pagination: &PaginationInfo) -> Self {
+        self.start = pagination.start();
+        self.end = pagination.end();
+        self.after = pagination
+            .after()
+            .map(|(block, tie_breaker)| (block, tie_breaker.to_owned()));
+        self.blocks = Some(pagination.blocks);
+        self.order = pagination.order();
+        self.limit = Some(pagination.fetch_limit());
+        self
+    }
+
+    /// The next `blocks` blocks of a live subscription, with all their events
+    pub(crate) fn live(mut self, start_block_timestamp_nanosec: i64, blocks: i64) -> Self {
+        self.start = nanosec_to_timestamp(start_block_timestamp_nanosec);
+        self.blocks = Some(blocks);
+        self
+    }
+
+    /// All events of any of the transactions between `from` and `to`
+    /// inclusive, for correlating them
+    pub(crate) fn transactions(
+        self,
+        transaction_ids: &[String],
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> Self {
+        let mut query = self.filter("transaction_id = ANY({})", Some(transaction_ids));
+        query.start = from;
+        query.end = Some(to + TimeDelta::microseconds(1));
+        query
+    }
+
+    pub(crate) async fn fetch_all(self, pg_pool: &PgPool) -> Result<Vec<T>, sqlx::Error> {
+        let span = self.span("fetch_all");
+        let (sql, arguments) = self.into_parts();
+        span.record("db.statement", sql.as_str());
+        async {
+            let mut connection = CancelOnDrop::acquire(pg_pool).await?;
+            let events = sqlx::query_as_with(&sql, arguments)
+                .fetch_all(&mut *connection)
+                .await?;
+            connection.finish(events.len());
+            Ok::<_, sqlx::Error>(events)
+        }
+        .instrument(span)
+        .await
+    }
+
+    pub(crate) async fn ndjson(
+        self,
+        pg_pool: PgPool,
+        pagination: &PaginationInfo,
+        timestamp: fn(&T) -> DateTime<Utc>,
+    ) -> Result<HttpResponse, ApiError>
+    where
+        T: Serialize,
+    {
+        let span = self.span("ndjson");
+        let (sql, arguments) = self.into_parts();
+        span.record("db.statement", sql.as_str());
+        ndjson::respond(sql, arguments, pg_pool, pagination, timestamp)
+            .instrument(span)
+            .await
+    }
+
+    /// Buckets of all events of the page's blocks
+    pub(crate) async fn aggregate(
+        mut self,
+        aggregation: &Aggregation,
+        pagination: &PaginationInfo,
+        state: &AppState,
+    ) -> Result<HttpResponse, ApiError> {
+        self = self.paginate(pagination);
+        self.limit = None;
+        let span = self.span("aggregate");
+        let (sql, arguments) = self.into_parts();
+        let sql = aggregation.sql(&sql);
+        span.record("db.statement", sql.as_str());
+        aggregate::respond(sqlx::query_with(&sql, arguments), state)
+            .instrument(span)
+            .await
+    }
+
+    pub(crate) async fn summarize<V: SummaryValue>(
+        mut self,
+        summary: Summary,
+        pagination: &PaginationInfo,
+        pg_pool: &PgPool,
+    ) -> Result<V, QueryError> {
+        self = self.paginate(pagination);
+        self.blocks = summary.blocks(pagination);
+        self.limit = summary.limit(pagination);
+        let span = self.span("summarize");
+        let (sql, arguments) = self.into_parts();
+        let sql = summary.sql(&sql);
+        span.record("db.statement", sql.as_str());
+        fetch_summary(sqlx::query_with(&sql, arguments), pg_pool)
+            .instrument(span)
+            .await
+    }
+
+    /// Span of running the query, with the table and the conditions of the
+    /// filters that are set, but not their values
+    fn span(&self, operation: &'static str) -> Span {
+        tracing::info_span!(
+            "query",
+            otel.kind = "client",
+            db.system = "postgresql",
+            db.operation = operation,
+            db.sql.table = self.table,
+            db.statement = field::Empty,
+            filters = %self.set_filters.join(" AND "),
+            blocks = self.blocks,
+        )
+    }
+
+    /// SQL of the query and its arguments, numbered in the order they were bound
+    pub(crate) fn into_parts(self) -> (String, PgArguments) {
+        let mut parameters = Parameters {
+            count: self.filters.len(),
+            arguments: self.arguments,
+        };
+        let (direction, after) = match self.order {
+            "desc" => ("DESC", "<"),
+            _ => ("ASC", ">"),
+        };
+        let tie_breaker = self.tie_breaker;
+
+        let mut conditions = vec![format!("timestamp >= {}", parameters.bind(self.start))];
+        if let Some(end) = self.end {
+            conditions.push(format!("timestamp < {}", parameters.bind(end)));
+        }
+        if let Some((block, last)) = self.after {
+            let (block, last) = (parameters.bind(block), parameters.bind(last));
+            conditions.push(format!(
+                "(timestamp, {tie_breaker}) {after} ({block}, {last})"
+            ));
+        }
+        conditions.extend(self.filters);
+        let conditions = conditions.join("\n    AND ");
+
+        // Blocks are numbered by a window over the index scan, which stops
+        // at the first block past the page
+        let mut blocks = Vec::new();
+        if let Some(sample) = self.sample_every_n_blocks {
+            let sample = parameters.bind(sample);
+            if let Some(n) = self.blocks {
+                blocks.push(format!("block_number <= {} * {sample}", parameters.bind(n)));
+            }
+            blocks.push(format!("(block_number - 1) % {sample} = 0"));
+        } else if let Some(n) = self.blocks {
+            blocks.push(format!("block_number <= {}", parameters.bind(n)));
+        }
+
+        let (table, columns) = (self.table, self.columns);
+        let mut sql = if blocks.is_empty() {
+            format!("SELECT {columns}\nFROM {table}\nWHERE {conditions}")
+        } else {
+            format!(
+                "SELECT {columns}
+FROM (
+    SELECT {columns}, dense_rank() OVER (ORDER BY timestamp {direction}) AS block_number
+    FROM {table}
+    WHERE {conditions}
+) events
+WHERE {}",
+                blocks.join("\n    AND ")
+            )
+        };
+        sql.push_str("\nORDER BY ");
+        if let (Some(column), "amount") = (self.amount_column, self.order_by) {
+            let amount_order = match self.amount_order {
+                "desc" => "DESC",
+                _ => "ASC",
+            };
+            sql.push_str(&format!("{column} {amount_order}, "));
+        }
+        sql.push_str(&format!("timestamp {direction}, {tie_breaker} {direction}"));
+        if let Some(limit) = self.limit {
+            sql.push_str(&format!("\nLIMIT {}", parameters.bind(limit)));
+        }
+        (sql, parameters.arguments)
+    }
+}
+
+/// Arguments of a query, numbered as they're bound
+struct Parameters {
+    count: usize,
+    arguments: PgArguments,
+}
+
+impl Parameters {
+    fn bind<'q, V>(&mut self, value: V) -> String
+    where
+        V: Encode<'q, Postgres> + Type<Postgres> + Send + 'q,
+    {
+        self.count += 1;
+        self.arguments.add(value);
+        format!("${}", self.count)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use inteat_events_api_types::ft::FtTransferFilter;
+    use sqlx::{postgres::PgPoolOptions, Row};
+
+    use super::*;
+    use crate::{endpoint::EventEndpoint, ft_events::FtTransferEvent};
+
+    fn query(pagination: &str) -> EventQuery<FtTransferEvent> {
+        let pagination: PaginationInfo = serde_urlencoded::from_str(pagination).unwrap();
+        EventQuery::new("ft_transfer", "receipt_id, timestamp")
+            .filter("token_id = ANY({})", Some(vec!["usdt.near".to_owned()]))
+            .paginate(&pagination)
+    }
+
+    #[test]
+    fn pages_continue_from_the_cursor() {
+        let cursor = crate::cursor::encode_inside_block(1_700_000_000_000_000_000, "r1");
+        let (sql, _) = query(&format!("blocks=10&limit=100&cursor={cursor}")).into_parts();
+        assert_eq!(
+            sql,
+            "SELECT receipt_id, timestamp
+FROM (
+    SELECT receipt_id, timestamp, dense_rank() OVER (ORDER BY timestamp ASC) AS block_number
+    FROM ft_transfer
+    WHERE timestamp >= $2
+    AND (timestamp, receipt_id) > ($3, $4)
+    AND ($1::TEXT[] IS NULL OR token_id = ANY($1))
+) events
+WHERE block_number <= $5
+ORDER BY timestamp ASC, receipt_id ASC
+LIMIT $6"
+        );
+    }
+
+    #[test]
+    fn descending_pages_are_read_backwards() {
+        let (sql, _) = query("blocks=10&limit=100&order=desc&end_block_timestamp_nanosec=1")
+            .sample(&Sampling {
+                sample_every_n_blocks: 3,
+            })
+            .into_parts();
+        assert_eq!(
+            sql,
+            "SELECT receipt_id, timestamp
+FROM (
+    SELECT receipt_id, timestamp, dense_rank() OVER (ORDER BY timestamp DESC) AS block_number
+    FROM ft_transfer
+    WHERE timestamp >= $2
+    AND timestamp < $3
+    AND ($1::TEXT[] IS NULL OR token_id = ANY($1))
+) events
+WHERE block_number <= $5 * $4
+    AND (block_number - 1) % $4 = 0
+ORDER BY timestamp DESC, receipt_id DESC
+LIMIT $6"
+        );
+    }
+
+    #[test]
+    fn queries_of_all_blocks_are_not_numbered() {
+        let (sql, _) = EventQuery::<FtTransferEvent>::new("ft_transfer", "receipt_id, timestamp")
+            .transactions(
+                &["tx1".to_owned()],
+                DateTime::UNIX_EPOCH,
+                DateTime::UNIX_EPOCH,
+            )
+            .into_parts();
+        assert_eq!(
+            sql,
+            "SELECT receipt_id, timestamp
+FROM ft_transfer
+WHERE timestamp >= $2
+    AND timestamp < $3
+    AND ($1::TEXT[] IS NULL OR transaction_id = ANY($1))
+ORDER BY timestamp ASC, receipt_id ASC"
+        );
+    }
+
+    /// Pool with a single connection, so that its temporary `ft_transfer`
+    /// table shadows the real one. Blocks have 1 to 3 events, and every 7th
+    /// has 41, in receipts of 3 events, alternating between two tokens.
+    async fn temporary_events(blocks: i64) -> PgPool {
+        let pg_pool = PgPoolOptions::new()
+            .max_connections(1)
+            .connect(&std::env::var("DATABASE_URL").unwrap())
+            .await
+            .unwrap();
+        let events = format!(
+            "INSERT INTO ft_transfer
+            SELECT 'alice.near', 'bob.near', event, block || '/' || event, 'tx' || block,
+                'r' || lpad((event / 3)::TEXT, 2, '0'), block,
+                TO_TIMESTAMP(1700000000) + block * INTERVAL '1 second',
+                CASE WHEN event % 2 = 0 THEN 'usdt.near' ELSE 'usdc.near' END
+            FROM generate_series(0, {blocks} - 1) block, generate_series(0, 40) event
+            WHERE event < CASE WHEN block % 7 = 0 THEN 41 ELSE 1 + block % 3 END"
+        );
+        for statement in [
+            "CREATE TEMPORARY TABLE ft_transfer (
+                old_owner_id TEXT NOT NULL,
+                new_owner_id TEXT NOT NULL,
+                amount NUMERIC NOT NULL,
+                memo TEXT,
+                transaction_id TEXT NOT NULL,
+                receipt_id TEXT NOT NULL,
+                block_height BIGINT NOT NULL,
+                timestamp TIMESTAMPTZ NOT NULL,
+                token_id TEXT NOT NULL
+            )",
+            &events,
+            "CREATE INDEX ON ft_transfer (timestamp, receipt_id)",
+            "ANALYZE ft_transfer",
+        ] {
+            sqlx::query(statement).execute(&pg_pool).await.unwrap();
+        }
+        pg_pool
+    }
+
+    /// Events of all pages, by their memo
+    async fn read_pages(pg_pool: &PgPool, filter: &str, pagination: &str) -> Vec<String> {
+        let filter: FtTransferFilter = serde_urlencoded::from_str(filter).unwrap();
+        let mut memos = Vec::new();
+        let mut cursor = String::new();
+        loop {
+            let pagination: PaginationInfo =
+                serde_urlencoded::from_str(&format!("{pagination}{cursor}")).unwrap();
+            let mut events = FtTransferEvent::query(&filter)
+                .paginate(&pagination)
+                .fetch_all(pg_pool)
+                .await
+                .unwrap();
+            let page = pagination
+                .page(
+                    &mut events,
+                    |event| event.timestamp,
+                    |event| &event.receipt_id,
+                )
+                .unwrap();
+            if events.is_empty() {
+                return memos;
+            }
+            memos.extend(events.into_iter().map(|event| event.memo.unwrap()));
+            cursor = format!("&cursor={}", page.cursor().unwrap());
+        }
+    }
+
+    #[tokio::test]
+    #[ignore = "needs DATABASE_URL"]
+    async fn every_event_is_on_exactly_one_page() {
+        let pg_pool = temporary_events(700).await;
+        for filter in ["", "token_account_id=usdt.near"] {
+            let expected: Vec<String> = sqlx::query_scalar(&format!(
+                "SELECT memo FROM ft_transfer WHERE {} ORDER BY memo",
+                if filter.is_empty() {
+                    "TRUE"
+                } else {
+                    "token_id = 'usdt.near'"
+                }
+            ))
+            .fetch_all(&pg_pool)
+            .await
+            .unwrap();
+            for order in ["asc", "desc"] {
+                let mut memos = read_pages(
+                    &pg_pool,
+                    filter,
+                    &format!("blocks=50&limit=25&order={order}"),
+                )
+                .await;
+                memos.sort();
+                assert_eq!(memos, expected, "{filter} {order}");
+            }
+        }
+    }
+
+    #[tokio::test]
+    #[ignore = "needs DATABASE_URL"]
+    async fn deep_pages_are_index_scans() {
+        let pg_pool = temporary_events(20_000).await;
+        for pagination in [
+            "blocks=50&limit=25&cursor=1797ae45fe6a3000",
+            "blocks=50&limit=25&order=desc&cursor=17979de70acf1000",
+        ] {
+            let pagination: PaginationInfo = serde_urlencoded::from_str(pagination).unwrap();
+            let (sql, arguments) =
+                EventQuery::<FtTransferEvent>::new("ft_transfer", "receipt_id, timestamp")
+                    .filter("token_id = ANY({})", Some(vec!["usdt.near".to_owned()]))
+                    .paginate(&pagination)
+                    .into_parts();
+            let plan =
+                sqlx::query_with(&format!("EXPLAIN (ANALYZE, FORMAT TEXT) {sql}"), arguments)
+                    .fetch_all(&pg_pool)
+                    .await
+                    .unwrap()
+                    .iter()
+                    .map(|row| row.get::<String, _>(0))
+                    .collect::<Vec<_>>()
+                    .join("\n");
+            assert!(plan.contains("Index Scan"), "{plan}");
+            assert!(!plan.contains("Seq Scan"), "{plan}");
+            assert!(plan.contains("Run Condition"), "{plan}");
+        }
+    }
+}
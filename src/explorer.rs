@@ -0,0 +1,11 @@
+use actix_web::{get, HttpResponse, Responder};
+
+const EXPLORER_HTML: &str = include_str!("../static/explorer.html");
+
+/// Small interactive page to try out the endpoints from a browser
+#[get("/")]
+pub async fn explorer() -> impl Responder {
+    HttpResponse::Ok()
+        .content_type("text/html; charset=utf-8")
+        .body(EXPLORER_HTML)
+}
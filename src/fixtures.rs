@@ -0,0 +1,241 @@
+use actix_web::{get, web, HttpResponse, Responder, Scope};
+
+/// Canonical request/response pairs for every event endpoint. Client SDKs can
+/// test their parsing against these without a database being involved.
+const FIXTURES: &[(&str, &str)] = &[
+    ("ft_mint", include_str!("../fixtures/ft_mint.json")),
+    ("ft_transfer", include_str!("../fixtures/ft_transfer.json")),
+    ("ft_burn", include_str!("../fixtures/ft_burn.json")),
+    ("nft_mint", include_str!("../fixtures/nft_mint.json")),
+    (
+        "nft_transfer",
+        include_str!("../fixtures/nft_transfer.json"),
+    ),
+    ("nft_burn", include_str!("../fixtures/nft_burn.json")),
+    ("nft_sale", include_str!("../fixtures/nft_sale.json")),
+    (
+        "potlock_donation",
+        include_str!("../fixtures/potlock_donation.json"),
+    ),
+    (
+        "potlock_pot_project_donation",
+        include_str!("../fixtures/potlock_pot_project_donation.json"),
+    ),
+    (
+        "potlock_pot_donation",
+        include_str!("../fixtures/potlock_pot_donation.json"),
+    ),
+    (
+        "potlock_pot_deployment",
+        include_str!("../fixtures/potlock_pot_deployment.json"),
+    ),
+    (
+        "potlock_pot_application",
+        include_str!("../fixtures/potlock_pot_application.json"),
+    ),
+    (
+        "potlock_pot_application_review",
+        include_str!("../fixtures/potlock_pot_application_review.json"),
+    ),
+    (
+        "potlock_pot_payout",
+        include_str!("../fixtures/potlock_pot_payout.json"),
+    ),
+    (
+        "potlock_pot_payout_challenge",
+        include_str!("../fixtures/potlock_pot_payout_challenge.json"),
+    ),
+    ("trade_pool", include_str!("../fixtures/trade_pool.json")),
+    ("trade_swap", include_str!("../fixtures/trade_swap.json")),
+    (
+        "trade_pool_change",
+        include_str!("../fixtures/trade_pool_change.json"),
+    ),
+    (
+        "trade_liquidity_add",
+        include_str!("../fixtures/trade_liquidity_add.json"),
+    ),
+    (
+        "trade_liquidity_remove",
+        include_str!("../fixtures/trade_liquidity_remove.json"),
+    ),
+    ("trade_wrap", include_str!("../fixtures/trade_wrap.json")),
+    (
+        "trade_unwrap",
+        include_str!("../fixtures/trade_unwrap.json"),
+    ),
+    (
+        "socialdb_post",
+        include_str!("../fixtures/socialdb_post.json"),
+    ),
+    (
+        "socialdb_comment",
+        include_str!("../fixtures/socialdb_comment.json"),
+    ),
+    (
+        "socialdb_like",
+        include_str!("../fixtures/socialdb_like.json"),
+    ),
+    (
+        "socialdb_follow",
+        include_str!("../fixtures/socialdb_follow.json"),
+    ),
+    (
+        "socialdb_profile_update",
+        include_str!("../fixtures/socialdb_profile_update.json"),
+    ),
+    (
+        "price_change",
+        include_str!("../fixtures/price_change.json"),
+    ),
+    (
+        "burrow_deposit",
+        include_str!("../fixtures/burrow_deposit.json"),
+    ),
+    (
+        "burrow_withdraw",
+        include_str!("../fixtures/burrow_withdraw.json"),
+    ),
+    (
+        "burrow_borrow",
+        include_str!("../fixtures/burrow_borrow.json"),
+    ),
+    (
+        "burrow_repay",
+        include_str!("../fixtures/burrow_repay.json"),
+    ),
+    (
+        "burrow_liquidation",
+        include_str!("../fixtures/burrow_liquidation.json"),
+    ),
+    (
+        "launch_create",
+        include_str!("../fixtures/launch_create.json"),
+    ),
+    (
+        "launch_deposit",
+        include_str!("../fixtures/launch_deposit.json"),
+    ),
+    (
+        "launch_withdraw",
+        include_str!("../fixtures/launch_withdraw.json"),
+    ),
+    (
+        "launch_finalize",
+        include_str!("../fixtures/launch_finalize.json"),
+    ),
+    (
+        "staking_stake",
+        include_str!("../fixtures/staking_stake.json"),
+    ),
+    (
+        "staking_unstake",
+        include_str!("../fixtures/staking_unstake.json"),
+    ),
+    (
+        "staking_withdraw",
+        include_str!("../fixtures/staking_withdraw.json"),
+    ),
+    (
+        "account_create",
+        include_str!("../fixtures/account_create.json"),
+    ),
+    (
+        "account_delete",
+        include_str!("../fixtures/account_delete.json"),
+    ),
+    ("key_added", include_str!("../fixtures/key_added.json")),
+    ("key_deleted", include_str!("../fixtures/key_deleted.json")),
+    (
+        "dao_proposal",
+        include_str!("../fixtures/dao_proposal.json"),
+    ),
+    ("dao_vote", include_str!("../fixtures/dao_vote.json")),
+    (
+        "dao_proposal_execute",
+        include_str!("../fixtures/dao_proposal_execute.json"),
+    ),
+    (
+        "bridge_deposit",
+        include_str!("../fixtures/bridge_deposit.json"),
+    ),
+    (
+        "bridge_withdraw",
+        include_str!("../fixtures/bridge_withdraw.json"),
+    ),
+    ("log_events", include_str!("../fixtures/log_events.json")),
+];
+
+pub fn scope() -> Scope {
+    web::scope("/_fixtures")
+        .service(list_fixtures)
+        .service(get_fixture)
+}
+
+#[get("")]
+async fn list_fixtures() -> impl Responder {
+    HttpResponse::Ok().json(
+        FIXTURES
+            .iter()
+            .map(|(name, _)| *name)
+            .collect::<Vec<&str>>(),
+    )
+}
+
+#[get("/{name}")]
+async fn get_fixture(name: web::Path<String>) -> impl Responder {
+    if let Some((_, fixture)) = FIXTURES.iter().find(|(n, _)| *n == name.as_str()) {
+        HttpResponse::Ok()
+            .content_type("application/json")
+            .body(*fixture)
+    } else {
+        HttpResponse::NotFound().finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        cursor::EventsPage,
+        meta::EventTable,
+        registry::{with_event_type, EventType},
+    };
+
+    #[derive(serde::Deserialize)]
+    struct Fixture {
+        request: String,
+        response: serde_json::Value,
+    }
+
+    /// Responses have to round-trip through the event type, so that fields
+    /// missing from either side fail too
+    #[test]
+    fn fixtures_deserialize_into_their_event_types() {
+        assert_eq!(FIXTURES.len(), EventTable::ALL.len());
+        for table in EventTable::ALL {
+            let (_, fixture) = FIXTURES
+                .iter()
+                .find(|(name, _)| *name == table.as_str())
+                .unwrap_or_else(|| panic!("No fixture for {}", table.as_str()));
+            let fixture: Fixture = serde_json::from_str(fixture).unwrap();
+            with_event_type!(table, |E| {
+                assert!(
+                    fixture.request.starts_with(E::SCOPE),
+                    "{} requests {}",
+                    table.as_str(),
+                    fixture.request
+                );
+                let page: EventsPage<E> = serde_json::from_value(fixture.response.clone())
+                    .unwrap_or_else(|err| panic!("{}: {err}", table.as_str()));
+                assert!(!page.events.is_empty(), "{} has no events", table.as_str());
+                assert_eq!(
+                    serde_json::to_value(&page).unwrap(),
+                    fixture.response,
+                    "{}",
+                    table.as_str()
+                );
+            });
+        }
+    }
+}
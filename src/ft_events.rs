@@ -0,0 +1,142 @@
+use chrono::prelude::{DateTime, Utc};
+pub use inteat_events_api_types::ft::{
+    FtBurnEvent, FtBurnFilter, FtMintEvent, FtMintFilter, FtTransferEvent, FtTransferFilter,
+};
+
+use crate::endpoint::{event_endpoint, EventEndpoint};
+use crate::event_query::EventQuery;
+use crate::meta::EventTable;
+
+impl EventEndpoint for FtMintEvent {
+    const TABLE: EventTable = EventTable::FtMint;
+    const SUM_FIELDS: &'static [(&'static str, &'static str)] = &[("amount", "amount")];
+    type Filter = FtMintFilter;
+
+    fn query(filter: &FtMintFilter) -> EventQuery<Self> {
+        EventQuery::new(
+            "ft_mint",
+            "owner_id, amount, memo, transaction_id, receipt_id, block_height, timestamp, token_id",
+        )
+        .filter("token_id = ANY({})", filter.token_account_id.as_deref())
+        .filter("owner_id = ANY({})", filter.account_id.as_deref())
+        .filter("transaction_id = ANY({})", filter.transaction_id.as_deref())
+        .filter("receipt_id = ANY({})", filter.receipt_id.as_deref())
+        .filter("owner_id <> ALL({})", filter.exclude_account_id.as_deref())
+        .filter(
+            "token_id <> ALL({})",
+            filter.exclude_token_account_id.as_deref(),
+        )
+    }
+
+    fn timestamp(&self) -> DateTime<Utc> {
+        self.timestamp
+    }
+
+    fn receipt_id(&self) -> &str {
+        &self.receipt_id
+    }
+
+    fn transaction(&self) -> (&str, &str) {
+        (&self.transaction_id, &self.owner_id)
+    }
+}
+
+event_endpoint! {
+    /// Fungible token mint events
+    ft_mint(FtMintEvent, FtMintFilter),
+    route = "/ft_mint",
+    scope = "/v0/ft",
+    tag = "FT",
+}
+
+impl EventEndpoint for FtTransferEvent {
+    const TABLE: EventTable = EventTable::FtTransfer;
+    const SUM_FIELDS: &'static [(&'static str, &'static str)] = &[("amount", "amount")];
+    type Filter = FtTransferFilter;
+
+    fn query(filter: &FtTransferFilter) -> EventQuery<Self> {
+        EventQuery::new(
+            "ft_transfer",
+            "old_owner_id, new_owner_id, amount, memo, transaction_id, receipt_id, block_height, timestamp, token_id",
+        )
+        .filter("token_id = ANY({})", filter.token_account_id.as_deref())
+        .filter("old_owner_id = ANY({})", filter.old_owner_id.as_deref())
+        .filter("new_owner_id = ANY({})", filter.new_owner_id.as_deref())
+        .filter(
+            "ARRAY[old_owner_id, new_owner_id] @> {}",
+            filter.involved_account_ids.as_deref(),
+        )
+        .filter("transaction_id = ANY({})", filter.transaction_id.as_deref())
+        .filter("receipt_id = ANY({})", filter.receipt_id.as_deref())
+        .filter(
+            "NOT (ARRAY[old_owner_id, new_owner_id] && {})",
+            filter.exclude_account_id.as_deref(),
+        )
+        .filter(
+            "token_id <> ALL({})",
+            filter.exclude_token_account_id.as_deref(),
+        )
+    }
+
+    fn timestamp(&self) -> DateTime<Utc> {
+        self.timestamp
+    }
+
+    fn receipt_id(&self) -> &str {
+        &self.receipt_id
+    }
+
+    fn transaction(&self) -> (&str, &str) {
+        (&self.transaction_id, &self.old_owner_id)
+    }
+}
+
+event_endpoint! {
+    /// Fungible token transfer events
+    ft_transfer(FtTransferEvent, FtTransferFilter),
+    route = "/ft_transfer",
+    scope = "/v0/ft",
+    tag = "FT",
+}
+
+impl EventEndpoint for FtBurnEvent {
+    const TABLE: EventTable = EventTable::FtBurn;
+    const SUM_FIELDS: &'static [(&'static str, &'static str)] = &[("amount", "amount")];
+    type Filter = FtBurnFilter;
+
+    fn query(filter: &FtBurnFilter) -> EventQuery<Self> {
+        EventQuery::new(
+            "ft_burn",
+            "owner_id, amount, memo, transaction_id, receipt_id, block_height, timestamp, token_id",
+        )
+        .filter("token_id = ANY({})", filter.token_account_id.as_deref())
+        .filter("owner_id = ANY({})", filter.account_id.as_deref())
+        .filter("transaction_id = ANY({})", filter.transaction_id.as_deref())
+        .filter("receipt_id = ANY({})", filter.receipt_id.as_deref())
+        .filter("owner_id <> ALL({})", filter.exclude_account_id.as_deref())
+        .filter(
+            "token_id <> ALL({})",
+            filter.exclude_token_account_id.as_deref(),
+        )
+    }
+
+    fn timestamp(&self) -> DateTime<Utc> {
+        self.timestamp
+    }
+
+    fn receipt_id(&self) -> &str {
+        &self.receipt_id
+    }
+
+    fn transaction(&self) -> (&str, &str) {
+        (&self.transaction_id, &self.owner_id)
+    }
+}
+
+event_endpoint! {
+    /// Fungible token burn events
+    ft_burn(FtBurnEvent, FtBurnFilter),
+    route = "/ft_burn",
+    scope = "/v0/ft",
+    tag = "FT",
+}
@@ -0,0 +1,223 @@
+use actix_web::{get, post, web, HttpResponse, Responder};
+use async_graphql::{
+    dynamic::{
+        Field, FieldFuture, FieldValue, InputValue, Object, ResolverContext, Scalar, Schema,
+        SchemaError, TypeRef,
+    },
+    http::GraphiQLSource,
+    BatchRequest, Executor, Value,
+};
+use serde_json::Value as Json;
+use utoipa::OpenApi;
+
+use crate::{
+    admin::QueryError,
+    error_reporting, live,
+    meta::EventTable,
+    openapi::{self, resolve, ApiDoc},
+    AppState,
+};
+
+/// Every event type in a query runs a database query, so a request can't
+/// select too many of them. Each event type and each of its fields adds 1.
+const MAX_QUERY_COMPLEXITY: usize = 200;
+
+/// Batches are limited too, since each query in a batch can be as complex as
+/// a single query
+const MAX_BATCH_SIZE: usize = 10;
+
+/// Query parameters of event endpoints that aren't filters. Events are
+/// paginated with `start_block_timestamp_nanosec` and `blocks` arguments.
+const NON_FILTER_PARAMS: &[&str] = &[
+    "start_block_timestamp_nanosec",
+    "end_block_timestamp_nanosec",
+    "cursor",
+    "blocks",
+    "limit",
+    "order",
+    "order_by",
+    "format",
+    "sample_every_n_blocks",
+    "aggregate",
+    "bucket",
+    "enrich",
+];
+
+const INT64: &str = "Int64";
+const JSON: &str = "JSON";
+
+/// Builds the schema from the OpenAPI spec, so that types and filters of
+/// queries are the same as of REST endpoints. Each event type is a query
+/// with the same name, e.g. `nft_transfer`, and the same filters as its
+/// endpoint.
+pub fn schema() -> Result<Schema, SchemaError> {
+    let spec = serde_json::to_value(ApiDoc::openapi()).expect("OpenAPI spec is serializable");
+    let mut query = Object::new("Query");
+    let mut types = Vec::new();
+    for (table, path, operation, type_name) in openapi::event_endpoints(&spec) {
+        let event = resolve(&spec, &spec["components"]["schemas"][type_name]);
+        let required = event["required"].as_array().cloned().unwrap_or_default();
+        let mut object = Object::new(type_name);
+        if let Some(description) = operation["summary"].as_str() {
+            object = object.description(description);
+        }
+        for (name, property) in event["properties"].as_object().into_iter().flatten() {
+            let nullable = !required.contains(&Json::String(name.clone()));
+            let field_name = name.clone();
+            object = object.field(Field::new(
+                name,
+                type_ref(&spec, property, nullable),
+                move |ctx| {
+                    let field_name = field_name.clone();
+                    FieldFuture::new(async move {
+                        let event = ctx.parent_value.try_downcast_ref::<Json>()?;
+                        Ok(Some(FieldValue::value(Value::from_json(
+                            event[&field_name].clone(),
+                        )?)))
+                    })
+                },
+            ));
+        }
+        types.push(object);
+
+        let mut field = Field::new(
+            table.as_str(),
+            TypeRef::named_nn_list_nn(type_name),
+            move |ctx| FieldFuture::new(async move { resolve_events(ctx, table).await }),
+        )
+        .description(format!("Same as `GET {path}`"))
+        .argument(
+            InputValue::new("start_block_timestamp_nanosec", TypeRef::named(INT64))
+                .default_value(0),
+        )
+        .argument(InputValue::new("blocks", TypeRef::named(TypeRef::INT)).default_value(10));
+        for parameter in operation["parameters"].as_array().into_iter().flatten() {
+            let Some(name) = parameter["name"].as_str() else {
+                continue;
+            };
+            if NON_FILTER_PARAMS.contains(&name) {
+                continue;
+            }
+            let mut argument = InputValue::new(name, TypeRef::named(TypeRef::STRING));
+            if let Some(description) = parameter["description"].as_str() {
+                argument = argument.description(description);
+            }
+            field = field.argument(argument);
+        }
+        query = query.field(field);
+    }
+
+    types
+        .into_iter()
+        .fold(Schema::build("Query", None, None), |schema, object| {
+            schema.register(object)
+        })
+        .register(query)
+        .register(Scalar::new(INT64).description(
+            "64-bit integer, e.g. a timestamp in nanoseconds. Serialized as a number, like in REST responses.",
+        ))
+        .register(Scalar::new(JSON).description("Arbitrary JSON value"))
+        .limit_complexity(MAX_QUERY_COMPLEXITY)
+        .finish()
+}
+
+async fn resolve_events(
+    ctx: ResolverContext<'_>,
+    table: EventTable,
+) -> async_graphql::Result<Option<FieldValue<'_>>> {
+    let state = ctx.data::<web::Data<AppState>>()?;
+    let mut start_block_timestamp_nanosec = 0;
+    let mut blocks = 10;
+    let mut filters = Vec::new();
+    for (name, value) in ctx.args.iter() {
+        match name.as_str() {
+            "start_block_timestamp_nanosec" => start_block_timestamp_nanosec = value.i64()?,
+            "blocks" => blocks = value.i64()?,
+            _ if value.is_null() => {}
+            _ => filters.push((name.to_string(), value.string()?.to_owned())),
+        }
+    }
+    let max_blocks = state.block_limits().of(table);
+    if !(1..=max_blocks).contains(&blocks) {
+        return Err(format!("blocks must be between 1 and {max_blocks}").into());
+    }
+
+    // Filters are parsed by the endpoint's filter struct, the same way as
+    // query parameters of the endpoint
+    let query = serde_urlencoded::to_string(&filters)?;
+    let events = live::fetch_events(
+        &state.pg_pool(),
+        table,
+        &query,
+        start_block_timestamp_nanosec,
+        blocks,
+    )
+    .await
+    .map_err(|err| match err {
+        QueryError::BadQuery(message) => message,
+        QueryError::Database(err) => {
+            error_reporting::report_query_error(&err);
+            "Internal server error".to_owned()
+        }
+    })?;
+    Ok(Some(FieldValue::list(
+        events
+            .into_iter()
+            .map(|event| FieldValue::owned_any(event.event)),
+    )))
+}
+
+/// GraphQL type of a property of an event. Types that can't be expressed in
+/// GraphQL, like objects with arbitrary keys, are `JSON`.
+fn type_ref(spec: &Json, property: &Json, nullable: bool) -> TypeRef {
+    let property = resolve(spec, property);
+    let types = match &property["type"] {
+        Json::String(ty) => vec![ty.as_str()],
+        Json::Array(types) => types.iter().filter_map(Json::as_str).collect(),
+        _ => Vec::new(),
+    };
+    let nullable = nullable || types.contains(&"null");
+    let name = match types.iter().find(|&&ty| ty != "null") {
+        Some(&"string") => TypeRef::STRING,
+        Some(&"boolean") => TypeRef::BOOLEAN,
+        Some(&"integer") => INT64,
+        Some(&"array") => {
+            let item = type_ref(spec, &property["items"], false);
+            return if nullable {
+                TypeRef::List(Box::new(item))
+            } else {
+                TypeRef::NonNull(Box::new(TypeRef::List(Box::new(item))))
+            };
+        }
+        _ => JSON,
+    };
+    if nullable {
+        TypeRef::named(name)
+    } else {
+        TypeRef::named_nn(name)
+    }
+}
+
+/// Runs a query, or a batch of queries sent as a JSON array
+#[post("/graphql")]
+pub async fn graphql(
+    state: web::Data<AppState>,
+    schema: web::Data<Schema>,
+    request: web::Json<BatchRequest>,
+) -> HttpResponse {
+    let request = request.into_inner();
+    if request.iter().count() > MAX_BATCH_SIZE {
+        return HttpResponse::BadRequest().body(format!(
+            "At most {MAX_BATCH_SIZE} queries are allowed per batch"
+        ));
+    }
+    HttpResponse::Ok().json(schema.execute_batch(request.data(state)).await)
+}
+
+/// GraphiQL, to explore the schema and try queries in a browser
+#[get("/graphql")]
+pub async fn graphiql() -> impl Responder {
+    HttpResponse::Ok()
+        .content_type("text/html; charset=utf-8")
+        .body(GraphiQLSource::build().endpoint("/v0/graphql").finish())
+}
@@ -0,0 +1,278 @@
+// tonic methods return `Status` errors, so helpers return them too
+#![allow(clippy::result_large_err)]
+
+use std::{net::SocketAddr, pin::Pin, time::Duration};
+
+use actix_web::web;
+use futures_util::{stream, Stream, StreamExt};
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::Value;
+use tonic::{transport::Server, Request, Response, Status};
+
+use crate::{
+    admin::QueryError,
+    error_reporting,
+    live::{fetch_events, Subscription, POLL_INTERVAL},
+    meta::EventTable,
+    AppState,
+};
+
+#[allow(clippy::all)]
+pub mod proto {
+    tonic::include_proto!("events.v0");
+}
+
+use proto::events_server::{Events, EventsServer};
+
+/// HTTP/2 pings keep idle subscriptions from being closed by proxies
+const KEEP_ALIVE_INTERVAL: Duration = Duration::from_secs(15);
+
+type EventStream<T> = Pin<Box<dyn Stream<Item = Result<T, Status>> + Send>>;
+
+/// Serves `proto/events.proto` on its own address, since gRPC needs HTTP/2
+/// which the HTTP server doesn't negotiate without TLS
+pub async fn serve(
+    state: web::Data<AppState>,
+    address: SocketAddr,
+) -> Result<(), tonic::transport::Error> {
+    Server::builder()
+        .http2_keepalive_interval(Some(KEEP_ALIVE_INTERVAL))
+        .add_service(EventsServer::new(EventsService { state }))
+        .serve(address)
+        .await
+}
+
+struct EventsService {
+    state: web::Data<AppState>,
+}
+
+impl EventsService {
+    async fn get<E: DeserializeOwned>(
+        &self,
+        table: EventTable,
+        request: impl Serialize,
+    ) -> Result<Vec<E>, Status> {
+        let request = parse_request(request)?;
+        let blocks = request.blocks.unwrap_or(10);
+        let max_blocks = self.state.block_limits().of(table);
+        if !(1..=max_blocks).contains(&blocks) {
+            return Err(Status::invalid_argument(format!(
+                "blocks must be between 1 and {max_blocks}"
+            )));
+        }
+        fetch_events(
+            &self.state.pg_pool(),
+            table,
+            &request.query,
+            request.start_block_timestamp_nanosec.unwrap_or(0),
+            blocks,
+        )
+        .await
+        .map_err(status)?
+        .into_iter()
+        .map(|event| to_message(event.event))
+        .collect()
+    }
+
+    async fn subscribe<E: DeserializeOwned + Send + 'static>(
+        &self,
+        table: EventTable,
+        request: impl Serialize,
+    ) -> Result<EventStream<E>, Status> {
+        let request = parse_request(request)?;
+        let subscription = Subscription::new(
+            &self.state.primary_pg_pool(),
+            table,
+            request.query,
+            request.start_block_timestamp_nanosec,
+        )
+        .await
+        .map_err(status)?;
+        let tail = Tail {
+            state: self.state.clone(),
+            subscription,
+            caught_up: false,
+        };
+        Ok(Box::pin(stream::unfold(tail, next_page).flat_map(
+            |events| stream::iter(events.into_iter().map(to_message)),
+        )))
+    }
+}
+
+struct Tail {
+    state: web::Data<AppState>,
+    subscription: Subscription,
+    /// Whether the last page reached the latest indexed block
+    caught_up: bool,
+}
+
+async fn next_page(mut tail: Tail) -> Option<(Vec<Value>, Tail)> {
+    loop {
+        if tail.caught_up {
+            tail.subscription.wait(&tail.state).await;
+        }
+        match tail.subscription.next_page(&tail.state).await {
+            Ok((events, has_more)) => {
+                tail.caught_up = !has_more;
+                if !events.is_empty() {
+                    return Some((events, tail));
+                }
+            }
+            Err(QueryError::Database(err)) => {
+                log::warn!("Failed to fetch events for gRPC subscription: {err}");
+                tokio::time::sleep(POLL_INTERVAL).await;
+            }
+            // Filters were already validated when subscribing
+            Err(QueryError::BadQuery(_)) => return None,
+        }
+    }
+}
+
+struct ParsedRequest {
+    start_block_timestamp_nanosec: Option<i64>,
+    blocks: Option<i64>,
+    /// Filters as a query string, to be parsed by the endpoint's filter struct
+    query: String,
+}
+
+/// Requests have the same fields as query parameters of the endpoints, so
+/// they're passed to the same code as a query string
+fn parse_request(request: impl Serialize) -> Result<ParsedRequest, Status> {
+    let Value::Object(fields) =
+        serde_json::to_value(request).map_err(|err| Status::internal(err.to_string()))?
+    else {
+        unreachable!("Requests are messages");
+    };
+    let mut start_block_timestamp_nanosec = None;
+    let mut blocks = None;
+    let mut filters = Vec::new();
+    for (name, value) in fields {
+        match (name.as_str(), value) {
+            ("start_block_timestamp_nanosec", value) => {
+                start_block_timestamp_nanosec = value.as_i64()
+            }
+            ("blocks", value) => blocks = value.as_i64(),
+            (_, Value::String(value)) => filters.push((name, value)),
+            _ => {}
+        }
+    }
+    Ok(ParsedRequest {
+        start_block_timestamp_nanosec,
+        blocks,
+        query: serde_urlencoded::to_string(filters)
+            .map_err(|err| Status::internal(err.to_string()))?,
+    })
+}
+
+/// Converts an event as returned by its endpoint to its message. Objects with
+/// arbitrary keys, like post contents, and arrays of them, like `data` of raw
+/// log events, are JSON-encoded strings.
+fn to_message<E: DeserializeOwned>(mut event: Value) -> Result<E, Status> {
+    if let Value::Object(fields) = &mut event {
+        for value in fields.values_mut() {
+            let has_objects = match &*value {
+                Value::Array(values) => values.iter().any(Value::is_object),
+                value => value.is_object(),
+            };
+            if has_objects {
+                *value = Value::String(value.to_string());
+            }
+        }
+    }
+    serde_json::from_value(event).map_err(|err| {
+        log::error!("Failed to convert an event to protobuf: {err}");
+        Status::internal("Internal server error")
+    })
+}
+
+fn status(err: QueryError) -> Status {
+    match err {
+        QueryError::BadQuery(message) => Status::invalid_argument(message),
+        QueryError::Database(err) => {
+            error_reporting::report_query_error(&err);
+            Status::internal("Internal server error")
+        }
+    }
+}
+
+/// Implements Get and Subscribe methods of each event type
+macro_rules! events_service {
+    ($($table:ident: $get:ident, $subscribe:ident, $stream:ident, $request:ident, $event:ident, $events:ident;)*) => {
+        #[tonic::async_trait]
+        impl Events for EventsService {
+            $(
+                async fn $get(
+                    &self,
+                    request: Request<proto::$request>,
+                ) -> Result<Response<proto::$events>, Status> {
+                    let events = self.get(EventTable::$table, request.into_inner()).await?;
+                    Ok(Response::new(proto::$events { events }))
+                }
+
+                type $stream = EventStream<proto::$event>;
+
+                async fn $subscribe(
+                    &self,
+                    request: Request<proto::$request>,
+                ) -> Result<Response<Self::$stream>, Status> {
+                    self.subscribe(EventTable::$table, request.into_inner())
+                        .await
+                        .map(Response::new)
+                }
+            )*
+        }
+    };
+}
+
+events_service! {
+    FtMint: get_ft_mint, subscribe_ft_mint, SubscribeFtMintStream, FtMintRequest, FtMintEvent, FtMintEvents;
+    FtTransfer: get_ft_transfer, subscribe_ft_transfer, SubscribeFtTransferStream, FtTransferRequest, FtTransferEvent, FtTransferEvents;
+    FtBurn: get_ft_burn, subscribe_ft_burn, SubscribeFtBurnStream, FtBurnRequest, FtBurnEvent, FtBurnEvents;
+    NftMint: get_nft_mint, subscribe_nft_mint, SubscribeNftMintStream, NftMintRequest, NftMintEvent, NftMintEvents;
+    NftTransfer: get_nft_transfer, subscribe_nft_transfer, SubscribeNftTransferStream, NftTransferRequest, NftTransferEvent, NftTransferEvents;
+    NftBurn: get_nft_burn, subscribe_nft_burn, SubscribeNftBurnStream, NftBurnRequest, NftBurnEvent, NftBurnEvents;
+    NftSale: get_nft_sale, subscribe_nft_sale, SubscribeNftSaleStream, NftSaleRequest, NftSaleEvent, NftSaleEvents;
+    PotlockDonation: get_potlock_donation, subscribe_potlock_donation, SubscribePotlockDonationStream, PotlockDonationRequest, PotlockDonationEvent, PotlockDonationEvents;
+    PotlockPotProjectDonation: get_potlock_pot_project_donation, subscribe_potlock_pot_project_donation, SubscribePotlockPotProjectDonationStream, PotlockPotProjectDonationRequest, PotlockPotProjectDonationEvent, PotlockPotProjectDonationEvents;
+    PotlockPotDonation: get_potlock_pot_donation, subscribe_potlock_pot_donation, SubscribePotlockPotDonationStream, PotlockPotDonationRequest, PotlockPotDonationEvent, PotlockPotDonationEvents;
+    PotlockPotDeployment: get_potlock_pot_deployment, subscribe_potlock_pot_deployment, SubscribePotlockPotDeploymentStream, PotlockPotDeploymentRequest, PotlockPotDeploymentEvent, PotlockPotDeploymentEvents;
+    PotlockPotApplication: get_potlock_pot_application, subscribe_potlock_pot_application, SubscribePotlockPotApplicationStream, PotlockPotApplicationRequest, PotlockPotApplicationEvent, PotlockPotApplicationEvents;
+    PotlockPotApplicationReview: get_potlock_pot_application_review, subscribe_potlock_pot_application_review, SubscribePotlockPotApplicationReviewStream, PotlockPotApplicationReviewRequest, PotlockPotApplicationReviewEvent, PotlockPotApplicationReviewEvents;
+    PotlockPotPayout: get_potlock_pot_payout, subscribe_potlock_pot_payout, SubscribePotlockPotPayoutStream, PotlockPotPayoutRequest, PotlockPotPayoutEvent, PotlockPotPayoutEvents;
+    PotlockPotPayoutChallenge: get_potlock_pot_payout_challenge, subscribe_potlock_pot_payout_challenge, SubscribePotlockPotPayoutChallengeStream, PotlockPotPayoutChallengeRequest, PotlockPotPayoutChallengeEvent, PotlockPotPayoutChallengeEvents;
+    TradePool: get_trade_pool, subscribe_trade_pool, SubscribeTradePoolStream, TradePoolRequest, TradePoolEvent, TradePoolEvents;
+    TradeSwap: get_trade_swap, subscribe_trade_swap, SubscribeTradeSwapStream, TradeSwapRequest, TradeSwapEvent, TradeSwapEvents;
+    TradePoolChange: get_trade_pool_change, subscribe_trade_pool_change, SubscribeTradePoolChangeStream, TradePoolChangeRequest, TradePoolChangeEvent, TradePoolChangeEvents;
+    TradeLiquidityAdd: get_trade_liquidity_add, subscribe_trade_liquidity_add, SubscribeTradeLiquidityAddStream, TradeLiquidityAddRequest, TradeLiquidityAddEvent, TradeLiquidityAddEvents;
+    TradeLiquidityRemove: get_trade_liquidity_remove, subscribe_trade_liquidity_remove, SubscribeTradeLiquidityRemoveStream, TradeLiquidityRemoveRequest, TradeLiquidityRemoveEvent, TradeLiquidityRemoveEvents;
+    TradeWrap: get_trade_wrap, subscribe_trade_wrap, SubscribeTradeWrapStream, TradeWrapRequest, TradeWrapEvent, TradeWrapEvents;
+    TradeUnwrap: get_trade_unwrap, subscribe_trade_unwrap, SubscribeTradeUnwrapStream, TradeUnwrapRequest, TradeUnwrapEvent, TradeUnwrapEvents;
+    SocialdbPost: get_socialdb_post, subscribe_socialdb_post, SubscribeSocialdbPostStream, SocialdbPostRequest, SocialdbPostEvent, SocialdbPostEvents;
+    SocialdbComment: get_socialdb_comment, subscribe_socialdb_comment, SubscribeSocialdbCommentStream, SocialdbCommentRequest, SocialdbCommentEvent, SocialdbCommentEvents;
+    SocialdbLike: get_socialdb_like, subscribe_socialdb_like, SubscribeSocialdbLikeStream, SocialdbLikeRequest, SocialdbLikeEvent, SocialdbLikeEvents;
+    SocialdbFollow: get_socialdb_follow, subscribe_socialdb_follow, SubscribeSocialdbFollowStream, SocialdbFollowRequest, SocialdbFollowEvent, SocialdbFollowEvents;
+    SocialdbProfileUpdate: get_socialdb_profile_update, subscribe_socialdb_profile_update, SubscribeSocialdbProfileUpdateStream, SocialdbProfileUpdateRequest, SocialdbProfileUpdateEvent, SocialdbProfileUpdateEvents;
+    PriceChange: get_price_change, subscribe_price_change, SubscribePriceChangeStream, PriceChangeRequest, PriceChangeEvent, PriceChangeEvents;
+    BurrowDeposit: get_burrow_deposit, subscribe_burrow_deposit, SubscribeBurrowDepositStream, BurrowDepositRequest, BurrowDepositEvent, BurrowDepositEvents;
+    BurrowWithdraw: get_burrow_withdraw, subscribe_burrow_withdraw, SubscribeBurrowWithdrawStream, BurrowWithdrawRequest, BurrowWithdrawEvent, BurrowWithdrawEvents;
+    BurrowBorrow: get_burrow_borrow, subscribe_burrow_borrow, SubscribeBurrowBorrowStream, BurrowBorrowRequest, BurrowBorrowEvent, BurrowBorrowEvents;
+    BurrowRepay: get_burrow_repay, subscribe_burrow_repay, SubscribeBurrowRepayStream, BurrowRepayRequest, BurrowRepayEvent, BurrowRepayEvents;
+    BurrowLiquidation: get_burrow_liquidation, subscribe_burrow_liquidation, SubscribeBurrowLiquidationStream, BurrowLiquidationRequest, BurrowLiquidationEvent, BurrowLiquidationEvents;
+    LaunchCreate: get_launch_create, subscribe_launch_create, SubscribeLaunchCreateStream, LaunchCreateRequest, LaunchCreateEvent, LaunchCreateEvents;
+    LaunchDeposit: get_launch_deposit, subscribe_launch_deposit, SubscribeLaunchDepositStream, LaunchDepositRequest, LaunchDepositEvent, LaunchDepositEvents;
+    LaunchWithdraw: get_launch_withdraw, subscribe_launch_withdraw, SubscribeLaunchWithdrawStream, LaunchWithdrawRequest, LaunchWithdrawEvent, LaunchWithdrawEvents;
+    LaunchFinalize: get_launch_finalize, subscribe_launch_finalize, SubscribeLaunchFinalizeStream, LaunchFinalizeRequest, LaunchFinalizeEvent, LaunchFinalizeEvents;
+    StakingStake: get_staking_stake, subscribe_staking_stake, SubscribeStakingStakeStream, StakingStakeRequest, StakingStakeEvent, StakingStakeEvents;
+    StakingUnstake: get_staking_unstake, subscribe_staking_unstake, SubscribeStakingUnstakeStream, StakingUnstakeRequest, StakingUnstakeEvent, StakingUnstakeEvents;
+    StakingWithdraw: get_staking_withdraw, subscribe_staking_withdraw, SubscribeStakingWithdrawStream, StakingWithdrawRequest, StakingWithdrawEvent, StakingWithdrawEvents;
+    AccountCreate: get_account_create, subscribe_account_create, SubscribeAccountCreateStream, AccountCreateRequest, AccountCreateEvent, AccountCreateEvents;
+    AccountDelete: get_account_delete, subscribe_account_delete, SubscribeAccountDeleteStream, AccountDeleteRequest, AccountDeleteEvent, AccountDeleteEvents;
+    KeyAdded: get_key_added, subscribe_key_added, SubscribeKeyAddedStream, KeyAddedRequest, KeyAddedEvent, KeyAddedEvents;
+    KeyDeleted: get_key_deleted, subscribe_key_deleted, SubscribeKeyDeletedStream, KeyDeletedRequest, KeyDeletedEvent, KeyDeletedEvents;
+    DaoProposal: get_dao_proposal, subscribe_dao_proposal, SubscribeDaoProposalStream, DaoProposalRequest, DaoProposalEvent, DaoProposalEvents;
+    DaoVote: get_dao_vote, subscribe_dao_vote, SubscribeDaoVoteStream, DaoVoteRequest, DaoVoteEvent, DaoVoteEvents;
+    DaoProposalExecute: get_dao_proposal_execute, subscribe_dao_proposal_execute, SubscribeDaoProposalExecuteStream, DaoProposalExecuteRequest, DaoProposalExecuteEvent, DaoProposalExecuteEvents;
+    BridgeDeposit: get_bridge_deposit, subscribe_bridge_deposit, SubscribeBridgeDepositStream, BridgeDepositRequest, BridgeDepositEvent, BridgeDepositEvents;
+    BridgeWithdraw: get_bridge_withdraw, subscribe_bridge_withdraw, SubscribeBridgeWithdrawStream, BridgeWithdrawRequest, BridgeWithdrawEvent, BridgeWithdrawEvents;
+    LogEvents: get_log_events, subscribe_log_events, SubscribeLogEventsStream, LogEventsRequest, LogEvent, LogEvents;
+}
@@ -0,0 +1,173 @@
+use std::{collections::HashMap, sync::RwLock, time::Duration};
+
+use actix_web::web;
+use sqlx::postgres::PgListener;
+use tokio::sync::broadcast::{self, error::RecvError};
+
+use crate::{
+    live::{latest_timestamp_nanosec, POLL_INTERVAL},
+    meta::EventTable,
+    AppState,
+};
+
+/// Updates that subscribers haven't received yet. Slow subscribers skip
+/// updates and check `Head::latest` instead.
+const UPDATES_CAPACITY: usize = 1024;
+
+/// Timestamp of the latest indexed block of an event type
+#[derive(Debug, Clone, Copy)]
+pub struct HeadUpdate {
+    pub event_type: EventTable,
+    pub timestamp_nanosec: i64,
+}
+
+/// Latest indexed block of each event type, kept up to date by a background
+/// task, so that live subscriptions and webhooks know when there are new
+/// events without querying every table
+pub struct Head {
+    latest: RwLock<HashMap<EventTable, i64>>,
+    updates: broadcast::Sender<HeadUpdate>,
+}
+
+impl Default for Head {
+    fn default() -> Self {
+        Self {
+            latest: RwLock::new(HashMap::new()),
+            updates: broadcast::channel(UPDATES_CAPACITY).0,
+        }
+    }
+}
+
+impl Head {
+    /// `None` if the event type has no events, or the head isn't known yet
+    pub fn latest(&self, event_type: EventTable) -> Option<i64> {
+        self.latest.read().unwrap().get(&event_type).copied()
+    }
+
+    /// Updates of all event types, sent when their head moves forward
+    pub fn subscribe(&self) -> broadcast::Receiver<HeadUpdate> {
+        self.updates.subscribe()
+    }
+
+    /// Waits until the event type has a block at or after `timestamp_nanosec`,
+    /// or until `timeout` elapses
+    pub async fn wait_for(
+        &self,
+        event_type: EventTable,
+        timestamp_nanosec: i64,
+        timeout: Duration,
+    ) {
+        let mut updates = self.subscribe();
+        if self
+            .latest(event_type)
+            .is_some_and(|latest| latest >= timestamp_nanosec)
+        {
+            return;
+        }
+        let _ = tokio::time::timeout(timeout, async {
+            loop {
+                match updates.recv().await {
+                    Ok(update)
+                        if update.event_type == event_type
+                            && update.timestamp_nanosec >= timestamp_nanosec =>
+                    {
+                        return;
+                    }
+                    Ok(_) => {}
+                    Err(RecvError::Lagged(_)) => {
+                        if self
+                            .latest(event_type)
+                            .is_some_and(|latest| latest >= timestamp_nanosec)
+                        {
+                            return;
+                        }
+                    }
+                    Err(RecvError::Closed) => return,
+                }
+            }
+        })
+        .await;
+    }
+
+    fn advance(&self, event_type: EventTable, timestamp_nanosec: i64) {
+        let mut latest = self.latest.write().unwrap();
+        let current = latest.entry(event_type).or_insert(i64::MIN);
+        if timestamp_nanosec > *current {
+            *current = timestamp_nanosec;
+            // No receivers isn't an error, nobody is waiting for new events
+            let _ = self.updates.send(HeadUpdate {
+                event_type,
+                timestamp_nanosec,
+            });
+        }
+    }
+}
+
+/// Follows the latest indexed blocks. If `NOTIFY_CHANNEL` is set, the
+/// indexer is expected to `NOTIFY` it with the event type as the payload
+/// after inserting events, and only that event type is checked. Otherwise,
+/// all event types are polled.
+pub fn start(state: web::Data<AppState>) {
+    let channel = std::env::var("NOTIFY_CHANNEL").ok();
+    tokio::spawn(async move {
+        match channel {
+            Some(channel) => loop {
+                if let Err(err) = listen(&state, &channel).await {
+                    log::error!("Failed to listen on {channel}: {err}");
+                }
+                tokio::time::sleep(POLL_INTERVAL).await;
+            },
+            None => {
+                let mut interval = tokio::time::interval(POLL_INTERVAL);
+                loop {
+                    interval.tick().await;
+                    if let Err(err) = refresh_all(&state).await {
+                        log::error!("Failed to refresh the head: {err}");
+                    }
+                }
+            }
+        }
+    });
+}
+
+async fn listen(state: &AppState, channel: &str) -> Result<(), sqlx::Error> {
+    let mut listener = PgListener::connect_with(&state.primary_pg_pool()).await?;
+    listener.listen(channel).await?;
+    // Notifications sent before listening, or while reconnecting, are lost,
+    // so all event types are checked right after (re)connecting
+    refresh_all(state).await?;
+    loop {
+        match listener.try_recv().await? {
+            Some(notification) => {
+                match EventTable::ALL
+                    .into_iter()
+                    .find(|table| table.as_str() == notification.payload())
+                {
+                    Some(table) => refresh(state, table).await?,
+                    None => log::warn!(
+                        "Unknown event type in a notification on {channel}: {}",
+                        notification.payload()
+                    ),
+                }
+            }
+            // The connection was lost, the listener reconnects on the next call
+            None => refresh_all(state).await?,
+        }
+    }
+}
+
+async fn refresh_all(state: &AppState) -> Result<(), sqlx::Error> {
+    for table in EventTable::ALL {
+        refresh(state, table).await?;
+    }
+    Ok(())
+}
+
+async fn refresh(state: &AppState, table: EventTable) -> Result<(), sqlx::Error> {
+    // A replica may not have replayed the notified insert yet, and with
+    // notifications there's no later poll to catch up
+    if let Some(latest) = latest_timestamp_nanosec(&state.primary_pg_pool(), table).await? {
+        state.head().advance(table, latest);
+    }
+    Ok(())
+}
@@ -0,0 +1,32 @@
+use std::time::Duration;
+
+use actix_web::{get, web, HttpResponse, Responder};
+
+use crate::AppState;
+
+/// Shorter than the pool's acquire timeout, so that a database that hangs is
+/// reported before the probe itself times out
+const READINESS_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// Liveness probe, succeeds as long as the server can handle requests
+#[get("/healthz")]
+pub async fn healthz() -> impl Responder {
+    HttpResponse::Ok().body("ok")
+}
+
+/// Readiness probe, fails with 503 if Postgres can't be queried
+#[get("/readyz")]
+pub async fn readyz(state: web::Data<AppState>) -> impl Responder {
+    let pg_pool = state.pg_pool();
+    match tokio::time::timeout(READINESS_TIMEOUT, sqlx::query("SELECT 1").execute(&pg_pool)).await {
+        Ok(Ok(_)) => HttpResponse::Ok().body("ok"),
+        Ok(Err(err)) => {
+            log::warn!("Readiness check failed: {err}");
+            HttpResponse::ServiceUnavailable().body("database is unreachable")
+        }
+        Err(_) => {
+            log::warn!("Readiness check timed out");
+            HttpResponse::ServiceUnavailable().body("database is unreachable")
+        }
+    }
+}
@@ -0,0 +1,126 @@
+use std::{collections::HashMap, sync::OnceLock};
+
+use actix_web::{get, web, HttpResponse};
+use serde_json::{json, Map, Value as Json};
+use utoipa::OpenApi;
+
+use crate::{
+    meta::EventTable,
+    openapi::{self, ApiDoc},
+};
+
+const COMPONENTS_PREFIX: &str = "#/components/schemas/";
+
+/// JSON Schema of an event of each event type. They're generated from the
+/// OpenAPI spec, which is derived from the structs that the endpoints
+/// serialize, so a schema changes together with the events.
+fn event_schemas() -> &'static HashMap<EventTable, Json> {
+    static EVENT_SCHEMAS: OnceLock<HashMap<EventTable, Json>> = OnceLock::new();
+    EVENT_SCHEMAS.get_or_init(|| {
+        let spec = serde_json::to_value(ApiDoc::openapi()).expect("OpenAPI spec is serializable");
+        openapi::event_endpoints(&spec)
+            .into_iter()
+            .map(|(table, path, _, type_name)| (table, event_schema(&spec, path, type_name)))
+            .collect()
+    })
+}
+
+/// Schema of a component as a standalone document. OpenAPI 3.1 schemas are
+/// JSON Schema 2020-12, so only the components it references need to be
+/// moved to `$defs`.
+fn event_schema(spec: &Json, path: &str, type_name: &str) -> Json {
+    let components = &spec["components"]["schemas"];
+    let mut defs = Map::new();
+    let mut pending = Vec::new();
+    references(&components[type_name], &mut pending);
+    while let Some(name) = pending.pop() {
+        if name == type_name || defs.contains_key(name) {
+            continue;
+        }
+        references(&components[name], &mut pending);
+        defs.insert(name.to_owned(), components[name].clone());
+    }
+
+    let mut schema = components[type_name].clone();
+    let object = schema.as_object_mut().expect("event schemas are objects");
+    object.insert(
+        "$schema".to_owned(),
+        json!("https://json-schema.org/draft/2020-12/schema"),
+    );
+    object.insert("title".to_owned(), json!(type_name));
+    object
+        .entry("description")
+        .or_insert_with(|| json!(format!("Event of `GET {path}`")));
+    if !defs.is_empty() {
+        object.insert("$defs".to_owned(), Json::Object(defs));
+    }
+    move_references(&mut schema, type_name);
+    schema
+}
+
+/// Names of the components that a schema references
+fn references<'a>(schema: &'a Json, names: &mut Vec<&'a str>) {
+    match schema {
+        Json::Object(object) => {
+            for (key, value) in object {
+                match (key.as_str(), value.as_str()) {
+                    ("$ref", Some(reference)) => {
+                        names.extend(reference.strip_prefix(COMPONENTS_PREFIX));
+                    }
+                    _ => references(value, names),
+                }
+            }
+        }
+        Json::Array(values) => values.iter().for_each(|value| references(value, names)),
+        _ => {}
+    }
+}
+
+/// Points references to components at `$defs`, or at the root for the event
+/// itself
+fn move_references(schema: &mut Json, type_name: &str) {
+    match schema {
+        Json::Object(object) => {
+            for (key, value) in object.iter_mut() {
+                match value {
+                    Json::String(reference) if key == "$ref" => {
+                        let moved = reference.strip_prefix(COMPONENTS_PREFIX).map(|name| {
+                            if name == type_name {
+                                "#".to_owned()
+                            } else {
+                                format!("#/$defs/{name}")
+                            }
+                        });
+                        if let Some(moved) = moved {
+                            *reference = moved;
+                        }
+                    }
+                    _ => move_references(value, type_name),
+                }
+            }
+        }
+        Json::Array(values) => values
+            .iter_mut()
+            .for_each(|value| move_references(value, type_name)),
+        _ => {}
+    }
+}
+
+/// JSON Schema (2020-12) of the events of an event type, e.g.
+/// `/v0/schema/nft_transfer`, to validate events in data pipelines
+#[utoipa::path(
+    get,
+    path = "/v0/schema/{event_type}",
+    tag = "Meta",
+    params(("event_type" = EventTable, Path)),
+    responses((status = 200, description = "JSON Schema of an event", content_type = "application/schema+json"))
+)]
+#[get("/schema/{event_type}")]
+pub async fn event_type_schema(event_type: web::Path<EventTable>) -> HttpResponse {
+    match event_schemas().get(&event_type.into_inner()) {
+        Some(schema) => HttpResponse::Ok()
+            .content_type("application/schema+json")
+            .json(schema),
+        None => HttpResponse::NotFound().finish(),
+    }
+}
@@ -0,0 +1,173 @@
+use chrono::prelude::{DateTime, Utc};
+pub use inteat_events_api_types::launch::{
+    LaunchCreateEvent, LaunchCreateFilter, LaunchDepositEvent, LaunchDepositFilter,
+    LaunchFinalizeEvent, LaunchFinalizeFilter, LaunchWithdrawEvent, LaunchWithdrawFilter,
+};
+
+use crate::endpoint::{event_endpoint, EventEndpoint};
+use crate::event_query::EventQuery;
+use crate::meta::EventTable;
+
+impl EventEndpoint for LaunchCreateEvent {
+    const TABLE: EventTable = EventTable::LaunchCreate;
+    type Filter = LaunchCreateFilter;
+
+    fn query(filter: &LaunchCreateFilter) -> EventQuery<Self> {
+        EventQuery::new(
+            "launch_create",
+            "meme_id, token_id, owner_id, name, symbol, decimals, total_supply, reference, deposit_token_id, end_timestamp_ms, contract_id, transaction_id, receipt_id, block_height, timestamp",
+        )
+        .filter("meme_id = ANY({})", filter.meme_id.as_deref())
+        .filter("token_id = ANY({})", filter.token_id.as_deref())
+        .filter("owner_id = ANY({})", filter.account_id.as_deref())
+        .filter("contract_id = ANY({})", filter.contract_id.as_deref())
+        .filter("transaction_id = ANY({})", filter.transaction_id.as_deref())
+        .filter("receipt_id = ANY({})", filter.receipt_id.as_deref())
+    }
+
+    fn timestamp(&self) -> DateTime<Utc> {
+        self.timestamp
+    }
+
+    fn receipt_id(&self) -> &str {
+        &self.receipt_id
+    }
+
+    fn transaction(&self) -> (&str, &str) {
+        (&self.transaction_id, &self.owner_id)
+    }
+}
+
+event_endpoint! {
+    /// Launchpad meme creation events
+    launch_create(LaunchCreateEvent, LaunchCreateFilter),
+    route = "/launch_create",
+    scope = "/v0/launch",
+    tag = "Launch",
+}
+
+impl EventEndpoint for LaunchDepositEvent {
+    const TABLE: EventTable = EventTable::LaunchDeposit;
+    const SUM_FIELDS: &'static [(&'static str, &'static str)] = &[
+        ("amount", "amount"),
+        ("protocol_fee", "protocol_fee"),
+        ("referrer_fee", "referrer_fee"),
+    ];
+    type Filter = LaunchDepositFilter;
+
+    fn query(filter: &LaunchDepositFilter) -> EventQuery<Self> {
+        EventQuery::new(
+            "launch_deposit",
+            "meme_id, token_id, account_id, amount, protocol_fee, referrer_id, referrer_fee, contract_id, transaction_id, receipt_id, block_height, timestamp",
+        )
+        .filter("meme_id = ANY({})", filter.meme_id.as_deref())
+        .filter("token_id = ANY({})", filter.token_id.as_deref())
+        .filter("account_id = ANY({})", filter.account_id.as_deref())
+        .filter("contract_id = ANY({})", filter.contract_id.as_deref())
+        .filter("transaction_id = ANY({})", filter.transaction_id.as_deref())
+        .filter("receipt_id = ANY({})", filter.receipt_id.as_deref())
+        .filter("account_id <> ALL({})", filter.exclude_account_id.as_deref())
+    }
+
+    fn timestamp(&self) -> DateTime<Utc> {
+        self.timestamp
+    }
+
+    fn receipt_id(&self) -> &str {
+        &self.receipt_id
+    }
+
+    fn transaction(&self) -> (&str, &str) {
+        (&self.transaction_id, &self.account_id)
+    }
+}
+
+event_endpoint! {
+    /// Launchpad deposit events
+    launch_deposit(LaunchDepositEvent, LaunchDepositFilter),
+    route = "/launch_deposit",
+    scope = "/v0/launch",
+    tag = "Launch",
+}
+
+impl EventEndpoint for LaunchWithdrawEvent {
+    const TABLE: EventTable = EventTable::LaunchWithdraw;
+    const SUM_FIELDS: &'static [(&'static str, &'static str)] =
+        &[("amount", "amount"), ("fee", "fee")];
+    type Filter = LaunchWithdrawFilter;
+
+    fn query(filter: &LaunchWithdrawFilter) -> EventQuery<Self> {
+        EventQuery::new(
+            "launch_withdraw",
+            "meme_id, token_id, account_id, amount, fee, contract_id, transaction_id, receipt_id, block_height, timestamp",
+        )
+        .filter("meme_id = ANY({})", filter.meme_id.as_deref())
+        .filter("token_id = ANY({})", filter.token_id.as_deref())
+        .filter("account_id = ANY({})", filter.account_id.as_deref())
+        .filter("contract_id = ANY({})", filter.contract_id.as_deref())
+        .filter("transaction_id = ANY({})", filter.transaction_id.as_deref())
+        .filter("receipt_id = ANY({})", filter.receipt_id.as_deref())
+        .filter("account_id <> ALL({})", filter.exclude_account_id.as_deref())
+    }
+
+    fn timestamp(&self) -> DateTime<Utc> {
+        self.timestamp
+    }
+
+    fn receipt_id(&self) -> &str {
+        &self.receipt_id
+    }
+
+    fn transaction(&self) -> (&str, &str) {
+        (&self.transaction_id, &self.account_id)
+    }
+}
+
+event_endpoint! {
+    /// Launchpad withdrawal events
+    launch_withdraw(LaunchWithdrawEvent, LaunchWithdrawFilter),
+    route = "/launch_withdraw",
+    scope = "/v0/launch",
+    tag = "Launch",
+}
+
+impl EventEndpoint for LaunchFinalizeEvent {
+    const TABLE: EventTable = EventTable::LaunchFinalize;
+    const SUM_FIELDS: &'static [(&'static str, &'static str)] =
+        &[("total_deposit", "total_deposit")];
+    type Filter = LaunchFinalizeFilter;
+
+    fn query(filter: &LaunchFinalizeFilter) -> EventQuery<Self> {
+        EventQuery::new(
+            "launch_finalize",
+            "meme_id, token_id, pool_id, total_deposit, contract_id, transaction_id, receipt_id, block_height, timestamp",
+        )
+        .filter("meme_id = ANY({})", filter.meme_id.as_deref())
+        .filter("token_id = ANY({})", filter.token_id.as_deref())
+        .filter("contract_id = ANY({})", filter.contract_id.as_deref())
+        .filter("transaction_id = ANY({})", filter.transaction_id.as_deref())
+        .filter("receipt_id = ANY({})", filter.receipt_id.as_deref())
+    }
+
+    fn timestamp(&self) -> DateTime<Utc> {
+        self.timestamp
+    }
+
+    fn receipt_id(&self) -> &str {
+        &self.receipt_id
+    }
+
+    fn transaction(&self) -> (&str, &str) {
+        // Finalization isn't tied to an account, so the transaction is looked
+        // up on the launchpad
+        (&self.transaction_id, &self.contract_id)
+    }
+}
+
+event_endpoint! {
+    /// Launchpad finalization events
+    launch_finalize(LaunchFinalizeEvent, LaunchFinalizeFilter),
+    route = "/launch_finalize",
+    scope = "/v0/launch",
+    tag = "Launch",
+}
@@ -0,0 +1,642 @@
+pub mod access_log;
+pub mod account_events;
+pub mod admin;
+pub mod aggregate;
+pub mod batch;
+pub mod bridge_events;
+pub mod burrow_events;
+pub mod cache;
+pub mod cancel;
+pub mod catch_panic;
+pub mod compression;
+pub mod config;
+pub mod correlated;
+pub mod cors;
+pub mod count;
+pub mod csv_format;
+pub mod cursor;
+pub mod dao_events;
+pub mod digests;
+pub mod endpoint;
+pub mod enrich;
+pub mod error;
+pub mod error_reporting;
+pub mod event_query;
+pub mod explorer;
+pub mod fixtures;
+pub mod ft_events;
+pub mod graphql;
+pub mod grpc;
+pub mod head;
+pub mod health;
+pub mod json_schema;
+pub mod launch_events;
+pub mod limits;
+pub mod live;
+pub mod lookup;
+pub mod meta;
+pub mod ndjson;
+pub mod nft_events;
+pub mod openapi;
+pub mod potlock_events;
+pub mod price_events;
+pub mod rate_limit;
+pub mod raw_events;
+pub mod registry;
+pub mod request_id;
+pub mod socialdb_events;
+pub mod sse;
+pub mod staking_events;
+pub mod strict_query;
+pub mod telemetry;
+pub mod tls;
+pub mod toggles;
+pub mod trade_events;
+pub mod typescript;
+pub mod utils;
+pub mod v1;
+pub mod webhooks;
+pub mod ws;
+
+pub use inteat_events_api_types::ref_pool;
+
+use std::{
+    str::FromStr,
+    sync::{
+        atomic::{AtomicUsize, Ordering as AtomicOrdering},
+        RwLock,
+    },
+    time::Duration,
+};
+
+use chrono::{DateTime, Utc};
+use cursor::{Page, ResponseFormat};
+use error::ApiError;
+use futures_util::future::try_join_all;
+use head::Head;
+use limits::{BlockLimits, HeavyQueries};
+use serde::Deserialize;
+use sqlx::{
+    postgres::{PgConnectOptions, PgPoolOptions},
+    PgPool,
+};
+use utoipa::{IntoParams, ToSchema};
+
+/// Default of `MAX_BLOCKS_PER_REQUEST`, and page size of live subscriptions
+const MAX_BLOCKS_PER_REQUEST: i64 = 50;
+const MAX_SAMPLE_EVERY_N_BLOCKS: i64 = 1000;
+/// Maximum and default `limit`. Blocks alone don't bound the size of a
+/// response, since a block can have thousands of swaps.
+const MAX_EVENTS_PER_REQUEST: i64 = 10_000;
+const DEFAULT_STATEMENT_TIMEOUT_SECS: u64 = 10;
+const DEFAULT_PG_MAX_CONNECTIONS: u32 = 10;
+const DEFAULT_PG_ACQUIRE_TIMEOUT_SECS: u64 = 5;
+
+pub struct AppState {
+    pg_pools: RwLock<PgPools>,
+    /// Replica of the next read, round-robin
+    next_replica: AtomicUsize,
+    /// Replaced when the config is reloaded
+    block_limits: RwLock<BlockLimits>,
+    heavy_queries: HeavyQueries,
+    head: Head,
+}
+
+/// Pools of the primary and of read replicas, swapped together on reconnect
+struct PgPools {
+    primary: PgPool,
+    replicas: Vec<PgPool>,
+}
+
+impl AppState {
+    pub fn new(
+        pg_pool: PgPool,
+        read_pg_pools: Vec<PgPool>,
+        block_limits: BlockLimits,
+        heavy_queries: HeavyQueries,
+    ) -> Self {
+        Self {
+            pg_pools: RwLock::new(PgPools {
+                primary: pg_pool,
+                replicas: read_pg_pools,
+            }),
+            next_replica: AtomicUsize::new(0),
+            block_limits: RwLock::new(block_limits),
+            heavy_queries,
+            head: Head::default(),
+        }
+    }
+
+    /// Pool for reads, one of the `DATABASE_READ_URLS` replicas in turn, or
+    /// the primary if there are none
+    pub fn pg_pool(&self) -> PgPool {
+        let pg_pools = self.pg_pools.read().unwrap();
+        if pg_pools.replicas.is_empty() {
+            return pg_pools.primary.clone();
+        }
+        let replica = self.next_replica.fetch_add(1, AtomicOrdering::Relaxed);
+        pg_pools.replicas[replica % pg_pools.replicas.len()].clone()
+    }
+
+    /// Pool for writes, and for reads of rows that were just written, e.g.
+    /// webhook subscriptions. Notifications of new events are also only
+    /// delivered on the primary.
+    pub fn primary_pg_pool(&self) -> PgPool {
+        self.pg_pools.read().unwrap().primary.clone()
+    }
+
+    pub fn block_limits(&self) -> BlockLimits {
+        self.block_limits.read().unwrap().clone()
+    }
+
+    pub fn set_block_limits(&self, block_limits: BlockLimits) {
+        *self.block_limits.write().unwrap() = block_limits;
+    }
+
+    pub fn heavy_queries(&self) -> &HeavyQueries {
+        &self.heavy_queries
+    }
+
+    pub fn head(&self) -> &Head {
+        &self.head
+    }
+
+    /// Closes all pools, waiting for connections in use to be released
+    pub async fn close(&self) {
+        self.primary_pg_pool().close().await;
+        let replicas = self.pg_pools.read().unwrap().replicas.clone();
+        for replica in replicas {
+            replica.close().await;
+        }
+    }
+
+    /// Connects with freshly read credentials and swaps the pools. The old
+    /// pools are closed in the background once in-flight queries release
+    /// their connections.
+    pub async fn reconnect(&self) -> Result<(), sqlx::Error> {
+        let pg_pools = PgPools {
+            primary: connect(&database_url()).await?,
+            replicas: connect_replicas().await?,
+        };
+        let old_pg_pools = std::mem::replace(&mut *self.pg_pools.write().unwrap(), pg_pools);
+        tokio::spawn(async move {
+            old_pg_pools.primary.close().await;
+            for replica in old_pg_pools.replicas {
+                replica.close().await;
+            }
+            log::info!("Closed old database connection pools");
+        });
+        Ok(())
+    }
+}
+
+/// Connects to a database, configured with environment variables:
+///
+/// - `STATEMENT_TIMEOUT_SECS`: queries that run longer are cancelled by
+///   Postgres, so that slow filter combinations can't pile up on the database.
+///   10 by default, 0 for no limit.
+/// - `PG_MAX_CONNECTIONS`: size of the pool, 10 by default.
+/// - `PG_ACQUIRE_TIMEOUT`: seconds to wait for a connection when all are in
+///   use before responding with `overloaded`, 5 by default.
+pub async fn connect(database_url: &str) -> Result<PgPool, sqlx::Error> {
+    fn parse<T: FromStr>(var: &str, default: T) -> T {
+        std::env::var(var).map_or(default, |value| {
+            value
+                .parse()
+                .unwrap_or_else(|_| panic!("{var} must be a non-negative number"))
+        })
+    }
+    let statement_timeout_secs = parse("STATEMENT_TIMEOUT_SECS", DEFAULT_STATEMENT_TIMEOUT_SECS);
+    let options = PgConnectOptions::from_str(database_url)?
+        .options([("statement_timeout", statement_timeout_secs * 1000)]);
+    PgPoolOptions::new()
+        .max_connections(parse("PG_MAX_CONNECTIONS", DEFAULT_PG_MAX_CONNECTIONS))
+        .acquire_timeout(Duration::from_secs(parse(
+            "PG_ACQUIRE_TIMEOUT",
+            DEFAULT_PG_ACQUIRE_TIMEOUT_SECS,
+        )))
+        .connect_with(options)
+        .await
+}
+
+/// Connects to the read replicas of comma-separated `DATABASE_READ_URLS`,
+/// with the same settings as the primary
+pub async fn connect_replicas() -> Result<Vec<PgPool>, sqlx::Error> {
+    let database_read_urls = std::env::var("DATABASE_READ_URLS").unwrap_or_default();
+    try_join_all(
+        database_read_urls
+            .split(',')
+            .map(str::trim)
+            .filter(|url| !url.is_empty())
+            .map(connect),
+    )
+    .await
+}
+
+/// Reads `DATABASE_URL_FILE` if it's set (e.g. a mounted secret that gets
+/// rotated), `DATABASE_URL` otherwise.
+pub fn database_url() -> String {
+    if let Ok(file) = std::env::var("DATABASE_URL_FILE") {
+        std::fs::read_to_string(file)
+            .expect("Failed to read DATABASE_URL_FILE")
+            .trim()
+            .to_owned()
+    } else {
+        std::env::var("DATABASE_URL").expect("DATABASE_URL environment variable must be set")
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(try_from = "PaginationParams")]
+struct PaginationInfo {
+    start_block_timestamp_nanosec: i64,
+    /// Exclusive
+    end_block_timestamp_nanosec: Option<i64>,
+    blocks: i64,
+    limit: i64,
+    /// With `desc`, the latest `blocks` blocks before `end_block_timestamp_nanosec`
+    /// are returned, newest first, and the cursor goes back in time
+    order: Order,
+    order_by: OrderBy,
+    format: ResponseFormat,
+    /// Block and tie breaker of the last event of a previous page that ended
+    /// inside the block, to continue right after it
+    after: Option<(i64, String)>,
+}
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+struct PaginationParams {
+    start_block_timestamp_nanosec: Option<i64>,
+    /// Exclusive
+    end_block_timestamp_nanosec: Option<i64>,
+    /// Continues from a previous response, instead of `start_block_timestamp_nanosec`,
+    /// or `end_block_timestamp_nanosec` with `order=desc`
+    cursor: Option<String>,
+    /// Number of blocks with events to return. The maximum is 50 unless the
+    /// server is configured otherwise, and is returned in the `X-Max-Blocks`
+    /// response header.
+    #[serde(default = "default_blocks_per_request")]
+    #[param(default = 10)]
+    blocks: i64,
+    /// Maximum number of events to return. Pages contain whole blocks, so if
+    /// the requested blocks have more events, the page ends before the block
+    /// that doesn't fit, and the cursor continues from it. A block with more
+    /// events than `limit` is split between receipts instead.
+    #[serde(default = "default_limit")]
+    #[param(default = 10000, minimum = 1, maximum = 10000)]
+    limit: i64,
+    /// Documented by `Ordering` on endpoints that support it
+    #[serde(default)]
+    #[param(ignore)]
+    order_by: OrderBy,
+    /// `desc` returns the latest blocks first
+    #[serde(default)]
+    #[param(inline)]
+    order: Order,
+    /// `legacy` returns just the array of events, without a cursor. `ndjson`
+    /// streams one event per line, and `csv` returns a table for spreadsheets,
+    /// both also without a cursor. `msgpack` is the same as `envelope`,
+    /// encoded as MessagePack.
+    #[serde(default)]
+    #[param(inline)]
+    format: ResponseFormat,
+}
+
+impl TryFrom<PaginationParams> for PaginationInfo {
+    type Error = String;
+
+    fn try_from(params: PaginationParams) -> Result<Self, Self::Error> {
+        // Sorting by amount sorts events within the blocks after
+        // `start_block_timestamp_nanosec`, so the blocks are always ascending
+        let order = match params.order_by {
+            OrderBy::Timestamp => params.order,
+            OrderBy::Amount => Order::Asc,
+        };
+        if !(1..=MAX_EVENTS_PER_REQUEST).contains(&params.limit) {
+            return Err(format!(
+                "limit must be between 1 and {MAX_EVENTS_PER_REQUEST}"
+            ));
+        }
+        let mut start_block_timestamp_nanosec = params.start_block_timestamp_nanosec;
+        let mut end_block_timestamp_nanosec = params.end_block_timestamp_nanosec;
+        let mut after = None;
+        if let Some(cursor) = params.cursor {
+            let (block_timestamp_nanosec, tie_breaker) = cursor::decode(&cursor)?;
+            // A cursor inside a block includes the rest of the block
+            let (bound, name, value) = match order {
+                Order::Asc => (
+                    &mut start_block_timestamp_nanosec,
+                    "start_block_timestamp_nanosec",
+                    block_timestamp_nanosec,
+                ),
+                Order::Desc => (
+                    &mut end_block_timestamp_nanosec,
+                    "end_block_timestamp_nanosec",
+                    match tie_breaker {
+                        Some(_) => block_timestamp_nanosec.saturating_add(1),
+                        None => block_timestamp_nanosec,
+                    },
+                ),
+            };
+            if bound.replace(value).is_some() {
+                return Err(format!("cursor and {name} can't be used together"));
+            }
+            after = tie_breaker.map(|tie_breaker| (block_timestamp_nanosec, tie_breaker));
+        }
+        Ok(Self {
+            start_block_timestamp_nanosec: start_block_timestamp_nanosec.unwrap_or_default(),
+            end_block_timestamp_nanosec,
+            blocks: params.blocks,
+            limit: params.limit,
+            order,
+            order_by: params.order_by,
+            format: params.format,
+            after,
+        })
+    }
+}
+
+impl PaginationInfo {
+    fn order(&self) -> &'static str {
+        match self.order {
+            Order::Asc => "asc",
+            Order::Desc => "desc",
+        }
+    }
+
+    /// `start_block_timestamp_nanosec` as bound to queries
+    fn start(&self) -> DateTime<Utc> {
+        utils::nanosec_to_timestamp(self.start_block_timestamp_nanosec)
+    }
+
+    /// `end_block_timestamp_nanosec` as bound to queries
+    fn end(&self) -> Option<DateTime<Utc>> {
+        self.end_block_timestamp_nanosec
+            .map(utils::nanosec_to_timestamp)
+    }
+
+    /// Rows to fetch, one more than `limit` to know if the last block fits
+    fn fetch_limit(&self) -> i64 {
+        self.limit + 1
+    }
+
+    /// Whether events are sorted by time, so that a block is never split by
+    /// events of other blocks
+    fn sorted_by_block(&self) -> bool {
+        self.order_by == OrderBy::Timestamp
+    }
+
+    /// `after` as bound to queries
+    fn after(&self) -> Option<(DateTime<Utc>, &str)> {
+        self.after
+            .as_ref()
+            .map(|(block, tie_breaker)| (utils::nanosec_to_timestamp(*block), tie_breaker.as_str()))
+    }
+
+    /// Events over `limit` are dropped together with the rest of their block,
+    /// so that the cursor doesn't skip the events that didn't fit. If not
+    /// even one block fits, the page ends inside it, after the last receipt
+    /// that fits, as ordered by `tie_breaker`.
+    fn page<T>(
+        &self,
+        events: &mut Vec<T>,
+        timestamp: impl Fn(&T) -> DateTime<Utc>,
+        tie_breaker: impl Fn(&T) -> &str,
+    ) -> Result<Page, ApiError> {
+        let Some(partial) = self.drop_partial_block(events, &timestamp)? else {
+            return Ok(Page::new(self.format, self.order, events, timestamp));
+        };
+        let block = partial.timestamp_nanos_opt().unwrap_or_default();
+        let partial_tie_breaker = tie_breaker(&events[self.limit as usize]).to_owned();
+        events.truncate(self.limit as usize);
+        events.retain(|event| tie_breaker(event) != partial_tie_breaker);
+        let Some(last) = events.last() else {
+            return Err(ApiError::Validation(format!(
+                "Block {block} has more than {} events of {partial_tie_breaker}, use a higher limit",
+                self.limit
+            )));
+        };
+        Ok(Page::inside_block(self.format, block, tie_breaker(last)))
+    }
+
+    /// Same as `page`, for events that can't be split inside a block
+    fn whole_block_page<T>(
+        &self,
+        events: &mut Vec<T>,
+        timestamp: impl Fn(&T) -> DateTime<Utc>,
+    ) -> Result<Page, ApiError> {
+        if let Some(partial) = self.drop_partial_block(events, &timestamp)? {
+            return Err(limit_exceeded(self.limit, partial.timestamp_nanos_opt()));
+        }
+        Ok(Page::new(self.format, self.order, events, timestamp))
+    }
+
+    /// Drops the block that doesn't fit in `limit`, and returns it if it's
+    /// the only one, with the events left as they were
+    fn drop_partial_block<T>(
+        &self,
+        events: &mut Vec<T>,
+        timestamp: impl Fn(&T) -> DateTime<Utc>,
+    ) -> Result<Option<DateTime<Utc>>, ApiError> {
+        let limit = self.limit as usize;
+        if events.len() <= limit {
+            return Ok(None);
+        }
+        if !self.sorted_by_block() {
+            return Err(limit_exceeded(self.limit, None));
+        }
+        events.truncate(limit + 1);
+        let partial = timestamp(&events[limit]);
+        let whole_blocks = events
+            .iter()
+            .position(|event| timestamp(event) == partial)
+            .unwrap_or(limit);
+        if whole_blocks == 0 {
+            return Ok(Some(partial));
+        }
+        events.truncate(whole_blocks);
+        Ok(None)
+    }
+}
+
+/// Error for when not even one block fits in `limit`, or when events aren't
+/// sorted by block (`None`), so the page can't end between blocks
+fn limit_exceeded(limit: i64, block_timestamp_nanosec: Option<i64>) -> ApiError {
+    ApiError::Validation(match block_timestamp_nanosec {
+        Some(timestamp) => {
+            format!("Block {timestamp} has more than {limit} events, use a higher limit")
+        }
+        None => format!(
+            "Requested blocks have more than {limit} events, use a higher limit or fewer blocks"
+        ),
+    })
+}
+
+fn default_blocks_per_request() -> i64 {
+    10
+}
+
+fn default_limit() -> i64 {
+    MAX_EVENTS_PER_REQUEST
+}
+
+/// Downsampling for charts: only every `sample_every_n_blocks`-th block with
+/// events is returned, so `blocks` blocks cover a longer time range
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+struct Sampling {
+    #[serde(default = "default_sample_every_n_blocks")]
+    #[param(default = 1, maximum = 1000)]
+    sample_every_n_blocks: i64,
+}
+
+fn default_sample_every_n_blocks() -> i64 {
+    1
+}
+
+/// Sorting of events within the requested block window
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+struct Ordering {
+    /// `amount` sorts events within the returned blocks by amount
+    #[serde(default)]
+    #[param(inline)]
+    order_by: OrderBy,
+    /// Documented by `PaginationParams`
+    #[serde(default)]
+    #[param(ignore)]
+    order: Order,
+}
+
+#[derive(Deserialize, ToSchema, Default, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+enum OrderBy {
+    #[default]
+    Timestamp,
+    Amount,
+}
+
+#[derive(Deserialize, ToSchema, Default, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+enum Order {
+    #[default]
+    Asc,
+    Desc,
+}
+
+impl Ordering {
+    fn order_by(&self) -> &'static str {
+        match self.order_by {
+            OrderBy::Timestamp => "timestamp",
+            OrderBy::Amount => "amount",
+        }
+    }
+
+    fn order(&self) -> &'static str {
+        match self.order {
+            Order::Asc => "asc",
+            Order::Desc => "desc",
+        }
+    }
+}
+
+/// Time range of statistics, from the first indexed event by default
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+struct TimeRange {
+    #[serde(default)]
+    start_block_timestamp_nanosec: i64,
+    /// Exclusive
+    end_block_timestamp_nanosec: Option<i64>,
+}
+
+impl TimeRange {
+    fn start(&self) -> DateTime<Utc> {
+        utils::nanosec_to_timestamp(self.start_block_timestamp_nanosec)
+    }
+
+    fn end(&self) -> Option<DateTime<Utc>> {
+        self.end_block_timestamp_nanosec
+            .map(utils::nanosec_to_timestamp)
+    }
+}
+
+#[derive(Deserialize, ToSchema, Default, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+enum StatsPeriod {
+    Day,
+    #[default]
+    Week,
+    Month,
+}
+
+impl StatsPeriod {
+    /// Name of the period as accepted by Postgres `date_trunc`
+    fn as_str(&self) -> &'static str {
+        match self {
+            StatsPeriod::Day => "day",
+            StatsPeriod::Week => "week",
+            StatsPeriod::Month => "month",
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pagination(query: &str) -> PaginationInfo {
+        serde_urlencoded::from_str(query).unwrap()
+    }
+
+    /// Events from `(block timestamp in seconds, receipt)`
+    fn events(receipts: &[(i64, &'static str)]) -> Vec<(DateTime<Utc>, &'static str)> {
+        receipts
+            .iter()
+            .map(|(block, receipt)| (DateTime::from_timestamp(*block, 0).unwrap(), *receipt))
+            .collect()
+    }
+
+    fn page(
+        pagination: &PaginationInfo,
+        events: &mut Vec<(DateTime<Utc>, &'static str)>,
+    ) -> Result<Option<String>, ApiError> {
+        pagination
+            .page(events, |event| event.0, |event| event.1)
+            .map(|page| page.cursor().map(str::to_owned))
+    }
+
+    #[test]
+    fn pages_end_before_the_block_that_does_not_fit() {
+        let mut page_events = events(&[(1, "a"), (1, "b"), (2, "a"), (2, "b")]);
+        let cursor = page(&pagination("limit=3"), &mut page_events).unwrap();
+        assert_eq!(page_events, events(&[(1, "a"), (1, "b")]));
+        assert_eq!(cursor, Some(cursor::encode(1_000_000_001)));
+    }
+
+    #[test]
+    fn pages_end_inside_a_block_that_does_not_fit_alone() {
+        let mut page_events = events(&[(1, "a"), (1, "b"), (1, "b"), (1, "c")]);
+        let cursor = page(&pagination("limit=2"), &mut page_events).unwrap();
+        assert_eq!(page_events, events(&[(1, "a")]));
+        assert_eq!(
+            cursor,
+            Some(cursor::encode_inside_block(1_000_000_000, "a"))
+        );
+
+        // The next page continues inside the block in either order
+        let next = pagination(&format!("limit=2&cursor={}", cursor.clone().unwrap()));
+        assert_eq!(next.start_block_timestamp_nanosec, 1_000_000_000);
+        assert_eq!(next.after, Some((1_000_000_000, "a".to_owned())));
+        let next = pagination(&format!("limit=2&order=desc&cursor={}", cursor.unwrap()));
+        assert_eq!(next.end_block_timestamp_nanosec, Some(1_000_000_001));
+        assert_eq!(next.after, Some((1_000_000_000, "a".to_owned())));
+    }
+
+    #[test]
+    fn receipts_are_not_split() {
+        let mut page_events = events(&[(1, "a"), (1, "a"), (1, "a")]);
+        assert!(page(&pagination("limit=2"), &mut page_events).is_err());
+    }
+}
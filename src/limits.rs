@@ -0,0 +1,322 @@
+use actix_web::{
+    body::{EitherBody, MessageBody},
+    dev::{ServiceRequest, ServiceResponse},
+    http::header::{HeaderName, HeaderValue},
+    middleware::Next,
+    web, Error,
+};
+use tokio::sync::{Semaphore, SemaphorePermit};
+
+use crate::{aggregate::MAX_BLOCKS_PER_AGGREGATE, error::ApiError, meta::EventTable, AppState};
+
+/// Actix payload limit, applies to endpoints that accept a request body
+const DEFAULT_MAX_PAYLOAD_SIZE: usize = 262_144;
+
+/// Request size limits, configured with environment variables. Requests whose
+/// request line and headers exceed 128 KiB in total are always rejected by
+/// actix, so URL and header limits can only lower that.
+#[derive(Clone, Copy)]
+pub struct RequestLimits {
+    /// `MAX_URL_LENGTH`, path and query string, in bytes
+    pub max_url_length: Option<usize>,
+    /// `MAX_HEADER_SIZE`, sum of all header names and values, in bytes
+    pub max_header_size: Option<usize>,
+    /// `MAX_PAYLOAD_SIZE`, request body, in bytes
+    pub max_payload_size: usize,
+}
+
+impl RequestLimits {
+    pub fn from_env() -> Self {
+        fn parse(var: &str) -> Option<usize> {
+            std::env::var(var).ok().map(|value| {
+                value
+                    .parse()
+                    .unwrap_or_else(|_| panic!("{var} must be a number of bytes"))
+            })
+        }
+        Self {
+            max_url_length: parse("MAX_URL_LENGTH"),
+            max_header_size: parse("MAX_HEADER_SIZE"),
+            max_payload_size: parse("MAX_PAYLOAD_SIZE").unwrap_or(DEFAULT_MAX_PAYLOAD_SIZE),
+        }
+    }
+
+    pub fn payload_config(&self) -> web::PayloadConfig {
+        web::PayloadConfig::new(self.max_payload_size)
+    }
+
+    pub fn json_config(&self) -> web::JsonConfig {
+        web::JsonConfig::default().limit(self.max_payload_size)
+    }
+}
+
+/// Maximum `blocks` per request, configured with environment variables.
+/// Event types with large events, like `trade_pool_change`, can have a lower
+/// limit, and ones with small events a higher one.
+#[derive(Debug, Clone)]
+pub struct BlockLimits {
+    /// `MAX_BLOCKS_PER_REQUEST`, defaults to 50
+    default: i64,
+    /// `MAX_BLOCKS_PER_REQUEST_OVERRIDES`, e.g. `trade_pool_change=10,nft_burn=200`
+    overrides: Vec<(EventTable, i64)>,
+}
+
+impl BlockLimits {
+    pub fn from_env() -> Self {
+        fn parse(var: &str, value: &str) -> i64 {
+            match value.trim().parse() {
+                Ok(blocks) if blocks > 0 => blocks,
+                _ => panic!("{var} must be a positive number of blocks"),
+            }
+        }
+        let default = std::env::var("MAX_BLOCKS_PER_REQUEST")
+            .map_or(crate::MAX_BLOCKS_PER_REQUEST, |value| {
+                parse("MAX_BLOCKS_PER_REQUEST", &value)
+            });
+        let overrides = std::env::var("MAX_BLOCKS_PER_REQUEST_OVERRIDES")
+            .unwrap_or_default()
+            .split(',')
+            .map(str::trim)
+            .filter(|entry| !entry.is_empty())
+            .map(|entry| {
+                let (event_type, blocks) = entry.split_once('=').unwrap_or_else(|| {
+                    panic!("MAX_BLOCKS_PER_REQUEST_OVERRIDES entries must be <event_type>=<blocks>")
+                });
+                let table = EventTable::ALL
+                    .into_iter()
+                    .find(|table| table.as_str() == event_type.trim())
+                    .unwrap_or_else(|| {
+                        panic!(
+                            "Unknown event type in MAX_BLOCKS_PER_REQUEST_OVERRIDES: {event_type}"
+                        )
+                    });
+                (table, parse("MAX_BLOCKS_PER_REQUEST_OVERRIDES", blocks))
+            })
+            .collect();
+        Self { default, overrides }
+    }
+
+    /// Limit of endpoints that aren't of a single event type, e.g. account
+    /// activity
+    pub fn default_limit(&self) -> i64 {
+        self.default
+    }
+
+    /// Limit of an event type's endpoint
+    pub fn of(&self, table: EventTable) -> i64 {
+        self.overrides
+            .iter()
+            .find(|(overridden, _)| *overridden == table)
+            .map_or(self.default, |(_, blocks)| *blocks)
+    }
+}
+
+/// Limits how many heavy queries, like aggregations, counts, and stats, run
+/// at once, so that they can't take all connections of the pool.
+/// `MAX_CONCURRENT_HEAVY_QUERIES`, unlimited by default.
+pub struct HeavyQueries {
+    semaphore: Option<Semaphore>,
+}
+
+impl HeavyQueries {
+    pub fn from_env() -> Self {
+        let semaphore = std::env::var("MAX_CONCURRENT_HEAVY_QUERIES")
+            .ok()
+            .map(|value| match value.trim().parse() {
+                Ok(permits) if permits > 0 => Semaphore::new(permits),
+                _ => panic!("MAX_CONCURRENT_HEAVY_QUERIES must be a positive number of queries"),
+            });
+        Self { semaphore }
+    }
+
+    /// Permit to run a heavy query until it's dropped. Requests over the
+    /// limit fail with `overloaded` right away instead of queueing up.
+    pub fn try_acquire(&self) -> Result<Option<SemaphorePermit<'_>>, ApiError> {
+        self.semaphore
+            .as_ref()
+            .map(|semaphore| semaphore.try_acquire().map_err(|_| ApiError::Overloaded))
+            .transpose()
+    }
+}
+
+/// Adds `X-Max-Blocks` with the effective limit of `blocks` to responses of
+/// event endpoints, so that clients can size their requests without
+/// hardcoding it
+pub async fn max_blocks_header(
+    state: web::Data<AppState>,
+    req: ServiceRequest,
+    next: Next<impl MessageBody>,
+) -> Result<ServiceResponse<impl MessageBody>, Error> {
+    let max_blocks = req
+        .path()
+        .strip_prefix("/v0/")
+        .and_then(|path| path.rsplit('/').next())
+        .and_then(|endpoint| {
+            let aggregated = req
+                .query_string()
+                .split('&')
+                .any(|param| param.starts_with("aggregate="));
+            if aggregated {
+                return EventTable::ALL
+                    .into_iter()
+                    .any(|table| table.as_str() == endpoint)
+                    .then_some(MAX_BLOCKS_PER_AGGREGATE);
+            }
+            match endpoint {
+                "correlated" | "activity" => Some(state.block_limits().default_limit()),
+                _ => EventTable::ALL
+                    .into_iter()
+                    .find(|table| table.as_str() == endpoint)
+                    .map(|table| state.block_limits().of(table)),
+            }
+        });
+    let mut res = next.call(req).await?;
+    if let Some(max_blocks) = max_blocks {
+        res.headers_mut().insert(
+            HeaderName::from_static("x-max-blocks"),
+            HeaderValue::from(max_blocks),
+        );
+    }
+    Ok(res)
+}
+
+/// Rejects requests with URL or headers over the configured limits with a
+/// message explaining which limit was hit.
+pub async fn enforce_limits(
+    limits: web::Data<RequestLimits>,
+    req: ServiceRequest,
+    next: Next<impl MessageBody>,
+) -> Result<ServiceResponse<EitherBody<impl MessageBody>>, Error> {
+    if let Some(max_url_length) = limits.max_url_length {
+        let url_length = req
+            .uri()
+            .path_and_query()
+            .map_or(0, |path_and_query| path_and_query.as_str().len());
+        if url_length > max_url_length {
+            return Ok(req
+                .error_response(ApiError::UriTooLong { max_url_length })
+                .map_into_right_body());
+        }
+    }
+    if let Some(max_header_size) = limits.max_header_size {
+        let header_size: usize = req
+            .headers()
+            .iter()
+            .map(|(name, value)| name.as_str().len() + value.len())
+            .sum();
+        if header_size > max_header_size {
+            return Ok(req
+                .error_response(ApiError::HeadersTooLarge { max_header_size })
+                .map_into_right_body());
+        }
+    }
+    next.call(req)
+        .await
+        .map(ServiceResponse::map_into_left_body)
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::{body::BoxBody, http::StatusCode, middleware, test, App, HttpResponse};
+    use sqlx::postgres::PgPoolOptions;
+
+    use super::*;
+
+    #[actix_web::test]
+    async fn max_blocks_header_is_added_to_responses_that_skip_the_handler() {
+        let pg_pool = PgPoolOptions::new()
+            .connect_lazy("postgres://localhost/events")
+            .unwrap();
+        let block_limits = BlockLimits {
+            default: 50,
+            overrides: vec![(EventTable::TradePoolChange, 10)],
+        };
+        let heavy_queries = HeavyQueries { semaphore: None };
+        let state = AppState::new(pg_pool, Vec::new(), block_limits, heavy_queries);
+        let app = test::init_service(
+            App::new()
+                .app_data(web::Data::new(state))
+                .default_service(web::to(|| async {
+                    panic!("the handler should not be called") as HttpResponse
+                }))
+                // Like a cache hit
+                .wrap(middleware::from_fn(
+                    |req: ServiceRequest, _: Next<BoxBody>| async {
+                        Ok(req.into_response(HttpResponse::Ok().finish()))
+                    },
+                ))
+                .wrap(middleware::from_fn(max_blocks_header)),
+        )
+        .await;
+
+        for (uri, max_blocks) in [
+            ("/v0/trade/trade_pool_change", Some("10")),
+            ("/v0/nft/nft_transfer", Some("50")),
+            ("/v0/nft/nft_transfer?aggregate=day", Some("100000")),
+            ("/v0/meta/head", None),
+        ] {
+            let req = test::TestRequest::get().uri(uri).to_request();
+            let res = test::call_service(&app, req).await;
+            assert_eq!(
+                res.headers()
+                    .get("x-max-blocks")
+                    .map(|value| value.to_str().unwrap()),
+                max_blocks,
+                "{uri}"
+            );
+        }
+    }
+
+    async fn limited_response(limits: RequestLimits, req: test::TestRequest) -> HttpResponse {
+        let app = test::init_service(
+            App::new()
+                .app_data(web::Data::new(limits))
+                .default_service(web::to(HttpResponse::Ok))
+                .wrap(middleware::from_fn(enforce_limits)),
+        )
+        .await;
+        test::call_service(&app, req.to_request())
+            .await
+            .map_into_boxed_body()
+            .into_parts()
+            .1
+    }
+
+    async fn error_code(res: HttpResponse) -> serde_json::Value {
+        let body = actix_web::body::to_bytes(res.into_body()).await.unwrap();
+        serde_json::from_slice::<serde_json::Value>(&body).unwrap()["error"]["code"].clone()
+    }
+
+    #[actix_web::test]
+    async fn long_urls_are_rejected_with_uri_too_long() {
+        let limits = RequestLimits {
+            max_url_length: Some(32),
+            max_header_size: None,
+            max_payload_size: DEFAULT_MAX_PAYLOAD_SIZE,
+        };
+        let res =
+            limited_response(limits, test::TestRequest::get().uri("/v0/ft/ft_transfer")).await;
+        assert_eq!(res.status(), StatusCode::OK);
+
+        let uri = "/v0/ft/ft_transfer?token_account_id=usdt.tether-token.near";
+        let res = limited_response(limits, test::TestRequest::get().uri(uri)).await;
+        assert_eq!(res.status(), StatusCode::URI_TOO_LONG);
+        assert_eq!(error_code(res).await, "uri_too_long");
+    }
+
+    #[actix_web::test]
+    async fn large_headers_are_rejected_with_headers_too_large() {
+        let limits = RequestLimits {
+            max_url_length: None,
+            max_header_size: Some(64),
+            max_payload_size: DEFAULT_MAX_PAYLOAD_SIZE,
+        };
+        let req = test::TestRequest::get().insert_header(("x-small", "1"));
+        assert_eq!(limited_response(limits, req).await.status(), StatusCode::OK);
+
+        let req = test::TestRequest::get().insert_header(("x-large", "1".repeat(64)));
+        let res = limited_response(limits, req).await;
+        assert_eq!(res.status(), StatusCode::REQUEST_HEADER_FIELDS_TOO_LARGE);
+        assert_eq!(error_code(res).await, "headers_too_large");
+    }
+}
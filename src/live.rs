@@ -0,0 +1,157 @@
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use serde_json::Value;
+use sqlx::PgPool;
+
+use crate::{
+    admin::QueryError,
+    meta::EventTable,
+    registry::{self, with_event_type},
+    AppState, MAX_BLOCKS_PER_REQUEST,
+};
+
+/// How often subscriptions check for new events once they've caught up
+pub(crate) const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// An event serialized as returned by its endpoint
+pub(crate) struct LiveEvent {
+    pub(crate) timestamp: DateTime<Utc>,
+    pub(crate) event: Value,
+}
+
+pub(crate) fn live<T: Serialize>(
+    events: Vec<T>,
+    timestamp: impl Fn(&T) -> DateTime<Utc>,
+) -> Vec<LiveEvent> {
+    events
+        .into_iter()
+        .map(|event| LiveEvent {
+            timestamp: timestamp(&event),
+            event: serde_json::to_value(event).unwrap(),
+        })
+        .collect()
+}
+
+/// Follows new events of one type matching the filters of its endpoint, given
+/// as a query string. Pages are fetched back to back until the tip is reached.
+pub(crate) struct Subscription {
+    event_type: EventTable,
+    query: String,
+    start_block_timestamp_nanosec: i64,
+}
+
+impl Subscription {
+    /// Starts after the latest indexed event if `start_block_timestamp_nanosec`
+    /// is not set, so that only events that land after subscribing are sent.
+    /// `pg_pool` is the primary, which later pages are fetched from too.
+    pub(crate) async fn new(
+        pg_pool: &PgPool,
+        event_type: EventTable,
+        query: String,
+        start_block_timestamp_nanosec: Option<i64>,
+    ) -> Result<Self, QueryError> {
+        let start_block_timestamp_nanosec = match start_block_timestamp_nanosec {
+            Some(start) => start,
+            None => latest_timestamp_nanosec(pg_pool, event_type)
+                .await
+                .map_err(QueryError::Database)?
+                .map_or(0, |latest| latest + 1),
+        };
+        let subscription = Self {
+            event_type,
+            query,
+            start_block_timestamp_nanosec,
+        };
+        // Bad filters are reported right away instead of on the first poll
+        subscription.fetch(pg_pool).await?;
+        Ok(subscription)
+    }
+
+    /// Where the next page starts
+    pub(crate) fn start_block_timestamp_nanosec(&self) -> i64 {
+        self.start_block_timestamp_nanosec
+    }
+
+    /// Next page of events, and whether there may be more right away
+    pub(crate) async fn next_page(
+        &mut self,
+        state: &AppState,
+    ) -> Result<(Vec<Value>, bool), QueryError> {
+        // Nothing was indexed since the last page, so there's no need to query
+        let head = state.head().latest(self.event_type);
+        if head.is_some_and(|head| head < self.start_block_timestamp_nanosec) {
+            return Ok((Vec::new(), false));
+        }
+        // The head comes from the primary, so the page does too. A lagging
+        // replica would return fewer events, and the cursor would still move
+        // past the head, so the missing events would never be sent.
+        let events = self.fetch(&state.primary_pg_pool()).await?;
+        let mut timestamps = events.iter().map(|e| e.timestamp).collect::<Vec<_>>();
+        timestamps.dedup();
+        let has_more = timestamps.len() as i64 >= MAX_BLOCKS_PER_REQUEST;
+        // Pages always contain whole blocks, so the next page starts right
+        // after the last block
+        if let Some(latest) = timestamps.iter().max() {
+            self.start_block_timestamp_nanosec =
+                latest.timestamp_nanos_opt().unwrap_or_default() + 1;
+        }
+        // Blocks up to the head had no more matching events, so they're
+        // skipped too, otherwise `wait` would return right away
+        if let (Some(head), false) = (head, has_more) {
+            self.start_block_timestamp_nanosec = self.start_block_timestamp_nanosec.max(head + 1);
+        }
+        Ok((events.into_iter().map(|e| e.event).collect(), has_more))
+    }
+
+    /// Waits until there may be new events: a block after the last page is
+    /// indexed, or `POLL_INTERVAL` elapses
+    pub(crate) async fn wait(&self, state: &AppState) {
+        state
+            .head()
+            .wait_for(
+                self.event_type,
+                self.start_block_timestamp_nanosec,
+                POLL_INTERVAL,
+            )
+            .await;
+    }
+
+    async fn fetch(&self, pg_pool: &PgPool) -> Result<Vec<LiveEvent>, QueryError> {
+        fetch_events(
+            pg_pool,
+            self.event_type,
+            &self.query,
+            self.start_block_timestamp_nanosec,
+            MAX_BLOCKS_PER_REQUEST,
+        )
+        .await
+    }
+}
+
+/// Events of the first `blocks` blocks starting from `start` nanoseconds,
+/// with filters of the type's endpoint given as a query string
+pub(crate) async fn fetch_events(
+    pg_pool: &PgPool,
+    table: EventTable,
+    query: &str,
+    start: i64,
+    blocks: i64,
+) -> Result<Vec<LiveEvent>, QueryError> {
+    with_event_type!(table, |E| {
+        registry::live_events::<E>(pg_pool, query, start, blocks).await
+    })
+}
+
+pub(crate) async fn latest_timestamp_nanosec(
+    pg_pool: &PgPool,
+    table: EventTable,
+) -> Result<Option<i64>, sqlx::Error> {
+    // The table name comes from `EventTable`, so it's safe to format it in
+    let latest: Option<DateTime<Utc>> =
+        sqlx::query_scalar(&format!("SELECT MAX(timestamp) FROM {}", table.as_str()))
+            .fetch_one(pg_pool)
+            .await?;
+    Ok(latest.and_then(|latest| latest.timestamp_nanos_opt()))
+}
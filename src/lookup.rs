@@ -0,0 +1,393 @@
+use std::collections::BTreeMap;
+
+use actix_web::{get, web, HttpResponse};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use serde_json::Value;
+use sqlx::PgPool;
+use utoipa::ToSchema;
+
+use crate::{
+    correlated::{fetch_events, CorrelatedGroup},
+    cursor::EventsPage,
+    error::{ApiError, ErrorResponse},
+    meta::EventTable,
+    utils::ValidAccountId,
+    AppState, Order, PaginationInfo, PaginationParams,
+};
+
+/// All indexed events of a transaction, e.g. for a transaction page of an
+/// explorer
+#[utoipa::path(
+    get,
+    path = "/v0/tx/{transaction_id}",
+    tag = "Lookup",
+    params(("transaction_id" = String, Path)),
+    responses(
+        (status = 200, body = CorrelatedGroup),
+        (status = 404, body = ErrorResponse),
+    )
+)]
+#[get("/tx/{transaction_id}")]
+pub async fn transaction(
+    state: web::Data<AppState>,
+    transaction_id: web::Path<String>,
+) -> Result<HttpResponse, ApiError> {
+    let transaction_id = transaction_id.into_inner();
+    let pg_pool = state.pg_pool();
+
+    let mut group: Option<CorrelatedGroup> = None;
+    for matches in find_matches(&pg_pool, &transaction_id, None, |table| {
+        table
+            .has_transaction_id()
+            .then(|| "transaction_id = $1".to_owned())
+    })
+    .await?
+    {
+        let mut events = fetch_events(
+            &pg_pool,
+            matches.table,
+            &matches.transaction_ids,
+            matches.first,
+            matches.last,
+        )
+        .await?;
+        events.sort_by_key(|event| event.timestamp);
+        let group = group.get_or_insert_with(|| CorrelatedGroup {
+            transaction_id: transaction_id.clone(),
+            timestamp: matches.first,
+            events: BTreeMap::new(),
+        });
+        group.timestamp = group.timestamp.min(matches.first);
+        group.events.insert(
+            matches.table.as_str(),
+            events.into_iter().map(|event| event.event).collect(),
+        );
+    }
+    match group {
+        Some(group) => Ok(HttpResponse::Ok().json(group)),
+        None => Err(ApiError::NotFound(format!(
+            "No events found for transaction {transaction_id}"
+        ))),
+    }
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ReceiptEvent {
+    /// Type of the event, e.g. `nft_transfer`
+    pub event_type: &'static str,
+    /// The event in the same format as returned by its endpoint
+    pub event: Value,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ReceiptEvents {
+    pub receipt_id: String,
+    /// Events of all types, ordered by timestamp
+    pub events: Vec<ReceiptEvent>,
+}
+
+/// All indexed events emitted by a receipt, to join events with receipts
+/// fetched from RPC
+#[utoipa::path(
+    get,
+    path = "/v0/receipt/{receipt_id}",
+    tag = "Lookup",
+    params(("receipt_id" = String, Path)),
+    responses(
+        (status = 200, body = ReceiptEvents),
+        (status = 404, body = ErrorResponse),
+    )
+)]
+#[get("/receipt/{receipt_id}")]
+pub async fn receipt(
+    state: web::Data<AppState>,
+    receipt_id: web::Path<String>,
+) -> Result<HttpResponse, ApiError> {
+    let receipt_id = receipt_id.into_inner();
+    let pg_pool = state.pg_pool();
+
+    // Events are fetched by transaction ids, so events of other receipts of
+    // the same transactions are filtered out afterwards
+    let mut events = Vec::new();
+    for matches in find_matches(&pg_pool, &receipt_id, None, |table| {
+        table
+            .has_transaction_id()
+            .then(|| "receipt_id = $1".to_owned())
+    })
+    .await?
+    {
+        for event in fetch_events(
+            &pg_pool,
+            matches.table,
+            &matches.transaction_ids,
+            matches.first,
+            matches.last,
+        )
+        .await?
+        {
+            if event.event["receipt_id"].as_str() == Some(&receipt_id) {
+                events.push((event.timestamp, matches.table.as_str(), event.event));
+            }
+        }
+    }
+    if events.is_empty() {
+        return Err(ApiError::NotFound(format!(
+            "No events found for receipt {receipt_id}"
+        )));
+    }
+    events.sort_by_key(|(timestamp, event_type, _)| (*timestamp, *event_type));
+    Ok(HttpResponse::Ok().json(ReceiptEvents {
+        receipt_id,
+        events: events
+            .into_iter()
+            .map(|(_, event_type, event)| ReceiptEvent { event_type, event })
+            .collect(),
+    }))
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ActivityEvent {
+    /// Type of the event, e.g. `nft_transfer`
+    #[serde(rename = "type")]
+    pub event_type: &'static str,
+    /// Fields of the event, the same as returned by its endpoint
+    #[serde(flatten)]
+    #[schema(value_type = Object)]
+    pub event: Value,
+    #[serde(skip)]
+    pub timestamp: DateTime<Utc>,
+}
+
+/// Events of all types where the account is one of the parties, e.g. the
+/// sender or the receiver of a transfer, the trader of a swap, or the donor
+/// of a donation, merged into one feed for wallets
+#[utoipa::path(
+    get,
+    path = "/v0/account/{account_id}/activity",
+    tag = "Lookup",
+    params(("account_id" = String, Path), PaginationParams),
+    responses(
+        (status = 200, body = EventsPage<ActivityEvent>),
+        (status = 400, body = ErrorResponse),
+    )
+)]
+#[get("/account/{account_id}/activity")]
+pub async fn account_activity(
+    state: web::Data<AppState>,
+    account_id: web::Path<String>,
+    pagination: web::Query<PaginationInfo>,
+) -> Result<HttpResponse, ApiError> {
+    let max_blocks = state.block_limits().default_limit();
+    if pagination.blocks > max_blocks {
+        return Err(ApiError::Validation(format!(
+            "Blocks per request must be less or equal to {max_blocks}"
+        )));
+    }
+    let account_id = account_id
+        .parse::<ValidAccountId>()
+        .map_err(ApiError::BadFilter)?
+        .into_inner();
+    let pg_pool = state.pg_pool();
+
+    // Blocks are counted across all tables, so the page is the requested
+    // number of blocks in which the account did anything
+    let timestamps = union(|table| {
+        account_condition(table)
+            .map(|condition| format!("SELECT timestamp FROM {} WHERE {condition}", table.as_str()))
+    });
+    // Tables are merged in the order of their timestamp indexes, and blocks
+    // are numbered as they're read, like pages of a single table
+    let direction = match pagination.order {
+        Order::Asc => "ASC",
+        Order::Desc => "DESC",
+    };
+    let (first, last): (Option<DateTime<Utc>>, Option<DateTime<Utc>>) = sqlx::query_as(&format!(
+        r#"
+            SELECT MIN(timestamp), MAX(timestamp)
+            FROM (
+                SELECT timestamp, dense_rank() OVER (ORDER BY timestamp {direction}) AS block_number
+                FROM ({timestamps}) events
+                WHERE timestamp >= $2
+                    AND timestamp < COALESCE($3::TIMESTAMPTZ, 'infinity')
+            ) blocks
+            WHERE block_number <= $4
+            "#
+    ))
+    .bind(&account_id)
+    .bind(pagination.start())
+    .bind(pagination.end())
+    .bind(pagination.blocks)
+    .fetch_one(&pg_pool)
+    .await?;
+
+    let mut events = Vec::new();
+    if let (Some(first), Some(last)) = (first, last) {
+        // Events are fetched by transaction ids, so events of the same
+        // transactions where the account isn't a party are filtered out
+        for matches in find_matches(
+            &pg_pool,
+            &account_id,
+            Some((first, last)),
+            account_condition,
+        )
+        .await?
+        {
+            let columns = matches.table.account_columns();
+            for event in fetch_events(
+                &pg_pool,
+                matches.table,
+                &matches.transaction_ids,
+                matches.first,
+                matches.last,
+            )
+            .await?
+            {
+                if columns
+                    .iter()
+                    .any(|column| event.event[column].as_str() == Some(&account_id))
+                {
+                    events.push(ActivityEvent {
+                        event_type: matches.table.as_str(),
+                        event: event.event,
+                        timestamp: event.timestamp,
+                    });
+                }
+            }
+        }
+    }
+    events.sort_by_key(|event| (event.timestamp, event.event_type));
+    if pagination.order == Order::Desc {
+        events.reverse();
+    }
+    Ok(pagination
+        .whole_block_page(&mut events, |event| event.timestamp)?
+        .respond(events))
+}
+
+/// Events where the account is in any of the table's account columns
+fn account_condition(table: EventTable) -> Option<String> {
+    let columns = table.account_columns();
+    (!columns.is_empty()).then(|| {
+        columns
+            .iter()
+            .map(|column| format!("{column} = $1"))
+            .collect::<Vec<_>>()
+            .join(" OR ")
+    })
+}
+
+/// Event type, transaction ids, and timestamps of the first and the last event
+type MatchesRow = (String, Vec<String>, DateTime<Utc>, DateTime<Utc>);
+
+/// Events of a table where a column equals the requested id
+struct Matches {
+    table: EventTable,
+    transaction_ids: Vec<String>,
+    /// Timestamp of the first event
+    first: DateTime<Utc>,
+    /// Timestamp of the last event
+    last: DateTime<Utc>,
+}
+
+/// Finds tables that have events matching `condition`, so that only these
+/// tables are queried for the events, and only in the time range where they
+/// are. `condition` returns the `WHERE` clause for a table, with `id` as `$1`,
+/// or `None` to skip the table.
+async fn find_matches(
+    pg_pool: &PgPool,
+    id: &str,
+    range: Option<(DateTime<Utc>, DateTime<Utc>)>,
+    condition: impl Fn(EventTable) -> Option<String>,
+) -> Result<Vec<Matches>, sqlx::Error> {
+    let events = union(|table| {
+        condition(table).map(|condition| {
+            format!(
+                "SELECT '{table}' as event_type, transaction_id, timestamp FROM {table} WHERE {condition}",
+                table = table.as_str()
+            )
+        })
+    });
+    let rows: Vec<MatchesRow> = sqlx::query_as(&format!(
+        r#"
+        SELECT event_type, array_agg(DISTINCT transaction_id), MIN(timestamp), MAX(timestamp)
+        FROM ({events}) events
+        WHERE $2::TIMESTAMPTZ IS NULL OR timestamp BETWEEN $2 AND $3
+        GROUP BY event_type
+        "#
+    ))
+    .bind(id)
+    .bind(range.map(|(from, _)| from))
+    .bind(range.map(|(_, to)| to))
+    .fetch_all(pg_pool)
+    .await?;
+    Ok(rows
+        .into_iter()
+        .filter_map(|(event_type, transaction_ids, first, last)| {
+            let table = EventTable::ALL
+                .into_iter()
+                .find(|table| table.as_str() == event_type)?;
+            Some(Matches {
+                table,
+                transaction_ids,
+                first,
+                last,
+            })
+        })
+        .collect())
+}
+
+/// Queries of all tables for which `query` returns `Some`, joined with
+/// `UNION ALL`. Table and column names are hardcoded, so it's safe to format
+/// them in.
+fn union(query: impl Fn(EventTable) -> Option<String>) -> String {
+    EventTable::ALL
+        .into_iter()
+        .filter_map(query)
+        .collect::<Vec<_>>()
+        .join(" UNION ALL ")
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::{
+        http::StatusCode,
+        test::{call_service, init_service, read_body_json, TestRequest},
+        App,
+    };
+    use sqlx::postgres::PgPoolOptions;
+
+    use super::*;
+    use crate::limits::{BlockLimits, HeavyQueries};
+
+    #[actix_web::test]
+    async fn malformed_account_ids_are_rejected() {
+        let pg_pool = PgPoolOptions::new()
+            .connect_lazy("postgres://localhost/events")
+            .unwrap();
+        let state = AppState::new(
+            pg_pool,
+            Vec::new(),
+            BlockLimits::from_env(),
+            HeavyQueries::from_env(),
+        );
+        let app = init_service(
+            App::new()
+                .app_data(web::Data::new(state))
+                .service(web::scope("/v0").service(account_activity)),
+        )
+        .await;
+
+        let req = TestRequest::get()
+            .uri("/v0/account/Not..Valid/activity")
+            .to_request();
+        let res = call_service(&app, req).await;
+        assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+        let error: Value = read_body_json(res).await;
+        assert_eq!(error["error"]["code"], "bad_filter");
+        assert_eq!(
+            error["error"]["message"],
+            "`Not..Valid` is not a valid NEAR account id"
+        );
+    }
+}
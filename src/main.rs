@@ -1,117 +1,375 @@
-use std::{fs::File, io::BufReader};
+use std::os::unix::fs::PermissionsExt;
 
-mod nft_events;
-mod potlock_events;
-mod trade_events;
-pub mod utils;
-
-use actix_cors::Cors;
 use actix_web::{middleware, web, App, HttpServer};
-use log::LevelFilter;
-use serde::Deserialize;
-use sqlx::PgPool;
-
-const MAX_BLOCKS_PER_REQUEST: i64 = 50;
-
-struct AppState {
-    pg_pool: PgPool,
-}
+use events_api_http_server::{
+    access_log, account_events,
+    admin::{self, AdminToken},
+    batch, bridge_events, burrow_events,
+    cache::{self, ResponseCache},
+    catch_panic::catch_panic,
+    compression::{self, ResponseCompression},
+    config, connect, connect_replicas, correlated,
+    cors::CorsConfig,
+    count, cursor, dao_events, database_url, digests,
+    enrich::TxStatusClient,
+    error, error_reporting, explorer, fixtures, ft_events, graphql, grpc, head, health,
+    json_schema, launch_events,
+    limits::{self, BlockLimits, HeavyQueries, RequestLimits},
+    lookup, meta, nft_events, openapi, potlock_events, price_events,
+    rate_limit::{self, RateLimiter},
+    raw_events,
+    request_id::{self, RequestIdSpan},
+    socialdb_events, sse, staking_events, strict_query,
+    telemetry::{self, LogFormat},
+    tls,
+    toggles::{self, EndpointToggles},
+    trade_events, v1, webhooks, ws, AppState,
+};
+use tokio::signal::unix::{signal, SignalKind};
+use tracing_actix_web::TracingLogger;
 
 #[tokio::main]
 async fn main() -> std::io::Result<()> {
     dotenvy::dotenv().ok();
-    simple_logger::SimpleLogger::new()
-        .with_level(LevelFilter::Info)
-        .init()
-        .unwrap();
-
-    let pg_pool = PgPool::connect(
-        &std::env::var("DATABASE_URL").expect("DATABASE_URL environment variable must be set"),
-    )
-    .await
-    .expect("Failed to connect to Postgres");
-
-    let tls_config = if let Ok(files) = std::env::var("SSL") {
-        #[allow(clippy::iter_nth_zero)]
-        let mut certs_file = BufReader::new(File::open(files.split(',').nth(0).unwrap()).unwrap());
-        let mut key_file = BufReader::new(File::open(files.split(',').nth(1).unwrap()).unwrap());
-        let tls_certs = rustls_pemfile::certs(&mut certs_file)
-            .collect::<Result<Vec<_>, _>>()
-            .unwrap();
-        let tls_key = rustls_pemfile::pkcs8_private_keys(&mut key_file)
-            .next()
-            .unwrap()
-            .unwrap();
-        Some(
-            rustls::ServerConfig::builder()
-                .with_no_client_auth()
-                .with_single_cert(tls_certs, rustls::pki_types::PrivateKeyDer::Pkcs8(tls_key))
-                .unwrap(),
-        )
-    } else {
-        None
-    };
+    config::load();
+    let log_format = LogFormat::from_env();
+    telemetry::init(log_format);
+    let _error_reporting = error_reporting::init();
+
+    let pg_pool = connect(&database_url())
+        .await
+        .expect("Failed to connect to Postgres");
+    let read_pg_pools = connect_replicas()
+        .await
+        .expect("Failed to connect to Postgres read replicas");
+    let state = web::Data::new(AppState::new(
+        pg_pool,
+        read_pg_pools,
+        BlockLimits::from_env(),
+        HeavyQueries::from_env(),
+    ));
+
+    let tls_config = tls::server_config();
+
+    head::start(state.clone());
+    let mock_mode = std::env::var("MOCK_MODE").is_ok();
+    let digests_enabled = std::env::var("ENABLE_DIGESTS").is_ok();
+    if digests_enabled {
+        digests::start(state.clone()).await;
+    }
+    let webhooks_enabled = std::env::var("ENABLE_WEBHOOKS").is_ok();
+    if webhooks_enabled {
+        webhooks::start(state.clone()).await;
+    }
+    let request_limits = RequestLimits::from_env();
+    let endpoint_toggles = web::Data::new(EndpointToggles::from_env());
+    let rate_limiter = web::Data::new(RateLimiter::from_env());
+    rate_limit::start(rate_limiter.clone());
+    let response_compression = web::Data::new(ResponseCompression::from_env());
+    let graphql_schema =
+        web::Data::new(graphql::schema().expect("Failed to build the GraphQL schema"));
+    let admin_token = std::env::var("ADMIN_TOKEN")
+        .ok()
+        .map(AdminToken)
+        .map(web::Data::new);
+    let tx_status_client = TxStatusClient::from_env().map(web::Data::new);
+    let response_cache = ResponseCache::from_env().await.map(web::Data::new);
+
+    // Rotated database credentials and changed limits are picked up on
+    // SIGHUP. Other settings, like addresses and TLS, need a restart.
+    let reload_state = state.clone();
+    let reload_rate_limiter = rate_limiter.clone();
+    let reload_response_cache = response_cache.clone();
+    tokio::spawn(async move {
+        let mut hangup = signal(SignalKind::hangup()).expect("Failed to listen for SIGHUP");
+        while hangup.recv().await.is_some() {
+            dotenvy::dotenv_override().ok();
+            if let Err(err) = config::reload() {
+                log::error!("Failed to reload the config, keeping the previous one: {err}");
+                continue;
+            }
+            // Invalid values panic like at startup, which only stops this reload
+            let (state, rate_limiter, response_cache) = (
+                reload_state.clone(),
+                reload_rate_limiter.clone(),
+                reload_response_cache.clone(),
+            );
+            let reloaded = tokio::spawn(async move {
+                state.set_block_limits(BlockLimits::from_env());
+                rate_limiter.reload();
+                if let Some(response_cache) = response_cache {
+                    response_cache.reload();
+                }
+                match state.reconnect().await {
+                    Ok(()) => log::info!("Reconnected to Postgres"),
+                    Err(err) => log::error!("Failed to reconnect to Postgres: {err}"),
+                }
+            })
+            .await;
+            match reloaded {
+                Ok(()) => log::info!("Reloaded the config"),
+                Err(_) => log::error!("Failed to apply the reloaded config"),
+            }
+        }
+    });
+
+    if let Ok(address) = std::env::var("GRPC_BIND_ADDRESS") {
+        let address = address
+            .parse()
+            .expect("GRPC_BIND_ADDRESS must be an address like 0.0.0.0:50051");
+        let grpc_state = state.clone();
+        tokio::spawn(async move {
+            if let Err(err) = grpc::serve(grpc_state, address).await {
+                log::error!("gRPC server failed: {err}");
+            }
+        });
+    }
 
+    let cors_config = CorsConfig::from_env();
+
+    // The pool is closed after the server stops
+    let shutdown_state = state.clone();
     let server = HttpServer::new(move || {
-        let cors = Cors::default()
-            .allow_any_origin()
-            .allowed_methods(vec!["GET"])
-            .max_age(3600)
-            .supports_credentials();
+        let cors = cors_config.cors();
+
+        let ft = web::scope("/ft")
+            .service(ft_events::ft_mint)
+            .service(ft_events::ft_transfer)
+            .service(ft_events::ft_burn);
 
         let nft = web::scope("/nft")
             .service(nft_events::nft_mint)
             .service(nft_events::nft_transfer)
-            .service(nft_events::nft_burn);
+            .service(nft_events::nft_burn)
+            .service(nft_events::nft_sale)
+            .service(nft_events::nft_transfer_velocity);
 
         let potlock = web::scope("/potlock")
             .service(potlock_events::potlock_donation)
             .service(potlock_events::potlock_pot_project_donation)
-            .service(potlock_events::potlock_pot_donation);
+            .service(potlock_events::potlock_pot_donation)
+            .service(potlock_events::potlock_pot_deployment)
+            .service(potlock_events::potlock_pot_application)
+            .service(potlock_events::potlock_pot_application_review)
+            .service(potlock_events::potlock_pot_payout)
+            .service(potlock_events::potlock_pot_payout_challenge)
+            .service(potlock_events::potlock_referrer_earnings)
+            .service(potlock_events::potlock_donor_retention)
+            .service(potlock_events::potlock_leaderboard)
+            .service(potlock_events::potlock_project_summary);
 
         let trade = web::scope("/trade")
             .service(trade_events::trade_pool)
             .service(trade_events::trade_swap)
-            .service(trade_events::trade_pool_change);
+            .service(trade_events::trade_pool_change)
+            .service(trade_events::trade_liquidity_add)
+            .service(trade_events::trade_liquidity_remove)
+            .service(trade_events::trade_wrap)
+            .service(trade_events::trade_unwrap)
+            .service(trade_events::trade_volume)
+            .service(trade_events::trade_candles)
+            .service(trade_events::trade_pool_at);
+
+        let socialdb = web::scope("/socialdb")
+            .service(socialdb_events::socialdb_post)
+            .service(socialdb_events::socialdb_comment)
+            .service(socialdb_events::socialdb_like)
+            .service(socialdb_events::socialdb_follow)
+            .service(socialdb_events::socialdb_profile_update);
+
+        let price = web::scope("/price").service(price_events::price_change);
+
+        let burrow = web::scope("/burrow")
+            .service(burrow_events::burrow_deposit)
+            .service(burrow_events::burrow_withdraw)
+            .service(burrow_events::burrow_borrow)
+            .service(burrow_events::burrow_repay)
+            .service(burrow_events::burrow_liquidation);
+
+        let launch = web::scope("/launch")
+            .service(launch_events::launch_create)
+            .service(launch_events::launch_deposit)
+            .service(launch_events::launch_withdraw)
+            .service(launch_events::launch_finalize);
 
-        let api_v0 = web::scope("/v0")
+        let staking = web::scope("/staking")
+            .service(staking_events::staking_stake)
+            .service(staking_events::staking_unstake)
+            .service(staking_events::staking_withdraw);
+
+        let account = web::scope("/account")
+            .service(account_events::account_create)
+            .service(account_events::account_delete)
+            .service(account_events::key_added)
+            .service(account_events::key_deleted);
+
+        let dao = web::scope("/dao")
+            .service(dao_events::dao_proposal)
+            .service(dao_events::dao_vote)
+            .service(dao_events::dao_proposal_execute);
+
+        let bridge = web::scope("/bridge")
+            .service(bridge_events::bridge_deposit)
+            .service(bridge_events::bridge_withdraw);
+
+        let raw = web::scope("/raw").service(raw_events::log_events);
+
+        // Counts are matched before the scopes of their endpoints, which would
+        // otherwise respond with 404
+        let mut api_v0 = web::scope("/v0")
+            .service(count::count)
+            .service(ft)
             .service(nft)
             .service(potlock)
-            .service(trade);
-
-        let state = AppState {
-            pg_pool: pg_pool.clone(),
-        };
+            .service(trade)
+            .service(socialdb)
+            .service(price)
+            .service(burrow)
+            .service(launch)
+            .service(staking)
+            .service(account)
+            .service(dao)
+            .service(bridge)
+            .service(raw)
+            .service(meta::scope())
+            .service(correlated::correlated_events)
+            .service(lookup::transaction)
+            .service(lookup::receipt)
+            .service(lookup::account_activity)
+            .service(batch::batch_query)
+            .service(graphql::graphql)
+            .service(graphql::graphiql)
+            .service(json_schema::event_type_schema)
+            .service(ws::ws)
+            .service(sse::sse);
+        if mock_mode {
+            api_v0 = api_v0.service(fixtures::scope());
+        }
+        if digests_enabled {
+            api_v0 = api_v0.service(digests::scope());
+        }
+        if webhooks_enabled {
+            api_v0 = api_v0.service(webhooks::scope());
+        }
 
-        App::new()
-            .app_data(web::Data::new(state))
+        let mut app = App::new()
+            .app_data(state.clone())
+            .app_data(web::Data::new(request_limits))
+            .app_data(endpoint_toggles.clone())
+            .app_data(rate_limiter.clone())
+            .app_data(response_compression.clone())
+            .app_data(graphql_schema.clone())
+            .app_data(request_limits.payload_config())
+            .app_data(request_limits.json_config())
+            .app_data(error::query_config())
             .service(api_v0)
+            .service(web::scope("/v1").service(v1::events))
+            .service(explorer::explorer)
+            .service(openapi::swagger_ui())
+            .service(health::healthz)
+            .service(health::readyz);
+        if let Some(admin_token) = &admin_token {
+            app = app.app_data(admin_token.clone()).service(admin::scope());
+        }
+        if let Some(tx_status_client) = &tx_status_client {
+            app = app.app_data(tx_status_client.clone());
+        }
+        if let Some(response_cache) = &response_cache {
+            app = app.app_data(response_cache.clone());
+        }
+
+        // Formats requested with `Accept` are cached under the same key as
+        // with `format`. Cached responses are built without calling the
+        // handler, so headers that all responses have are added outside.
+        app.wrap(middleware::from_fn(cache::cache_responses))
+            .wrap(middleware::from_fn(limits::max_blocks_header))
+            .wrap(middleware::from_fn(cursor::negotiate_format))
+            .wrap(middleware::from_fn(strict_query::reject_unknown_params))
+            .wrap(middleware::from_fn(strict_query::resolve_aliases))
+            .wrap(middleware::from_fn(toggles::reject_disabled))
+            .wrap(middleware::from_fn(catch_panic))
+            .wrap(middleware::from_fn(limits::enforce_limits))
+            .wrap(middleware::from_fn(rate_limit::enforce_rate_limit))
             .wrap(cors)
-            .wrap(middleware::Logger::new(
-                "%{r}a %a \"%r\"	Code: %s Size: %b bytes \"%{Referer}i\" \"%{User-Agent}i\" %T",
+            .wrap(middleware::from_fn(compression::compress_responses))
+            .wrap(middleware::Condition::new(
+                log_format == LogFormat::Text,
+                middleware::Logger::new(
+                    "%{r}a %a \"%r\"	Code: %s Size: %b bytes \"%{Referer}i\" \"%{User-Agent}i\" %T %{request_id}xi",
+                )
+                .custom_request_replace("request_id", |req| {
+                    request_id::of(req)
+                        .map(|id| id.to_string())
+                        .unwrap_or_default()
+                }),
             ))
+            .wrap(middleware::Condition::new(
+                log_format == LogFormat::Json,
+                middleware::from_fn(access_log::log_request),
+            ))
+            // So the span covers the whole request and the access log is
+            // written inside it
+            .wrap(middleware::from_fn(error_reporting::scope_errors))
+            .wrap(TracingLogger::<RequestIdSpan>::new())
+            .wrap(middleware::from_fn(request_id::assign_request_id))
     });
 
-    let server = if let Some(tls_config) = tls_config {
-        server.bind_rustls_0_22(
-            std::env::var("BIND_ADDRESS").unwrap_or("0.0.0.0:8080".to_string()),
-            tls_config,
-        )?
-    } else {
-        server.bind(std::env::var("BIND_ADDRESS").unwrap_or("0.0.0.0:8080".to_string()))?
-    };
-
-    server.run().await
-}
+    // Comma-separated list of addresses. Each one is served over TLS if `SSL`
+    // is set, unless it's explicitly prefixed with `http://`, or is a unix
+    // socket prefixed with `unix:`.
+    let bind_addresses = std::env::var("BIND_ADDRESS").unwrap_or("0.0.0.0:8080".to_string());
+    let unix_socket_mode = std::env::var("UNIX_SOCKET_MODE").ok().map(|mode| {
+        u32::from_str_radix(&mode, 8).expect("UNIX_SOCKET_MODE must be an octal file mode like 660")
+    });
+    let mut server = server;
+    for address in bind_addresses.split(',').map(str::trim) {
+        server = if let Some(path) = address.strip_prefix("unix:") {
+            let server = server.bind_uds(path)?;
+            if let Some(mode) = unix_socket_mode {
+                std::fs::set_permissions(path, std::fs::Permissions::from_mode(mode))?;
+            }
+            server
+        } else if let Some(address) = address.strip_prefix("http://") {
+            server.bind(address)?
+        } else if let Some(address) = address.strip_prefix("https://") {
+            let tls_config = tls_config
+                .clone()
+                .expect("SSL or ACME_DOMAIN must be set to bind https:// addresses");
+            server.bind_rustls_0_22(address, tls_config)?
+        } else if let Some(tls_config) = &tls_config {
+            server.bind_rustls_0_22(address, tls_config.clone())?
+        } else {
+            server.bind(address)?
+        };
+    }
 
-#[derive(Deserialize)]
-struct PaginationInfo {
-    #[serde(default)]
-    start_block_timestamp_nanosec: i64,
-    #[serde(default = "default_blocks_per_request")]
-    blocks: i64,
-}
+    // Signals are handled here instead of by actix, which stops immediately
+    // on SIGINT. On SIGTERM or SIGINT, new connections are refused, and
+    // in-flight requests get `SHUTDOWN_TIMEOUT_SECS` to finish.
+    let shutdown_timeout = std::env::var("SHUTDOWN_TIMEOUT_SECS").map_or(30, |value| {
+        value
+            .parse()
+            .expect("SHUTDOWN_TIMEOUT_SECS must be a number of seconds")
+    });
+    let server = server
+        .shutdown_timeout(shutdown_timeout)
+        .disable_signals()
+        .run();
+    let server_handle = server.handle();
+    tokio::spawn(async move {
+        let mut terminate = signal(SignalKind::terminate()).expect("Failed to listen for SIGTERM");
+        let mut interrupt = signal(SignalKind::interrupt()).expect("Failed to listen for SIGINT");
+        tokio::select! {
+            _ = terminate.recv() => {}
+            _ = interrupt.recv() => {}
+        }
+        log::info!("Shutting down, waiting up to {shutdown_timeout}s for in-flight requests");
+        server_handle.stop(true).await;
+    });
+    server.await?;
 
-fn default_blocks_per_request() -> i64 {
-    10
+    shutdown_state.close().await;
+    log::info!("Closed database connection pools");
+    telemetry::shutdown();
+    Ok(())
 }
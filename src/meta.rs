@@ -0,0 +1,470 @@
+use std::collections::BTreeMap;
+
+use actix_web::{get, web, HttpResponse, Responder, Scope};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use utoipa::{IntoParams, ToSchema};
+
+use crate::{
+    error::{ApiError, ErrorResponse},
+    error_reporting, AppState,
+};
+
+const MAX_GAP_RANGE_BLOCKS: i64 = 1_000_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum EventTable {
+    FtMint,
+    FtTransfer,
+    FtBurn,
+    NftMint,
+    NftTransfer,
+    NftBurn,
+    NftSale,
+    PotlockDonation,
+    PotlockPotProjectDonation,
+    PotlockPotDonation,
+    PotlockPotDeployment,
+    PotlockPotApplication,
+    PotlockPotApplicationReview,
+    PotlockPotPayout,
+    PotlockPotPayoutChallenge,
+    SocialdbPost,
+    SocialdbComment,
+    SocialdbLike,
+    SocialdbFollow,
+    SocialdbProfileUpdate,
+    PriceChange,
+    TradePool,
+    TradeSwap,
+    TradePoolChange,
+    TradeLiquidityAdd,
+    TradeLiquidityRemove,
+    TradeWrap,
+    TradeUnwrap,
+    BurrowDeposit,
+    BurrowWithdraw,
+    BurrowBorrow,
+    BurrowRepay,
+    BurrowLiquidation,
+    LaunchCreate,
+    LaunchDeposit,
+    LaunchWithdraw,
+    LaunchFinalize,
+    StakingStake,
+    StakingUnstake,
+    StakingWithdraw,
+    AccountCreate,
+    AccountDelete,
+    KeyAdded,
+    KeyDeleted,
+    DaoProposal,
+    DaoVote,
+    DaoProposalExecute,
+    BridgeDeposit,
+    BridgeWithdraw,
+    LogEvents,
+}
+
+impl EventTable {
+    pub const ALL: [EventTable; 50] = [
+        EventTable::FtMint,
+        EventTable::FtTransfer,
+        EventTable::FtBurn,
+        EventTable::NftMint,
+        EventTable::NftTransfer,
+        EventTable::NftBurn,
+        EventTable::NftSale,
+        EventTable::PotlockDonation,
+        EventTable::PotlockPotProjectDonation,
+        EventTable::PotlockPotDonation,
+        EventTable::PotlockPotDeployment,
+        EventTable::PotlockPotApplication,
+        EventTable::PotlockPotApplicationReview,
+        EventTable::PotlockPotPayout,
+        EventTable::PotlockPotPayoutChallenge,
+        EventTable::SocialdbPost,
+        EventTable::SocialdbComment,
+        EventTable::SocialdbLike,
+        EventTable::SocialdbFollow,
+        EventTable::SocialdbProfileUpdate,
+        EventTable::PriceChange,
+        EventTable::TradePool,
+        EventTable::TradeSwap,
+        EventTable::TradePoolChange,
+        EventTable::TradeLiquidityAdd,
+        EventTable::TradeLiquidityRemove,
+        EventTable::TradeWrap,
+        EventTable::TradeUnwrap,
+        EventTable::BurrowDeposit,
+        EventTable::BurrowWithdraw,
+        EventTable::BurrowBorrow,
+        EventTable::BurrowRepay,
+        EventTable::BurrowLiquidation,
+        EventTable::LaunchCreate,
+        EventTable::LaunchDeposit,
+        EventTable::LaunchWithdraw,
+        EventTable::LaunchFinalize,
+        EventTable::StakingStake,
+        EventTable::StakingUnstake,
+        EventTable::StakingWithdraw,
+        EventTable::AccountCreate,
+        EventTable::AccountDelete,
+        EventTable::KeyAdded,
+        EventTable::KeyDeleted,
+        EventTable::DaoProposal,
+        EventTable::DaoVote,
+        EventTable::DaoProposalExecute,
+        EventTable::BridgeDeposit,
+        EventTable::BridgeWithdraw,
+        EventTable::LogEvents,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EventTable::FtMint => "ft_mint",
+            EventTable::FtTransfer => "ft_transfer",
+            EventTable::FtBurn => "ft_burn",
+            EventTable::NftMint => "nft_mint",
+            EventTable::NftTransfer => "nft_transfer",
+            EventTable::NftBurn => "nft_burn",
+            EventTable::NftSale => "nft_sale",
+            EventTable::PotlockDonation => "potlock_donation",
+            EventTable::PotlockPotProjectDonation => "potlock_pot_project_donation",
+            EventTable::PotlockPotDonation => "potlock_pot_donation",
+            EventTable::PotlockPotDeployment => "potlock_pot_deployment",
+            EventTable::PotlockPotApplication => "potlock_pot_application",
+            EventTable::PotlockPotApplicationReview => "potlock_pot_application_review",
+            EventTable::PotlockPotPayout => "potlock_pot_payout",
+            EventTable::PotlockPotPayoutChallenge => "potlock_pot_payout_challenge",
+            EventTable::SocialdbPost => "socialdb_post",
+            EventTable::SocialdbComment => "socialdb_comment",
+            EventTable::SocialdbLike => "socialdb_like",
+            EventTable::SocialdbFollow => "socialdb_follow",
+            EventTable::SocialdbProfileUpdate => "socialdb_profile_update",
+            EventTable::PriceChange => "price_change",
+            EventTable::TradePool => "trade_pool",
+            EventTable::TradeSwap => "trade_swap",
+            EventTable::TradePoolChange => "trade_pool_change",
+            EventTable::TradeLiquidityAdd => "trade_liquidity_add",
+            EventTable::TradeLiquidityRemove => "trade_liquidity_remove",
+            EventTable::TradeWrap => "trade_wrap",
+            EventTable::TradeUnwrap => "trade_unwrap",
+            EventTable::BurrowDeposit => "burrow_deposit",
+            EventTable::BurrowWithdraw => "burrow_withdraw",
+            EventTable::BurrowBorrow => "burrow_borrow",
+            EventTable::BurrowRepay => "burrow_repay",
+            EventTable::BurrowLiquidation => "burrow_liquidation",
+            EventTable::LaunchCreate => "launch_create",
+            EventTable::LaunchDeposit => "launch_deposit",
+            EventTable::LaunchWithdraw => "launch_withdraw",
+            EventTable::LaunchFinalize => "launch_finalize",
+            EventTable::StakingStake => "staking_stake",
+            EventTable::StakingUnstake => "staking_unstake",
+            EventTable::StakingWithdraw => "staking_withdraw",
+            EventTable::AccountCreate => "account_create",
+            EventTable::AccountDelete => "account_delete",
+            EventTable::KeyAdded => "key_added",
+            EventTable::KeyDeleted => "key_deleted",
+            EventTable::DaoProposal => "dao_proposal",
+            EventTable::DaoVote => "dao_vote",
+            EventTable::DaoProposalExecute => "dao_proposal_execute",
+            EventTable::BridgeDeposit => "bridge_deposit",
+            EventTable::BridgeWithdraw => "bridge_withdraw",
+            EventTable::LogEvents => "log_events",
+        }
+    }
+
+    /// Pool changes and prices aren't caused by a single transaction, so they
+    /// can't be looked up or grouped by one
+    pub fn has_transaction_id(&self) -> bool {
+        !matches!(self, EventTable::TradePoolChange | EventTable::PriceChange)
+    }
+
+    /// Columns with account ids of the parties of an event, e.g. the sender
+    /// and the receiver of a transfer. Contracts the event happened on, like
+    /// tokens and pots, aren't parties, so that their activity isn't every
+    /// event on them.
+    pub fn account_columns(&self) -> &'static [&'static str] {
+        match self {
+            EventTable::FtMint | EventTable::FtBurn => &["owner_id"],
+            EventTable::FtTransfer => &["old_owner_id", "new_owner_id"],
+            EventTable::NftMint | EventTable::NftBurn => &["owner_id"],
+            EventTable::NftTransfer => &["old_owner_id", "new_owner_id"],
+            EventTable::PotlockDonation => &["donor_id", "project_id", "referrer_id"],
+            EventTable::PotlockPotProjectDonation => {
+                &["donor_id", "project_id", "referrer_id", "chef_id"]
+            }
+            EventTable::PotlockPotDonation => &["donor_id", "referrer_id", "chef_id"],
+            EventTable::PotlockPotDeployment => &["deployer_id", "owner_id", "chef_id"],
+            EventTable::PotlockPotApplication => &["project_id"],
+            EventTable::PotlockPotApplicationReview => &["project_id", "reviewer_id"],
+            EventTable::PotlockPotPayout => &["project_id", "admin_id"],
+            EventTable::PotlockPotPayoutChallenge => &["challenger_id"],
+            EventTable::SocialdbPost | EventTable::SocialdbProfileUpdate => &["account_id"],
+            EventTable::SocialdbComment | EventTable::SocialdbLike | EventTable::SocialdbFollow => {
+                &["account_id", "parent_account_id"]
+            }
+            EventTable::TradePool | EventTable::TradeSwap => &["trader"],
+            EventTable::PriceChange | EventTable::TradePoolChange => &[],
+            EventTable::BurrowDeposit
+            | EventTable::BurrowWithdraw
+            | EventTable::BurrowBorrow
+            | EventTable::BurrowRepay => &["account_id"],
+            EventTable::BurrowLiquidation => &["account_id", "liquidator_id"],
+            EventTable::LaunchCreate => &["owner_id"],
+            EventTable::LaunchDeposit => &["account_id", "referrer_id"],
+            EventTable::LaunchWithdraw => &["account_id"],
+            EventTable::LaunchFinalize => &[],
+            EventTable::StakingStake | EventTable::StakingUnstake | EventTable::StakingWithdraw => {
+                &["delegator_id"]
+            }
+            EventTable::AccountCreate => &["account_id", "creator_id"],
+            EventTable::AccountDelete => &["account_id", "beneficiary_id"],
+            EventTable::KeyAdded => &["account_id"],
+            EventTable::KeyDeleted => &["account_id"],
+            EventTable::DaoProposal => &["proposer"],
+            EventTable::DaoVote => &["account_id"],
+            EventTable::DaoProposalExecute => &["proposer", "account_id"],
+            EventTable::NftSale => &["seller_id", "buyer_id"],
+            EventTable::TradeLiquidityAdd => &["account_id"],
+            EventTable::TradeLiquidityRemove => &["account_id"],
+            EventTable::TradeWrap => &["account_id"],
+            EventTable::TradeUnwrap => &["account_id"],
+            EventTable::BridgeDeposit => &["account_id"],
+            EventTable::BridgeWithdraw => &["account_id"],
+            EventTable::LogEvents => &["contract_id"],
+        }
+    }
+}
+
+pub fn scope() -> Scope {
+    web::scope("/meta")
+        .service(gaps)
+        .service(freshness)
+        .service(last_block)
+}
+
+#[derive(Debug, Serialize, sqlx::FromRow, ToSchema)]
+pub struct Gap {
+    pub start_block_height: i64,
+    pub end_block_height: i64,
+    pub missing_blocks: i64,
+}
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+struct GapsQuery {
+    #[param(inline)]
+    table: EventTable,
+    /// First block height of the range
+    from: i64,
+    /// Last block height of the range, at most 1000000 blocks after `from`
+    to: i64,
+    #[serde(default = "default_min_gap_blocks")]
+    #[param(default = 1000, minimum = 1)]
+    min_gap_blocks: i64,
+}
+
+fn default_min_gap_blocks() -> i64 {
+    1000
+}
+
+/// Ranges of at least `min_gap_blocks` consecutive blocks without a single
+/// event in the table. On busy tables, these are likely indexer outages.
+#[utoipa::path(
+    get,
+    path = "/v0/meta/gaps",
+    tag = "Meta",
+    params(GapsQuery),
+    responses(
+        (status = 200, body = Vec<Gap>),
+        (status = 400, description = "The range is empty or too long"),
+    )
+)]
+#[get("/gaps")]
+pub async fn gaps(state: web::Data<AppState>, query: web::Query<GapsQuery>) -> impl Responder {
+    if query.to < query.from || query.to - query.from > MAX_GAP_RANGE_BLOCKS {
+        return HttpResponse::BadRequest().body(format!(
+            "to must be after from, and the range must be at most {MAX_GAP_RANGE_BLOCKS} blocks"
+        ));
+    }
+    if query.min_gap_blocks < 1 {
+        return HttpResponse::BadRequest().body("min_gap_blocks must be positive");
+    }
+
+    // The table name comes from `EventTable`, so it's safe to format it in.
+    // Heights just outside the range are added so that gaps at the edges of
+    // the range are reported too.
+    let sql = format!(
+        r#"
+        WITH heights AS (
+            SELECT DISTINCT block_height
+            FROM {}
+            WHERE block_height BETWEEN $1 AND $2
+            UNION SELECT $1 - 1
+            UNION SELECT $2 + 1
+        ),
+        neighbors AS (
+            SELECT block_height, LAG(block_height) OVER (ORDER BY block_height) as previous
+            FROM heights
+        )
+        SELECT previous + 1 as start_block_height,
+            block_height - 1 as end_block_height,
+            block_height - previous - 1 as missing_blocks
+        FROM neighbors
+        WHERE block_height - previous - 1 >= $3
+        ORDER BY start_block_height ASC
+        "#,
+        query.table.as_str()
+    );
+    match sqlx::query_as::<_, Gap>(&sql)
+        .bind(query.from)
+        .bind(query.to)
+        .bind(query.min_gap_blocks)
+        .fetch_all(&state.pg_pool())
+        .await
+    {
+        Ok(res) => HttpResponse::Ok().json(res),
+        Err(err) => {
+            error_reporting::report_query_error(&err);
+            HttpResponse::InternalServerError().finish()
+        }
+    }
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct Freshness {
+    pub table: EventTable,
+    /// `None` if the table is empty
+    pub latest_block_height: Option<i64>,
+    #[serde(
+        with = "chrono::serde::ts_nanoseconds_option",
+        rename = "latest_block_timestamp_nanosec"
+    )]
+    #[schema(value_type = Option<i64>)]
+    pub latest_timestamp: Option<DateTime<Utc>>,
+    /// Time since the latest event. Tables with frequent events should stay
+    /// within seconds of the chain, a growing lag means the indexer is behind.
+    pub lag_nanosec: Option<i64>,
+}
+
+#[derive(sqlx::FromRow)]
+struct LatestEvent {
+    table_name: String,
+    block_height: i64,
+    timestamp: DateTime<Utc>,
+}
+
+/// Latest event of each table, empty tables are missing
+async fn latest_events(pg_pool: &PgPool) -> Result<Vec<LatestEvent>, sqlx::Error> {
+    let sql = EventTable::ALL
+        .iter()
+        .map(|table| {
+            format!(
+                "(SELECT '{table}' as table_name, block_height, timestamp FROM {table} ORDER BY timestamp DESC LIMIT 1)",
+                table = table.as_str()
+            )
+        })
+        .collect::<Vec<_>>()
+        .join(" UNION ALL ");
+    sqlx::query_as::<_, LatestEvent>(&sql)
+        .fetch_all(pg_pool)
+        .await
+}
+
+/// Latest event of each table, and how far behind the chain it is
+#[utoipa::path(
+    get,
+    path = "/v0/meta/freshness",
+    tag = "Meta",
+    responses((status = 200, body = Vec<Freshness>))
+)]
+#[get("/freshness")]
+pub async fn freshness(state: web::Data<AppState>) -> impl Responder {
+    let res = match latest_events(&state.pg_pool()).await {
+        Ok(res) => res,
+        Err(err) => {
+            error_reporting::report_query_error(&err);
+            return HttpResponse::InternalServerError().finish();
+        }
+    };
+    let now = Utc::now();
+    let freshness = EventTable::ALL
+        .into_iter()
+        .map(|table| {
+            let latest = res.iter().find(|row| row.table_name == table.as_str());
+            Freshness {
+                table,
+                latest_block_height: latest.map(|row| row.block_height),
+                latest_timestamp: latest.map(|row| row.timestamp),
+                lag_nanosec: latest.and_then(|row| (now - row.timestamp).num_nanoseconds()),
+            }
+        })
+        .collect::<Vec<_>>();
+    HttpResponse::Ok().json(freshness)
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct LastBlock {
+    /// `None` if all tables are empty
+    pub block_height: Option<i64>,
+    #[serde(
+        with = "chrono::serde::ts_nanoseconds_option",
+        rename = "block_timestamp_nanosec"
+    )]
+    #[schema(value_type = Option<i64>)]
+    pub timestamp: Option<DateTime<Utc>>,
+    /// Latest block of each table, `None` for empty tables
+    #[schema(value_type = Object)]
+    pub tables: BTreeMap<&'static str, Option<TableLastBlock>>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct TableLastBlock {
+    pub block_height: i64,
+    #[serde(
+        with = "chrono::serde::ts_nanoseconds",
+        rename = "block_timestamp_nanosec"
+    )]
+    #[schema(value_type = i64)]
+    pub timestamp: DateTime<Utc>,
+}
+
+/// Latest block the indexer has written events for. Events are written block
+/// by block, so once a request covers this block, there's nothing more to
+/// fetch until it changes.
+#[utoipa::path(
+    get,
+    path = "/v0/meta/last_block",
+    tag = "Meta",
+    responses(
+        (status = 200, body = LastBlock),
+        (status = 500, body = ErrorResponse),
+    )
+)]
+#[get("/last_block")]
+pub async fn last_block(state: web::Data<AppState>) -> Result<HttpResponse, ApiError> {
+    let res = latest_events(&state.pg_pool()).await?;
+    let latest = res.iter().max_by_key(|row| row.timestamp);
+    let tables = EventTable::ALL
+        .into_iter()
+        .map(|table| {
+            let latest = res.iter().find(|row| row.table_name == table.as_str());
+            (
+                table.as_str(),
+                latest.map(|row| TableLastBlock {
+                    block_height: row.block_height,
+                    timestamp: row.timestamp,
+                }),
+            )
+        })
+        .collect();
+    Ok(HttpResponse::Ok().json(LastBlock {
+        block_height: latest.map(|row| row.block_height),
+        timestamp: latest.map(|row| row.timestamp),
+        tables,
+    }))
+}
@@ -0,0 +1,120 @@
+use actix_web::{web::Bytes, HttpResponse};
+use chrono::{DateTime, Utc};
+use futures_util::{stream, Stream, StreamExt};
+use serde::Serialize;
+use sqlx::{
+    postgres::{PgArguments, PgRow},
+    FromRow, PgPool,
+};
+use tokio::sync::mpsc;
+
+use crate::{error::ApiError, error_reporting, limit_exceeded, PaginationInfo};
+
+pub(crate) const CONTENT_TYPE: &str = "application/x-ndjson";
+
+/// Lines read ahead of a slow client, so that a whole page isn't buffered
+const BUFFERED_LINES: usize = 64;
+
+/// Streams events as they're read from Postgres, one JSON object per line,
+/// so that large pages aren't buffered in memory. The status is sent with the
+/// first line, so a database error midway aborts the response instead.
+///
+/// Like other formats, the response ends before a block that doesn't fit in
+/// `limit`, so events of a block are held back until the next block starts.
+pub(crate) async fn respond<T>(
+    sql: String,
+    arguments: PgArguments,
+    pg_pool: PgPool,
+    pagination: &PaginationInfo,
+    timestamp: fn(&T) -> DateTime<Utc>,
+) -> Result<HttpResponse, ApiError>
+where
+    T: for<'r> FromRow<'r, PgRow> + Serialize + Send + Unpin + 'static,
+{
+    let (limit, sorted_by_block) = (pagination.limit, pagination.sorted_by_block());
+    let (sender, receiver) = mpsc::channel(BUFFERED_LINES);
+    tokio::spawn(async move {
+        let rows = sqlx::query_as_with::<_, T, _>(&sql, arguments).fetch(&pg_pool);
+        send_lines(rows, sender, limit, sorted_by_block, timestamp).await;
+    });
+    stream_lines(receiver).await
+}
+
+async fn send_lines<T: Serialize>(
+    mut rows: impl Stream<Item = Result<T, sqlx::Error>> + Unpin,
+    sender: mpsc::Sender<Result<Bytes, ApiError>>,
+    limit: i64,
+    sorted_by_block: bool,
+    timestamp: fn(&T) -> DateTime<Utc>,
+) {
+    // Events that aren't sorted by block are all held back, since any
+    // of them can be in the same block as the one that doesn't fit
+    let mut block = Vec::new();
+    let mut block_timestamp = None;
+    let mut sent = 0;
+    while let Some(row) = rows.next().await {
+        let event = match row {
+            Ok(event) => event,
+            Err(err) => {
+                let _ = sender.send(Err(ApiError::Database(err))).await;
+                return;
+            }
+        };
+        let event_block = sorted_by_block.then(|| timestamp(&event));
+        if block_timestamp != Some(event_block) {
+            for line in block.drain(..) {
+                // Stops the query if the client has disconnected
+                if sender.send(Ok(line)).await.is_err() {
+                    return;
+                }
+                sent += 1;
+            }
+            block_timestamp = Some(event_block);
+        }
+        if sent + block.len() as i64 == limit {
+            if sent == 0 {
+                let block_timestamp_nanosec =
+                    event_block.and_then(|timestamp| timestamp.timestamp_nanos_opt());
+                let _ = sender
+                    .send(Err(limit_exceeded(limit, block_timestamp_nanosec)))
+                    .await;
+            }
+            return;
+        }
+        block.push(line(&event));
+    }
+    for line in block {
+        if sender.send(Ok(line)).await.is_err() {
+            return;
+        }
+    }
+}
+
+async fn stream_lines(
+    mut receiver: mpsc::Receiver<Result<Bytes, ApiError>>,
+) -> Result<HttpResponse, ApiError> {
+    // Errors before the first line get a proper error response
+    let first = match receiver.recv().await {
+        Some(Err(err)) => return Err(err),
+        first => first,
+    };
+    let lines = stream::iter(first).chain(stream::unfold(receiver, |mut receiver| async move {
+        Some((receiver.recv().await?, receiver))
+    }));
+    Ok(HttpResponse::Ok()
+        .content_type(CONTENT_TYPE)
+        .streaming(lines.map(|line| {
+            line.map_err(|err| {
+                if let ApiError::Database(err) = &err {
+                    error_reporting::report_query_error(err);
+                }
+                actix_web::error::ErrorInternalServerError("Database error")
+            })
+        })))
+}
+
+pub(crate) fn line(event: &impl Serialize) -> Bytes {
+    let mut line = serde_json::to_vec(event).unwrap();
+    line.push(b'\n');
+    line.into()
+}
@@ -1,218 +1,291 @@
-use actix_web::{get, web, HttpResponse, Responder};
+use actix_web::{get, web, HttpResponse};
 use chrono::prelude::{DateTime, Utc};
+pub use inteat_events_api_types::nft::{
+    NftBurnEvent, NftBurnFilter, NftMintEvent, NftMintFilter, NftSaleEvent, NftSaleFilter,
+    NftTransferEvent, NftTransferFilter,
+};
 use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
 
-use crate::utils::VecBalance;
-use crate::{AppState, PaginationInfo, MAX_BLOCKS_PER_REQUEST};
+use crate::endpoint::{event_endpoint, EventEndpoint};
+use crate::error::{ApiError, ErrorResponse};
+use crate::event_query::EventQuery;
+use crate::meta::EventTable;
+use crate::utils::ValidAccountId;
+use crate::{AppState, StatsPeriod, TimeRange};
 
-type TransactionId = String;
-type ReceiptId = String;
-type AccountId = String;
-type NftTokenId = String;
-type BlockHeight = i64;
+impl EventEndpoint for NftMintEvent {
+    const TABLE: EventTable = EventTable::NftMint;
+    const SUM_FIELDS: &'static [(&'static str, &'static str)] =
+        &[("tokens", "cardinality(token_ids)")];
+    type Filter = NftMintFilter;
 
-#[derive(Debug, Serialize, Deserialize)]
-pub struct NftMintEvent {
-    pub owner_id: AccountId,
-    pub token_ids: Vec<NftTokenId>,
-    pub memo: Option<String>,
+    fn query(filter: &NftMintFilter) -> EventQuery<Self> {
+        EventQuery::new(
+            "nft_mint",
+            "owner_id, token_ids, memo, transaction_id, receipt_id, block_height, timestamp, contract_id",
+        )
+        .filter("contract_id = ANY({})", filter.token_account_id.as_deref())
+        .filter("owner_id = ANY({})", filter.account_id.as_deref())
+        .filter("transaction_id = ANY({})", filter.transaction_id.as_deref())
+        .filter("receipt_id = ANY({})", filter.receipt_id.as_deref())
+        .filter("token_ids && {}", filter.token_id.as_deref())
+        .filter(
+            "owner_id IS NULL OR owner_id <> ALL({})",
+            filter.exclude_account_id.as_deref(),
+        )
+        .filter(
+            "contract_id <> ALL({})",
+            filter.exclude_token_account_id.as_deref(),
+        )
+    }
 
-    pub transaction_id: TransactionId,
-    pub receipt_id: ReceiptId,
-    pub block_height: BlockHeight,
-    #[serde(
-        with = "chrono::serde::ts_nanoseconds",
-        rename = "block_timestamp_nanosec"
-    )]
-    pub timestamp: DateTime<Utc>,
-    pub contract_id: String,
+    fn timestamp(&self) -> DateTime<Utc> {
+        self.timestamp
+    }
+
+    fn receipt_id(&self) -> &str {
+        &self.receipt_id
+    }
+
+    fn transaction(&self) -> (&str, &str) {
+        (&self.transaction_id, &self.owner_id)
+    }
 }
 
-#[derive(Debug, Serialize, Deserialize)]
-pub struct NftTransferEvent {
-    pub old_owner_id: AccountId,
-    pub new_owner_id: AccountId,
-    pub token_ids: Vec<NftTokenId>,
-    pub memo: Option<String>,
-    pub token_prices_near: VecBalance,
-
-    pub transaction_id: TransactionId,
-    pub receipt_id: ReceiptId,
-    pub block_height: BlockHeight,
-    #[serde(
-        with = "chrono::serde::ts_nanoseconds",
-        rename = "block_timestamp_nanosec"
-    )]
-    pub timestamp: DateTime<Utc>,
-    pub contract_id: String,
+event_endpoint! {
+    /// NFT mint events
+    nft_mint(NftMintEvent, NftMintFilter),
+    route = "/nft_mint",
+    scope = "/v0/nft",
+    tag = "NFT",
 }
 
-#[derive(Debug, Serialize, Deserialize)]
-pub struct NftBurnEvent {
-    pub owner_id: AccountId,
-    pub token_ids: Vec<NftTokenId>,
-    pub memo: Option<String>,
+impl EventEndpoint for NftTransferEvent {
+    const TABLE: EventTable = EventTable::NftTransfer;
+    const SUM_FIELDS: &'static [(&'static str, &'static str)] = &[
+        ("tokens", "cardinality(token_ids)"),
+        (
+            "token_prices_near",
+            "(SELECT SUM(price) FROM unnest(token_prices_near) as price)",
+        ),
+    ];
+    type Filter = NftTransferFilter;
 
-    pub transaction_id: TransactionId,
-    pub receipt_id: ReceiptId,
-    pub block_height: BlockHeight,
-    #[serde(
-        with = "chrono::serde::ts_nanoseconds",
-        rename = "block_timestamp_nanosec"
-    )]
-    pub timestamp: DateTime<Utc>,
-    pub contract_id: String,
+    fn query(filter: &NftTransferFilter) -> EventQuery<Self> {
+        EventQuery::new(
+            "nft_transfer",
+            "old_owner_id, new_owner_id, token_ids, memo, token_prices_near, transaction_id, receipt_id, block_height, timestamp, contract_id",
+        )
+        .filter("contract_id = ANY({})", filter.token_account_id.as_deref())
+        .filter("old_owner_id = ANY({})", filter.old_owner_id.as_deref())
+        .filter("new_owner_id = ANY({})", filter.new_owner_id.as_deref())
+        .filter(
+            "ARRAY[old_owner_id, new_owner_id] @> {}",
+            filter.involved_account_ids.as_deref(),
+        )
+        .filter("transaction_id = ANY({})", filter.transaction_id.as_deref())
+        .filter("receipt_id = ANY({})", filter.receipt_id.as_deref())
+        .filter("token_ids && {}", filter.token_id.as_deref())
+        .filter(
+            "NOT (ARRAY[old_owner_id, new_owner_id] && {})",
+            filter.exclude_account_id.as_deref(),
+        )
+        .filter(
+            "contract_id <> ALL({})",
+            filter.exclude_token_account_id.as_deref(),
+        )
+    }
+
+    fn timestamp(&self) -> DateTime<Utc> {
+        self.timestamp
+    }
+
+    fn receipt_id(&self) -> &str {
+        &self.receipt_id
+    }
+
+    fn transaction(&self) -> (&str, &str) {
+        (&self.transaction_id, &self.old_owner_id)
+    }
 }
 
-#[derive(Deserialize)]
-struct NftMintFilter {
-    token_account_id: Option<String>,
-    account_id: Option<String>,
+event_endpoint! {
+    /// NFT transfer events
+    nft_transfer(NftTransferEvent, NftTransferFilter),
+    route = "/nft_transfer",
+    scope = "/v0/nft",
+    tag = "NFT",
 }
 
-#[get("/nft_mint")]
-pub async fn nft_mint(
-    state: web::Data<AppState>,
-    pagination: web::Query<PaginationInfo>,
-    filter: web::Query<NftMintFilter>,
-) -> impl Responder {
-    if pagination.blocks > MAX_BLOCKS_PER_REQUEST {
-        return HttpResponse::BadRequest().body(format!(
-            "Blocks per request must be less or equal to {MAX_BLOCKS_PER_REQUEST}"
-        ));
-    }
+impl EventEndpoint for NftBurnEvent {
+    const TABLE: EventTable = EventTable::NftBurn;
+    const SUM_FIELDS: &'static [(&'static str, &'static str)] =
+        &[("tokens", "cardinality(token_ids)")];
+    type Filter = NftBurnFilter;
 
-    if let Ok(res) = sqlx::query_as!(NftMintEvent,
-        r#"
-        WITH blocks AS (
-            SELECT DISTINCT timestamp as t
-            FROM nft_mint
-            WHERE extract(epoch from timestamp) * 1_000_000_000 >= $1
-                AND ($3::TEXT IS NULL OR contract_id = $3)
-                AND ($4::TEXT IS NULL OR owner_id = $4)
-            ORDER BY t
-            LIMIT $2
+    fn query(filter: &NftBurnFilter) -> EventQuery<Self> {
+        EventQuery::new(
+            "nft_burn",
+            "owner_id, token_ids, memo, transaction_id, receipt_id, block_height, timestamp, contract_id",
         )
-        SELECT owner_id, token_ids, memo, transaction_id, receipt_id, block_height, timestamp, contract_id
-        FROM nft_mint
-        INNER JOIN blocks ON timestamp = blocks.t
-        WHERE ($3::TEXT IS NULL OR contract_id = $3)
-            AND ($4::TEXT IS NULL OR owner_id = $4)
-        ORDER BY timestamp ASC
-        "#,
-        pagination.start_block_timestamp_nanosec as i64,
-        pagination.blocks,
-        filter.token_account_id.as_deref(),
-        filter.account_id.as_deref(),
-    ).fetch_all(&state.pg_pool).await {
-        HttpResponse::Ok().json(res)
-    } else {
-        HttpResponse::InternalServerError().finish()
+        .filter("contract_id = ANY({})", filter.token_account_id.as_deref())
+        .filter("owner_id = ANY({})", filter.account_id.as_deref())
+        .filter("transaction_id = ANY({})", filter.transaction_id.as_deref())
+        .filter("receipt_id = ANY({})", filter.receipt_id.as_deref())
+        .filter("token_ids && {}", filter.token_id.as_deref())
+        .filter(
+            "owner_id IS NULL OR owner_id <> ALL({})",
+            filter.exclude_account_id.as_deref(),
+        )
+        .filter(
+            "contract_id <> ALL({})",
+            filter.exclude_token_account_id.as_deref(),
+        )
+    }
+
+    fn timestamp(&self) -> DateTime<Utc> {
+        self.timestamp
+    }
+
+    fn receipt_id(&self) -> &str {
+        &self.receipt_id
+    }
+
+    fn transaction(&self) -> (&str, &str) {
+        (&self.transaction_id, &self.owner_id)
     }
 }
 
-#[derive(Deserialize)]
-struct NftTransferFilter {
-    token_account_id: Option<String>,
-    old_owner_id: Option<String>,
-    new_owner_id: Option<String>,
-    involved_account_ids: Option<String>,
+event_endpoint! {
+    /// NFT burn events
+    nft_burn(NftBurnEvent, NftBurnFilter),
+    route = "/nft_burn",
+    scope = "/v0/nft",
+    tag = "NFT",
 }
 
-#[get("/nft_transfer")]
-pub async fn nft_transfer(
-    state: web::Data<AppState>,
-    pagination: web::Query<PaginationInfo>,
-    filter: web::Query<NftTransferFilter>,
-) -> impl Responder {
-    if pagination.blocks > MAX_BLOCKS_PER_REQUEST {
-        return HttpResponse::BadRequest().body(format!(
-            "Blocks per request must be less or equal to {MAX_BLOCKS_PER_REQUEST}"
-        ));
-    }
-
-    let involved_account_ids = filter
-        .involved_account_ids
-        .as_ref()
-        .map(|s| s.split(',').map(ToOwned::to_owned).collect::<Vec<String>>());
-    if let Ok(res) = sqlx::query_as!(NftTransferEvent,
-        r#"
-        WITH blocks AS (
-            SELECT DISTINCT timestamp as t
-            FROM nft_transfer
-            WHERE extract(epoch from timestamp) * 1_000_000_000 >= $1
-                AND ($3::TEXT IS NULL OR contract_id = $3)
-                AND ($4::TEXT IS NULL OR old_owner_id = $4)
-                AND ($5::TEXT IS NULL OR new_owner_id = $5)
-                AND ($6::TEXT[] IS NULL OR ARRAY[old_owner_id, new_owner_id] @> $6)
-            ORDER BY t
-            LIMIT $2
+impl EventEndpoint for NftSaleEvent {
+    const TABLE: EventTable = EventTable::NftSale;
+    type Filter = NftSaleFilter;
+
+    fn query(filter: &NftSaleFilter) -> EventQuery<Self> {
+        EventQuery::new(
+            "nft_sale",
+            "token_id, seller_id, buyer_id, price, ft_token_id, marketplace_id, transaction_id, receipt_id, block_height, timestamp, contract_id",
+        )
+        .filter("contract_id = ANY({})", filter.contract_id.as_deref())
+        .filter("token_id = ANY({})", filter.token_id.as_deref())
+        .filter("ARRAY[seller_id, buyer_id] && {}", filter.account_id.as_deref())
+        .filter("seller_id = ANY({})", filter.seller_id.as_deref())
+        .filter("buyer_id = ANY({})", filter.buyer_id.as_deref())
+        .filter("marketplace_id = ANY({})", filter.marketplace_id.as_deref())
+        .filter("transaction_id = ANY({})", filter.transaction_id.as_deref())
+        .filter("receipt_id = ANY({})", filter.receipt_id.as_deref())
+        .filter(
+            "NOT (ARRAY[seller_id, buyer_id] && {})",
+            filter.exclude_account_id.as_deref(),
         )
-        SELECT old_owner_id, new_owner_id, token_ids, memo, token_prices_near, transaction_id, receipt_id, block_height, timestamp, contract_id
-        FROM nft_transfer
-        INNER JOIN blocks ON timestamp = blocks.t
-        WHERE ($3::TEXT IS NULL OR contract_id = $3)
-            AND ($4::TEXT IS NULL OR old_owner_id = $4)
-            AND ($5::TEXT IS NULL OR new_owner_id = $5)
-            AND ($6::TEXT IS NULL OR ARRAY[old_owner_id, new_owner_id] @> $6)
-        ORDER BY timestamp ASC
-        "#,
-        pagination.start_block_timestamp_nanosec as i64,
-        pagination.blocks,
-        filter.token_account_id.as_deref(),
-        filter.old_owner_id.as_deref(),
-        filter.new_owner_id.as_deref(),
-        involved_account_ids.as_deref(),
-    ).fetch_all(&state.pg_pool).await {
-        HttpResponse::Ok().json(res)
-    } else {
-        HttpResponse::InternalServerError().finish()
+    }
+
+    fn timestamp(&self) -> DateTime<Utc> {
+        self.timestamp
+    }
+
+    fn receipt_id(&self) -> &str {
+        &self.receipt_id
+    }
+
+    fn transaction(&self) -> (&str, &str) {
+        (&self.transaction_id, &self.buyer_id)
     }
 }
 
-#[derive(Deserialize)]
-struct NftBurnFilter {
-    token_account_id: Option<String>,
-    account_id: Option<String>,
+event_endpoint! {
+    /// NFT marketplace sale events
+    nft_sale(NftSaleEvent, NftSaleFilter),
+    route = "/nft_sale",
+    scope = "/v0/nft",
+    tag = "NFT",
 }
 
-#[get("/nft_burn")]
-pub async fn nft_burn(
-    state: web::Data<AppState>,
-    pagination: web::Query<PaginationInfo>,
-    filter: web::Query<NftBurnFilter>,
-) -> impl Responder {
-    if pagination.blocks > MAX_BLOCKS_PER_REQUEST {
-        return HttpResponse::BadRequest().body(format!(
-            "Blocks per request must be less or equal to {MAX_BLOCKS_PER_REQUEST}"
-        ));
-    }
+#[derive(Debug, Serialize, ToSchema)]
+pub struct NftTransferVelocity {
+    #[serde(
+        with = "chrono::serde::ts_nanoseconds",
+        rename = "bucket_start_nanosec"
+    )]
+    #[schema(value_type = i64)]
+    pub bucket: DateTime<Utc>,
+    pub transfers: i64,
+    pub tokens: i64,
+    pub transfers_per_token: f64,
+    /// Average time between a transfer and the previous transfer of the same
+    /// token, `None` if no token in the bucket was transferred before.
+    pub avg_hold_duration_nanosec: Option<i64>,
+}
 
-    if let Ok(res) = sqlx::query_as!(NftBurnEvent,
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+struct NftTransferVelocityFilter {
+    contract_id: ValidAccountId,
+    #[serde(default)]
+    #[param(inline)]
+    bucket: StatsPeriod,
+}
+
+/// Transfers of a collection per day, week, or month, and how long tokens
+/// are held between transfers
+#[utoipa::path(
+    get,
+    path = "/v0/nft/stats/velocity",
+    tag = "NFT",
+    params(TimeRange, NftTransferVelocityFilter),
+    responses(
+        (status = 200, body = Vec<NftTransferVelocity>),
+        (status = 400, body = ErrorResponse),
+        (status = 503, body = ErrorResponse),
+    )
+)]
+#[get("/stats/velocity")]
+pub async fn nft_transfer_velocity(
+    state: web::Data<AppState>,
+    range: web::Query<TimeRange>,
+    filter: web::Query<NftTransferVelocityFilter>,
+) -> Result<HttpResponse, ApiError> {
+    let _permit = state.heavy_queries().try_acquire()?;
+    // Hold durations are computed over the whole history of the contract, so
+    // the first transfer in the range still knows when the token was received.
+    let res = sqlx::query_as!(
+        NftTransferVelocity,
         r#"
-        WITH blocks AS (
-            SELECT DISTINCT timestamp as t
-            FROM nft_burn
-            WHERE extract(epoch from timestamp) * 1_000_000_000 >= $1
-                AND ($3::TEXT IS NULL OR contract_id = $3)
-                AND ($4::TEXT IS NULL OR owner_id = $4)
-            ORDER BY t
-            LIMIT $2
+        WITH transfers AS (
+            SELECT token_id, timestamp
+            FROM nft_transfer, unnest(token_ids) as token_id
+            WHERE contract_id = $1
+        ),
+        holds AS (
+            SELECT token_id, timestamp,
+                timestamp - LAG(timestamp) OVER (PARTITION BY token_id ORDER BY timestamp) as held
+            FROM transfers
         )
-        SELECT owner_id, token_ids, memo, transaction_id, receipt_id, block_height, timestamp, contract_id
-        FROM nft_burn
-        INNER JOIN blocks ON timestamp = blocks.t
-        WHERE ($3::TEXT IS NULL OR contract_id = $3)
-            AND ($4::TEXT IS NULL OR owner_id = $4)
-        ORDER BY timestamp ASC
+        SELECT date_trunc($2, timestamp) as "bucket!",
+            COUNT(*) as "transfers!",
+            COUNT(DISTINCT token_id) as "tokens!",
+            COUNT(*)::DOUBLE PRECISION / COUNT(DISTINCT token_id) as "transfers_per_token!",
+            (AVG(extract(epoch from held)) * 1000000000)::BIGINT as avg_hold_duration_nanosec
+        FROM holds
+        WHERE timestamp >= $3
+            AND ($4::TIMESTAMPTZ IS NULL OR timestamp < $4)
+        GROUP BY 1
+        ORDER BY 1 ASC
         "#,
-        pagination.start_block_timestamp_nanosec as i64,
-        pagination.blocks,
-        filter.token_account_id.as_deref(),
-        filter.account_id.as_deref(),
-    ).fetch_all(&state.pg_pool).await {
-        HttpResponse::Ok().json(res)
-    } else {
-        HttpResponse::InternalServerError().finish()
-    }
+        filter.contract_id.as_str(),
+        filter.bucket.as_str(),
+        range.start(),
+        range.end(),
+    )
+    .fetch_all(&state.pg_pool())
+    .await?;
+    Ok(HttpResponse::Ok().json(res))
 }
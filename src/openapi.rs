@@ -0,0 +1,219 @@
+use serde_json::Value as Json;
+use utoipa::{
+    openapi::security::{Http, HttpAuthScheme, SecurityScheme},
+    Modify, OpenApi,
+};
+use utoipa_swagger_ui::SwaggerUi;
+
+use crate::{
+    account_events, bridge_events, burrow_events, correlated, count, dao_events, digests,
+    ft_events, json_schema, launch_events, lookup,
+    meta::{self, EventTable},
+    nft_events, potlock_events, price_events, raw_events, socialdb_events, sse, staking_events,
+    trade_events,
+    utils::{AnyAccountId, AnyId, AnyOf},
+    v1, webhooks,
+};
+
+/// Endpoints of the HTTP API, except the WebSocket and GraphQL ones. Schemas
+/// of the events and query parameters are derived from the same structs the
+/// endpoints use, so they can't drift apart.
+#[derive(OpenApi)]
+#[openapi(
+    info(
+        title = "Intear Events API",
+        description = "Historical NEAR events indexed by Intear. Live events are available over `/v0/ws` and `/v0/sse/{event_type}`.",
+    ),
+    paths(
+        ft_events::ft_mint,
+        ft_events::ft_transfer,
+        ft_events::ft_burn,
+        nft_events::nft_mint,
+        nft_events::nft_transfer,
+        nft_events::nft_burn,
+        nft_events::nft_sale,
+        nft_events::nft_transfer_velocity,
+        potlock_events::potlock_donation,
+        potlock_events::potlock_pot_project_donation,
+        potlock_events::potlock_pot_donation,
+        potlock_events::potlock_pot_deployment,
+        potlock_events::potlock_pot_application,
+        potlock_events::potlock_pot_application_review,
+        potlock_events::potlock_pot_payout,
+        potlock_events::potlock_pot_payout_challenge,
+        potlock_events::potlock_referrer_earnings,
+        potlock_events::potlock_donor_retention,
+        potlock_events::potlock_leaderboard,
+        potlock_events::potlock_project_summary,
+        trade_events::trade_pool,
+        trade_events::trade_swap,
+        trade_events::trade_pool_change,
+        trade_events::trade_liquidity_add,
+        trade_events::trade_liquidity_remove,
+        trade_events::trade_wrap,
+        trade_events::trade_unwrap,
+        trade_events::trade_volume,
+        trade_events::trade_candles,
+        trade_events::trade_pool_at,
+        socialdb_events::socialdb_post,
+        socialdb_events::socialdb_comment,
+        socialdb_events::socialdb_like,
+        socialdb_events::socialdb_follow,
+        socialdb_events::socialdb_profile_update,
+        price_events::price_change,
+        burrow_events::burrow_deposit,
+        burrow_events::burrow_withdraw,
+        burrow_events::burrow_borrow,
+        burrow_events::burrow_repay,
+        burrow_events::burrow_liquidation,
+        launch_events::launch_create,
+        launch_events::launch_deposit,
+        launch_events::launch_withdraw,
+        launch_events::launch_finalize,
+        staking_events::staking_stake,
+        staking_events::staking_unstake,
+        staking_events::staking_withdraw,
+        account_events::account_create,
+        account_events::account_delete,
+        account_events::key_added,
+        account_events::key_deleted,
+        dao_events::dao_proposal,
+        dao_events::dao_vote,
+        dao_events::dao_proposal_execute,
+        bridge_events::bridge_deposit,
+        bridge_events::bridge_withdraw,
+        raw_events::log_events,
+        count::count,
+        lookup::transaction,
+        lookup::receipt,
+        lookup::account_activity,
+        correlated::correlated_events,
+        sse::sse,
+        meta::gaps,
+        meta::freshness,
+        meta::last_block,
+        json_schema::event_type_schema,
+        webhooks::create_webhook,
+        webhooks::get_webhook,
+        webhooks::delete_webhook,
+        digests::create_digest,
+        digests::delete_digest,
+        v1::events,
+    ),
+    components(schemas(AnyOf, AnyAccountId, AnyId)),
+    modifiers(&BearerToken),
+    tags(
+        (name = "FT", description = "NEP-141 fungible tokens"),
+        (name = "NFT", description = "NEP-171 non-fungible tokens"),
+        (name = "Potlock", description = "Potlock donations and Pot lifecycle"),
+        (name = "Trade", description = "Swaps, liquidity, wNEAR wrapping, and liquidity pool changes"),
+        (name = "Social", description = "NEAR Social posts, comments, likes, follows, and profiles"),
+        (name = "Price", description = "Token prices in USD"),
+        (name = "Burrow", description = "Burrow lending: deposits, withdrawals, borrows, repayments, and liquidations"),
+        (name = "Launch", description = "Meme launchpads: new memes, deposits, withdrawals, and finalized launches"),
+        (name = "Staking", description = "Staking pools: stakes, unstakes, and withdrawals"),
+        (name = "Account", description = "Account creations, deletions, and access keys"),
+        (name = "DAO", description = "Sputnik DAO proposals, votes, and executed proposals"),
+        (name = "Bridge", description = "Cross-chain bridge deposits and withdrawals"),
+        (name = "Raw", description = "NEP-297 events as logged by contracts, for standards without their own endpoints"),
+        (name = "Count", description = "Numbers of events matching the filters of an event endpoint"),
+        (name = "Lookup", description = "Events of a transaction, a receipt, or an account across all event types"),
+        (name = "Live", description = "New events as they're indexed"),
+        (name = "Meta", description = "Indexing progress, gaps, and JSON Schemas of events"),
+        (name = "Webhooks", description = "Push delivery of new events, if enabled on the server"),
+        (name = "Digests", description = "Scheduled summaries delivered to a webhook, if enabled on the server"),
+        (name = "v1", description = "Events of any type in the same envelope"),
+    )
+)]
+pub struct ApiDoc;
+
+/// `Authorization: Bearer <token>` of webhooks and digests, with the token
+/// returned on creation
+struct BearerToken;
+
+impl Modify for BearerToken {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        if let Some(components) = openapi.components.as_mut() {
+            components.add_security_scheme(
+                "bearer",
+                SecurityScheme::Http(Http::new(HttpAuthScheme::Bearer)),
+            );
+        }
+    }
+}
+
+/// Serves the spec at `/openapi.json` and the UI at `/docs`
+pub fn swagger_ui() -> SwaggerUi {
+    SwaggerUi::new("/docs/{_:.*}").url("/openapi.json", ApiDoc::openapi())
+}
+
+/// Endpoint of each event type in the serialized spec, as `(table, path,
+/// operation, name of the event schema)`
+pub(crate) fn event_endpoints(spec: &Json) -> Vec<(EventTable, &str, &Json, &str)> {
+    EventTable::ALL
+        .into_iter()
+        .filter_map(|table| {
+            let (path, item) = spec["paths"]
+                .as_object()?
+                .iter()
+                .find(|(path, _)| path.ends_with(&format!("/{}", table.as_str())))?;
+            let operation = &item["get"];
+            // Responses are `EventsPage_<event struct>`
+            let (_, type_name) = operation["responses"]["200"]["content"]["application/json"]
+                ["schema"]["$ref"]
+                .as_str()?
+                .rsplit_once("/EventsPage_")?;
+            Some((table, path.as_str(), operation, type_name))
+        })
+        .collect()
+}
+
+/// Follows `$ref`s to components of the spec
+pub(crate) fn resolve<'a>(spec: &'a Json, schema: &'a Json) -> &'a Json {
+    match schema["$ref"].as_str().and_then(|r| r.rsplit('/').next()) {
+        Some(name) => resolve(spec, &spec["components"]["schemas"][name]),
+        None => schema,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn endpoints_other_than_event_pages_are_documented() {
+        let spec = serde_json::to_value(ApiDoc::openapi()).unwrap();
+        for path in [
+            "/v0/{category}/{endpoint}/count",
+            "/v0/nft/stats/velocity",
+            "/v0/potlock/referrer/{account_id}/earnings",
+            "/v0/potlock/stats/retention",
+            "/v0/potlock/leaderboard",
+            "/v0/potlock/project_summary",
+            "/v0/trade/volume",
+            "/v0/trade/candles",
+            "/v0/trade/pool_at",
+            "/v0/tx/{transaction_id}",
+            "/v0/receipt/{receipt_id}",
+            "/v0/account/{account_id}/activity",
+            "/v0/correlated",
+            "/v0/sse/{event_type}",
+            "/v0/meta/gaps",
+            "/v0/meta/freshness",
+            "/v0/meta/last_block",
+            "/v0/schema/{event_type}",
+            "/v0/webhooks",
+            "/v0/webhooks/{id}",
+            "/v0/digests",
+            "/v0/digests/{id}",
+            "/v1/events/{event_type}",
+        ] {
+            assert!(spec["paths"][path].is_object(), "{path} isn't documented");
+        }
+        assert_eq!(
+            spec["components"]["securitySchemes"]["bearer"]["scheme"],
+            "bearer"
+        );
+        assert!(spec["paths"]["/v0/webhooks/{id}"]["get"]["security"][0]["bearer"].is_array());
+    }
+}
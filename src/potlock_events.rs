@@ -1,249 +1,938 @@
-use actix_web::{get, web, HttpResponse, Responder};
-use chrono::prelude::{DateTime, Utc};
+use std::collections::BTreeMap;
+
+use actix_web::{get, web, HttpResponse};
+use chrono::{
+    prelude::{DateTime, Utc},
+    TimeDelta,
+};
+pub use inteat_events_api_types::potlock::{
+    PotlockDonationEvent, PotlockDonationFilter, PotlockPotApplicationEvent,
+    PotlockPotApplicationFilter, PotlockPotApplicationReviewEvent,
+    PotlockPotApplicationReviewFilter, PotlockPotDeploymentEvent, PotlockPotDeploymentFilter,
+    PotlockPotDonationEvent, PotlockPotDonationFilter, PotlockPotPayoutChallengeEvent,
+    PotlockPotPayoutChallengeFilter, PotlockPotPayoutEvent, PotlockPotPayoutFilter,
+    PotlockPotProjectDonationEvent, PotlockPotProjectDonationFilter,
+};
 use serde::{Deserialize, Serialize};
+use sqlx::types::BigDecimal;
+use utoipa::{IntoParams, ToSchema};
 
 use crate::{
-    utils::{Balance, OptionalBalance},
-    AppState, PaginationInfo, MAX_BLOCKS_PER_REQUEST,
+    endpoint::{event_endpoint, EventEndpoint},
+    error::{ApiError, ErrorResponse},
+    event_query::EventQuery,
+    meta::EventTable,
+    utils::{Balance, ValidAccountId},
+    AppState, Ordering, StatsPeriod, TimeRange,
 };
 
-type TransactionId = String;
-type ReceiptId = String;
 type AccountId = String;
-type BlockHeight = i64;
-type DonationId = i64;
 type ProjectId = AccountId;
 
-#[derive(Debug, Serialize, Deserialize)]
-pub struct PotlockDonationEvent {
-    pub transaction_id: TransactionId,
-    pub receipt_id: ReceiptId,
-    pub block_height: BlockHeight,
-    #[serde(
-        with = "chrono::serde::ts_nanoseconds",
-        rename = "block_timestamp_nanosec"
-    )]
-    pub timestamp: DateTime<Utc>,
+impl EventEndpoint for PotlockDonationEvent {
+    const TABLE: EventTable = EventTable::PotlockDonation;
+    const SUM_FIELDS: &'static [(&'static str, &'static str)] = &[
+        ("total_amount", "total_amount"),
+        ("protocol_fee", "protocol_fee"),
+        ("referrer_fee", "referrer_fee"),
+    ];
+    type Filter = PotlockDonationFilter;
 
-    pub donation_id: DonationId,
-    pub donor_id: AccountId,
-    pub total_amount: Balance,
-    pub ft_id: AccountId,
-    pub message: Option<String>,
-    #[serde(with = "chrono::serde::ts_milliseconds")]
-    pub donated_at: DateTime<Utc>,
+    fn query(filter: &PotlockDonationFilter) -> EventQuery<Self> {
+        EventQuery::new(
+            "potlock_donation",
+            "transaction_id, receipt_id, block_height, timestamp, donation_id, donor_id, total_amount, ft_id, message, donated_at, project_id, protocol_fee, referrer_id, referrer_fee",
+        )
+        .filter("project_id = ANY({})", filter.project_id.as_deref())
+        .filter("donor_id = ANY({})", filter.donor_id.as_deref())
+        .filter("referrer_id = ANY({})", filter.referrer_id.as_deref())
+        .filter("transaction_id = ANY({})", filter.transaction_id.as_deref())
+        .filter("receipt_id = ANY({})", filter.receipt_id.as_deref())
+        .filter(
+            "NOT (ARRAY[donor_id, project_id, referrer_id] && {})",
+            filter.exclude_account_id.as_deref(),
+        )
+        .filter("total_amount >= {}", filter.min_amount.as_ref().map(|amount| &amount.0))
+        .filter("total_amount <= {}", filter.max_amount.as_ref().map(|amount| &amount.0))
+        .amount_column("total_amount")
+    }
+
+    fn timestamp(&self) -> DateTime<Utc> {
+        self.timestamp
+    }
+
+    fn receipt_id(&self) -> &str {
+        &self.receipt_id
+    }
+
+    fn transaction(&self) -> (&str, &str) {
+        (&self.transaction_id, &self.donor_id)
+    }
+}
+
+event_endpoint! {
+    /// Potlock donation events
+    potlock_donation(PotlockDonationEvent, PotlockDonationFilter, Ordering),
+    route = "/potlock_donation",
+    scope = "/v0/potlock",
+    tag = "Potlock",
+}
+
+impl EventEndpoint for PotlockPotProjectDonationEvent {
+    const TABLE: EventTable = EventTable::PotlockPotProjectDonation;
+    const SUM_FIELDS: &'static [(&'static str, &'static str)] = &[
+        ("total_amount", "total_amount"),
+        ("net_amount", "net_amount"),
+        ("protocol_fee", "protocol_fee"),
+        ("referrer_fee", "referrer_fee"),
+        ("chef_fee", "chef_fee"),
+    ];
+    type Filter = PotlockPotProjectDonationFilter;
+
+    fn query(filter: &PotlockPotProjectDonationFilter) -> EventQuery<Self> {
+        EventQuery::new(
+            "potlock_pot_project_donation",
+            "transaction_id, receipt_id, block_height, timestamp, donation_id, pot_id, donor_id, total_amount, net_amount, message, donated_at, project_id, referrer_id, referrer_fee, protocol_fee, chef_id, chef_fee",
+        )
+        .filter("pot_id = ANY({})", filter.pot_id.as_deref())
+        .filter("project_id = ANY({})", filter.project_id.as_deref())
+        .filter("donor_id = ANY({})", filter.donor_id.as_deref())
+        .filter("referrer_id = ANY({})", filter.referrer_id.as_deref())
+        .filter("transaction_id = ANY({})", filter.transaction_id.as_deref())
+        .filter("receipt_id = ANY({})", filter.receipt_id.as_deref())
+        .filter(
+            "NOT (ARRAY[donor_id, project_id, referrer_id, chef_id] && {})",
+            filter.exclude_account_id.as_deref(),
+        )
+        .filter("total_amount >= {}", filter.min_amount.as_ref().map(|amount| &amount.0))
+        .filter("total_amount <= {}", filter.max_amount.as_ref().map(|amount| &amount.0))
+        .amount_column("total_amount")
+    }
+
+    fn timestamp(&self) -> DateTime<Utc> {
+        self.timestamp
+    }
+
+    fn receipt_id(&self) -> &str {
+        &self.receipt_id
+    }
+
+    fn transaction(&self) -> (&str, &str) {
+        (&self.transaction_id, &self.donor_id)
+    }
+}
+
+event_endpoint! {
+    /// Potlock Pot Project donation events
+    potlock_pot_project_donation(PotlockPotProjectDonationEvent, PotlockPotProjectDonationFilter, Ordering),
+    route = "/potlock_pot_project_donation",
+    scope = "/v0/potlock",
+    tag = "Potlock",
+}
+
+impl EventEndpoint for PotlockPotDonationEvent {
+    const TABLE: EventTable = EventTable::PotlockPotDonation;
+    const SUM_FIELDS: &'static [(&'static str, &'static str)] = &[
+        ("total_amount", "total_amount"),
+        ("net_amount", "net_amount"),
+        ("protocol_fee", "protocol_fee"),
+        ("referrer_fee", "referrer_fee"),
+        ("chef_fee", "chef_fee"),
+    ];
+    type Filter = PotlockPotDonationFilter;
+
+    fn query(filter: &PotlockPotDonationFilter) -> EventQuery<Self> {
+        EventQuery::new(
+            "potlock_pot_donation",
+            "transaction_id, receipt_id, block_height, timestamp, donation_id, pot_id, donor_id, total_amount, net_amount, message, donated_at, referrer_id, referrer_fee, protocol_fee, chef_id, chef_fee",
+        )
+        .filter("pot_id = ANY({})", filter.pot_id.as_deref())
+        .filter("donor_id = ANY({})", filter.donor_id.as_deref())
+        .filter("referrer_id = ANY({})", filter.referrer_id.as_deref())
+        .filter("transaction_id = ANY({})", filter.transaction_id.as_deref())
+        .filter("receipt_id = ANY({})", filter.receipt_id.as_deref())
+        .filter(
+            "NOT (ARRAY[donor_id, referrer_id, chef_id] && {})",
+            filter.exclude_account_id.as_deref(),
+        )
+        .filter("total_amount >= {}", filter.min_amount.as_ref().map(|amount| &amount.0))
+        .filter("total_amount <= {}", filter.max_amount.as_ref().map(|amount| &amount.0))
+        .amount_column("total_amount")
+    }
+
+    fn timestamp(&self) -> DateTime<Utc> {
+        self.timestamp
+    }
+
+    fn receipt_id(&self) -> &str {
+        &self.receipt_id
+    }
+
+    fn transaction(&self) -> (&str, &str) {
+        (&self.transaction_id, &self.donor_id)
+    }
+}
+
+event_endpoint! {
+    /// Potlock Pot donation events
+    potlock_pot_donation(PotlockPotDonationEvent, PotlockPotDonationFilter, Ordering),
+    route = "/potlock_pot_donation",
+    scope = "/v0/potlock",
+    tag = "Potlock",
+}
+
+impl EventEndpoint for PotlockPotDeploymentEvent {
+    const TABLE: EventTable = EventTable::PotlockPotDeployment;
+    type Filter = PotlockPotDeploymentFilter;
+
+    fn query(filter: &PotlockPotDeploymentFilter) -> EventQuery<Self> {
+        EventQuery::new(
+            "potlock_pot_deployment",
+            "transaction_id, receipt_id, block_height, timestamp, pot_id, deployer_id, owner_id, chef_id, pot_name",
+        )
+        .filter("pot_id = ANY({})", filter.pot_id.as_deref())
+        .filter("deployer_id = ANY({})", filter.deployer_id.as_deref())
+        .filter("transaction_id = ANY({})", filter.transaction_id.as_deref())
+        .filter("receipt_id = ANY({})", filter.receipt_id.as_deref())
+        .filter(
+            "NOT (ARRAY[deployer_id, owner_id, chef_id] && {})",
+            filter.exclude_account_id.as_deref(),
+        )
+    }
+
+    fn timestamp(&self) -> DateTime<Utc> {
+        self.timestamp
+    }
+
+    fn receipt_id(&self) -> &str {
+        &self.receipt_id
+    }
+
+    fn transaction(&self) -> (&str, &str) {
+        (&self.transaction_id, &self.deployer_id)
+    }
+}
+
+event_endpoint! {
+    /// Potlock Pot deployment events
+    potlock_pot_deployment(PotlockPotDeploymentEvent, PotlockPotDeploymentFilter),
+    route = "/potlock_pot_deployment",
+    scope = "/v0/potlock",
+    tag = "Potlock",
+}
+
+impl EventEndpoint for PotlockPotApplicationEvent {
+    const TABLE: EventTable = EventTable::PotlockPotApplication;
+    type Filter = PotlockPotApplicationFilter;
+
+    fn query(filter: &PotlockPotApplicationFilter) -> EventQuery<Self> {
+        EventQuery::new(
+            "potlock_pot_application",
+            "transaction_id, receipt_id, block_height, timestamp, pot_id, project_id, message",
+        )
+        .filter("pot_id = ANY({})", filter.pot_id.as_deref())
+        .filter("project_id = ANY({})", filter.project_id.as_deref())
+        .filter("transaction_id = ANY({})", filter.transaction_id.as_deref())
+        .filter("receipt_id = ANY({})", filter.receipt_id.as_deref())
+        .filter(
+            "project_id IS NULL OR project_id <> ALL({})",
+            filter.exclude_account_id.as_deref(),
+        )
+    }
+
+    fn timestamp(&self) -> DateTime<Utc> {
+        self.timestamp
+    }
+
+    fn receipt_id(&self) -> &str {
+        &self.receipt_id
+    }
+
+    fn transaction(&self) -> (&str, &str) {
+        (&self.transaction_id, &self.project_id)
+    }
+}
+
+event_endpoint! {
+    /// Potlock Pot application events
+    potlock_pot_application(PotlockPotApplicationEvent, PotlockPotApplicationFilter),
+    route = "/potlock_pot_application",
+    scope = "/v0/potlock",
+    tag = "Potlock",
+}
+
+impl EventEndpoint for PotlockPotApplicationReviewEvent {
+    const TABLE: EventTable = EventTable::PotlockPotApplicationReview;
+    type Filter = PotlockPotApplicationReviewFilter;
+
+    fn query(filter: &PotlockPotApplicationReviewFilter) -> EventQuery<Self> {
+        EventQuery::new(
+            "potlock_pot_application_review",
+            "transaction_id, receipt_id, block_height, timestamp, pot_id, project_id, reviewer_id, status, notes",
+        )
+        .filter("pot_id = ANY({})", filter.pot_id.as_deref())
+        .filter("project_id = ANY({})", filter.project_id.as_deref())
+        .filter("status = ANY({})", filter.status.as_deref())
+        .filter("transaction_id = ANY({})", filter.transaction_id.as_deref())
+        .filter("receipt_id = ANY({})", filter.receipt_id.as_deref())
+        .filter("NOT (ARRAY[project_id, reviewer_id] && {})", filter.exclude_account_id.as_deref())
+    }
+
+    fn timestamp(&self) -> DateTime<Utc> {
+        self.timestamp
+    }
+
+    fn receipt_id(&self) -> &str {
+        &self.receipt_id
+    }
+
+    fn transaction(&self) -> (&str, &str) {
+        (&self.transaction_id, &self.reviewer_id)
+    }
+}
+
+event_endpoint! {
+    /// Potlock Pot application review events
+    potlock_pot_application_review(PotlockPotApplicationReviewEvent, PotlockPotApplicationReviewFilter),
+    route = "/potlock_pot_application_review",
+    scope = "/v0/potlock",
+    tag = "Potlock",
+}
+
+impl EventEndpoint for PotlockPotPayoutEvent {
+    const TABLE: EventTable = EventTable::PotlockPotPayout;
+    const SUM_FIELDS: &'static [(&'static str, &'static str)] = &[("amount", "amount")];
+    type Filter = PotlockPotPayoutFilter;
+
+    fn query(filter: &PotlockPotPayoutFilter) -> EventQuery<Self> {
+        EventQuery::new(
+            "potlock_pot_payout",
+            "transaction_id, receipt_id, block_height, timestamp, pot_id, project_id, amount, admin_id",
+        )
+        .filter("pot_id = ANY({})", filter.pot_id.as_deref())
+        .filter("project_id = ANY({})", filter.project_id.as_deref())
+        .filter("transaction_id = ANY({})", filter.transaction_id.as_deref())
+        .filter("receipt_id = ANY({})", filter.receipt_id.as_deref())
+        .filter(
+            "NOT (ARRAY[project_id, admin_id] && {})",
+            filter.exclude_account_id.as_deref(),
+        )
+    }
+
+    fn timestamp(&self) -> DateTime<Utc> {
+        self.timestamp
+    }
+
+    fn receipt_id(&self) -> &str {
+        &self.receipt_id
+    }
+
+    fn transaction(&self) -> (&str, &str) {
+        (&self.transaction_id, &self.admin_id)
+    }
+}
+
+event_endpoint! {
+    /// Potlock Pot payout events
+    potlock_pot_payout(PotlockPotPayoutEvent, PotlockPotPayoutFilter),
+    route = "/potlock_pot_payout",
+    scope = "/v0/potlock",
+    tag = "Potlock",
+}
+
+impl EventEndpoint for PotlockPotPayoutChallengeEvent {
+    const TABLE: EventTable = EventTable::PotlockPotPayoutChallenge;
+    type Filter = PotlockPotPayoutChallengeFilter;
+
+    fn query(filter: &PotlockPotPayoutChallengeFilter) -> EventQuery<Self> {
+        EventQuery::new(
+            "potlock_pot_payout_challenge",
+            "transaction_id, receipt_id, block_height, timestamp, pot_id, challenger_id, reason",
+        )
+        .filter("pot_id = ANY({})", filter.pot_id.as_deref())
+        .filter("challenger_id = ANY({})", filter.challenger_id.as_deref())
+        .filter("transaction_id = ANY({})", filter.transaction_id.as_deref())
+        .filter("receipt_id = ANY({})", filter.receipt_id.as_deref())
+        .filter(
+            "challenger_id IS NULL OR challenger_id <> ALL({})",
+            filter.exclude_account_id.as_deref(),
+        )
+    }
+
+    fn timestamp(&self) -> DateTime<Utc> {
+        self.timestamp
+    }
+
+    fn receipt_id(&self) -> &str {
+        &self.receipt_id
+    }
+
+    fn transaction(&self) -> (&str, &str) {
+        (&self.transaction_id, &self.challenger_id)
+    }
+}
+
+event_endpoint! {
+    /// Potlock Pot payout challenge events
+    potlock_pot_payout_challenge(PotlockPotPayoutChallengeEvent, PotlockPotPayoutChallengeFilter),
+    route = "/potlock_pot_payout_challenge",
+    scope = "/v0/potlock",
+    tag = "Potlock",
+}
+
+/// Pots only accept native NEAR, so their fees are always denominated in it.
+const POT_FT_ID: &str = "near";
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ReferrerProjectEarnings {
     pub project_id: ProjectId,
-    pub protocol_fee: Balance,
-    pub referrer_id: Option<AccountId>,
-    pub referrer_fee: OptionalBalance,
+    pub ft_id: AccountId,
+    pub amount: Balance,
+    pub donations: i64,
 }
 
-#[derive(Deserialize)]
-struct PotlockDonationFilter {
-    project_id: Option<String>,
-    donor_id: Option<String>,
-    referrer_id: Option<String>,
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ReferrerPotEarnings {
+    pub pot_id: AccountId,
+    pub amount: Balance,
+    pub donations: i64,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ReferrerEarnings {
+    pub referrer_id: AccountId,
+    /// ft_id -> sum of referrer fees across all donation tables
+    pub total: BTreeMap<AccountId, Balance>,
+    pub projects: Vec<ReferrerProjectEarnings>,
+    pub pots: Vec<ReferrerPotEarnings>,
 }
 
-#[get("/potlock_donation")]
-pub async fn potlock_donation(
+/// Referrer fees earned by an account, by project and by Pot
+#[utoipa::path(
+    get,
+    path = "/v0/potlock/referrer/{account_id}/earnings",
+    tag = "Potlock",
+    params(("account_id" = String, Path, description = "Referrer"), TimeRange),
+    responses(
+        (status = 200, body = ReferrerEarnings),
+        (status = 400, body = ErrorResponse),
+        (status = 503, body = ErrorResponse),
+    )
+)]
+#[get("/referrer/{account_id}/earnings")]
+pub async fn potlock_referrer_earnings(
     state: web::Data<AppState>,
-    pagination: web::Query<PaginationInfo>,
-    filter: web::Query<PotlockDonationFilter>,
-) -> impl Responder {
-    if pagination.blocks > MAX_BLOCKS_PER_REQUEST {
-        return HttpResponse::BadRequest().body(format!(
-            "Blocks per request must be less or equal to {MAX_BLOCKS_PER_REQUEST}"
-        ));
-    }
+    referrer_id: web::Path<String>,
+    range: web::Query<TimeRange>,
+) -> Result<HttpResponse, ApiError> {
+    let referrer_id = referrer_id
+        .parse::<ValidAccountId>()
+        .map_err(ApiError::BadFilter)?
+        .into_inner();
+    let _permit = state.heavy_queries().try_acquire()?;
 
-    if let Ok(res) = sqlx::query_as!(PotlockDonationEvent,
+    let projects = sqlx::query_as!(
+        ReferrerProjectEarnings,
         r#"
-        WITH blocks AS (
-            SELECT DISTINCT timestamp as t
-            FROM potlock_donation
-            WHERE extract(epoch from timestamp) * 1_000_000_000 >= $1
-                AND ($3::TEXT IS NULL OR project_id = $3)
-                AND ($4::TEXT IS NULL OR donor_id = $4)
-                AND ($5::TEXT IS NULL OR referrer_id = $5)
-            ORDER BY t
-            LIMIT $2
-        )
-        SELECT transaction_id, receipt_id, block_height, timestamp, donation_id, donor_id, total_amount, ft_id, message, donated_at, project_id, protocol_fee, referrer_id, referrer_fee
+        SELECT project_id, ft_id, SUM(referrer_fee) as "amount!", COUNT(*) as "donations!"
         FROM potlock_donation
-        INNER JOIN blocks ON timestamp = blocks.t
-        WHERE ($3::TEXT IS NULL OR project_id = $3)
-            AND ($4::TEXT IS NULL OR donor_id = $4)
-            AND ($5::TEXT IS NULL OR referrer_id = $5)
-        ORDER BY timestamp ASC
+        WHERE referrer_id = $1
+            AND referrer_fee IS NOT NULL
+            AND timestamp >= $2
+            AND ($3::TIMESTAMPTZ IS NULL OR timestamp < $3)
+        GROUP BY project_id, ft_id
+        ORDER BY project_id, ft_id
         "#,
-        pagination.start_block_timestamp_nanosec as i64,
-        pagination.blocks,
-        filter.project_id.as_deref(),
-        filter.donor_id.as_deref(),
-        filter.referrer_id.as_deref(),
-    ).fetch_all(&state.pg_pool).await {
-        HttpResponse::Ok().json(res)
-    } else {
-        HttpResponse::InternalServerError().finish()
+        referrer_id,
+        range.start(),
+        range.end(),
+    )
+    .fetch_all(&state.pg_pool())
+    .await?;
+
+    let pots = sqlx::query_as!(
+        ReferrerPotEarnings,
+        r#"
+        WITH pot_fees AS (
+            SELECT pot_id, referrer_fee, timestamp
+            FROM potlock_pot_project_donation
+            WHERE referrer_id = $1 AND referrer_fee IS NOT NULL
+            UNION ALL
+            SELECT pot_id, referrer_fee, timestamp
+            FROM potlock_pot_donation
+            WHERE referrer_id = $1 AND referrer_fee IS NOT NULL
+        )
+        SELECT pot_id as "pot_id!", SUM(referrer_fee) as "amount!", COUNT(*) as "donations!"
+        FROM pot_fees
+        WHERE timestamp >= $2
+            AND ($3::TIMESTAMPTZ IS NULL OR timestamp < $3)
+        GROUP BY pot_id
+        ORDER BY pot_id
+        "#,
+        referrer_id,
+        range.start(),
+        range.end(),
+    )
+    .fetch_all(&state.pg_pool())
+    .await?;
+
+    let mut total: BTreeMap<AccountId, BigDecimal> = BTreeMap::new();
+    for project in projects.iter() {
+        *total.entry(project.ft_id.clone()).or_default() += &project.amount.0;
     }
+    for pot in pots.iter() {
+        *total.entry(POT_FT_ID.to_owned()).or_default() += &pot.amount.0;
+    }
+
+    Ok(HttpResponse::Ok().json(ReferrerEarnings {
+        referrer_id,
+        total: total
+            .into_iter()
+            .map(|(ft_id, amount)| (ft_id, Balance(amount)))
+            .collect(),
+        projects,
+        pots,
+    }))
 }
 
-#[derive(Debug, Serialize, Deserialize)]
-pub struct PotlockPotProjectDonationEvent {
-    pub transaction_id: TransactionId,
-    pub receipt_id: ReceiptId,
-    pub block_height: BlockHeight,
+#[derive(Debug, Serialize, ToSchema)]
+pub struct DonorRetention {
     #[serde(
         with = "chrono::serde::ts_nanoseconds",
-        rename = "block_timestamp_nanosec"
+        rename = "period_start_nanosec"
     )]
-    pub timestamp: DateTime<Utc>,
+    #[schema(value_type = i64)]
+    pub period: DateTime<Utc>,
+    pub new_donors: i64,
+    pub returning_donors: i64,
+}
 
-    pub donation_id: DonationId,
-    pub pot_id: AccountId,
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+struct DonorRetentionFilter {
+    /// Donors of one project, instead of all of Potlock
+    project_id: Option<ValidAccountId>,
+    #[serde(default)]
+    #[param(inline)]
+    period: StatsPeriod,
+}
+
+/// New and returning donors per day, week, or month
+#[utoipa::path(
+    get,
+    path = "/v0/potlock/stats/retention",
+    tag = "Potlock",
+    params(TimeRange, DonorRetentionFilter),
+    responses(
+        (status = 200, body = Vec<DonorRetention>),
+        (status = 400, body = ErrorResponse),
+        (status = 503, body = ErrorResponse),
+    )
+)]
+#[get("/stats/retention")]
+pub async fn potlock_donor_retention(
+    state: web::Data<AppState>,
+    range: web::Query<TimeRange>,
+    filter: web::Query<DonorRetentionFilter>,
+) -> Result<HttpResponse, ApiError> {
+    let _permit = state.heavy_queries().try_acquire()?;
+    // A donor's first donation is looked up over the whole history, so that
+    // donors who gave before the requested range are counted as returning.
+    // Pot (matching pool) donations have no project, so they only count
+    // when no project is requested.
+    let res = sqlx::query_as!(DonorRetention,
+        r#"
+        WITH donations AS (
+            SELECT donor_id, project_id, timestamp FROM potlock_donation
+            UNION ALL
+            SELECT donor_id, project_id, timestamp FROM potlock_pot_project_donation
+            UNION ALL
+            SELECT donor_id, NULL, timestamp FROM potlock_pot_donation WHERE $1::TEXT IS NULL
+        ),
+        filtered AS (
+            SELECT donor_id, date_trunc($2, timestamp) as period
+            FROM donations
+            WHERE $1::TEXT IS NULL OR project_id = $1
+        ),
+        donor_periods AS (
+            SELECT DISTINCT donor_id, period, MIN(period) OVER (PARTITION BY donor_id) as first_period
+            FROM filtered
+        )
+        SELECT period as "period!",
+            COUNT(*) FILTER (WHERE period = first_period) as "new_donors!",
+            COUNT(*) FILTER (WHERE period > first_period) as "returning_donors!"
+        FROM donor_periods
+        WHERE period >= $3
+            AND ($4::TIMESTAMPTZ IS NULL OR period < $4)
+        GROUP BY period
+        ORDER BY period ASC
+        "#,
+        filter.project_id.as_deref(),
+        filter.period.as_str(),
+        range.start(),
+        range.end(),
+    ).fetch_all(&state.pg_pool()).await?;
+    Ok(HttpResponse::Ok().json(res))
+}
+
+/// Leaderboards are short, so they're cheap to compute and render
+const DEFAULT_LEADERBOARD_SIZE: i64 = 10;
+const MAX_LEADERBOARD_SIZE: i64 = 100;
+
+#[derive(Deserialize, ToSchema, Default, Clone, Copy)]
+enum LeaderboardPeriod {
+    #[serde(rename = "7d")]
+    Week,
+    #[serde(rename = "30d")]
+    Month,
+    #[default]
+    #[serde(rename = "all")]
+    All,
+}
+
+impl LeaderboardPeriod {
+    /// Start of the period, `None` for all time
+    fn start(&self) -> Option<DateTime<Utc>> {
+        match self {
+            LeaderboardPeriod::Week => Some(Utc::now() - TimeDelta::days(7)),
+            LeaderboardPeriod::Month => Some(Utc::now() - TimeDelta::days(30)),
+            LeaderboardPeriod::All => None,
+        }
+    }
+}
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+struct LeaderboardFilter {
+    #[serde(default)]
+    #[param(inline)]
+    period: LeaderboardPeriod,
+    /// Amounts of different tokens can't be added up, so the leaderboard is
+    /// of one token
+    #[serde(default = "default_leaderboard_ft_id")]
+    #[param(default = "near")]
+    ft_id: ValidAccountId,
+    #[serde(default = "default_leaderboard_size")]
+    #[param(default = 10, minimum = 1, maximum = 100)]
+    limit: i64,
+}
+
+fn default_leaderboard_ft_id() -> ValidAccountId {
+    POT_FT_ID.parse().expect("POT_FT_ID is a valid account id")
+}
+
+fn default_leaderboard_size() -> i64 {
+    DEFAULT_LEADERBOARD_SIZE
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct LeaderboardDonor {
     pub donor_id: AccountId,
-    pub total_amount: Balance,
-    pub net_amount: Balance,
-    pub message: Option<String>,
-    #[serde(with = "chrono::serde::ts_milliseconds")]
-    pub donated_at: DateTime<Utc>,
+    pub amount: Balance,
+    pub donations: i64,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct LeaderboardProject {
     pub project_id: ProjectId,
-    pub referrer_id: Option<AccountId>,
-    pub referrer_fee: OptionalBalance,
-    pub protocol_fee: Balance,
-    pub chef_id: Option<AccountId>,
-    pub chef_fee: OptionalBalance,
+    pub amount: Balance,
+    pub donations: i64,
+    pub donors: i64,
 }
 
-#[derive(Deserialize)]
-struct PotlockPotProjectDonationFilter {
-    pot_id: Option<String>,
-    project_id: Option<String>,
-    donor_id: Option<String>,
-    referrer_id: Option<String>,
+#[derive(Debug, Serialize, ToSchema)]
+pub struct Leaderboard {
+    pub ft_id: AccountId,
+    pub donors: Vec<LeaderboardDonor>,
+    pub projects: Vec<LeaderboardProject>,
 }
 
-#[get("/potlock_pot_project_donation")]
-pub async fn potlock_pot_project_donation(
+/// Top donors and projects by amount donated in one token
+#[utoipa::path(
+    get,
+    path = "/v0/potlock/leaderboard",
+    tag = "Potlock",
+    params(LeaderboardFilter),
+    responses(
+        (status = 200, body = Leaderboard),
+        (status = 400, body = ErrorResponse),
+        (status = 503, body = ErrorResponse),
+    )
+)]
+#[get("/leaderboard")]
+pub async fn potlock_leaderboard(
     state: web::Data<AppState>,
-    pagination: web::Query<PaginationInfo>,
-    filter: web::Query<PotlockPotProjectDonationFilter>,
-) -> impl Responder {
-    if pagination.blocks > MAX_BLOCKS_PER_REQUEST {
-        return HttpResponse::BadRequest().body(format!(
-            "Blocks per request must be less or equal to {MAX_BLOCKS_PER_REQUEST}"
-        ));
+    filter: web::Query<LeaderboardFilter>,
+) -> Result<HttpResponse, ApiError> {
+    let _permit = state.heavy_queries().try_acquire()?;
+    if !(1..=MAX_LEADERBOARD_SIZE).contains(&filter.limit) {
+        return Err(ApiError::Validation(format!(
+            "limit must be between 1 and {MAX_LEADERBOARD_SIZE}"
+        )));
     }
+    let since = filter.period.start();
+    // Pots only accept NEAR, so their donations are only counted on the
+    // NEAR leaderboard. Matching pool donations have no project, so they only
+    // count for donors.
+    let include_pots = filter.ft_id.as_str() == POT_FT_ID;
 
-    if let Ok(res) = sqlx::query_as!(PotlockPotProjectDonationEvent,
+    let donors = sqlx::query_as!(
+        LeaderboardDonor,
         r#"
-        WITH blocks AS (
-            SELECT DISTINCT timestamp as t
+        WITH donations AS (
+            SELECT donor_id, total_amount, timestamp
+            FROM potlock_donation
+            WHERE ft_id = $1
+            UNION ALL
+            SELECT donor_id, total_amount, timestamp
             FROM potlock_pot_project_donation
-            WHERE extract(epoch from timestamp) * 1_000_000_000 >= $1
-                AND ($3::TEXT IS NULL OR pot_id = $3)
-                AND ($4::TEXT IS NULL OR project_id = $4)
-                AND ($5::TEXT IS NULL OR donor_id = $5)
-                AND ($6::TEXT IS NULL OR referrer_id = $6)
-            ORDER BY t
-            LIMIT $2
-        )
-        SELECT transaction_id, receipt_id, block_height, timestamp, donation_id, pot_id, donor_id, total_amount, net_amount, message, donated_at, project_id, referrer_id, referrer_fee, protocol_fee, chef_id, chef_fee
-        FROM potlock_pot_project_donation
-        INNER JOIN blocks ON timestamp = blocks.t
-        WHERE ($3::TEXT IS NULL OR pot_id = $3)
-            AND ($4::TEXT IS NULL OR project_id = $4)
-            AND ($5::TEXT IS NULL OR donor_id = $5)
-            AND ($6::TEXT IS NULL OR referrer_id = $6)
-        ORDER BY timestamp ASC
+            WHERE $2
+            UNION ALL
+            SELECT donor_id, total_amount, timestamp
+            FROM potlock_pot_donation
+            WHERE $2
+        )
+        SELECT donor_id as "donor_id!", SUM(total_amount) as "amount!", COUNT(*) as "donations!"
+        FROM donations
+        WHERE $3::TIMESTAMPTZ IS NULL OR timestamp >= $3
+        GROUP BY donor_id
+        ORDER BY 2 DESC, donor_id
+        LIMIT $4
         "#,
-        pagination.start_block_timestamp_nanosec as i64,
-        pagination.blocks,
-        filter.pot_id.as_deref(),
-        filter.project_id.as_deref(),
-        filter.donor_id.as_deref(),
-        filter.referrer_id.as_deref(),
-    ).fetch_all(&state.pg_pool).await {
-        HttpResponse::Ok().json(res)
-    } else {
-        HttpResponse::InternalServerError().finish()
-    }
+        filter.ft_id.as_str(),
+        include_pots,
+        since,
+        filter.limit,
+    )
+    .fetch_all(&state.pg_pool())
+    .await?;
+
+    let projects = sqlx::query_as!(
+        LeaderboardProject,
+        r#"
+        WITH donations AS (
+            SELECT project_id, donor_id, total_amount, timestamp
+            FROM potlock_donation
+            WHERE ft_id = $1
+            UNION ALL
+            SELECT project_id, donor_id, total_amount, timestamp
+            FROM potlock_pot_project_donation
+            WHERE $2
+        )
+        SELECT project_id as "project_id!",
+            SUM(total_amount) as "amount!",
+            COUNT(*) as "donations!",
+            COUNT(DISTINCT donor_id) as "donors!"
+        FROM donations
+        WHERE $3::TIMESTAMPTZ IS NULL OR timestamp >= $3
+        GROUP BY project_id
+        ORDER BY 2 DESC, project_id
+        LIMIT $4
+        "#,
+        filter.ft_id.as_str(),
+        include_pots,
+        since,
+        filter.limit,
+    )
+    .fetch_all(&state.pg_pool())
+    .await?;
+
+    Ok(HttpResponse::Ok().json(Leaderboard {
+        ft_id: filter.into_inner().ft_id.into_inner(),
+        donors,
+        projects,
+    }))
+}
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+struct ProjectSummaryFilter {
+    project_id: ValidAccountId,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
-pub struct PotlockPotDonationEvent {
-    pub transaction_id: TransactionId,
-    pub receipt_id: ReceiptId,
-    pub block_height: BlockHeight,
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ProjectPotSummary {
+    pub pot_id: AccountId,
+    pub amount: Balance,
+    pub donations: i64,
+    pub donors: i64,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ProjectSummary {
+    pub project_id: ProjectId,
+    /// ft_id -> sum of direct and Pot donations
+    pub total: BTreeMap<AccountId, Balance>,
+    pub donations: i64,
+    pub donors: i64,
     #[serde(
-        with = "chrono::serde::ts_nanoseconds",
-        rename = "block_timestamp_nanosec"
+        with = "chrono::serde::ts_nanoseconds_option",
+        rename = "first_donation_nanosec"
+    )]
+    #[schema(value_type = Option<i64>)]
+    pub first_donation: Option<DateTime<Utc>>,
+    #[serde(
+        with = "chrono::serde::ts_nanoseconds_option",
+        rename = "last_donation_nanosec"
     )]
-    pub timestamp: DateTime<Utc>,
+    #[schema(value_type = Option<i64>)]
+    pub last_donation: Option<DateTime<Utc>>,
+    pub pots: Vec<ProjectPotSummary>,
+}
 
-    pub donation_id: DonationId,
-    pub pot_id: AccountId,
-    pub donor_id: AccountId,
-    pub total_amount: Balance,
-    pub net_amount: Balance,
-    pub message: Option<String>,
-    #[serde(with = "chrono::serde::ts_milliseconds")]
-    pub donated_at: DateTime<Utc>,
-    pub referrer_id: Option<AccountId>,
-    pub referrer_fee: OptionalBalance,
-    pub protocol_fee: Balance,
-    pub chef_id: Option<AccountId>,
-    pub chef_fee: OptionalBalance,
-}
-
-#[derive(Deserialize)]
-struct PotlockPotDonationFilter {
-    pot_id: Option<String>,
-    donor_id: Option<String>,
-    referrer_id: Option<String>,
-}
-
-#[get("/potlock_pot_donation")]
-pub async fn potlock_pot_donation(
+struct ProjectTotal {
+    ft_id: AccountId,
+    amount: Balance,
+}
+
+struct ProjectStats {
+    donations: i64,
+    donors: i64,
+    first_donation: Option<DateTime<Utc>>,
+    last_donation: Option<DateTime<Utc>>,
+}
+
+/// Donations to a project over all time, directly and through Pots
+#[utoipa::path(
+    get,
+    path = "/v0/potlock/project_summary",
+    tag = "Potlock",
+    params(ProjectSummaryFilter),
+    responses(
+        (status = 200, body = ProjectSummary),
+        (status = 400, body = ErrorResponse),
+        (status = 503, body = ErrorResponse),
+    )
+)]
+#[get("/project_summary")]
+pub async fn potlock_project_summary(
     state: web::Data<AppState>,
-    pagination: web::Query<PaginationInfo>,
-    filter: web::Query<PotlockPotDonationFilter>,
-) -> impl Responder {
-    if pagination.blocks > MAX_BLOCKS_PER_REQUEST {
-        return HttpResponse::BadRequest().body(format!(
-            "Blocks per request must be less or equal to {MAX_BLOCKS_PER_REQUEST}"
-        ));
-    }
+    filter: web::Query<ProjectSummaryFilter>,
+) -> Result<HttpResponse, ApiError> {
+    let _permit = state.heavy_queries().try_acquire()?;
+    let project_id = filter.into_inner().project_id.into_inner();
+    let pg_pool = state.pg_pool();
 
-    if let Ok(res) = sqlx::query_as!(PotlockPotDonationEvent,
+    // Matching pool donations go to a Pot, not to a project, so only direct
+    // donations and donations to the project through a Pot are counted
+    let totals = sqlx::query_as!(
+        ProjectTotal,
         r#"
-        WITH blocks AS (
-            SELECT DISTINCT timestamp as t
-            FROM potlock_pot_donation
-            WHERE extract(epoch from timestamp) * 1_000_000_000 >= $1
-                AND ($3::TEXT IS NULL OR pot_id = $3)
-                AND ($4::TEXT IS NULL OR donor_id = $4)
-                AND ($5::TEXT IS NULL OR referrer_id = $5)
-            ORDER BY t
-            LIMIT $2
-        )
-        SELECT transaction_id, receipt_id, block_height, timestamp, donation_id, pot_id, donor_id, total_amount, net_amount, message, donated_at, referrer_id, referrer_fee, protocol_fee, chef_id, chef_fee
-        FROM potlock_pot_donation
-        INNER JOIN blocks ON timestamp = blocks.t
-        WHERE ($3::TEXT IS NULL OR pot_id = $3)
-            AND ($4::TEXT IS NULL OR donor_id = $4)
-            AND ($5::TEXT IS NULL OR referrer_id = $5)
-        ORDER BY timestamp ASC
+        WITH donations AS (
+            SELECT ft_id, total_amount
+            FROM potlock_donation
+            WHERE project_id = $1
+            UNION ALL
+            SELECT $2, total_amount
+            FROM potlock_pot_project_donation
+            WHERE project_id = $1
+        )
+        SELECT ft_id as "ft_id!", SUM(total_amount) as "amount!"
+        FROM donations
+        GROUP BY ft_id
+        ORDER BY ft_id
+        "#,
+        project_id,
+        POT_FT_ID,
+    )
+    .fetch_all(&pg_pool)
+    .await?;
+
+    let stats = sqlx::query_as!(
+        ProjectStats,
+        r#"
+        WITH donations AS (
+            SELECT donor_id, timestamp
+            FROM potlock_donation
+            WHERE project_id = $1
+            UNION ALL
+            SELECT donor_id, timestamp
+            FROM potlock_pot_project_donation
+            WHERE project_id = $1
+        )
+        SELECT COUNT(*) as "donations!",
+            COUNT(DISTINCT donor_id) as "donors!",
+            MIN(timestamp) as first_donation,
+            MAX(timestamp) as last_donation
+        FROM donations
         "#,
-        pagination.start_block_timestamp_nanosec as i64,
-        pagination.blocks as i64,
-        filter.pot_id.as_deref(),
-        filter.donor_id.as_deref(),
-        filter.referrer_id.as_deref(),
-    ).fetch_all(&state.pg_pool).await {
-        HttpResponse::Ok().json(res)
-    } else {
-        HttpResponse::InternalServerError().finish()
+        project_id,
+    )
+    .fetch_one(&pg_pool)
+    .await?;
+
+    let pots = sqlx::query_as!(
+        ProjectPotSummary,
+        r#"
+        SELECT pot_id,
+            SUM(total_amount) as "amount!",
+            COUNT(*) as "donations!",
+            COUNT(DISTINCT donor_id) as "donors!"
+        FROM potlock_pot_project_donation
+        WHERE project_id = $1
+        GROUP BY pot_id
+        ORDER BY 2 DESC, pot_id
+        "#,
+        project_id,
+    )
+    .fetch_all(&pg_pool)
+    .await?;
+
+    Ok(HttpResponse::Ok().json(ProjectSummary {
+        project_id,
+        total: totals
+            .into_iter()
+            .map(|total| (total.ft_id, total.amount))
+            .collect(),
+        donations: stats.donations,
+        donors: stats.donors,
+        first_donation: stats.first_donation,
+        last_donation: stats.last_donation,
+        pots,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::{
+        http::StatusCode,
+        test::{call_service, init_service, read_body_json, TestRequest},
+        App,
+    };
+    use serde_json::Value;
+    use sqlx::postgres::PgPoolOptions;
+
+    use super::*;
+    use crate::limits::{BlockLimits, HeavyQueries};
+
+    #[actix_web::test]
+    async fn malformed_referrer_ids_are_rejected() {
+        let pg_pool = PgPoolOptions::new()
+            .connect_lazy("postgres://localhost/events")
+            .unwrap();
+        let state = AppState::new(
+            pg_pool,
+            Vec::new(),
+            BlockLimits::from_env(),
+            HeavyQueries::from_env(),
+        );
+        let app = init_service(
+            App::new()
+                .app_data(web::Data::new(state))
+                .service(web::scope("/v0/potlock").service(potlock_referrer_earnings)),
+        )
+        .await;
+
+        let req = TestRequest::get()
+            .uri("/v0/potlock/referrer/Not..Valid/earnings")
+            .to_request();
+        let res = call_service(&app, req).await;
+        assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+        let error: Value = read_body_json(res).await;
+        assert_eq!(error["error"]["code"], "bad_filter");
+        assert_eq!(
+            error["error"]["message"],
+            "`Not..Valid` is not a valid NEAR account id"
+        );
     }
 }
@@ -0,0 +1,119 @@
+use actix_web::{get, web, HttpResponse};
+use chrono::{DateTime, Utc};
+pub use inteat_events_api_types::price::{PriceChangeEvent, PriceChangeFilter};
+
+use crate::{
+    admin::{parse_query, QueryError},
+    aggregate::{AggregationParams, MAX_BLOCKS_PER_AGGREGATE},
+    cursor::{EventsPage, ResponseFormat},
+    error::{ApiError, ErrorResponse},
+    event_query::EventQuery,
+    meta::EventTable,
+    registry::EventType,
+    AppState, PaginationInfo, PaginationParams, Sampling, MAX_SAMPLE_EVERY_N_BLOCKS,
+};
+
+fn price_change_query(filter: &PriceChangeFilter) -> EventQuery<PriceChangeEvent> {
+    EventQuery::new(
+        "price_change",
+        "token_account_id, price_usd, source, block_height, timestamp",
+    )
+    .filter(
+        "token_account_id = ANY({})",
+        filter.token_account_id.as_deref(),
+    )
+    .filter(
+        "token_account_id <> ALL({})",
+        filter.exclude_token_account_id.as_deref(),
+    )
+    // Prices have no receipt, and there's one per token in a block
+    .tie_breaker("token_account_id")
+}
+
+impl EventType for PriceChangeEvent {
+    const TABLE: EventTable = EventTable::PriceChange;
+    const SCOPE: &'static str = "/v0/price";
+
+    fn filtered(query: &str) -> Result<EventQuery<Self>, QueryError> {
+        let filter: PriceChangeFilter = parse_query(query)?;
+        Ok(price_change_query(&filter))
+    }
+
+    fn with_options(
+        event_query: EventQuery<Self>,
+        query: &str,
+    ) -> Result<EventQuery<Self>, QueryError> {
+        let sampling: Sampling = parse_query(query)?;
+        Ok(event_query.sample(&sampling))
+    }
+
+    fn unfiltered() -> EventQuery<Self> {
+        price_change_query(&PriceChangeFilter::default())
+    }
+
+    fn timestamp(&self) -> DateTime<Utc> {
+        self.timestamp
+    }
+
+    fn transaction_id(&self) -> Option<&str> {
+        None
+    }
+}
+
+/// Token price updates
+#[utoipa::path(
+    get,
+    path = "/v0/price/price_change",
+    tag = "Price",
+    params(PaginationParams, PriceChangeFilter, Sampling, AggregationParams),
+    responses(
+        (status = 200, body = EventsPage<PriceChangeEvent>),
+        (status = 400, body = ErrorResponse),
+        (status = 500, body = ErrorResponse),
+    )
+)]
+#[get("/price_change")]
+pub async fn price_change(
+    state: web::Data<AppState>,
+    pagination: web::Query<PaginationInfo>,
+    filter: web::Query<PriceChangeFilter>,
+    sampling: web::Query<Sampling>,
+    aggregation: web::Query<AggregationParams>,
+) -> Result<HttpResponse, ApiError> {
+    let aggregation = aggregation.parse(&[]).map_err(ApiError::Validation)?;
+    let max_blocks = if aggregation.is_some() {
+        MAX_BLOCKS_PER_AGGREGATE
+    } else {
+        state.block_limits().of(EventTable::PriceChange)
+    };
+    if pagination.blocks > max_blocks {
+        return Err(ApiError::Validation(format!(
+            "Blocks per request must be less or equal to {max_blocks}"
+        )));
+    }
+
+    if !(1..=MAX_SAMPLE_EVERY_N_BLOCKS).contains(&sampling.sample_every_n_blocks) {
+        return Err(ApiError::Validation(format!(
+            "sample_every_n_blocks must be between 1 and {MAX_SAMPLE_EVERY_N_BLOCKS}"
+        )));
+    }
+
+    let query = price_change_query(&filter).sample(&sampling);
+    if let Some(aggregation) = aggregation {
+        return query.aggregate(&aggregation, &pagination, &state).await;
+    }
+    let query = query.paginate(&pagination);
+    if pagination.format == ResponseFormat::Ndjson {
+        return query
+            .ndjson(state.pg_pool(), &pagination, |event| event.timestamp)
+            .await;
+    }
+    let mut res = query.fetch_all(&state.pg_pool()).await?;
+    Ok(pagination
+        .page(
+            &mut res,
+            |event| event.timestamp,
+            |event| &event.token_account_id,
+        )?
+        .respond(res))
+}
@@ -0,0 +1,226 @@
+use std::{
+    collections::HashMap,
+    net::IpAddr,
+    sync::{Mutex, RwLock},
+    time::{Duration, Instant},
+};
+
+use actix_web::{
+    body::{EitherBody, MessageBody},
+    dev::{ServiceRequest, ServiceResponse},
+    middleware::Next,
+    web, Error,
+};
+
+use crate::error::ApiError;
+
+/// How often buckets that are full again are forgotten, so that the map
+/// doesn't grow with every client that ever connected
+const SWEEP_INTERVAL: Duration = Duration::from_secs(60);
+
+/// New IPs aren't limited until the next sweep once this many are tracked,
+/// to bound memory when requests come from many addresses at once
+const MAX_TRACKED_IPS: usize = 100_000;
+
+/// Probes shouldn't fail because the orchestrator polls them often
+const EXEMPT_PATHS: &[&str] = &["/healthz", "/readyz"];
+
+/// Per-IP token buckets, configured with environment variables. Each IP can
+/// make `burst` requests at once, and gets `requests_per_minute` more evenly
+/// spread over a minute.
+pub struct RateLimiter {
+    /// Replaced when the config is reloaded, buckets are kept
+    settings: RwLock<RateLimitSettings>,
+    buckets: Mutex<HashMap<IpAddr, Bucket>>,
+}
+
+#[derive(Clone, Copy)]
+struct RateLimitSettings {
+    /// `RATE_LIMIT_PER_MINUTE`, rate limiting is disabled if it's not set
+    requests_per_minute: Option<u32>,
+    /// `RATE_LIMIT_BURST`, defaults to `requests_per_minute`
+    burst: u32,
+    /// `RATE_LIMIT_TRUST_FORWARDED`, use the client IP from `Forwarded` or
+    /// `X-Forwarded-For` headers set by a reverse proxy. Clients can spoof
+    /// these headers, so only set it if the proxy overwrites them.
+    trust_forwarded: bool,
+}
+
+struct Bucket {
+    tokens: f64,
+    updated_at: Instant,
+}
+
+impl RateLimiter {
+    pub fn from_env() -> Self {
+        Self {
+            settings: RwLock::new(RateLimitSettings::from_env()),
+            buckets: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Applies changed environment variables
+    pub fn reload(&self) {
+        *self.settings.write().unwrap() = RateLimitSettings::from_env();
+    }
+
+    /// Takes a token from the IP's bucket, or returns how long to wait until
+    /// there's one
+    fn acquire(&self, ip: IpAddr) -> Result<(), Duration> {
+        let settings = *self.settings.read().unwrap();
+        let Some(requests_per_minute) = settings.requests_per_minute else {
+            return Ok(());
+        };
+        let refill_per_sec = requests_per_minute as f64 / 60.0;
+        let capacity = settings.burst as f64;
+        let now = Instant::now();
+
+        let mut buckets = self.buckets.lock().unwrap();
+        if buckets.len() >= MAX_TRACKED_IPS && !buckets.contains_key(&ip) {
+            return Ok(());
+        }
+        let bucket = buckets.entry(ip).or_insert(Bucket {
+            tokens: capacity,
+            updated_at: now,
+        });
+        bucket.tokens = (bucket.tokens
+            + now.duration_since(bucket.updated_at).as_secs_f64() * refill_per_sec)
+            .min(capacity);
+        bucket.updated_at = now;
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            Ok(())
+        } else {
+            Err(Duration::from_secs_f64(
+                (1.0 - bucket.tokens) / refill_per_sec,
+            ))
+        }
+    }
+
+    /// Forgets buckets that are full again, they behave like new ones
+    fn sweep(&self) {
+        let settings = *self.settings.read().unwrap();
+        let mut buckets = self.buckets.lock().unwrap();
+        let Some(requests_per_minute) = settings.requests_per_minute else {
+            buckets.clear();
+            return;
+        };
+        let refill_per_sec = requests_per_minute as f64 / 60.0;
+        let capacity = settings.burst as f64;
+        let now = Instant::now();
+        buckets.retain(|_, bucket| {
+            bucket.tokens + now.duration_since(bucket.updated_at).as_secs_f64() * refill_per_sec
+                < capacity
+        });
+    }
+
+    fn client_ip(&self, req: &ServiceRequest) -> Option<IpAddr> {
+        if self.settings.read().unwrap().trust_forwarded {
+            if let Some(ip) = req
+                .connection_info()
+                .realip_remote_addr()
+                .and_then(parse_ip)
+            {
+                return Some(ip);
+            }
+        }
+        req.peer_addr().map(|addr| addr.ip())
+    }
+}
+
+impl RateLimitSettings {
+    fn from_env() -> Self {
+        fn parse(var: &str) -> Option<u32> {
+            std::env::var(var).ok().map(|value| {
+                value
+                    .parse()
+                    .ok()
+                    .filter(|&value| value > 0)
+                    .unwrap_or_else(|| panic!("{var} must be a positive number of requests"))
+            })
+        }
+        let requests_per_minute = parse("RATE_LIMIT_PER_MINUTE");
+        Self {
+            requests_per_minute,
+            burst: parse("RATE_LIMIT_BURST")
+                .or(requests_per_minute)
+                .unwrap_or_default(),
+            trust_forwarded: std::env::var("RATE_LIMIT_TRUST_FORWARDED").is_ok(),
+        }
+    }
+}
+
+/// Sweeps the buckets every `SWEEP_INTERVAL`
+pub fn start(rate_limiter: web::Data<RateLimiter>) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(SWEEP_INTERVAL);
+        loop {
+            interval.tick().await;
+            rate_limiter.sweep();
+        }
+    });
+}
+
+/// `realip_remote_addr` may or may not include a port
+fn parse_ip(addr: &str) -> Option<IpAddr> {
+    addr.parse().ok().or_else(|| {
+        addr.parse::<std::net::SocketAddr>()
+            .ok()
+            .map(|addr| addr.ip())
+    })
+}
+
+/// Rejects requests of clients that ran out of tokens with
+/// `429 Too Many Requests` and a `Retry-After` header
+pub async fn enforce_rate_limit(
+    rate_limiter: web::Data<RateLimiter>,
+    req: ServiceRequest,
+    next: Next<impl MessageBody>,
+) -> Result<ServiceResponse<EitherBody<impl MessageBody>>, Error> {
+    if !EXEMPT_PATHS.contains(&req.path()) {
+        if let Some(ip) = rate_limiter.client_ip(&req) {
+            if let Err(retry_after) = rate_limiter.acquire(ip) {
+                let retry_after_secs = retry_after.as_secs_f64().ceil().max(1.0) as u64;
+                return Ok(req
+                    .error_response(ApiError::RateLimited { retry_after_secs })
+                    .map_into_right_body());
+            }
+        }
+    }
+    next.call(req)
+        .await
+        .map(ServiceResponse::map_into_left_body)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sweeps_forget_only_full_buckets() {
+        let rate_limiter = RateLimiter {
+            settings: RwLock::new(RateLimitSettings {
+                requests_per_minute: Some(1),
+                burst: 2,
+                trust_forwarded: false,
+            }),
+            buckets: Mutex::new(HashMap::new()),
+        };
+        let (limited, idle) = ([10, 0, 0, 1].into(), [10, 0, 0, 2].into());
+        rate_limiter.acquire(limited).unwrap();
+        rate_limiter.acquire(limited).unwrap();
+        assert!(rate_limiter.acquire(limited).is_err());
+        rate_limiter.buckets.lock().unwrap().insert(
+            idle,
+            Bucket {
+                tokens: 2.0,
+                updated_at: Instant::now(),
+            },
+        );
+
+        rate_limiter.sweep();
+        let buckets = rate_limiter.buckets.lock().unwrap();
+        assert!(buckets.contains_key(&limited));
+        assert!(!buckets.contains_key(&idle));
+    }
+}
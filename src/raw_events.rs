@@ -0,0 +1,43 @@
+use chrono::prelude::{DateTime, Utc};
+pub use inteat_events_api_types::raw::{LogEvent, LogEventFilter};
+
+use crate::endpoint::{event_endpoint, EventEndpoint};
+use crate::event_query::EventQuery;
+use crate::meta::EventTable;
+
+impl EventEndpoint for LogEvent {
+    const TABLE: EventTable = EventTable::LogEvents;
+    type Filter = LogEventFilter;
+
+    fn query(filter: &LogEventFilter) -> EventQuery<Self> {
+        EventQuery::new(
+            "log_events",
+            "contract_id, event_standard, event_version, event_name, data, transaction_id, receipt_id, block_height, timestamp",
+        )
+        .filter("contract_id = ANY({})", filter.contract_id.as_deref())
+        .filter("event_standard = ANY({})", filter.event_standard.as_deref())
+        .filter("event_name = ANY({})", filter.event_name.as_deref())
+        .filter("transaction_id = ANY({})", filter.transaction_id.as_deref())
+        .filter("receipt_id = ANY({})", filter.receipt_id.as_deref())
+    }
+
+    fn timestamp(&self) -> DateTime<Utc> {
+        self.timestamp
+    }
+
+    fn receipt_id(&self) -> &str {
+        &self.receipt_id
+    }
+
+    fn transaction(&self) -> (&str, &str) {
+        (&self.transaction_id, &self.contract_id)
+    }
+}
+
+event_endpoint! {
+    /// NEP-297 `EVENT_JSON` logs of any standard
+    log_events(LogEvent, LogEventFilter),
+    route = "/log_events",
+    scope = "/v0/raw",
+    tag = "Raw",
+}
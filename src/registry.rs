@@ -0,0 +1,220 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use sqlx::{
+    postgres::{PgArguments, PgRow},
+    FromRow, PgPool,
+};
+
+use crate::{
+    admin::{parse_query, QueryError, Summary, SummaryValue},
+    correlated::{correlated, CorrelatedEvent},
+    endpoint::{EndpointScope, EventEndpoint},
+    event_query::EventQuery,
+    live::{live, LiveEvent},
+    meta::EventTable,
+    strict_query, Ordering, PaginationInfo,
+};
+
+/// Event type of a table, for features that take the parameters of its
+/// endpoint as a query string, like summaries and live subscriptions. Every
+/// `EventEndpoint` is one, event types with a custom handler implement it
+/// themselves.
+pub(crate) trait EventType:
+    for<'r> FromRow<'r, PgRow> + Serialize + Send + Unpin + 'static
+{
+    const TABLE: EventTable;
+    /// Scope of the endpoint, e.g. `/v0/nft`
+    const SCOPE: &'static str;
+
+    /// Query with the filters of the endpoint
+    fn filtered(query: &str) -> Result<EventQuery<Self>, QueryError>;
+
+    /// Applies the other parameters of the endpoint except pagination, e.g.
+    /// ordering, for summaries
+    fn with_options(
+        event_query: EventQuery<Self>,
+        query: &str,
+    ) -> Result<EventQuery<Self>, QueryError> {
+        let ordering: Ordering = parse_query(query)?;
+        Ok(event_query.ordering(&ordering))
+    }
+
+    /// Query of all events, to look them up by transaction
+    fn unfiltered() -> EventQuery<Self>;
+
+    /// Path of the endpoint, e.g. `/v0/nft/nft_mint`
+    fn endpoint() -> String {
+        format!("{}/{}", Self::SCOPE, Self::TABLE.as_str())
+    }
+
+    fn timestamp(&self) -> DateTime<Utc>;
+
+    /// `None` if the table has no transaction id
+    fn transaction_id(&self) -> Option<&str>;
+}
+
+impl<E: EventEndpoint + EndpointScope> EventType for E {
+    const TABLE: EventTable = <E as EventEndpoint>::TABLE;
+    const SCOPE: &'static str = <E as EndpointScope>::SCOPE;
+
+    fn filtered(query: &str) -> Result<EventQuery<Self>, QueryError> {
+        let filter: E::Filter = parse_query(query)?;
+        Ok(E::query(&filter))
+    }
+
+    fn unfiltered() -> EventQuery<Self> {
+        E::query(&Default::default())
+    }
+
+    fn timestamp(&self) -> DateTime<Utc> {
+        EventEndpoint::timestamp(self)
+    }
+
+    fn transaction_id(&self) -> Option<&str> {
+        Some(self.transaction().0)
+    }
+}
+
+/// Evaluates `$body` with `$event` standing for the event type of a table,
+/// e.g. `with_event_type!(table, |E| E::SCOPE)`
+macro_rules! with_event_type {
+    (@match $table:expr, $event:ident, $body:expr, $($variant:ident => $type:ty,)*) => {
+        match $table {
+            $(crate::meta::EventTable::$variant => {
+                type $event = $type;
+                $body
+            })*
+        }
+    };
+    ($table:expr, |$event:ident| $body:expr) => {
+        crate::registry::with_event_type!(
+            @match $table, $event, $body,
+            FtMint => crate::ft_events::FtMintEvent,
+            FtTransfer => crate::ft_events::FtTransferEvent,
+            FtBurn => crate::ft_events::FtBurnEvent,
+            NftMint => crate::nft_events::NftMintEvent,
+            NftTransfer => crate::nft_events::NftTransferEvent,
+            NftBurn => crate::nft_events::NftBurnEvent,
+            NftSale => crate::nft_events::NftSaleEvent,
+            PotlockDonation => crate::potlock_events::PotlockDonationEvent,
+            PotlockPotProjectDonation => crate::potlock_events::PotlockPotProjectDonationEvent,
+            PotlockPotDonation => crate::potlock_events::PotlockPotDonationEvent,
+            PotlockPotDeployment => crate::potlock_events::PotlockPotDeploymentEvent,
+            PotlockPotApplication => crate::potlock_events::PotlockPotApplicationEvent,
+            PotlockPotApplicationReview => crate::potlock_events::PotlockPotApplicationReviewEvent,
+            PotlockPotPayout => crate::potlock_events::PotlockPotPayoutEvent,
+            PotlockPotPayoutChallenge => crate::potlock_events::PotlockPotPayoutChallengeEvent,
+            SocialdbPost => crate::socialdb_events::SocialdbPostEvent,
+            SocialdbComment => crate::socialdb_events::SocialdbCommentEvent,
+            SocialdbLike => crate::socialdb_events::SocialdbLikeEvent,
+            SocialdbFollow => crate::socialdb_events::SocialdbFollowEvent,
+            SocialdbProfileUpdate => crate::socialdb_events::SocialdbProfileUpdateEvent,
+            PriceChange => crate::price_events::PriceChangeEvent,
+            TradePool => crate::trade_events::TradePoolEvent,
+            TradeSwap => crate::trade_events::TradeSwapEvent,
+            TradePoolChange => crate::trade_events::TradePoolChangeEvent,
+            TradeLiquidityAdd => crate::trade_events::TradeLiquidityAddEvent,
+            TradeLiquidityRemove => crate::trade_events::TradeLiquidityRemoveEvent,
+            TradeWrap => crate::trade_events::TradeWrapEvent,
+            TradeUnwrap => crate::trade_events::TradeUnwrapEvent,
+            BurrowDeposit => crate::burrow_events::BurrowDepositEvent,
+            BurrowWithdraw => crate::burrow_events::BurrowWithdrawEvent,
+            BurrowBorrow => crate::burrow_events::BurrowBorrowEvent,
+            BurrowRepay => crate::burrow_events::BurrowRepayEvent,
+            BurrowLiquidation => crate::burrow_events::BurrowLiquidationEvent,
+            LaunchCreate => crate::launch_events::LaunchCreateEvent,
+            LaunchDeposit => crate::launch_events::LaunchDepositEvent,
+            LaunchWithdraw => crate::launch_events::LaunchWithdrawEvent,
+            LaunchFinalize => crate::launch_events::LaunchFinalizeEvent,
+            StakingStake => crate::staking_events::StakingStakeEvent,
+            StakingUnstake => crate::staking_events::StakingUnstakeEvent,
+            StakingWithdraw => crate::staking_events::StakingWithdrawEvent,
+            AccountCreate => crate::account_events::AccountCreateEvent,
+            AccountDelete => crate::account_events::AccountDeleteEvent,
+            KeyAdded => crate::account_events::KeyAddedEvent,
+            KeyDeleted => crate::account_events::KeyDeletedEvent,
+            DaoProposal => crate::dao_events::DaoProposalEvent,
+            DaoVote => crate::dao_events::DaoVoteEvent,
+            DaoProposalExecute => crate::dao_events::DaoProposalExecuteEvent,
+            BridgeDeposit => crate::bridge_events::BridgeDepositEvent,
+            BridgeWithdraw => crate::bridge_events::BridgeWithdrawEvent,
+            LogEvents => crate::raw_events::LogEvent,
+        )
+    };
+}
+
+pub(crate) use with_event_type;
+
+/// Query plan or number of events of an endpoint with the given query string
+pub(crate) async fn summarize<E: EventType, T: SummaryValue>(
+    pg_pool: &PgPool,
+    query: &str,
+    summary: Summary,
+) -> Result<T, QueryError> {
+    strict_query::check_params(&E::endpoint(), query).map_err(QueryError::BadQuery)?;
+    let pagination: PaginationInfo = parse_query(query)?;
+    E::with_options(E::filtered(query)?, query)?
+        .summarize(summary, &pagination, pg_pool)
+        .await
+}
+
+/// SQL and arguments of the page that the endpoint of `table` reads for a
+/// query string, for benchmarks
+#[doc(hidden)]
+pub fn page_query(table: EventTable, query: &str) -> Result<(String, PgArguments), QueryError> {
+    with_event_type!(table, |E| {
+        strict_query::check_params(&E::endpoint(), query).map_err(QueryError::BadQuery)?;
+        let pagination: PaginationInfo = parse_query(query)?;
+        Ok(E::with_options(E::filtered(query)?, query)?
+            .paginate(&pagination)
+            .into_parts())
+    })
+}
+
+/// Checks filters from a query string without querying, e.g. of a
+/// subscription that's stored to be run later
+pub(crate) fn check_filter<E: EventType>(query: &str) -> Result<(), QueryError> {
+    strict_query::check_filters(&E::endpoint(), query).map_err(QueryError::BadQuery)?;
+    E::filtered(query).map(drop)
+}
+
+/// Page of events for live subscriptions, with filters from a query string
+pub(crate) async fn live_events<E: EventType>(
+    pg_pool: &PgPool,
+    query: &str,
+    start_block_timestamp_nanosec: i64,
+    blocks: i64,
+) -> Result<Vec<LiveEvent>, QueryError> {
+    strict_query::check_filters(&E::endpoint(), query).map_err(QueryError::BadQuery)?;
+    E::filtered(query)?
+        .live(start_block_timestamp_nanosec, blocks)
+        .fetch_all(pg_pool)
+        .await
+        .map(|events| live(events, E::timestamp))
+        .map_err(QueryError::Database)
+}
+
+/// Events that belong to any of the transactions, between `from` and `to`
+/// inclusive. None if the table has no transaction id.
+pub(crate) async fn correlated_events<E: EventType>(
+    pg_pool: &PgPool,
+    transaction_ids: &[String],
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+) -> Result<Vec<CorrelatedEvent>, sqlx::Error> {
+    if !E::TABLE.has_transaction_id() {
+        return Ok(Vec::new());
+    }
+    E::unfiltered()
+        .transactions(transaction_ids, from, to)
+        .fetch_all(pg_pool)
+        .await
+        .map(|events| {
+            correlated(events, |event| {
+                (
+                    event.transaction_id().unwrap_or_default().to_owned(),
+                    E::timestamp(event),
+                )
+            })
+        })
+}
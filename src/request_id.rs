@@ -0,0 +1,236 @@
+use std::fmt;
+
+use actix_web::{
+    body::{self, BoxBody, MessageBody},
+    dev::{ServiceRequest, ServiceResponse},
+    error::ErrorInternalServerError,
+    http::header::{self, HeaderMap, HeaderName, HeaderValue},
+    middleware::Next,
+    web::Bytes,
+    Error, HttpMessage, HttpResponse,
+};
+use serde_json::Value;
+use tracing::Span;
+use tracing_actix_web::{DefaultRootSpanBuilder, RootSpanBuilder};
+use uuid::Uuid;
+
+pub const REQUEST_ID_HEADER: &str = "x-request-id";
+
+/// Longer ids from clients are replaced, so that they can't flood the logs
+const MAX_REQUEST_ID_LENGTH: usize = 128;
+
+/// Id of a request, taken from its `X-Request-Id` header or generated. It's
+/// returned in the same header and in error responses, and logged with
+/// everything that happens while handling the request, so that a failed
+/// request a user reports can be found in the logs.
+#[derive(Clone)]
+pub struct RequestId(String);
+
+impl RequestId {
+    fn from_request(req: &ServiceRequest) -> Self {
+        let id = req
+            .headers()
+            .get(REQUEST_ID_HEADER)
+            .and_then(|id| id.to_str().ok())
+            .filter(|id| {
+                (1..=MAX_REQUEST_ID_LENGTH).contains(&id.len())
+                    && id.bytes().all(|byte| byte.is_ascii_graphic())
+            })
+            .map(ToOwned::to_owned)
+            .unwrap_or_else(|| Uuid::new_v4().to_string());
+        Self(id)
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for RequestId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// Id of a request that went through `assign_request_id`
+pub fn of(req: &impl HttpMessage) -> Option<RequestId> {
+    req.extensions().get::<RequestId>().cloned()
+}
+
+/// Outermost middleware, so that the id is known to the tracing span and the
+/// access log. Errors are small enough not to be compressed, so the id can be
+/// added to their JSON.
+pub async fn assign_request_id(
+    req: ServiceRequest,
+    next: Next<impl MessageBody + 'static>,
+) -> Result<ServiceResponse<BoxBody>, Error> {
+    let request_id = RequestId::from_request(&req);
+    req.extensions_mut().insert(request_id.clone());
+    let mut res = match next.call(req).await {
+        Ok(res) => res.map_into_boxed_body(),
+        // Errors like panics of handlers come without the request, which
+        // can't be kept here because routing needs exclusive access to it,
+        // so the id is added when actix turns the error into a response
+        Err(mut err) => {
+            err.add_response_mapper(move |mut res| {
+                insert_header(&request_id, res.headers_mut());
+                if !is_json_error(&res) {
+                    return res;
+                }
+                let (res, body) = res.into_parts();
+                // Bodies of errors are in memory, they're never streamed
+                match body.try_into_bytes() {
+                    Ok(body) => res.set_body(BoxBody::new(add_to_error(&request_id, body))),
+                    Err(body) => res.set_body(body),
+                }
+            });
+            return Err(err);
+        }
+    };
+
+    insert_header(&request_id, res.headers_mut());
+    if !is_json_error(res.response()) {
+        return Ok(res);
+    }
+    let (req, res) = res.into_parts();
+    let (res, body) = res.into_parts();
+    let body = body::to_bytes(body)
+        .await
+        .map_err(ErrorInternalServerError)?;
+    let body = add_to_error(&request_id, body);
+    Ok(ServiceResponse::new(req, res.set_body(BoxBody::new(body))))
+}
+
+fn insert_header(request_id: &RequestId, headers: &mut HeaderMap) {
+    let header_value =
+        HeaderValue::from_str(request_id.as_str()).expect("request ids are visible ASCII");
+    headers.insert(HeaderName::from_static(REQUEST_ID_HEADER), header_value);
+}
+
+fn is_json_error<B>(res: &HttpResponse<B>) -> bool {
+    (res.status().is_client_error() || res.status().is_server_error())
+        && !res.headers().contains_key(header::CONTENT_ENCODING)
+        && res
+            .headers()
+            .get(header::CONTENT_TYPE)
+            .is_some_and(|content_type| content_type.as_bytes().starts_with(b"application/json"))
+}
+
+/// Body of a JSON error with the id in `error.request_id`
+fn add_to_error(request_id: &RequestId, body: Bytes) -> Bytes {
+    match serde_json::from_slice::<Value>(&body) {
+        Ok(mut error) => match error.get_mut("error").and_then(Value::as_object_mut) {
+            Some(details) => {
+                details.insert("request_id".to_owned(), request_id.as_str().into());
+                error.to_string().into()
+            }
+            None => body,
+        },
+        Err(_) => body,
+    }
+}
+
+/// Root span of requests, with the id in `x_request_id`. `request_id` of
+/// the default span is a separate id that isn't shown to clients.
+pub struct RequestIdSpan;
+
+impl RootSpanBuilder for RequestIdSpan {
+    fn on_request_start(request: &ServiceRequest) -> Span {
+        let request_id = of(request).map(|id| id.to_string()).unwrap_or_default();
+        tracing_actix_web::root_span!(request, x_request_id = %request_id)
+    }
+
+    fn on_request_end<B: MessageBody>(span: Span, outcome: &Result<ServiceResponse<B>, Error>) {
+        DefaultRootSpanBuilder::on_request_end(span, outcome);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::{dev::Service, http::StatusCode, middleware, test, web, App, HttpResponse};
+
+    use super::*;
+    use crate::error::ApiError;
+
+    async fn not_found() -> Result<HttpResponse, ApiError> {
+        Err(ApiError::NotFound("No such event".to_owned()))
+    }
+
+    /// Header and `error.request_id` of a response
+    async fn request_ids(res: HttpResponse) -> (String, Value) {
+        let header = res
+            .headers()
+            .get(REQUEST_ID_HEADER)
+            .unwrap()
+            .to_str()
+            .unwrap();
+        let header = header.to_owned();
+        let body = body::to_bytes(res.into_body()).await.unwrap();
+        let body: Value = serde_json::from_slice(&body).unwrap();
+        (header, body["error"]["request_id"].clone())
+    }
+
+    #[actix_web::test]
+    async fn accepts_request_id_of_the_client() {
+        let app = test::init_service(
+            App::new()
+                .route("/", web::get().to(not_found))
+                .wrap(middleware::from_fn(assign_request_id)),
+        )
+        .await;
+        let req = test::TestRequest::get()
+            .insert_header((REQUEST_ID_HEADER, "client-id-1"))
+            .to_request();
+        let res = test::call_service(&app, req).await;
+        assert_eq!(res.status(), StatusCode::NOT_FOUND);
+        let (header, body) = request_ids(res.into_parts().1).await;
+        assert_eq!(header, "client-id-1");
+        assert_eq!(body, "client-id-1");
+    }
+
+    #[actix_web::test]
+    async fn generates_request_id_if_the_client_has_none_or_an_invalid_one() {
+        let app = test::init_service(
+            App::new()
+                .route("/", web::get().to(not_found))
+                .wrap(middleware::from_fn(assign_request_id)),
+        )
+        .await;
+        for invalid in [None, Some("has spaces"), Some(&*"x".repeat(129))] {
+            let mut req = test::TestRequest::get();
+            if let Some(invalid) = invalid {
+                req = req.insert_header((REQUEST_ID_HEADER, invalid));
+            }
+            let res = test::call_service(&app, req.to_request()).await;
+            let (header, body) = request_ids(res.into_parts().1).await;
+            assert!(Uuid::parse_str(&header).is_ok());
+            assert_eq!(body, header.as_str());
+        }
+    }
+
+    #[actix_web::test]
+    async fn tags_errors_of_inner_middlewares() {
+        let app = test::init_service(
+            App::new()
+                .route("/", web::get().to(HttpResponse::Ok))
+                .wrap(middleware::from_fn(
+                    |_: ServiceRequest, _: Next<BoxBody>| async {
+                        Err::<ServiceResponse, _>(ApiError::Overloaded.into())
+                    },
+                ))
+                .wrap(middleware::from_fn(assign_request_id)),
+        )
+        .await;
+        let req = test::TestRequest::get()
+            .insert_header((REQUEST_ID_HEADER, "client-id-2"))
+            .to_request();
+        let Err(err) = app.call(req).await else {
+            panic!("the error should be passed on");
+        };
+        let res = err.error_response();
+        assert_eq!(res.status(), StatusCode::SERVICE_UNAVAILABLE);
+        let (header, body) = request_ids(res).await;
+        assert_eq!(header, "client-id-2");
+        assert_eq!(body, "client-id-2");
+    }
+}
@@ -0,0 +1,219 @@
+use chrono::prelude::{DateTime, Utc};
+pub use inteat_events_api_types::socialdb::{
+    SocialdbCommentEvent, SocialdbCommentFilter, SocialdbFollowEvent, SocialdbFollowFilter,
+    SocialdbLikeEvent, SocialdbLikeFilter, SocialdbPostEvent, SocialdbPostFilter,
+    SocialdbProfileUpdateEvent, SocialdbProfileUpdateFilter,
+};
+
+use crate::{
+    endpoint::{event_endpoint, EventEndpoint},
+    event_query::EventQuery,
+    meta::EventTable,
+};
+
+impl EventEndpoint for SocialdbPostEvent {
+    const TABLE: EventTable = EventTable::SocialdbPost;
+    type Filter = SocialdbPostFilter;
+
+    fn query(filter: &SocialdbPostFilter) -> EventQuery<Self> {
+        EventQuery::new(
+            "socialdb_post",
+            "transaction_id, receipt_id, block_height, timestamp, account_id, content",
+        )
+        .filter("account_id = ANY({})", filter.account_id.as_deref())
+        .filter("transaction_id = ANY({})", filter.transaction_id.as_deref())
+        .filter("receipt_id = ANY({})", filter.receipt_id.as_deref())
+        .filter(
+            "account_id <> ALL({})",
+            filter.exclude_account_id.as_deref(),
+        )
+    }
+
+    fn timestamp(&self) -> DateTime<Utc> {
+        self.timestamp
+    }
+
+    fn receipt_id(&self) -> &str {
+        &self.receipt_id
+    }
+
+    fn transaction(&self) -> (&str, &str) {
+        (&self.transaction_id, &self.account_id)
+    }
+}
+
+event_endpoint! {
+    /// NEAR Social post events
+    socialdb_post(SocialdbPostEvent, SocialdbPostFilter),
+    route = "/socialdb_post",
+    scope = "/v0/socialdb",
+    tag = "Social",
+}
+
+impl EventEndpoint for SocialdbCommentEvent {
+    const TABLE: EventTable = EventTable::SocialdbComment;
+    type Filter = SocialdbCommentFilter;
+
+    fn query(filter: &SocialdbCommentFilter) -> EventQuery<Self> {
+        EventQuery::new(
+            "socialdb_comment",
+            "transaction_id, receipt_id, block_height, timestamp, account_id, parent_account_id, parent_block_height, content",
+        )
+        .filter("account_id = ANY({})", filter.account_id.as_deref())
+        .filter(
+            "parent_account_id = ANY({})",
+            filter.parent_account_id.as_deref(),
+        )
+        .filter("transaction_id = ANY({})", filter.transaction_id.as_deref())
+        .filter("receipt_id = ANY({})", filter.receipt_id.as_deref())
+        .filter(
+            "NOT (ARRAY[account_id, parent_account_id] && {})",
+            filter.exclude_account_id.as_deref(),
+        )
+    }
+
+    fn timestamp(&self) -> DateTime<Utc> {
+        self.timestamp
+    }
+
+    fn receipt_id(&self) -> &str {
+        &self.receipt_id
+    }
+
+    fn transaction(&self) -> (&str, &str) {
+        (&self.transaction_id, &self.account_id)
+    }
+}
+
+event_endpoint! {
+    /// NEAR Social comment events
+    socialdb_comment(SocialdbCommentEvent, SocialdbCommentFilter),
+    route = "/socialdb_comment",
+    scope = "/v0/socialdb",
+    tag = "Social",
+}
+
+impl EventEndpoint for SocialdbLikeEvent {
+    const TABLE: EventTable = EventTable::SocialdbLike;
+    type Filter = SocialdbLikeFilter;
+
+    fn query(filter: &SocialdbLikeFilter) -> EventQuery<Self> {
+        EventQuery::new(
+            "socialdb_like",
+            "transaction_id, receipt_id, block_height, timestamp, account_id, parent_account_id, parent_block_height, liked",
+        )
+        .filter("account_id = ANY({})", filter.account_id.as_deref())
+        .filter(
+            "parent_account_id = ANY({})",
+            filter.parent_account_id.as_deref(),
+        )
+        .filter("transaction_id = ANY({})", filter.transaction_id.as_deref())
+        .filter("receipt_id = ANY({})", filter.receipt_id.as_deref())
+        .filter(
+            "NOT (ARRAY[account_id, parent_account_id] && {})",
+            filter.exclude_account_id.as_deref(),
+        )
+    }
+
+    fn timestamp(&self) -> DateTime<Utc> {
+        self.timestamp
+    }
+
+    fn receipt_id(&self) -> &str {
+        &self.receipt_id
+    }
+
+    fn transaction(&self) -> (&str, &str) {
+        (&self.transaction_id, &self.account_id)
+    }
+}
+
+event_endpoint! {
+    /// NEAR Social like events
+    socialdb_like(SocialdbLikeEvent, SocialdbLikeFilter),
+    route = "/socialdb_like",
+    scope = "/v0/socialdb",
+    tag = "Social",
+}
+
+impl EventEndpoint for SocialdbFollowEvent {
+    const TABLE: EventTable = EventTable::SocialdbFollow;
+    type Filter = SocialdbFollowFilter;
+
+    fn query(filter: &SocialdbFollowFilter) -> EventQuery<Self> {
+        EventQuery::new(
+            "socialdb_follow",
+            "transaction_id, receipt_id, block_height, timestamp, account_id, parent_account_id, followed",
+        )
+        .filter("account_id = ANY({})", filter.account_id.as_deref())
+        .filter(
+            "parent_account_id = ANY({})",
+            filter.parent_account_id.as_deref(),
+        )
+        .filter("transaction_id = ANY({})", filter.transaction_id.as_deref())
+        .filter("receipt_id = ANY({})", filter.receipt_id.as_deref())
+        .filter(
+            "NOT (ARRAY[account_id, parent_account_id] && {})",
+            filter.exclude_account_id.as_deref(),
+        )
+    }
+
+    fn timestamp(&self) -> DateTime<Utc> {
+        self.timestamp
+    }
+
+    fn receipt_id(&self) -> &str {
+        &self.receipt_id
+    }
+
+    fn transaction(&self) -> (&str, &str) {
+        (&self.transaction_id, &self.account_id)
+    }
+}
+
+event_endpoint! {
+    /// NEAR Social follow events
+    socialdb_follow(SocialdbFollowEvent, SocialdbFollowFilter),
+    route = "/socialdb_follow",
+    scope = "/v0/socialdb",
+    tag = "Social",
+}
+
+impl EventEndpoint for SocialdbProfileUpdateEvent {
+    const TABLE: EventTable = EventTable::SocialdbProfileUpdate;
+    type Filter = SocialdbProfileUpdateFilter;
+
+    fn query(filter: &SocialdbProfileUpdateFilter) -> EventQuery<Self> {
+        EventQuery::new(
+            "socialdb_profile_update",
+            "transaction_id, receipt_id, block_height, timestamp, account_id, profile",
+        )
+        .filter("account_id = ANY({})", filter.account_id.as_deref())
+        .filter("transaction_id = ANY({})", filter.transaction_id.as_deref())
+        .filter("receipt_id = ANY({})", filter.receipt_id.as_deref())
+        .filter(
+            "account_id <> ALL({})",
+            filter.exclude_account_id.as_deref(),
+        )
+    }
+
+    fn timestamp(&self) -> DateTime<Utc> {
+        self.timestamp
+    }
+
+    fn receipt_id(&self) -> &str {
+        &self.receipt_id
+    }
+
+    fn transaction(&self) -> (&str, &str) {
+        (&self.transaction_id, &self.account_id)
+    }
+}
+
+event_endpoint! {
+    /// NEAR Social profile update events
+    socialdb_profile_update(SocialdbProfileUpdateEvent, SocialdbProfileUpdateFilter),
+    route = "/socialdb_profile_update",
+    scope = "/v0/socialdb",
+    tag = "Social",
+}
@@ -0,0 +1,122 @@
+use std::time::{Duration, Instant};
+
+use actix_web::{get, web, HttpRequest, HttpResponse, Responder};
+use futures_util::stream;
+use serde::Deserialize;
+use utoipa::IntoParams;
+
+use crate::{
+    admin::QueryError,
+    error_reporting,
+    live::{Subscription, POLL_INTERVAL},
+    meta::EventTable,
+    AppState,
+};
+
+/// Proxies tend to close connections that have been idle for a while, so a
+/// comment is sent when there were no events for this long
+const KEEP_ALIVE_INTERVAL: Duration = Duration::from_secs(15);
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+struct SseParams {
+    /// Starts after the latest indexed event by default
+    start_block_timestamp_nanosec: Option<i64>,
+}
+
+/// Live events as Server-Sent Events, one `data:` message per event. Accepts
+/// the same filters as the event type's endpoint.
+#[utoipa::path(
+    get,
+    path = "/v0/sse/{event_type}",
+    tag = "Live",
+    params(("event_type" = EventTable, Path), SseParams),
+    responses(
+        (status = 200, description = "One `data:` message per event", content_type = "text/event-stream"),
+        (status = 400, description = "Filters of the event type are invalid"),
+    )
+)]
+#[get("/sse/{event_type}")]
+pub async fn sse(
+    state: web::Data<AppState>,
+    event_type: web::Path<EventTable>,
+    params: web::Query<SseParams>,
+    req: HttpRequest,
+) -> impl Responder {
+    // Everything but the start is a filter of the event type
+    let filters = match serde_urlencoded::from_str::<Vec<(String, String)>>(req.query_string()) {
+        Ok(params) => serde_urlencoded::to_string(
+            params
+                .into_iter()
+                .filter(|(name, _)| name != "start_block_timestamp_nanosec")
+                .collect::<Vec<_>>(),
+        )
+        .unwrap_or_default(),
+        Err(_) => req.query_string().to_owned(),
+    };
+    let subscription = match Subscription::new(
+        &state.primary_pg_pool(),
+        event_type.into_inner(),
+        filters,
+        params.start_block_timestamp_nanosec,
+    )
+    .await
+    {
+        Ok(subscription) => subscription,
+        Err(QueryError::BadQuery(err)) => return HttpResponse::BadRequest().body(err),
+        Err(QueryError::Database(err)) => {
+            error_reporting::report_query_error(&err);
+            return HttpResponse::InternalServerError().finish();
+        }
+    };
+
+    let tail = Tail {
+        state,
+        subscription,
+        caught_up: false,
+        last_sent: Instant::now(),
+    };
+    HttpResponse::Ok()
+        .content_type("text/event-stream")
+        .insert_header(("Cache-Control", "no-cache"))
+        .streaming(stream::unfold(tail, next_chunk))
+}
+
+struct Tail {
+    state: web::Data<AppState>,
+    subscription: Subscription,
+    /// Whether the last page reached the latest indexed block
+    caught_up: bool,
+    last_sent: Instant,
+}
+
+async fn next_chunk(mut tail: Tail) -> Option<(Result<web::Bytes, actix_web::Error>, Tail)> {
+    loop {
+        if tail.caught_up {
+            tail.subscription.wait(&tail.state).await;
+        }
+        match tail.subscription.next_page(&tail.state).await {
+            Ok((events, has_more)) => {
+                tail.caught_up = !has_more;
+                if !events.is_empty() {
+                    let chunk = events
+                        .iter()
+                        .map(|event| format!("data: {event}\n\n"))
+                        .collect::<String>();
+                    tail.last_sent = Instant::now();
+                    return Some((Ok(web::Bytes::from(chunk)), tail));
+                }
+                if tail.last_sent.elapsed() >= KEEP_ALIVE_INTERVAL {
+                    tail.last_sent = Instant::now();
+                    return Some((Ok(web::Bytes::from_static(b": keep-alive\n\n")), tail));
+                }
+            }
+            Err(QueryError::Database(err)) => {
+                log::warn!("Failed to fetch events for SSE: {err}");
+                tokio::time::sleep(POLL_INTERVAL).await;
+            }
+            // Filters were already validated when subscribing
+            Err(QueryError::BadQuery(_)) => return None,
+        }
+    }
+}
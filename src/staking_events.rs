@@ -0,0 +1,125 @@
+use chrono::prelude::{DateTime, Utc};
+pub use inteat_events_api_types::staking::{
+    StakingStakeEvent, StakingStakeFilter, StakingUnstakeEvent, StakingUnstakeFilter,
+    StakingWithdrawEvent, StakingWithdrawFilter,
+};
+
+use crate::endpoint::{event_endpoint, EventEndpoint};
+use crate::event_query::EventQuery;
+use crate::meta::EventTable;
+
+impl EventEndpoint for StakingStakeEvent {
+    const TABLE: EventTable = EventTable::StakingStake;
+    const SUM_FIELDS: &'static [(&'static str, &'static str)] =
+        &[("amount", "amount"), ("shares", "shares")];
+    type Filter = StakingStakeFilter;
+
+    fn query(filter: &StakingStakeFilter) -> EventQuery<Self> {
+        EventQuery::new(
+            "staking_stake",
+            "validator_id, delegator_id, amount, shares, transaction_id, receipt_id, block_height, timestamp",
+        )
+        .filter("validator_id = ANY({})", filter.validator_id.as_deref())
+        .filter("delegator_id = ANY({})", filter.delegator_id.as_deref())
+        .filter("transaction_id = ANY({})", filter.transaction_id.as_deref())
+        .filter("receipt_id = ANY({})", filter.receipt_id.as_deref())
+        .filter("delegator_id <> ALL({})", filter.exclude_account_id.as_deref())
+    }
+
+    fn timestamp(&self) -> DateTime<Utc> {
+        self.timestamp
+    }
+
+    fn receipt_id(&self) -> &str {
+        &self.receipt_id
+    }
+
+    fn transaction(&self) -> (&str, &str) {
+        (&self.transaction_id, &self.delegator_id)
+    }
+}
+
+event_endpoint! {
+    /// Staking pool stake events
+    staking_stake(StakingStakeEvent, StakingStakeFilter),
+    route = "/staking_stake",
+    scope = "/v0/staking",
+    tag = "Staking",
+}
+
+impl EventEndpoint for StakingUnstakeEvent {
+    const TABLE: EventTable = EventTable::StakingUnstake;
+    const SUM_FIELDS: &'static [(&'static str, &'static str)] =
+        &[("amount", "amount"), ("shares", "shares")];
+    type Filter = StakingUnstakeFilter;
+
+    fn query(filter: &StakingUnstakeFilter) -> EventQuery<Self> {
+        EventQuery::new(
+            "staking_unstake",
+            "validator_id, delegator_id, amount, shares, transaction_id, receipt_id, block_height, timestamp",
+        )
+        .filter("validator_id = ANY({})", filter.validator_id.as_deref())
+        .filter("delegator_id = ANY({})", filter.delegator_id.as_deref())
+        .filter("transaction_id = ANY({})", filter.transaction_id.as_deref())
+        .filter("receipt_id = ANY({})", filter.receipt_id.as_deref())
+        .filter("delegator_id <> ALL({})", filter.exclude_account_id.as_deref())
+    }
+
+    fn timestamp(&self) -> DateTime<Utc> {
+        self.timestamp
+    }
+
+    fn receipt_id(&self) -> &str {
+        &self.receipt_id
+    }
+
+    fn transaction(&self) -> (&str, &str) {
+        (&self.transaction_id, &self.delegator_id)
+    }
+}
+
+event_endpoint! {
+    /// Staking pool unstake events
+    staking_unstake(StakingUnstakeEvent, StakingUnstakeFilter),
+    route = "/staking_unstake",
+    scope = "/v0/staking",
+    tag = "Staking",
+}
+
+impl EventEndpoint for StakingWithdrawEvent {
+    const TABLE: EventTable = EventTable::StakingWithdraw;
+    const SUM_FIELDS: &'static [(&'static str, &'static str)] = &[("amount", "amount")];
+    type Filter = StakingWithdrawFilter;
+
+    fn query(filter: &StakingWithdrawFilter) -> EventQuery<Self> {
+        EventQuery::new(
+            "staking_withdraw",
+            "validator_id, delegator_id, amount, transaction_id, receipt_id, block_height, timestamp",
+        )
+        .filter("validator_id = ANY({})", filter.validator_id.as_deref())
+        .filter("delegator_id = ANY({})", filter.delegator_id.as_deref())
+        .filter("transaction_id = ANY({})", filter.transaction_id.as_deref())
+        .filter("receipt_id = ANY({})", filter.receipt_id.as_deref())
+        .filter("delegator_id <> ALL({})", filter.exclude_account_id.as_deref())
+    }
+
+    fn timestamp(&self) -> DateTime<Utc> {
+        self.timestamp
+    }
+
+    fn receipt_id(&self) -> &str {
+        &self.receipt_id
+    }
+
+    fn transaction(&self) -> (&str, &str) {
+        (&self.transaction_id, &self.delegator_id)
+    }
+}
+
+event_endpoint! {
+    /// Staking pool withdrawal events
+    staking_withdraw(StakingWithdrawEvent, StakingWithdrawFilter),
+    route = "/staking_withdraw",
+    scope = "/v0/staking",
+    tag = "Staking",
+}
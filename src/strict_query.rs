@@ -0,0 +1,401 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    sync::OnceLock,
+};
+
+use actix_web::{
+    body::{EitherBody, MessageBody},
+    dev::{ServiceRequest, ServiceResponse},
+    http::{
+        header::{HeaderName, HeaderValue},
+        Uri,
+    },
+    middleware::Next,
+    Error,
+};
+use utoipa::{openapi::path::ParameterIn, IntoParams, OpenApi};
+
+use crate::{
+    aggregate::AggregationParams, enrich::EnrichParams, error::ApiError, openapi::ApiDoc,
+    PaginationParams,
+};
+
+/// Query parameters of each documented endpoint, by route, e.g.
+/// `/v0/nft/nft_mint`. Parameters come from the same structs the endpoints
+/// parse, so the list can't drift apart from what's accepted.
+fn known_params() -> &'static BTreeMap<String, BTreeSet<String>> {
+    static KNOWN_PARAMS: OnceLock<BTreeMap<String, BTreeSet<String>>> = OnceLock::new();
+    KNOWN_PARAMS.get_or_init(|| {
+        ApiDoc::openapi()
+            .paths
+            .paths
+            .into_iter()
+            .filter_map(|(path, item)| {
+                let params = item
+                    .get?
+                    .parameters
+                    .unwrap_or_default()
+                    .into_iter()
+                    .filter(|param| param.parameter_in == ParameterIn::Query)
+                    .map(|param| param.name)
+                    .collect();
+                Some((path, params))
+            })
+            .collect()
+    })
+}
+
+/// Filters of each documented event endpoint, which are its query parameters
+/// except the ones of pagination, aggregation and enrichment
+fn known_filters() -> &'static BTreeMap<String, BTreeSet<String>> {
+    static KNOWN_FILTERS: OnceLock<BTreeMap<String, BTreeSet<String>>> = OnceLock::new();
+    KNOWN_FILTERS.get_or_init(|| {
+        let shared = [
+            PaginationParams::into_params(|| None),
+            AggregationParams::into_params(|| None),
+            EnrichParams::into_params(|| None),
+        ]
+        .into_iter()
+        .flatten()
+        .map(|param| param.name)
+        .collect::<BTreeSet<_>>();
+        known_params()
+            .iter()
+            .map(|(path, params)| (path.clone(), params - &shared))
+            .collect()
+    })
+}
+
+/// Filters that are named differently on some endpoints, as `(canonical,
+/// deprecated)`. Responses call the contract of an event `contract_id`, so
+/// it's accepted as a filter everywhere, same as on `/v1`.
+const FILTER_ALIASES: &[(&str, &str)] = &[
+    ("contract_id", "token_account_id"),
+    ("exclude_contract_id", "exclude_token_account_id"),
+];
+
+/// Route of counts, which accept the same parameters as their endpoint
+const COUNT_ROUTE: &str = "/v0/{category}/{endpoint}/count";
+
+/// Routes that take the filters of the event type in the path, which can't be
+/// listed in the spec
+const EVENT_TYPE_ROUTES: &[&str] = &["/v0/sse/{event_type}", "/v1/events/{event_type}"];
+
+/// Query parameters of the endpoint a request is routed to, if it's documented
+fn endpoint_params(req: &ServiceRequest) -> Option<&'static BTreeSet<String>> {
+    let endpoint = req.match_pattern().unwrap_or_else(|| req.path().to_owned());
+    match endpoint.as_str() {
+        COUNT_ROUTE => req
+            .path()
+            .strip_suffix("/count")
+            .and_then(|counted| known_params().get(counted)),
+        route if EVENT_TYPE_ROUTES.contains(&route) => None,
+        route => known_params().get(route),
+    }
+}
+
+/// Accepts the canonical names of filters on endpoints that take them under
+/// a deprecated name, e.g. `contract_id` for `token_account_id`. Requests
+/// that use a deprecated name get a `Deprecation: true` header, and a
+/// `Warning` with the name to use instead.
+pub async fn resolve_aliases(
+    mut req: ServiceRequest,
+    next: Next<impl MessageBody>,
+) -> Result<ServiceResponse<EitherBody<impl MessageBody>>, Error> {
+    let (Some(known), Ok(params)) = (
+        endpoint_params(&req),
+        serde_urlencoded::from_str::<Vec<(String, String)>>(req.query_string()),
+    ) else {
+        return next
+            .call(req)
+            .await
+            .map(ServiceResponse::map_into_left_body);
+    };
+    let aliases = FILTER_ALIASES
+        .iter()
+        .filter(|(canonical, deprecated)| {
+            !known.contains(*canonical) && known.contains(*deprecated)
+        })
+        .collect::<Vec<_>>();
+    let has = |name: &str| params.iter().any(|(param, _)| param == name);
+    if let Some((canonical, deprecated)) = aliases
+        .iter()
+        .find(|(canonical, deprecated)| has(canonical) && has(deprecated))
+    {
+        let err = ApiError::BadFilter(format!(
+            "`{canonical}` and `{deprecated}` are the same filter, use `{canonical}`"
+        ));
+        return Ok(req.error_response(err).map_into_right_body());
+    }
+    let deprecated = aliases
+        .iter()
+        .filter(|(_, deprecated)| has(deprecated))
+        .map(|(canonical, deprecated)| format!("`{deprecated}` is deprecated, use `{canonical}`"))
+        .collect::<Vec<_>>();
+
+    if aliases.iter().any(|(canonical, _)| has(canonical)) {
+        let params = params
+            .into_iter()
+            .map(|(name, value)| {
+                let name = aliases
+                    .iter()
+                    .find(|(canonical, _)| *canonical == name)
+                    .map_or(name, |(_, deprecated)| (*deprecated).to_owned());
+                (name, value)
+            })
+            .collect::<Vec<_>>();
+        if let Ok(query) = serde_urlencoded::to_string(&params) {
+            let mut parts = req.uri().clone().into_parts();
+            parts.path_and_query = format!("{}?{query}", req.path()).parse().ok();
+            if let Ok(uri) = Uri::from_parts(parts) {
+                req.head_mut().uri = uri;
+            }
+        }
+    }
+
+    let mut res = next.call(req).await?;
+    if !deprecated.is_empty() {
+        let headers = res.headers_mut();
+        headers.insert(
+            HeaderName::from_static("deprecation"),
+            HeaderValue::from_static("true"),
+        );
+        if let Ok(warning) = HeaderValue::from_str(&format!("299 - \"{}\"", deprecated.join("; ")))
+        {
+            headers.insert(HeaderName::from_static("warning"), warning);
+        }
+    }
+    Ok(res.map_into_left_body())
+}
+
+/// Rejects requests to documented endpoints with query parameters that the
+/// endpoint doesn't accept, so that a typo like `projectid=` isn't silently
+/// ignored and doesn't return events of all projects.
+pub async fn reject_unknown_params(
+    req: ServiceRequest,
+    next: Next<impl MessageBody>,
+) -> Result<ServiceResponse<EitherBody<impl MessageBody>>, Error> {
+    if let Some(known) = endpoint_params(&req) {
+        if let Err(message) = check(req.query_string(), known) {
+            return Ok(req
+                .error_response(ApiError::BadFilter(message))
+                .map_into_right_body());
+        }
+    }
+    next.call(req)
+        .await
+        .map(ServiceResponse::map_into_left_body)
+}
+
+/// Rejects parameters of a query string that the event endpoint, e.g.
+/// `/v0/nft/nft_mint`, doesn't accept, for query strings that don't come
+/// through its route, like summaries
+pub(crate) fn check_params(endpoint: &str, query: &str) -> Result<(), String> {
+    known_params()
+        .get(endpoint)
+        .map_or(Ok(()), |known| check(query, known))
+}
+
+/// Rejects parameters of a query string that aren't filters of the event
+/// endpoint, for live subscriptions, which don't paginate
+pub(crate) fn check_filters(endpoint: &str, query: &str) -> Result<(), String> {
+    known_filters()
+        .get(endpoint)
+        .map_or(Ok(()), |known| check(query, known))
+}
+
+fn check(query: &str, known: &BTreeSet<String>) -> Result<(), String> {
+    // Query strings that can't be parsed are reported by the parser
+    let Ok(params) = serde_urlencoded::from_str::<Vec<(String, String)>>(query) else {
+        return Ok(());
+    };
+    match params.iter().find(|(name, _)| !known.contains(name)) {
+        Some((unknown, _)) => Err(unknown_param_message(unknown, known)),
+        None => Ok(()),
+    }
+}
+
+/// Suggests the parameter that was likely meant, ignoring case and
+/// underscores, e.g. `project_id` for `projectId`, or lists all of them
+fn unknown_param_message(unknown: &str, known: &BTreeSet<String>) -> String {
+    let normalize = |name: &str| name.replace(['_', '-'], "").to_lowercase();
+    match known
+        .iter()
+        .find(|name| normalize(name) == normalize(unknown))
+    {
+        Some(suggestion) => {
+            format!("Unknown query parameter `{unknown}`, did you mean `{suggestion}`?")
+        }
+        None => format!(
+            "Unknown query parameter `{unknown}`, expected one of: {}",
+            known
+                .iter()
+                .map(|name| format!("`{name}`"))
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::{
+        body::BoxBody,
+        http::StatusCode,
+        middleware,
+        test::{call_service, init_service, read_body, TestRequest},
+        web, App, HttpRequest, HttpResponse,
+    };
+    use serde_json::Value;
+
+    use super::*;
+    use crate::{
+        meta::EventTable,
+        registry::{with_event_type, EventType},
+    };
+
+    /// Parameters that every event endpoint takes
+    const PAGINATION_PARAMS: &[&str] = &[
+        "start_block_timestamp_nanosec",
+        "end_block_timestamp_nanosec",
+        "cursor",
+        "blocks",
+        "limit",
+        "order",
+        "format",
+    ];
+
+    async fn echo_query(req: HttpRequest) -> HttpResponse {
+        HttpResponse::Ok().body(req.query_string().to_owned())
+    }
+
+    /// Response of a request that went through both middlewares, in the same
+    /// order as in the server
+    async fn call(uri: &str) -> ServiceResponse<BoxBody> {
+        let app = init_service(
+            App::new()
+                .route("/v0/ft/ft_transfer", web::get().to(echo_query))
+                .route("/v0/nft/nft_sale", web::get().to(echo_query))
+                .route("/v0/potlock/project_summary", web::get().to(echo_query))
+                .route("/v0/{category}/{endpoint}/count", web::get().to(echo_query))
+                .wrap(middleware::from_fn(reject_unknown_params))
+                .wrap(middleware::from_fn(resolve_aliases)),
+        )
+        .await;
+        let req = TestRequest::get().uri(uri).to_request();
+        call_service(&app, req).await.map_into_boxed_body()
+    }
+
+    async fn body(res: ServiceResponse<BoxBody>) -> String {
+        String::from_utf8(read_body(res).await.to_vec()).unwrap()
+    }
+
+    async fn error_message(res: ServiceResponse<BoxBody>) -> String {
+        assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+        let error: Value = serde_json::from_str(&body(res).await).unwrap();
+        assert_eq!(error["error"]["code"], "bad_filter");
+        error["error"]["message"].as_str().unwrap().to_owned()
+    }
+
+    #[test]
+    fn every_event_endpoint_is_documented_with_pagination_params() {
+        for table in EventTable::ALL {
+            let endpoint = with_event_type!(table, |E| E::endpoint());
+            let params = known_params()
+                .get(&endpoint)
+                .unwrap_or_else(|| panic!("{endpoint} isn't documented"));
+            for param in PAGINATION_PARAMS {
+                assert!(params.contains(*param), "{} {param}", table.as_str());
+            }
+        }
+    }
+
+    #[actix_web::test]
+    async fn unknown_params_are_rejected_with_a_suggestion() {
+        let res = call("/v0/ft/ft_transfer?oldOwnerId=a.near").await;
+        assert_eq!(
+            error_message(res).await,
+            "Unknown query parameter `oldOwnerId`, did you mean `old_owner_id`?"
+        );
+        let res = call("/v0/ft/ft_transfer?sender=a.near").await;
+        assert!(error_message(res)
+            .await
+            .starts_with("Unknown query parameter `sender`, expected one of: "));
+
+        let res = call("/v0/ft/ft_transfer?old_owner_id=a.near&blocks=5&limit=10").await;
+        assert_eq!(res.status(), StatusCode::OK);
+    }
+
+    #[actix_web::test]
+    async fn endpoints_other_than_event_pages_reject_unknown_params() {
+        let res = call("/v0/potlock/project_summary?projectid=a.near").await;
+        assert_eq!(
+            error_message(res).await,
+            "Unknown query parameter `projectid`, did you mean `project_id`?"
+        );
+        let res = call("/v0/potlock/project_summary?project_id=a.near").await;
+        assert_eq!(res.status(), StatusCode::OK);
+    }
+
+    #[test]
+    fn live_filters_are_checked_without_pagination() {
+        let endpoint = "/v0/ft/ft_transfer";
+        assert_eq!(check_filters(endpoint, "old_owner_id=a.near"), Ok(()));
+        assert_eq!(
+            check_filters(endpoint, "oldownerid=a.near"),
+            Err("Unknown query parameter `oldownerid`, did you mean `old_owner_id`?".to_owned())
+        );
+        assert!(check_filters(endpoint, "old_owner_id=a.near&blocks=5")
+            .unwrap_err()
+            .starts_with("Unknown query parameter `blocks`, expected one of: "));
+
+        assert_eq!(
+            check_params(endpoint, "old_owner_id=a.near&blocks=5"),
+            Ok(())
+        );
+        assert!(check_params(endpoint, "sender=a.near").is_err());
+    }
+
+    #[actix_web::test]
+    async fn counts_take_the_params_of_their_endpoint() {
+        let res = call("/v0/ft/ft_transfer/count?old_owner_id=a.near").await;
+        assert_eq!(res.status(), StatusCode::OK);
+        let res = call("/v0/ft/ft_transfer/count?old_owner=a.near").await;
+        assert!(error_message(res)
+            .await
+            .starts_with("Unknown query parameter `old_owner`"));
+    }
+
+    #[actix_web::test]
+    async fn contract_id_is_rewritten_to_the_filter_of_the_endpoint() {
+        let res = call("/v0/ft/ft_transfer?contract_id=usdt.near&blocks=5").await;
+        assert_eq!(res.status(), StatusCode::OK);
+        assert!(!res.headers().contains_key("deprecation"));
+        assert_eq!(body(res).await, "token_account_id=usdt.near&blocks=5");
+
+        let res = call("/v0/ft/ft_transfer/count?contract_id=usdt.near").await;
+        assert_eq!(body(res).await, "token_account_id=usdt.near");
+
+        // Endpoints that call it `contract_id` already take it as it is
+        let res = call("/v0/nft/nft_sale?contract_id=x.paras.near").await;
+        assert_eq!(body(res).await, "contract_id=x.paras.near");
+
+        let res = call("/v0/ft/ft_transfer?contract_id=usdt.near&token_account_id=usdt.near").await;
+        assert_eq!(
+            error_message(res).await,
+            "`contract_id` and `token_account_id` are the same filter, use `contract_id`"
+        );
+    }
+
+    #[actix_web::test]
+    async fn deprecated_names_get_deprecation_and_warning_headers() {
+        let res = call("/v0/ft/ft_transfer?token_account_id=usdt.near").await;
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(res.headers().get("deprecation").unwrap(), "true");
+        assert_eq!(
+            res.headers().get("warning").unwrap(),
+            "299 - \"`token_account_id` is deprecated, use `contract_id`\""
+        );
+        assert_eq!(body(res).await, "token_account_id=usdt.near");
+    }
+}
@@ -0,0 +1,77 @@
+use opentelemetry::{global, trace::TracerProvider as _, KeyValue};
+use opentelemetry_otlp::WithExportConfig;
+use opentelemetry_sdk::{
+    propagation::TraceContextPropagator,
+    runtime,
+    trace::{Config, TracerProvider},
+    Resource,
+};
+use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt, EnvFilter};
+
+const DEFAULT_SERVICE_NAME: &str = "events-api-http-server";
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum LogFormat {
+    /// Human-readable lines, with the access log in the format of
+    /// `actix_web::middleware::Logger`
+    Text,
+    /// One JSON object per line, with a structured event per request
+    Json,
+}
+
+impl LogFormat {
+    /// Reads `LOG_FORMAT`, `text` by default
+    pub fn from_env() -> Self {
+        match std::env::var("LOG_FORMAT").as_deref() {
+            Err(_) | Ok("text") => Self::Text,
+            Ok("json") => Self::Json,
+            Ok(_) => panic!("LOG_FORMAT must be `text` or `json`"),
+        }
+    }
+}
+
+/// Logs to stdout in `log_format`, filtered with `RUST_LOG` (`info` by default). If
+/// `OTEL_EXPORTER_OTLP_ENDPOINT` is set, e.g. `http://localhost:4317`, spans
+/// of requests and their queries are also exported there over OTLP, joining
+/// the trace of the caller if it sent a `traceparent` header. Records of the
+/// `log` crate become events of the current span.
+pub fn init(log_format: LogFormat) {
+    let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info"));
+    let otel = std::env::var("OTEL_EXPORTER_OTLP_ENDPOINT")
+        .ok()
+        .map(|endpoint| {
+            let service_name =
+                std::env::var("OTEL_SERVICE_NAME").unwrap_or(DEFAULT_SERVICE_NAME.to_string());
+            let tracer_provider: TracerProvider =
+                opentelemetry_otlp::new_pipeline()
+                    .tracing()
+                    .with_exporter(
+                        opentelemetry_otlp::new_exporter()
+                            .tonic()
+                            .with_endpoint(endpoint),
+                    )
+                    .with_trace_config(Config::default().with_resource(Resource::new([
+                        KeyValue::new("service.name", service_name),
+                    ])))
+                    .install_batch(runtime::Tokio)
+                    .expect("Failed to set up the OTLP exporter");
+            global::set_text_map_propagator(TraceContextPropagator::new());
+            global::set_tracer_provider(tracer_provider.clone());
+            tracing_opentelemetry::layer().with_tracer(tracer_provider.tracer(DEFAULT_SERVICE_NAME))
+        });
+    tracing_subscriber::registry()
+        .with(filter)
+        .with((log_format == LogFormat::Text).then(tracing_subscriber::fmt::layer))
+        .with(
+            (log_format == LogFormat::Json)
+                .then(|| tracing_subscriber::fmt::layer().json().flatten_event(true)),
+        )
+        .with(otel)
+        .init();
+}
+
+/// Exports spans that are still buffered, so the last requests before a
+/// shutdown aren't lost
+pub fn shutdown() {
+    global::shutdown_tracer_provider();
+}
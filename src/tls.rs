@@ -0,0 +1,187 @@
+use std::{
+    fs::File,
+    io::BufReader,
+    sync::{Arc, RwLock},
+    time::{Duration, SystemTime},
+};
+
+use futures_util::StreamExt;
+use rustls::{
+    crypto::ring::sign::any_supported_type,
+    server::{danger::ClientCertVerifier, ClientHello, ResolvesServerCert, WebPkiClientVerifier},
+    sign::CertifiedKey,
+    ServerConfig,
+};
+use rustls_acme::{acme::ACME_TLS_ALPN_NAME, caches::DirCache, AcmeConfig};
+
+const DEFAULT_ACME_CACHE_DIR: &str = "acme-cache";
+
+/// How often the files of `SSL` are checked for a renewed certificate
+const CERT_POLL_INTERVAL: Duration = Duration::from_secs(60);
+
+/// TLS config of `https://` addresses, with certificates of `SSL` or
+/// obtained with ACME, `None` if neither is configured
+pub fn server_config() -> Option<ServerConfig> {
+    let ssl = std::env::var("SSL").ok();
+    let acme_domains = std::env::var("ACME_DOMAIN").ok();
+    match (ssl, acme_domains) {
+        (Some(_), Some(_)) => panic!("Only one of SSL and ACME_DOMAIN can be set"),
+        (Some(files), None) => Some(from_files(&files)),
+        (None, Some(domains)) => Some(acme(&domains)),
+        (None, None) => None,
+    }
+}
+
+/// `SSL=<cert_file>,<key_file>`. The files are checked for changes, e.g. by
+/// certbot renewing the certificate, and new connections use the new
+/// certificate, while open ones aren't interrupted.
+fn from_files(files: &str) -> ServerConfig {
+    let (cert_file, key_file) = files
+        .split_once(',')
+        .map(|(cert_file, key_file)| (cert_file.trim().to_owned(), key_file.trim().to_owned()))
+        .expect("SSL must be <cert_file>,<key_file>");
+    let certified_key = load_certified_key(&cert_file, &key_file)
+        .unwrap_or_else(|err| panic!("Failed to load the SSL certificate: {err}"));
+    let client_cert_verifier =
+        client_cert_verifier().unwrap_or_else(|err| panic!("Failed to load SSL_CLIENT_CA: {err}"));
+    let resolver = Arc::new(ReloadingCert {
+        certified_key: RwLock::new(Arc::new(certified_key)),
+    });
+
+    let watched = resolver.clone();
+    tokio::spawn(async move {
+        let mut modified = modified_times(&cert_file, &key_file);
+        let mut interval = tokio::time::interval(CERT_POLL_INTERVAL);
+        loop {
+            interval.tick().await;
+            let now_modified = modified_times(&cert_file, &key_file);
+            if now_modified == modified {
+                continue;
+            }
+            // The files may be replaced one after the other, so a pair that
+            // doesn't load is retried on the next check
+            match load_certified_key(&cert_file, &key_file) {
+                Ok(certified_key) => {
+                    *watched.certified_key.write().unwrap() = Arc::new(certified_key);
+                    modified = now_modified;
+                    log::info!("Reloaded the SSL certificate");
+                }
+                Err(err) => log::warn!("Failed to reload the SSL certificate: {err}"),
+            }
+        }
+    });
+
+    ServerConfig::builder()
+        .with_client_cert_verifier(client_cert_verifier)
+        .with_cert_resolver(resolver)
+}
+
+/// Certificate of `SSL`, replaced when its files change
+#[derive(Debug)]
+struct ReloadingCert {
+    certified_key: RwLock<Arc<CertifiedKey>>,
+}
+
+impl ResolvesServerCert for ReloadingCert {
+    fn resolve(&self, _client_hello: ClientHello) -> Option<Arc<CertifiedKey>> {
+        Some(self.certified_key.read().unwrap().clone())
+    }
+}
+
+fn load_certified_key(cert_file: &str, key_file: &str) -> Result<CertifiedKey, String> {
+    let mut certs_reader = BufReader::new(
+        File::open(cert_file).map_err(|err| format!("Failed to open {cert_file}: {err}"))?,
+    );
+    let mut key_reader = BufReader::new(
+        File::open(key_file).map_err(|err| format!("Failed to open {key_file}: {err}"))?,
+    );
+    let certs = rustls_pemfile::certs(&mut certs_reader)
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|err| format!("Failed to read {cert_file}: {err}"))?;
+    if certs.is_empty() {
+        return Err(format!("{cert_file} has no certificates"));
+    }
+    let key = rustls_pemfile::private_key(&mut key_reader)
+        .map_err(|err| format!("Failed to read {key_file}: {err}"))?
+        .ok_or_else(|| format!("{key_file} has no private key"))?;
+    let signing_key = any_supported_type(&key)
+        .map_err(|err| format!("Unsupported private key in {key_file}: {err}"))?;
+    Ok(CertifiedKey::new(certs, signing_key))
+}
+
+/// Metadata follows symlinks, so certificates that are swapped by pointing a
+/// link at new files, like certbot's `live` directory, are noticed too
+fn modified_times(cert_file: &str, key_file: &str) -> [Option<SystemTime>; 2] {
+    [cert_file, key_file].map(|file| {
+        std::fs::metadata(file)
+            .and_then(|metadata| metadata.modified())
+            .ok()
+    })
+}
+
+/// Certificates for the comma-separated domains of `ACME_DOMAIN`, obtained
+/// from Let's Encrypt with the TLS-ALPN-01 challenge, so one of the
+/// addresses must be reachable on port 443 of the domains. Certificates and
+/// the account are kept in `ACME_CACHE_DIR`, and renewed certificates are
+/// used for new connections right away.
+fn acme(domains: &str) -> ServerConfig {
+    // Let's Encrypt connects for the challenge without a client certificate
+    if std::env::var("SSL_CLIENT_CA").is_ok() {
+        panic!("SSL_CLIENT_CA can't be used with ACME_DOMAIN, it would reject the challenge");
+    }
+    let domains = domains
+        .split(',')
+        .map(str::trim)
+        .filter(|domain| !domain.is_empty())
+        .map(ToOwned::to_owned)
+        .collect::<Vec<_>>();
+    let email = std::env::var("ACME_EMAIL").expect("ACME_EMAIL must be set with ACME_DOMAIN");
+    let cache_dir = std::env::var("ACME_CACHE_DIR").unwrap_or(DEFAULT_ACME_CACHE_DIR.to_string());
+    // Staging certificates aren't trusted, but its rate limits are much higher
+    let production = std::env::var("ACME_STAGING").is_err();
+    let mut state = AcmeConfig::new(domains)
+        .contact_push(format!("mailto:{email}"))
+        .cache(DirCache::new(cache_dir))
+        .directory_lets_encrypt(production)
+        .state();
+    let resolver = state.resolver();
+    // Orders and renews certificates while it's polled
+    tokio::spawn(async move {
+        while let Some(event) = state.next().await {
+            match event {
+                Ok(event) => log::info!("ACME: {event:?}"),
+                Err(err) => log::error!("ACME failed: {err:?}"),
+            }
+        }
+    });
+
+    let mut config = ServerConfig::builder()
+        .with_no_client_auth()
+        .with_cert_resolver(resolver);
+    config.alpn_protocols.push(ACME_TLS_ALPN_NAME.to_vec());
+    config
+}
+
+/// Clients must present a certificate signed by a CA of `SSL_CLIENT_CA` if
+/// it's set
+fn client_cert_verifier() -> Result<Arc<dyn ClientCertVerifier>, String> {
+    let Ok(ca_file) = std::env::var("SSL_CLIENT_CA") else {
+        return Ok(WebPkiClientVerifier::no_client_auth());
+    };
+    let mut ca_reader = BufReader::new(
+        File::open(&ca_file).map_err(|err| format!("Failed to open {ca_file}: {err}"))?,
+    );
+    let mut roots = rustls::RootCertStore::empty();
+    for cert in rustls_pemfile::certs(&mut ca_reader) {
+        let cert = cert.map_err(|err| format!("Failed to read {ca_file}: {err}"))?;
+        roots
+            .add(cert)
+            .map_err(|err| format!("Invalid CA certificate in {ca_file}: {err}"))?;
+    }
+    if roots.is_empty() {
+        return Err(format!("{ca_file} has no certificates"));
+    }
+    WebPkiClientVerifier::builder(Arc::new(roots))
+        .build()
+        .map_err(|err| format!("Failed to use the CA certificates of {ca_file}: {err}"))
+}
@@ -0,0 +1,126 @@
+use std::{collections::BTreeSet, sync::RwLock};
+
+use actix_web::{
+    body::{EitherBody, MessageBody},
+    dev::{ServiceRequest, ServiceResponse},
+    middleware::Next,
+    web, Error,
+};
+
+use crate::error::ApiError;
+
+/// Endpoints that are temporarily turned off, identified by their route
+/// pattern, e.g. `/v0/trade/trade_pool_change`. Shared by all workers.
+#[derive(Default)]
+pub struct EndpointToggles {
+    disabled: RwLock<BTreeSet<String>>,
+}
+
+impl EndpointToggles {
+    /// Initial state comes from comma-separated `DISABLED_ENDPOINTS`
+    pub fn from_env() -> Self {
+        let disabled = std::env::var("DISABLED_ENDPOINTS")
+            .map(|endpoints| {
+                endpoints
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|endpoint| !endpoint.is_empty())
+                    .map(ToOwned::to_owned)
+                    .collect()
+            })
+            .unwrap_or_default();
+        Self {
+            disabled: RwLock::new(disabled),
+        }
+    }
+
+    pub fn is_disabled(&self, endpoint: &str) -> bool {
+        self.disabled.read().unwrap().contains(endpoint)
+    }
+
+    pub fn disable(&self, endpoint: String) {
+        self.disabled.write().unwrap().insert(endpoint);
+    }
+
+    pub fn enable(&self, endpoint: &str) {
+        self.disabled.write().unwrap().remove(endpoint);
+    }
+
+    pub fn disabled(&self) -> Vec<String> {
+        self.disabled.read().unwrap().iter().cloned().collect()
+    }
+}
+
+pub async fn reject_disabled(
+    toggles: web::Data<EndpointToggles>,
+    req: ServiceRequest,
+    next: Next<impl MessageBody>,
+) -> Result<ServiceResponse<EitherBody<impl MessageBody>>, Error> {
+    let endpoint = req.match_pattern().unwrap_or_else(|| req.path().to_owned());
+    // Counts run the same query as their endpoint, so they're disabled with it
+    let counted_disabled = req
+        .path()
+        .strip_suffix("/count")
+        .is_some_and(|counted| toggles.is_disabled(counted));
+    if toggles.is_disabled(&endpoint) || counted_disabled {
+        return Ok(req
+            .error_response(ApiError::EndpointDisabled(endpoint))
+            .map_into_right_body());
+    }
+    next.call(req)
+        .await
+        .map(ServiceResponse::map_into_left_body)
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::{
+        http::StatusCode,
+        middleware,
+        test::{call_service, init_service, read_body_json, TestRequest},
+        App, HttpResponse,
+    };
+    use serde_json::Value;
+
+    use super::*;
+
+    #[actix_web::test]
+    async fn disabled_endpoints_and_their_counts_are_rejected_until_enabled() {
+        let toggles = web::Data::new(EndpointToggles::default());
+        let app = init_service(
+            App::new()
+                .app_data(toggles.clone())
+                .route("/v0/ft/ft_transfer", web::get().to(HttpResponse::Ok))
+                .route("/v0/ft/ft_transfer/count", web::get().to(HttpResponse::Ok))
+                .route("/v0/ft/ft_mint", web::get().to(HttpResponse::Ok))
+                .wrap(middleware::from_fn(reject_disabled)),
+        )
+        .await;
+        let status = |uri| {
+            let app = &app;
+            async move {
+                let req = TestRequest::get().uri(uri).to_request();
+                call_service(app, req).await.status()
+            }
+        };
+
+        toggles.disable("/v0/ft/ft_transfer".to_owned());
+        let req = TestRequest::get().uri("/v0/ft/ft_transfer").to_request();
+        let res = call_service(&app, req).await;
+        assert_eq!(res.status(), StatusCode::SERVICE_UNAVAILABLE);
+        // Unlike overloads, it's not known when the endpoint is enabled again
+        assert!(!res.headers().contains_key("retry-after"));
+        let body: Value = read_body_json(res).await;
+        assert_eq!(body["error"]["code"], "endpoint_disabled");
+        assert_eq!(
+            status("/v0/ft/ft_transfer/count").await,
+            StatusCode::SERVICE_UNAVAILABLE
+        );
+        assert_eq!(status("/v0/ft/ft_mint").await, StatusCode::OK);
+
+        toggles.enable("/v0/ft/ft_transfer");
+        assert_eq!(status("/v0/ft/ft_transfer").await, StatusCode::OK);
+        assert_eq!(status("/v0/ft/ft_transfer/count").await, StatusCode::OK);
+        assert!(toggles.disabled().is_empty());
+    }
+}
@@ -1,208 +1,848 @@
-use actix_web::{get, web, HttpResponse, Responder};
+use actix_web::{get, web, HttpResponse};
 use chrono::prelude::{DateTime, Utc};
+pub use inteat_events_api_types::trade::{
+    TradeLiquidityAddEvent, TradeLiquidityAddFilter, TradeLiquidityRemoveEvent,
+    TradeLiquidityRemoveFilter, TradePoolChangeEvent, TradePoolChangeFilter, TradePoolEvent,
+    TradePoolFilter, TradeSwapEvent, TradeSwapFilter, TradeUnwrapEvent, TradeUnwrapFilter,
+    TradeWrapEvent, TradeWrapFilter,
+};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
+use sqlx::{postgres::PgRow, FromRow, PgPool};
+use utoipa::{IntoParams, ToSchema};
 
-use crate::{utils::Balance, AppState, PaginationInfo, MAX_BLOCKS_PER_REQUEST};
+use crate::{
+    admin::{parse_query, QueryError},
+    aggregate::{AggregationParams, MAX_BLOCKS_PER_AGGREGATE},
+    cursor::{EventsPage, ResponseFormat},
+    endpoint::{event_endpoint, EventEndpoint},
+    enrich::{self, EnrichParams, TxStatusClient},
+    error::{ApiError, ErrorResponse},
+    event_query::EventQuery,
+    meta::EventTable,
+    registry::EventType,
+    utils::{nanosec_to_timestamp, Balance, ValidAccountId},
+    AppState, Ordering, PaginationInfo, PaginationParams, Sampling, TimeRange,
+    MAX_SAMPLE_EVERY_N_BLOCKS,
+};
 
-type TransactionId = String;
 type ReceiptId = String;
 type AccountId = String;
 type BlockHeight = i64;
 type PoolId = String;
 
-#[derive(Debug, Serialize, Deserialize)]
-pub struct TradePoolEvent {
-    pub trader: AccountId,
-    pub block_height: BlockHeight,
+fn trade_pool_query(filter: &TradePoolFilter) -> EventQuery<TradePoolEvent> {
+    EventQuery::new(
+        "trade_pool",
+        "trader, block_height, timestamp, transaction_id, receipt_id, pool, token_in, token_out, amount_in, amount_out",
+    )
+    .filter("pool = ANY({})", filter.pool_id.as_deref())
+    .filter("trader = ANY({})", filter.account_id.as_deref())
+    .filter("transaction_id = ANY({})", filter.transaction_id.as_deref())
+    .filter("receipt_id = ANY({})", filter.receipt_id.as_deref())
+    .filter("trader IS NULL OR trader <> ALL({})", filter.exclude_account_id.as_deref())
+    .filter("amount_in >= {}", filter.min_amount.as_ref().map(|amount| &amount.0))
+    .filter("amount_in <= {}", filter.max_amount.as_ref().map(|amount| &amount.0))
+    .amount_column("amount_in")
+}
+
+/// Raw pool swap events
+#[utoipa::path(
+    get,
+    path = "/v0/trade/trade_pool",
+    tag = "Trade",
+    params(PaginationParams, TradePoolFilter, Ordering, Sampling, AggregationParams, EnrichParams),
+    responses(
+        (status = 200, body = EventsPage<TradePoolEvent>),
+        (status = 400, body = ErrorResponse),
+        (status = 500, body = ErrorResponse),
+    )
+)]
+#[get("/trade_pool")]
+#[allow(clippy::too_many_arguments)]
+pub async fn trade_pool(
+    state: web::Data<AppState>,
+    pagination: web::Query<PaginationInfo>,
+    filter: web::Query<TradePoolFilter>,
+    ordering: web::Query<Ordering>,
+    sampling: web::Query<Sampling>,
+    aggregation: web::Query<AggregationParams>,
+    enrich: web::Query<EnrichParams>,
+    tx_status: Option<web::Data<TxStatusClient>>,
+) -> Result<HttpResponse, ApiError> {
+    let aggregation = aggregation
+        .parse(&[("amount_in", "amount_in"), ("amount_out", "amount_out")])
+        .map_err(ApiError::Validation)?;
+    let max_blocks = if aggregation.is_some() {
+        MAX_BLOCKS_PER_AGGREGATE
+    } else {
+        state.block_limits().of(EventTable::TradePool)
+    };
+    if pagination.blocks > max_blocks {
+        return Err(ApiError::Validation(format!(
+            "Blocks per request must be less or equal to {max_blocks}"
+        )));
+    }
+
+    if !(1..=MAX_SAMPLE_EVERY_N_BLOCKS).contains(&sampling.sample_every_n_blocks) {
+        return Err(ApiError::Validation(format!(
+            "sample_every_n_blocks must be between 1 and {MAX_SAMPLE_EVERY_N_BLOCKS}"
+        )));
+    }
+
+    let tx_status = enrich
+        .parse(tx_status.as_ref())
+        .map_err(ApiError::Validation)?;
+
+    let query = trade_pool_query(&filter)
+        .ordering(&ordering)
+        .sample(&sampling);
+    if let Some(aggregation) = aggregation {
+        return query.aggregate(&aggregation, &pagination, &state).await;
+    }
+    let query = query.paginate(&pagination);
+    if pagination.format == ResponseFormat::Ndjson && tx_status.is_none() {
+        return query
+            .ndjson(state.pg_pool(), &pagination, |event| event.timestamp)
+            .await;
+    }
+    let mut res = query.fetch_all(&state.pg_pool()).await?;
+    let page = pagination.page(&mut res, |event| event.timestamp, |event| &event.receipt_id)?;
+    Ok(enrich::respond(page, tx_status, res, |event| {
+        (event.transaction_id.as_str(), event.trader.as_str())
+    })
+    .await)
+}
+
+impl EventType for TradePoolEvent {
+    const TABLE: EventTable = EventTable::TradePool;
+    const SCOPE: &'static str = "/v0/trade";
+
+    fn filtered(query: &str) -> Result<EventQuery<Self>, QueryError> {
+        let filter: TradePoolFilter = parse_query(query)?;
+        Ok(trade_pool_query(&filter))
+    }
+
+    fn with_options(
+        event_query: EventQuery<Self>,
+        query: &str,
+    ) -> Result<EventQuery<Self>, QueryError> {
+        let ordering: Ordering = parse_query(query)?;
+        let sampling: Sampling = parse_query(query)?;
+        Ok(event_query.ordering(&ordering).sample(&sampling))
+    }
+
+    fn unfiltered() -> EventQuery<Self> {
+        trade_pool_query(&TradePoolFilter::default())
+    }
+
+    fn timestamp(&self) -> DateTime<Utc> {
+        self.timestamp
+    }
+
+    fn transaction_id(&self) -> Option<&str> {
+        Some(&self.transaction_id)
+    }
+}
+
+impl EventEndpoint for TradeSwapEvent {
+    const TABLE: EventTable = EventTable::TradeSwap;
+    type Filter = TradeSwapFilter;
+
+    fn query(filter: &TradeSwapFilter) -> EventQuery<Self> {
+        EventQuery::new(
+            "trade_swap",
+            "trader, block_height, timestamp, transaction_id, receipt_id, balance_changes",
+        )
+        .filter("trader = ANY({})", filter.account_id.as_deref())
+        .filter(
+            "balance_changes ?& {}",
+            filter.involved_token_account_ids.as_deref(),
+        )
+        .filter("transaction_id = ANY({})", filter.transaction_id.as_deref())
+        .filter("receipt_id = ANY({})", filter.receipt_id.as_deref())
+        .filter("trader IS NULL OR trader <> ALL({})", filter.exclude_account_id.as_deref())
+        .filter(
+            "EXISTS (SELECT 1 FROM jsonb_each_text(balance_changes) WHERE key = ANY({}) AND value::NUMERIC > 0)",
+            filter.token_bought.as_deref(),
+        )
+        .filter(
+            "EXISTS (SELECT 1 FROM jsonb_each_text(balance_changes) WHERE key = ANY({}) AND value::NUMERIC < 0)",
+            filter.token_sold.as_deref(),
+        )
+    }
+
+    fn timestamp(&self) -> DateTime<Utc> {
+        self.timestamp
+    }
+
+    fn receipt_id(&self) -> &str {
+        &self.receipt_id
+    }
+
+    fn transaction(&self) -> (&str, &str) {
+        (&self.transaction_id, &self.trader)
+    }
+}
+
+event_endpoint! {
+    /// Swap events with all raw pool swaps and net balance changes
+    trade_swap(TradeSwapEvent, TradeSwapFilter),
+    route = "/trade_swap",
+    scope = "/v0/trade",
+    tag = "Trade",
+}
+
+impl EventEndpoint for TradeLiquidityAddEvent {
+    const TABLE: EventTable = EventTable::TradeLiquidityAdd;
+    const SUM_FIELDS: &'static [(&'static str, &'static str)] = &[("shares", "shares")];
+    type Filter = TradeLiquidityAddFilter;
+
+    fn query(filter: &TradeLiquidityAddFilter) -> EventQuery<Self> {
+        EventQuery::new(
+            "trade_liquidity_add",
+            "pool_id, account_id, token_ids, amounts, shares, transaction_id, receipt_id, block_height, timestamp",
+        )
+        .filter("pool_id = ANY({})", filter.pool_id.as_deref())
+        .filter("account_id = ANY({})", filter.account_id.as_deref())
+        .filter("token_ids && {}", filter.token_id.as_deref())
+        .filter("transaction_id = ANY({})", filter.transaction_id.as_deref())
+        .filter("receipt_id = ANY({})", filter.receipt_id.as_deref())
+        .filter("account_id <> ALL({})", filter.exclude_account_id.as_deref())
+    }
+
+    fn timestamp(&self) -> DateTime<Utc> {
+        self.timestamp
+    }
+
+    fn receipt_id(&self) -> &str {
+        &self.receipt_id
+    }
+
+    fn transaction(&self) -> (&str, &str) {
+        (&self.transaction_id, &self.account_id)
+    }
+}
+
+event_endpoint! {
+    /// Liquidity addition events
+    trade_liquidity_add(TradeLiquidityAddEvent, TradeLiquidityAddFilter),
+    route = "/trade_liquidity_add",
+    scope = "/v0/trade",
+    tag = "Trade",
+}
+
+impl EventEndpoint for TradeLiquidityRemoveEvent {
+    const TABLE: EventTable = EventTable::TradeLiquidityRemove;
+    const SUM_FIELDS: &'static [(&'static str, &'static str)] = &[("shares", "shares")];
+    type Filter = TradeLiquidityRemoveFilter;
+
+    fn query(filter: &TradeLiquidityRemoveFilter) -> EventQuery<Self> {
+        EventQuery::new(
+            "trade_liquidity_remove",
+            "pool_id, account_id, token_ids, amounts, shares, transaction_id, receipt_id, block_height, timestamp",
+        )
+        .filter("pool_id = ANY({})", filter.pool_id.as_deref())
+        .filter("account_id = ANY({})", filter.account_id.as_deref())
+        .filter("token_ids && {}", filter.token_id.as_deref())
+        .filter("transaction_id = ANY({})", filter.transaction_id.as_deref())
+        .filter("receipt_id = ANY({})", filter.receipt_id.as_deref())
+        .filter("account_id <> ALL({})", filter.exclude_account_id.as_deref())
+    }
+
+    fn timestamp(&self) -> DateTime<Utc> {
+        self.timestamp
+    }
+
+    fn receipt_id(&self) -> &str {
+        &self.receipt_id
+    }
+
+    fn transaction(&self) -> (&str, &str) {
+        (&self.transaction_id, &self.account_id)
+    }
+}
+
+event_endpoint! {
+    /// Liquidity removal events
+    trade_liquidity_remove(TradeLiquidityRemoveEvent, TradeLiquidityRemoveFilter),
+    route = "/trade_liquidity_remove",
+    scope = "/v0/trade",
+    tag = "Trade",
+}
+
+impl EventEndpoint for TradeWrapEvent {
+    const TABLE: EventTable = EventTable::TradeWrap;
+    const SUM_FIELDS: &'static [(&'static str, &'static str)] = &[("amount", "amount")];
+    type Filter = TradeWrapFilter;
+
+    fn query(filter: &TradeWrapFilter) -> EventQuery<Self> {
+        EventQuery::new(
+            "trade_wrap",
+            "account_id, amount, transaction_id, receipt_id, block_height, timestamp",
+        )
+        .filter("account_id = ANY({})", filter.account_id.as_deref())
+        .filter("transaction_id = ANY({})", filter.transaction_id.as_deref())
+        .filter("receipt_id = ANY({})", filter.receipt_id.as_deref())
+        .filter(
+            "account_id <> ALL({})",
+            filter.exclude_account_id.as_deref(),
+        )
+    }
+
+    fn timestamp(&self) -> DateTime<Utc> {
+        self.timestamp
+    }
+
+    fn receipt_id(&self) -> &str {
+        &self.receipt_id
+    }
+
+    fn transaction(&self) -> (&str, &str) {
+        (&self.transaction_id, &self.account_id)
+    }
+}
+
+event_endpoint! {
+    /// wNEAR wrapping events, `near_deposit` on `wrap.near`
+    trade_wrap(TradeWrapEvent, TradeWrapFilter),
+    route = "/trade_wrap",
+    scope = "/v0/trade",
+    tag = "Trade",
+}
+
+impl EventEndpoint for TradeUnwrapEvent {
+    const TABLE: EventTable = EventTable::TradeUnwrap;
+    const SUM_FIELDS: &'static [(&'static str, &'static str)] = &[("amount", "amount")];
+    type Filter = TradeUnwrapFilter;
+
+    fn query(filter: &TradeUnwrapFilter) -> EventQuery<Self> {
+        EventQuery::new(
+            "trade_unwrap",
+            "account_id, amount, transaction_id, receipt_id, block_height, timestamp",
+        )
+        .filter("account_id = ANY({})", filter.account_id.as_deref())
+        .filter("transaction_id = ANY({})", filter.transaction_id.as_deref())
+        .filter("receipt_id = ANY({})", filter.receipt_id.as_deref())
+        .filter(
+            "account_id <> ALL({})",
+            filter.exclude_account_id.as_deref(),
+        )
+    }
+
+    fn timestamp(&self) -> DateTime<Utc> {
+        self.timestamp
+    }
+
+    fn receipt_id(&self) -> &str {
+        &self.receipt_id
+    }
+
+    fn transaction(&self) -> (&str, &str) {
+        (&self.transaction_id, &self.account_id)
+    }
+}
+
+event_endpoint! {
+    /// wNEAR unwrapping events, `near_withdraw` on `wrap.near`
+    trade_unwrap(TradeUnwrapEvent, TradeUnwrapFilter),
+    route = "/trade_unwrap",
+    scope = "/v0/trade",
+    tag = "Trade",
+}
+
+/// Pool change event with `pool` as stored by the indexer, for `raw=true`
+#[derive(Debug, Serialize, FromRow)]
+struct RawTradePoolChangeEvent {
+    pool_id: PoolId,
+    receipt_id: ReceiptId,
     #[serde(
         with = "chrono::serde::ts_nanoseconds",
         rename = "block_timestamp_nanosec"
     )]
-    pub timestamp: DateTime<Utc>,
-    pub transaction_id: TransactionId,
-    pub receipt_id: ReceiptId,
+    timestamp: DateTime<Utc>,
+    block_height: BlockHeight,
+    pool: Value,
+}
 
-    pub pool: PoolId,
-    pub token_in: AccountId,
-    pub token_out: AccountId,
-    pub amount_in: Balance,
-    pub amount_out: Balance,
+impl From<RawTradePoolChangeEvent> for TradePoolChangeEvent {
+    fn from(event: RawTradePoolChangeEvent) -> Self {
+        TradePoolChangeEvent {
+            pool_id: event.pool_id,
+            receipt_id: event.receipt_id,
+            timestamp: event.timestamp,
+            block_height: event.block_height,
+            pool: event.pool.into(),
+        }
+    }
 }
 
-#[derive(Deserialize)]
-struct TradePoolFilter {
-    pool_id: Option<String>,
-    account_id: Option<String>,
+/// Same query for both formats of `pool`
+fn trade_pool_change_query<T>(filter: &TradePoolChangeFilter) -> EventQuery<T>
+where
+    T: for<'r> FromRow<'r, PgRow> + Send + Unpin + 'static,
+{
+    EventQuery::new(
+        "trade_pool_change",
+        "pool_id, receipt_id, timestamp, block_height, pool",
+    )
+    .filter("pool_id = ANY({})", filter.pool_id.as_deref())
 }
 
-#[get("/trade_pool")]
-pub async fn trade_pool(
+impl EventType for TradePoolChangeEvent {
+    const TABLE: EventTable = EventTable::TradePoolChange;
+    const SCOPE: &'static str = "/v0/trade";
+
+    fn filtered(query: &str) -> Result<EventQuery<Self>, QueryError> {
+        let filter: TradePoolChangeFilter = parse_query(query)?;
+        Ok(trade_pool_change_query(&filter))
+    }
+
+    fn with_options(
+        event_query: EventQuery<Self>,
+        query: &str,
+    ) -> Result<EventQuery<Self>, QueryError> {
+        let sampling: Sampling = parse_query(query)?;
+        Ok(event_query.sample(&sampling))
+    }
+
+    fn unfiltered() -> EventQuery<Self> {
+        trade_pool_change_query(&TradePoolChangeFilter::default())
+    }
+
+    fn timestamp(&self) -> DateTime<Utc> {
+        self.timestamp
+    }
+
+    fn transaction_id(&self) -> Option<&str> {
+        None
+    }
+}
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+struct PoolFormat {
+    /// Return `pool` as stored by the indexer, in the format of Ref Finance's
+    /// contract, instead of the typed model
+    #[serde(default)]
+    raw: bool,
+}
+
+/// Pool change events, when someone swaps, adds or removes liquidity, etc.
+#[utoipa::path(
+    get,
+    path = "/v0/trade/trade_pool_change",
+    tag = "Trade",
+    params(PaginationParams, TradePoolChangeFilter, Sampling, AggregationParams, PoolFormat),
+    responses(
+        (status = 200, body = EventsPage<TradePoolChangeEvent>),
+        (status = 400, body = ErrorResponse),
+        (status = 500, body = ErrorResponse),
+    )
+)]
+#[get("/trade_pool_change")]
+pub async fn trade_pool_change(
     state: web::Data<AppState>,
     pagination: web::Query<PaginationInfo>,
-    filter: web::Query<TradePoolFilter>,
-) -> impl Responder {
-    if pagination.blocks > MAX_BLOCKS_PER_REQUEST {
-        return HttpResponse::BadRequest().body(format!(
-            "Blocks per request must be less or equal to {MAX_BLOCKS_PER_REQUEST}"
-        ));
+    filter: web::Query<TradePoolChangeFilter>,
+    sampling: web::Query<Sampling>,
+    aggregation: web::Query<AggregationParams>,
+    format: web::Query<PoolFormat>,
+) -> Result<HttpResponse, ApiError> {
+    let aggregation = aggregation.parse(&[]).map_err(ApiError::Validation)?;
+    let max_blocks = if aggregation.is_some() {
+        MAX_BLOCKS_PER_AGGREGATE
+    } else {
+        state.block_limits().of(EventTable::TradePoolChange)
+    };
+    if pagination.blocks > max_blocks {
+        return Err(ApiError::Validation(format!(
+            "Blocks per request must be less or equal to {max_blocks}"
+        )));
     }
 
-    if let Ok(res) = sqlx::query_as!(TradePoolEvent,
-        r#"
-        WITH blocks AS (
-            SELECT DISTINCT timestamp as t
-            FROM trade_pool
-            WHERE extract(epoch from timestamp) * 1_000_000_000 >= $1
-                AND ($3::TEXT IS NULL OR pool = $3)
-                AND ($4::TEXT IS NULL OR trader = $4)
-            ORDER BY t
-            LIMIT $2
+    if !(1..=MAX_SAMPLE_EVERY_N_BLOCKS).contains(&sampling.sample_every_n_blocks) {
+        return Err(ApiError::Validation(format!(
+            "sample_every_n_blocks must be between 1 and {MAX_SAMPLE_EVERY_N_BLOCKS}"
+        )));
+    }
+
+    if let Some(aggregation) = aggregation {
+        return trade_pool_change_query::<TradePoolChangeEvent>(&filter)
+            .sample(&sampling)
+            .aggregate(&aggregation, &pagination, &state)
+            .await;
+    }
+
+    if format.raw {
+        let query = trade_pool_change_query::<RawTradePoolChangeEvent>(&filter)
+            .sample(&sampling)
+            .paginate(&pagination);
+        return respond_pool_changes(
+            query,
+            state.pg_pool(),
+            &pagination,
+            |event| event.timestamp,
+            |event| &event.receipt_id,
         )
-        SELECT trader, block_height, timestamp, transaction_id, receipt_id, pool, token_in, token_out, amount_in, amount_out
-        FROM trade_pool
-        INNER JOIN blocks ON timestamp = blocks.t
-        WHERE ($3::TEXT IS NULL OR pool = $3)
-            AND ($4::TEXT IS NULL OR trader = $4)
-        ORDER BY timestamp ASC
-        "#,
-        pagination.start_block_timestamp_nanosec as i64,
-        pagination.blocks,
-        filter.pool_id.as_deref(),
-        filter.account_id.as_deref(),
-    ).fetch_all(&state.pg_pool).await {
-        HttpResponse::Ok().json(res)
-    } else {
-        HttpResponse::InternalServerError().finish()
+        .await;
+    }
+    let query = trade_pool_change_query::<TradePoolChangeEvent>(&filter)
+        .sample(&sampling)
+        .paginate(&pagination);
+    respond_pool_changes(
+        query,
+        state.pg_pool(),
+        &pagination,
+        |event| event.timestamp,
+        |event| &event.receipt_id,
+    )
+    .await
+}
+
+/// Responds with pool change events in either format of `pool`
+async fn respond_pool_changes<T>(
+    query: EventQuery<T>,
+    pg_pool: PgPool,
+    pagination: &PaginationInfo,
+    timestamp: fn(&T) -> DateTime<Utc>,
+    receipt_id: fn(&T) -> &str,
+) -> Result<HttpResponse, ApiError>
+where
+    T: for<'r> FromRow<'r, PgRow> + Serialize + Send + Unpin + 'static,
+{
+    if pagination.format == ResponseFormat::Ndjson {
+        return query.ndjson(pg_pool, pagination, timestamp).await;
     }
+    let mut res = query.fetch_all(&pg_pool).await?;
+    Ok(pagination
+        .page(&mut res, timestamp, receipt_id)?
+        .respond(res))
 }
 
-#[derive(Debug, Serialize, Deserialize)]
-pub struct TradeSwapEvent {
-    pub trader: AccountId,
-    pub block_height: BlockHeight,
+#[derive(Debug, Serialize, ToSchema)]
+pub struct TradeVolume {
     #[serde(
         with = "chrono::serde::ts_nanoseconds",
-        rename = "block_timestamp_nanosec"
+        rename = "bucket_start_nanosec"
     )]
-    pub timestamp: DateTime<Utc>,
-    pub transaction_id: TransactionId,
-    pub receipt_id: ReceiptId,
+    #[schema(value_type = i64)]
+    pub bucket: DateTime<Utc>,
+    /// Only set with `group_by=pool`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pool_id: Option<PoolId>,
+    pub token_account_id: AccountId,
+    /// Amount of the token swapped in either direction, in its smallest units
+    pub volume: Balance,
+    pub swaps: i64,
+}
 
-    pub balance_changes: Value, // account_id: String -> balance_change: Balance
+#[derive(Deserialize, ToSchema, Default, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+enum VolumeBucket {
+    Hour,
+    #[default]
+    Day,
 }
 
-#[derive(Deserialize)]
-struct TradeSwapFilter {
-    account_id: Option<String>,
-    involved_token_account_ids: Option<String>,
+impl VolumeBucket {
+    /// Name of the bucket as accepted by Postgres `date_trunc`
+    fn as_str(&self) -> &'static str {
+        match self {
+            VolumeBucket::Hour => "hour",
+            VolumeBucket::Day => "day",
+        }
+    }
+
+    /// Longest range of a request, so that hourly buckets can't scan the
+    /// whole table
+    fn max_range_days(&self) -> i64 {
+        match self {
+            VolumeBucket::Hour => 31,
+            VolumeBucket::Day => 366,
+        }
+    }
+}
+
+#[derive(Deserialize, ToSchema, Default, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+enum VolumeGroupBy {
+    #[default]
+    Token,
+    Pool,
+}
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+struct TradeVolumeFilter {
+    /// Ranges can be up to 31 days with `hour`, and up to 366 days with `day`
+    #[serde(default)]
+    #[param(inline)]
+    bucket: VolumeBucket,
+    #[serde(default)]
+    #[param(inline)]
+    group_by: VolumeGroupBy,
+    token_account_id: Option<ValidAccountId>,
+    pool_id: Option<String>,
 }
 
-#[get("/trade_swap")]
-pub async fn trade_swap(
+/// Swap volume per token, or per pool and token, by hour or day
+#[utoipa::path(
+    get,
+    path = "/v0/trade/volume",
+    tag = "Trade",
+    params(TimeRange, TradeVolumeFilter),
+    responses(
+        (status = 200, body = Vec<TradeVolume>),
+        (status = 400, body = ErrorResponse),
+        (status = 503, body = ErrorResponse),
+    )
+)]
+#[get("/volume")]
+pub async fn trade_volume(
     state: web::Data<AppState>,
-    pagination: web::Query<PaginationInfo>,
-    filter: web::Query<TradeSwapFilter>,
-) -> impl Responder {
-    if pagination.blocks > MAX_BLOCKS_PER_REQUEST {
-        return HttpResponse::BadRequest().body(format!(
-            "Blocks per request must be less or equal to {MAX_BLOCKS_PER_REQUEST}"
-        ));
-    }
-
-    let involved_tokens = filter
-        .involved_token_account_ids
-        .as_ref()
-        .map(|s| s.split(',').map(ToOwned::to_owned).collect::<Vec<String>>());
-    if let Ok(res) = sqlx::query_as!(
-        TradeSwapEvent,
+    range: web::Query<TimeRange>,
+    filter: web::Query<TradeVolumeFilter>,
+) -> Result<HttpResponse, ApiError> {
+    let _permit = state.heavy_queries().try_acquire()?;
+    let max_days = filter.bucket.max_range_days();
+    let end = range
+        .end_block_timestamp_nanosec
+        .unwrap_or_else(|| Utc::now().timestamp_nanos_opt().unwrap_or(i64::MAX));
+    if end.saturating_sub(range.start_block_timestamp_nanosec)
+        > max_days * 24 * 60 * 60 * 1_000_000_000
+    {
+        return Err(ApiError::Validation(format!(
+            "The range must be at most {max_days} days with bucket={}, set start_block_timestamp_nanosec",
+            filter.bucket.as_str()
+        )));
+    }
+
+    // Every pool swap is counted once for the token that went in, and once
+    // for the token that went out
+    let res = sqlx::query_as!(
+        TradeVolume,
         r#"
-        WITH blocks AS (
-            SELECT DISTINCT timestamp as t
-            FROM trade_swap
-            WHERE extract(epoch from timestamp) * 1_000_000_000 >= $1
-                AND ($3::TEXT IS NULL OR trader = $3)
-                AND ($4::TEXT[] IS NULL OR balance_changes ?& $4)
-            ORDER BY t
-            LIMIT $2
+        WITH sides AS (
+            SELECT timestamp, pool, token_in as token, amount_in as amount
+            FROM trade_pool
+            WHERE timestamp >= $3 AND timestamp < $4
+                AND ($6::TEXT IS NULL OR pool = $6)
+            UNION ALL
+            SELECT timestamp, pool, token_out as token, amount_out as amount
+            FROM trade_pool
+            WHERE timestamp >= $3 AND timestamp < $4
+                AND ($6::TEXT IS NULL OR pool = $6)
         )
-        SELECT trader, block_height, timestamp, transaction_id, receipt_id, balance_changes
-        FROM trade_swap
-        INNER JOIN blocks ON timestamp = blocks.t
-        WHERE ($3::TEXT IS NULL OR trader = $3)
-            AND ($4::TEXT[] IS NULL OR balance_changes ?& $4)
-        ORDER BY timestamp ASC
+        SELECT date_trunc($1, timestamp) as "bucket!",
+            CASE WHEN $2::TEXT = 'pool' THEN pool END as pool_id,
+            token as "token_account_id!",
+            SUM(amount) as "volume!",
+            COUNT(*) as "swaps!"
+        FROM sides
+        WHERE $5::TEXT IS NULL OR token = $5
+        GROUP BY 1, 2, 3
+        ORDER BY 1 ASC, 2 ASC, 3 ASC
         "#,
-        pagination.start_block_timestamp_nanosec as i64,
-        pagination.blocks,
-        filter.account_id.as_deref(),
-        involved_tokens.as_deref(),
+        filter.bucket.as_str(),
+        match filter.group_by {
+            VolumeGroupBy::Token => "token",
+            VolumeGroupBy::Pool => "pool",
+        },
+        range.start(),
+        nanosec_to_timestamp(end),
+        filter.token_account_id.as_deref(),
+        filter.pool_id.as_deref(),
     )
-    .fetch_all(&state.pg_pool)
-    .await
-    {
-        HttpResponse::Ok().json(res)
-    } else {
-        HttpResponse::InternalServerError().finish()
-    }
+    .fetch_all(&state.pg_pool())
+    .await?;
+    Ok(HttpResponse::Ok().json(res))
 }
 
-#[derive(Debug, Serialize, Deserialize)]
-pub struct TradePoolChangeEvent {
-    pub pool_id: PoolId,
-    pub receipt_id: ReceiptId,
+/// Most candles per request, so that short resolutions can't scan the whole
+/// table
+const MAX_CANDLES_PER_REQUEST: i64 = 1000;
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct Candle {
     #[serde(
         with = "chrono::serde::ts_nanoseconds",
-        rename = "block_timestamp_nanosec"
+        rename = "bucket_start_nanosec"
     )]
-    pub timestamp: DateTime<Utc>,
-    pub block_height: BlockHeight,
-    pub pool: Value,
+    #[schema(value_type = i64)]
+    pub bucket: DateTime<Utc>,
+    pub open: Balance,
+    pub high: Balance,
+    pub low: Balance,
+    pub close: Balance,
+    /// Amount of the base token swapped in either direction
+    pub volume: Balance,
+    pub swaps: i64,
 }
 
-#[derive(Deserialize)]
-struct TradePoolChangeFilter {
-    pool_id: Option<String>,
+#[derive(Deserialize, ToSchema, Default, Clone, Copy)]
+enum CandleResolution {
+    #[serde(rename = "1m")]
+    OneMinute,
+    #[serde(rename = "5m")]
+    FiveMinutes,
+    #[default]
+    #[serde(rename = "1h")]
+    OneHour,
+    #[serde(rename = "1d")]
+    OneDay,
 }
 
-#[get("/trade_pool_change")]
-pub async fn trade_pool_change(
+impl CandleResolution {
+    fn as_secs(&self) -> i64 {
+        match self {
+            CandleResolution::OneMinute => 60,
+            CandleResolution::FiveMinutes => 5 * 60,
+            CandleResolution::OneHour => 60 * 60,
+            CandleResolution::OneDay => 24 * 60 * 60,
+        }
+    }
+}
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+struct TradeCandlesFilter {
+    pool_id: String,
+    /// Prices are in `quote_token_account_id` per `base_token_account_id`
+    base_token_account_id: ValidAccountId,
+    quote_token_account_id: ValidAccountId,
+    #[serde(default)]
+    #[param(inline)]
+    resolution: CandleResolution,
+}
+
+/// OHLC prices of a token pair in a pool. The range can be up to 1000
+/// candles.
+#[utoipa::path(
+    get,
+    path = "/v0/trade/candles",
+    tag = "Trade",
+    params(TimeRange, TradeCandlesFilter),
+    responses(
+        (status = 200, body = Vec<Candle>),
+        (status = 400, body = ErrorResponse),
+        (status = 503, body = ErrorResponse),
+    )
+)]
+#[get("/candles")]
+pub async fn trade_candles(
     state: web::Data<AppState>,
-    pagination: web::Query<PaginationInfo>,
-    filter: web::Query<TradePoolChangeFilter>,
-) -> impl Responder {
-    if pagination.blocks > MAX_BLOCKS_PER_REQUEST {
-        return HttpResponse::BadRequest().body(format!(
-            "Blocks per request must be less or equal to {MAX_BLOCKS_PER_REQUEST}"
-        ));
+    range: web::Query<TimeRange>,
+    filter: web::Query<TradeCandlesFilter>,
+) -> Result<HttpResponse, ApiError> {
+    let _permit = state.heavy_queries().try_acquire()?;
+    let resolution_secs = filter.resolution.as_secs();
+    let end = range
+        .end_block_timestamp_nanosec
+        .unwrap_or_else(|| Utc::now().timestamp_nanos_opt().unwrap_or(i64::MAX));
+    if end.saturating_sub(range.start_block_timestamp_nanosec) / 1_000_000_000 / resolution_secs
+        > MAX_CANDLES_PER_REQUEST
+    {
+        return Err(ApiError::Validation(format!(
+            "The range must be at most {MAX_CANDLES_PER_REQUEST} candles, set start_block_timestamp_nanosec"
+        )));
     }
 
-    if let Ok(res) = sqlx::query_as!(
-        TradePoolChangeEvent,
+    // Prices are ratios of raw amounts, not adjusted for decimals. Swaps of
+    // other tokens in multi-token pools are ignored.
+    let res = sqlx::query_as!(
+        Candle,
         r#"
-        WITH blocks AS (
-            SELECT DISTINCT timestamp as t
-            FROM trade_pool_change
-            WHERE extract(epoch from timestamp) * 1_000_000_000 >= $1
-                AND ($3::TEXT IS NULL OR pool_id = $3)
-            ORDER BY t
-            LIMIT $2
+        WITH swaps AS (
+            SELECT timestamp,
+                CASE WHEN token_in = $2
+                    THEN amount_out / NULLIF(amount_in, 0)
+                    ELSE amount_in / NULLIF(amount_out, 0)
+                END as price,
+                CASE WHEN token_in = $2 THEN amount_in ELSE amount_out END as base_amount
+            FROM trade_pool
+            WHERE pool = $1
+                AND ((token_in = $2 AND token_out = $3) OR (token_in = $3 AND token_out = $2))
+                AND timestamp >= $5 AND timestamp < $6
         )
+        SELECT date_bin(make_interval(secs => $4::BIGINT), timestamp, TIMESTAMPTZ 'epoch') as "bucket!",
+            (array_agg(price ORDER BY timestamp ASC))[1] as "open!",
+            MAX(price) as "high!",
+            MIN(price) as "low!",
+            (array_agg(price ORDER BY timestamp DESC))[1] as "close!",
+            SUM(base_amount) as "volume!",
+            COUNT(*) as "swaps!"
+        FROM swaps
+        WHERE price IS NOT NULL
+        GROUP BY 1
+        ORDER BY 1 ASC
+        "#,
+        filter.pool_id,
+        filter.base_token_account_id.as_str(),
+        filter.quote_token_account_id.as_str(),
+        resolution_secs,
+        range.start(),
+        nanosec_to_timestamp(end),
+    )
+    .fetch_all(&state.pg_pool())
+    .await?;
+    Ok(HttpResponse::Ok().json(res))
+}
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+struct PoolAtFilter {
+    /// Pool id in format `REF-<number>`
+    pool_id: String,
+    /// Defaults to now, for the latest state
+    timestamp_nanosec: Option<i64>,
+    /// Return `pool` as stored by the indexer
+    #[serde(default)]
+    raw: bool,
+}
+
+/// State of a pool as of a time: the latest pool change at or before it, in
+/// the same format as `trade_pool_change` events
+#[utoipa::path(
+    get,
+    path = "/v0/trade/pool_at",
+    tag = "Trade",
+    params(PoolAtFilter),
+    responses(
+        (status = 200, body = TradePoolChangeEvent),
+        (status = 400, body = ErrorResponse),
+        (status = 404, body = ErrorResponse),
+    )
+)]
+#[get("/pool_at")]
+pub async fn trade_pool_at(
+    state: web::Data<AppState>,
+    filter: web::Query<PoolAtFilter>,
+) -> Result<HttpResponse, ApiError> {
+    let res = sqlx::query_as!(
+        RawTradePoolChangeEvent,
+        r#"
         SELECT pool_id, receipt_id, timestamp, block_height, pool
         FROM trade_pool_change
-        INNER JOIN blocks ON timestamp = blocks.t
-        WHERE ($3::TEXT IS NULL OR pool_id = $3)
-        ORDER BY timestamp ASC
+        WHERE pool_id = $1
+            AND ($2::TIMESTAMPTZ IS NULL OR timestamp < $2)
+        ORDER BY timestamp DESC
+        LIMIT 1
         "#,
-        pagination.start_block_timestamp_nanosec as i64,
-        pagination.blocks,
-        filter.pool_id.as_deref(),
+        filter.pool_id,
+        // Inclusive, so it's the first microsecond after it that's excluded
+        filter
+            .timestamp_nanosec
+            .map(|timestamp| nanosec_to_timestamp(timestamp.saturating_add(1))),
     )
-    .fetch_all(&state.pg_pool)
-    .await
-    {
-        HttpResponse::Ok().json(res)
-    } else {
-        HttpResponse::InternalServerError().finish()
+    .fetch_optional(&state.pg_pool())
+    .await?;
+    match res {
+        Some(event) if filter.raw => Ok(HttpResponse::Ok().json(event)),
+        Some(event) => Ok(HttpResponse::Ok().json(TradePoolChangeEvent::from(event))),
+        None => Err(ApiError::NotFound(format!(
+            "No state of pool {} found at this time",
+            filter.pool_id
+        ))),
     }
 }
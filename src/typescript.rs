@@ -0,0 +1,219 @@
+use std::collections::BTreeSet;
+
+use inteat_events_api_types::utils::BALANCE_DESCRIPTION;
+use serde_json::Value as Json;
+use utoipa::OpenApi;
+
+use crate::openapi::{self, resolve, ApiDoc};
+
+/// Types that the generated definitions use for values that are easy to get
+/// wrong when written by hand
+const PRELUDE: &str = r#"// Generated by `cargo run --bin gen-ts`, don't edit.
+
+/**
+ * Integer amount as a decimal string, e.g. yoctoNEAR. Amounts don't fit in a
+ * `number`, use `BigInt(amount)` to do math with them.
+ */
+export type Balance = string;
+
+/**
+ * Nanoseconds since the Unix epoch, serialized as a JSON number. It's larger
+ * than `Number.MAX_SAFE_INTEGER`, so `JSON.parse` rounds it to a multiple of
+ * 256 nanoseconds: `new Date(timestamp / 1e6)` is exact to the millisecond,
+ * but a timestamp from a response isn't exact enough to start the next page
+ * with. Use `cursor` for that, or parse responses with a JSON parser that
+ * keeps large integers.
+ */
+export type NanosecTimestamp = number;
+
+/** Page of an event endpoint */
+export interface EventsPage<T> {
+  events: T[];
+  /**
+   * Pass as `cursor` to get the next page. `null` if there were no events, in
+   * which case the same request should be repeated later.
+   */
+  cursor: string | null;
+}
+"#;
+
+/// TypeScript definitions of the events of all endpoints, generated from the
+/// OpenAPI spec, so that they're the same types as documented. Balances are
+/// `Balance` and timestamps in nanoseconds are `NanosecTimestamp`, both with
+/// notes on how to handle them. `Events` maps each event type, e.g.
+/// `nft_transfer`, to its event.
+pub fn definitions() -> String {
+    let spec = serde_json::to_value(ApiDoc::openapi()).expect("OpenAPI spec is serializable");
+    let endpoints = openapi::event_endpoints(&spec);
+    let mut generator = Generator {
+        spec: &spec,
+        referenced: endpoints
+            .iter()
+            .map(|(_, _, _, type_name)| *type_name)
+            .collect(),
+        generated: BTreeSet::new(),
+    };
+    let mut definitions = vec![PRELUDE.to_owned()];
+    while let Some(name) = generator.referenced.pop() {
+        if generator.generated.insert(name) {
+            definitions.push(generator.definition(name));
+        }
+    }
+
+    let events = endpoints
+        .iter()
+        .map(|(table, path, _, type_name)| {
+            format!(
+                "  /** `GET {path}` */\n  {}: {type_name};\n",
+                table.as_str()
+            )
+        })
+        .collect::<String>();
+    definitions.push(format!(
+        "/** Event of each event type */\nexport interface Events {{\n{events}}}\n"
+    ));
+    definitions.join("\n")
+}
+
+struct Generator<'a> {
+    spec: &'a Json,
+    /// Components that are used by a definition and may not be generated yet
+    referenced: Vec<&'a str>,
+    generated: BTreeSet<&'a str>,
+}
+
+impl<'a> Generator<'a> {
+    /// `interface` of a component that's an object, or a `type` otherwise
+    fn definition(&mut self, name: &'a str) -> String {
+        let spec = self.spec;
+        let schema = &spec["components"]["schemas"][name];
+        let doc = doc_comment(schema, "");
+        if schema["properties"].is_object() {
+            let fields = self
+                .fields(schema)
+                .into_iter()
+                .map(|(property, name, ty)| {
+                    format!("{}  {name}: {ty};\n", doc_comment(property, "  "))
+                })
+                .collect::<String>();
+            format!("{doc}export interface {name} {{\n{fields}}}\n")
+        } else {
+            format!("{doc}export type {name} = {};\n", self.ts_type(schema, ""))
+        }
+    }
+
+    /// Fields of an object type as `(schema, name, type)`. Fields that are
+    /// `Option`s are serialized as `null` rather than left out, so they
+    /// aren't optional.
+    fn fields(&mut self, schema: &'a Json) -> Vec<(&'a Json, &'a str, String)> {
+        let required = schema["required"].as_array().cloned().unwrap_or_default();
+        schema["properties"]
+            .as_object()
+            .into_iter()
+            .flatten()
+            .map(|(name, property)| {
+                let mut ty = self.ts_type(property, name);
+                if !required.contains(&Json::String(name.clone())) && !ty.ends_with("| null") {
+                    ty.push_str(" | null");
+                }
+                (property, name.as_str(), ty)
+            })
+            .collect()
+    }
+
+    /// Type of a schema, `property` is the name of the field that has it
+    fn ts_type(&mut self, schema: &'a Json, property: &str) -> String {
+        let resolved = resolve(self.spec, schema);
+        let types = match &resolved["type"] {
+            Json::String(ty) => vec![ty.as_str()],
+            Json::Array(types) => types.iter().filter_map(Json::as_str).collect(),
+            _ => Vec::new(),
+        };
+        let nullable = types.contains(&"null");
+        let is_balance =
+            types.contains(&"string") && resolved["description"] == BALANCE_DESCRIPTION;
+        let ty = if is_balance {
+            "Balance".to_owned()
+        } else if let Some(name) = schema["$ref"].as_str().and_then(|r| r.rsplit('/').next()) {
+            self.referenced.push(name);
+            name.to_owned()
+        } else if let Some(variants) = resolved["oneOf"]
+            .as_array()
+            .or(resolved["anyOf"].as_array())
+        {
+            self.join(variants, property, " | ")
+        } else if let Some(parts) = resolved["allOf"].as_array() {
+            self.join(parts, property, " & ")
+        } else if let Some(values) = resolved["enum"].as_array() {
+            // JSON strings and numbers are TypeScript literals as they are
+            values
+                .iter()
+                .map(Json::to_string)
+                .collect::<Vec<_>>()
+                .join(" | ")
+        } else {
+            match types.iter().find(|&&ty| ty != "null") {
+                Some(&"string") => "string".to_owned(),
+                Some(&"boolean") => "boolean".to_owned(),
+                Some(&"integer") if property.ends_with("_nanosec") => "NanosecTimestamp".to_owned(),
+                Some(&"integer" | &"number") => "number".to_owned(),
+                Some(&"array") => {
+                    let item = self.ts_type(&resolved["items"], "");
+                    if item.contains(' ') {
+                        format!("({item})[]")
+                    } else {
+                        format!("{item}[]")
+                    }
+                }
+                Some(&"object") if resolved["properties"].is_object() => {
+                    let fields = self
+                        .fields(resolved)
+                        .into_iter()
+                        .map(|(_, name, ty)| format!("{name}: {ty}"))
+                        .collect::<Vec<_>>();
+                    format!("{{ {} }}", fields.join("; "))
+                }
+                Some(&"object") if resolved["additionalProperties"].is_object() => {
+                    let value = self.ts_type(&resolved["additionalProperties"], "");
+                    format!("Record<string, {value}>")
+                }
+                Some(&"object") => "Record<string, unknown>".to_owned(),
+                None if nullable => return "null".to_owned(),
+                _ => "unknown".to_owned(),
+            }
+        };
+        if nullable && !ty.ends_with("| null") {
+            format!("{ty} | null")
+        } else {
+            ty
+        }
+    }
+
+    fn join(&mut self, schemas: &'a [Json], property: &str, separator: &str) -> String {
+        let mut types = schemas
+            .iter()
+            .map(|schema| self.ts_type(schema, property))
+            .collect::<Vec<_>>();
+        // `Option`s of other types are `oneOf` the type and `null`
+        if let Some(null) = types.iter().position(|ty| ty == "null") {
+            types.remove(null);
+            types.push("null".to_owned());
+        }
+        types.join(separator)
+    }
+}
+
+/// Description of a schema as a doc comment, empty if it has none
+fn doc_comment(schema: &Json, indent: &str) -> String {
+    match schema["description"].as_str() {
+        Some(description) if description.contains('\n') => {
+            let lines = description
+                .lines()
+                .map(|line| format!("{indent} * {line}").trim_end().to_owned() + "\n")
+                .collect::<String>();
+            format!("{indent}/**\n{lines}{indent} */\n")
+        }
+        Some(description) => format!("{indent}/** {description} */\n"),
+        None => String::new(),
+    }
+}
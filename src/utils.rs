@@ -1,101 +1,54 @@
-use std::str::FromStr;
-
-use serde::{Deserialize, Serialize};
-use sqlx::types::BigDecimal;
-
-#[derive(Debug)]
-pub struct Balance(pub BigDecimal);
-
-impl From<BigDecimal> for Balance {
-    fn from(value: BigDecimal) -> Self {
-        Balance(value)
-    }
-}
-
-impl Serialize for Balance {
-    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
-    where
-        S: serde::Serializer,
-    {
-        self.0.to_string().serialize(serializer)
-    }
-}
-
-impl<'de> Deserialize<'de> for Balance {
-    fn deserialize<D>(deserializer: D) -> Result<Balance, D::Error>
-    where
-        D: serde::Deserializer<'de>,
-    {
-        let s = String::deserialize(deserializer)?;
-        Ok(Balance(
-            BigDecimal::from_str(&s).map_err(serde::de::Error::custom)?,
-        ))
-    }
-}
-
-#[derive(Debug)]
-pub struct OptionalBalance(pub Option<BigDecimal>);
-
-impl From<Option<BigDecimal>> for OptionalBalance {
-    fn from(value: Option<BigDecimal>) -> Self {
-        OptionalBalance(value)
-    }
-}
-
-impl Serialize for OptionalBalance {
-    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
-    where
-        S: serde::Serializer,
-    {
-        self.0.as_ref().map(|v| v.to_string()).serialize(serializer)
-    }
-}
-
-impl<'de> Deserialize<'de> for OptionalBalance {
-    fn deserialize<D>(deserializer: D) -> Result<OptionalBalance, D::Error>
-    where
-        D: serde::Deserializer<'de>,
-    {
-        let s = Option::<String>::deserialize(deserializer)?;
-        Ok(OptionalBalance(
-            s.map(|s| BigDecimal::from_str(&s).map_err(serde::de::Error::custom))
-                .transpose()?,
-        ))
-    }
-}
-
-#[derive(Debug)]
-pub struct VecBalance(pub Vec<BigDecimal>);
-
-impl From<Vec<BigDecimal>> for VecBalance {
-    fn from(value: Vec<BigDecimal>) -> Self {
-        VecBalance(value)
-    }
-}
-
-impl Serialize for VecBalance {
-    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
-    where
-        S: serde::Serializer,
-    {
-        self.0
-            .iter()
-            .map(|v| v.to_string())
-            .collect::<Vec<String>>()
-            .serialize(serializer)
-    }
-}
-
-impl<'de> Deserialize<'de> for VecBalance {
-    fn deserialize<D>(deserializer: D) -> Result<VecBalance, D::Error>
-    where
-        D: serde::Deserializer<'de>,
-    {
-        let s = Vec::<String>::deserialize(deserializer)?;
-        Ok(VecBalance(
-            s.into_iter()
-                .map(|s| BigDecimal::from_str(&s).map_err(serde::de::Error::custom))
-                .collect::<Result<Vec<BigDecimal>, _>>()?,
-        ))
+use chrono::{DateTime, Utc};
+pub use inteat_events_api_types::utils::*;
+
+/// Nanosecond timestamp of the API as a `TIMESTAMPTZ` to compare with the
+/// `timestamp` column, which has microsecond precision. It's rounded up, so
+/// that `timestamp >= t` and `timestamp < t` match the same events as
+/// comparing nanoseconds. Negative values are clamped to the epoch.
+pub fn nanosec_to_timestamp(nanosec: i64) -> DateTime<Utc> {
+    let nanosec = nanosec.max(0);
+    let microsec = nanosec / 1000 + i64::from(nanosec % 1000 != 0);
+    DateTime::from_timestamp_micros(microsec).expect("any i64 of microseconds is a valid timestamp")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    proptest! {
+        /// Pages end before the cursor and the next one starts at it, so an
+        /// event is on exactly one side of every boundary
+        #[test]
+        fn nanosec_bounds_match_nanosec_comparison(
+            event_microsec in 0i64..4_000_000_000_000_000,
+            bound in 0i64..4_000_000_000_000_000_000,
+        ) {
+            let event = DateTime::from_timestamp_micros(event_microsec).unwrap();
+            let bound_timestamp = nanosec_to_timestamp(bound);
+            prop_assert_eq!(event >= bound_timestamp, event_microsec * 1000 >= bound);
+            prop_assert_eq!(event < bound_timestamp, event_microsec * 1000 < bound);
+        }
+
+        #[test]
+        fn nanosec_bounds_of_events_match_themselves(event_microsec in 0i64..4_000_000_000_000_000) {
+            let event = DateTime::from_timestamp_micros(event_microsec).unwrap();
+            prop_assert_eq!(nanosec_to_timestamp(event.timestamp_nanos_opt().unwrap()), event);
+            prop_assert!(nanosec_to_timestamp(event.timestamp_nanos_opt().unwrap() + 1) > event);
+        }
+    }
+
+    #[test]
+    fn nanosec_to_timestamp_clamps_negative_values() {
+        assert_eq!(nanosec_to_timestamp(-1), DateTime::UNIX_EPOCH);
+        assert_eq!(nanosec_to_timestamp(i64::MIN), DateTime::UNIX_EPOCH);
+    }
+
+    #[test]
+    fn nanosec_to_timestamp_rounds_up_the_largest_value() {
+        assert_eq!(
+            nanosec_to_timestamp(i64::MAX),
+            DateTime::from_timestamp_micros(9_223_372_036_854_776).unwrap()
+        );
     }
 }
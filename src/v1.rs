@@ -0,0 +1,219 @@
+use actix_web::{get, web, HttpRequest, HttpResponse};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use utoipa::{IntoParams, ToSchema};
+
+use crate::{
+    admin::QueryError,
+    cursor,
+    error::{ApiError, ErrorResponse},
+    live,
+    meta::EventTable,
+    AppState,
+};
+
+/// Query parameters of `/v1` that aren't filters
+const PAGINATION_PARAMS: &[&str] = &["cursor", "start_block_timestamp_nanosec", "blocks"];
+
+/// `/v1` names of filters and event fields that are named differently on
+/// `/v0`, as `(v1, v0)`. The contract that emitted an event is always
+/// `contract_id`, and the account that made a trade is `account_id`, the same
+/// as the filter of it.
+fn renamed_filters(table: EventTable) -> &'static [(&'static str, &'static str)] {
+    match table {
+        EventTable::FtMint
+        | EventTable::FtTransfer
+        | EventTable::FtBurn
+        | EventTable::NftMint
+        | EventTable::NftTransfer
+        | EventTable::NftBurn
+        | EventTable::PriceChange => &[
+            ("contract_id", "token_account_id"),
+            ("exclude_contract_id", "exclude_token_account_id"),
+        ],
+        _ => &[],
+    }
+}
+
+fn renamed_fields(table: EventTable) -> &'static [(&'static str, &'static str)] {
+    match table {
+        EventTable::FtMint | EventTable::FtTransfer | EventTable::FtBurn => {
+            &[("contract_id", "token_id")]
+        }
+        EventTable::PriceChange => &[("contract_id", "token_account_id")],
+        EventTable::TradePool => &[("account_id", "trader"), ("pool_id", "pool")],
+        EventTable::TradeSwap => &[("account_id", "trader")],
+        _ => &[],
+    }
+}
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+struct V1Params {
+    /// Continues from a previous response, instead of
+    /// `start_block_timestamp_nanosec`
+    cursor: Option<String>,
+    start_block_timestamp_nanosec: Option<i64>,
+    #[serde(default = "default_blocks")]
+    #[param(default = 10, minimum = 1)]
+    blocks: i64,
+}
+
+fn default_blocks() -> i64 {
+    10
+}
+
+#[derive(Serialize, ToSchema)]
+struct V1Page {
+    events: Vec<V1Event>,
+    /// Always set, so that polling for new events is repeating the request
+    /// with the cursor of the last response. If there were no events, it's
+    /// the same cursor as requested.
+    cursor: String,
+}
+
+#[derive(Serialize, ToSchema)]
+struct V1Event {
+    block: Block,
+    /// `None` for events that aren't emitted by a transaction, like price
+    /// changes
+    transaction: Option<Transaction>,
+    /// Fields specific to the event type
+    event: Map<String, Value>,
+}
+
+#[derive(Serialize, ToSchema)]
+struct Block {
+    height: Value,
+    timestamp_nanosec: Value,
+}
+
+#[derive(Serialize, ToSchema)]
+struct Transaction {
+    id: Value,
+    receipt_id: Value,
+}
+
+/// Events of any type in the same shape: where it happened in `block` and
+/// `transaction`, and what happened in `event`. Filters are the same as of
+/// the `/v0` endpoint of the event type, except that the contract is always
+/// `contract_id`. Pages are continued with `cursor`.
+#[utoipa::path(
+    get,
+    path = "/v1/events/{event_type}",
+    tag = "v1",
+    params(("event_type" = EventTable, Path), V1Params),
+    responses(
+        (status = 200, body = V1Page),
+        (status = 400, body = ErrorResponse),
+        (status = 500, body = ErrorResponse),
+    )
+)]
+#[get("/events/{event_type}")]
+pub async fn events(
+    state: web::Data<AppState>,
+    event_type: web::Path<EventTable>,
+    params: web::Query<V1Params>,
+    req: HttpRequest,
+) -> Result<HttpResponse, ApiError> {
+    let table = event_type.into_inner();
+    let params = params.into_inner();
+    let start = match (&params.cursor, params.start_block_timestamp_nanosec) {
+        (Some(_), Some(_)) => {
+            return Err(ApiError::Validation(
+                "cursor and start_block_timestamp_nanosec can't be used together".to_owned(),
+            ))
+        }
+        (Some(cursor), None) => cursor::decode_block(cursor).map_err(ApiError::Validation)?,
+        (None, start) => start.unwrap_or_default(),
+    };
+    let max_blocks = state.block_limits().of(table);
+    if !(1..=max_blocks).contains(&params.blocks) {
+        return Err(ApiError::Validation(format!(
+            "blocks must be between 1 and {max_blocks}"
+        )));
+    }
+
+    let filter = v0_filter(table, req.query_string())?;
+    let events = live::fetch_events(&state.pg_pool(), table, &filter, start, params.blocks)
+        .await
+        .map_err(|err| match err {
+            QueryError::BadQuery(message) => ApiError::BadFilter(rename_in_message(table, message)),
+            QueryError::Database(err) => ApiError::Database(err),
+        })?;
+
+    // Pages contain whole blocks, so the next one starts after the latest
+    let next = events
+        .iter()
+        .filter_map(|event| event.timestamp.timestamp_nanos_opt())
+        .max()
+        .map_or(start, |latest| latest + 1);
+    Ok(HttpResponse::Ok().json(V1Page {
+        events: events
+            .into_iter()
+            .map(|event| envelope(table, event.event))
+            .collect(),
+        cursor: cursor::encode(next),
+    }))
+}
+
+/// Filters of the query string under their `/v0` names
+fn v0_filter(table: EventTable, query: &str) -> Result<String, ApiError> {
+    let params = serde_urlencoded::from_str::<Vec<(String, String)>>(query)
+        .map_err(|err| ApiError::BadFilter(err.to_string()))?;
+    let renamed = renamed_filters(table);
+    let mut filter = Vec::with_capacity(params.len());
+    for (name, value) in params {
+        if PAGINATION_PARAMS.contains(&name.as_str()) {
+            continue;
+        }
+        if let Some((v1, _)) = renamed.iter().find(|(_, v0)| *v0 == name) {
+            return Err(ApiError::BadFilter(format!(
+                "Unknown query parameter `{name}`, did you mean `{v1}`?"
+            )));
+        }
+        let name = renamed
+            .iter()
+            .find(|(v1, _)| *v1 == name)
+            .map_or(name.as_str(), |(_, v0)| v0)
+            .to_owned();
+        filter.push((name, value));
+    }
+    serde_urlencoded::to_string(&filter).map_err(|err| ApiError::BadFilter(err.to_string()))
+}
+
+/// Errors of filters mention them by their `/v0` names
+fn rename_in_message(table: EventTable, mut message: String) -> String {
+    for (v1, v0) in renamed_filters(table) {
+        message = message.replace(&format!("`{v0}`"), &format!("`{v1}`"));
+    }
+    message
+}
+
+/// Splits an event as returned by `/v0` into the `/v1` envelope
+fn envelope(table: EventTable, event: Value) -> V1Event {
+    let Value::Object(mut fields) = event else {
+        unreachable!("events are serialized from structs");
+    };
+    let mut take = |name: &str| fields.remove(name).unwrap_or(Value::Null);
+    let block = Block {
+        height: take("block_height"),
+        timestamp_nanosec: take("block_timestamp_nanosec"),
+    };
+    let transaction = Transaction {
+        id: take("transaction_id"),
+        receipt_id: take("receipt_id"),
+    };
+    let transaction =
+        (!transaction.id.is_null() || !transaction.receipt_id.is_null()).then_some(transaction);
+    for (v1, v0) in renamed_fields(table) {
+        if let Some(value) = fields.remove(*v0) {
+            fields.insert((*v1).to_owned(), value);
+        }
+    }
+    V1Event {
+        block,
+        transaction,
+        event: fields,
+    }
+}
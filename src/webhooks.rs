@@ -0,0 +1,438 @@
+use std::{collections::BTreeMap, time::Duration};
+
+use actix_web::{delete, get, http::header, post, web, HttpRequest, HttpResponse, Scope};
+use chrono::{TimeDelta, Utc};
+use futures_util::StreamExt;
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use sha2::Sha256;
+use utoipa::ToSchema;
+
+use crate::{
+    admin::QueryError,
+    error::{ApiError, ErrorResponse},
+    live::{self, Subscription, POLL_INTERVAL},
+    meta::EventTable,
+    AppState,
+};
+
+const DELIVERY_TIMEOUT: Duration = Duration::from_secs(10);
+/// Webhooks claimed by one tick. Claimed webhooks are skipped by other
+/// instances until the delivery is done or the lease expires.
+const DELIVERIES_PER_TICK: i64 = 100;
+const CONCURRENT_DELIVERIES: usize = 16;
+pub(crate) const LEASE: TimeDelta = TimeDelta::seconds(60);
+/// Delay after the first failed delivery, doubled after each next failure
+const FIRST_RETRY_DELAY: TimeDelta = TimeDelta::seconds(5);
+const MAX_RETRY_DELAY: TimeDelta = TimeDelta::hours(1);
+const SIGNATURE_HEADER: &str = "X-Webhook-Signature";
+
+struct Webhook {
+    id: i64,
+    event_type: String,
+    filter: String,
+    url: String,
+    secret: String,
+    cursor_nanosec: i64,
+    failures: i32,
+}
+
+#[derive(Serialize)]
+struct WebhookPayload {
+    webhook_id: i64,
+    event_type: EventTable,
+    /// Events of whole blocks, in the same format as returned by the event
+    /// type's endpoint
+    events: Vec<Value>,
+}
+
+pub fn scope() -> Scope {
+    web::scope("/webhooks")
+        .service(create_webhook)
+        .service(get_webhook)
+        .service(delete_webhook)
+}
+
+/// Runs database migrations needed for webhooks and starts delivering events
+pub async fn start(state: web::Data<AppState>) {
+    sqlx::migrate!()
+        .run(&state.primary_pg_pool())
+        .await
+        .expect("Failed to run webhook migrations");
+    tokio::spawn(async move {
+        let client = reqwest::Client::builder()
+            .timeout(DELIVERY_TIMEOUT)
+            .build()
+            .unwrap();
+        let mut interval = tokio::time::interval(POLL_INTERVAL);
+        loop {
+            interval.tick().await;
+            if let Err(err) = deliver_due_webhooks(&state, &client).await {
+                log::error!("Failed to deliver webhooks: {err}");
+            }
+        }
+    });
+}
+
+async fn deliver_due_webhooks(
+    state: &AppState,
+    client: &reqwest::Client,
+) -> Result<(), sqlx::Error> {
+    let webhooks = sqlx::query_as!(
+        Webhook,
+        r#"
+        UPDATE webhooks SET next_attempt_at = $1
+        WHERE id IN (
+            SELECT id FROM webhooks
+            WHERE next_attempt_at <= now()
+            ORDER BY next_attempt_at
+            LIMIT $2
+            FOR UPDATE SKIP LOCKED
+        )
+        RETURNING id, event_type, filter, url, secret, cursor_nanosec, failures
+        "#,
+        Utc::now() + LEASE,
+        DELIVERIES_PER_TICK,
+    )
+    .fetch_all(&state.primary_pg_pool())
+    .await?;
+
+    futures_util::stream::iter(webhooks)
+        .for_each_concurrent(CONCURRENT_DELIVERIES, |webhook| async move {
+            if let Err(err) = deliver(state, client, &webhook).await {
+                log::error!("Failed to deliver webhook {}: {err}", webhook.id);
+            }
+        })
+        .await;
+    Ok(())
+}
+
+/// Sends the next page of events after the webhook's cursor. The cursor only
+/// moves after the receiver responds with a 2xx status, so each event is
+/// delivered at least once.
+async fn deliver(
+    state: &AppState,
+    client: &reqwest::Client,
+    webhook: &Webhook,
+) -> Result<(), sqlx::Error> {
+    let pg_pool = state.primary_pg_pool();
+    let Some(table) = EventTable::ALL
+        .into_iter()
+        .find(|table| table.as_str() == webhook.event_type)
+    else {
+        return failed(state, webhook, "Unknown event type").await;
+    };
+    // Nothing was indexed after the cursor, so there's no need to query
+    let head = state.head().latest(table);
+    if head.is_some_and(|head| head < webhook.cursor_nanosec) {
+        return idle(state, webhook, webhook.cursor_nanosec).await;
+    }
+    let blocks = state.block_limits().of(table);
+    let events = match live::fetch_events(
+        &pg_pool,
+        table,
+        &webhook.filter,
+        webhook.cursor_nanosec,
+        blocks,
+    )
+    .await
+    {
+        Ok(events) => events,
+        Err(QueryError::BadQuery(message)) => return failed(state, webhook, &message).await,
+        Err(QueryError::Database(err)) => return Err(err),
+    };
+    let mut timestamps = events
+        .iter()
+        .map(|event| event.timestamp)
+        .collect::<Vec<_>>();
+    timestamps.dedup();
+    // Blocks up to the head had no more matching events if the page isn't
+    // full, so they're skipped
+    let skipped = match head {
+        Some(head) if (timestamps.len() as i64) < blocks => head + 1,
+        _ => webhook.cursor_nanosec,
+    };
+    let Some(latest) = timestamps.iter().max() else {
+        return idle(state, webhook, skipped).await;
+    };
+
+    let body = serde_json::to_vec(&WebhookPayload {
+        webhook_id: webhook.id,
+        event_type: table,
+        events: events.into_iter().map(|event| event.event).collect(),
+    })
+    .unwrap();
+    let delivery = client
+        .post(&webhook.url)
+        .header(reqwest::header::CONTENT_TYPE, "application/json")
+        .header(SIGNATURE_HEADER, signature(&webhook.secret, &body))
+        .body(body)
+        .send()
+        .await
+        .and_then(|res| res.error_for_status());
+    if let Err(err) = delivery {
+        log::warn!(
+            "Failed to deliver webhook {} to {}: {err}",
+            webhook.id,
+            webhook.url
+        );
+        return failed(state, webhook, &err.to_string()).await;
+    }
+
+    // Pages always contain whole blocks, so the next page starts right after
+    // the last block. It's due right away in case there are more events.
+    sqlx::query!(
+        r#"
+        UPDATE webhooks
+        SET cursor_nanosec = $2, failures = 0, last_error = NULL, next_attempt_at = now()
+        WHERE id = $1
+        "#,
+        webhook.id,
+        (latest.timestamp_nanos_opt().unwrap_or_default() + 1).max(skipped),
+    )
+    .execute(&pg_pool)
+    .await?;
+    Ok(())
+}
+
+/// Checks again after `POLL_INTERVAL`, starting from `cursor_nanosec`
+async fn idle(state: &AppState, webhook: &Webhook, cursor_nanosec: i64) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        "UPDATE webhooks SET cursor_nanosec = $2, next_attempt_at = $3 WHERE id = $1",
+        webhook.id,
+        cursor_nanosec,
+        Utc::now() + POLL_INTERVAL,
+    )
+    .execute(&state.primary_pg_pool())
+    .await?;
+    Ok(())
+}
+
+/// Schedules a retry with exponential backoff
+async fn failed(state: &AppState, webhook: &Webhook, error: &str) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        r#"
+        UPDATE webhooks SET failures = failures + 1, last_error = $2, next_attempt_at = $3
+        WHERE id = $1
+        "#,
+        webhook.id,
+        error,
+        Utc::now() + retry_delay(webhook.failures + 1),
+    )
+    .execute(&state.primary_pg_pool())
+    .await?;
+    Ok(())
+}
+
+pub(crate) fn retry_delay(failures: i32) -> TimeDelta {
+    let exponent = failures.clamp(1, 16) as u32 - 1;
+    (FIRST_RETRY_DELAY * 2i32.pow(exponent)).min(MAX_RETRY_DELAY)
+}
+
+/// `sha256=` and hex-encoded HMAC-SHA256 of the body, keyed with the
+/// webhook's secret
+fn signature(secret: &str, body: &[u8]) -> String {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any size");
+    mac.update(body);
+    format!("sha256={}", hex::encode(mac.finalize().into_bytes()))
+}
+
+#[derive(Deserialize, ToSchema)]
+struct CreateWebhook {
+    event_type: EventTable,
+    /// Same filters as the event type's endpoint accepts
+    #[serde(default)]
+    filter: BTreeMap<String, String>,
+    url: String,
+    /// Key of the HMAC signature of each delivery
+    secret: String,
+}
+
+/// Events are delivered starting after the latest indexed event. Deliveries
+/// are signed in the `X-Webhook-Signature` header.
+#[utoipa::path(
+    post,
+    path = "/v0/webhooks",
+    tag = "Webhooks",
+    request_body = CreateWebhook,
+    responses(
+        (status = 201, description = "`id` of the webhook, and the `token` to manage it"),
+        (status = 400, body = ErrorResponse),
+    )
+)]
+#[post("")]
+pub async fn create_webhook(
+    state: web::Data<AppState>,
+    body: web::Json<CreateWebhook>,
+) -> Result<HttpResponse, ApiError> {
+    if !body.url.starts_with("https://") {
+        return Err(ApiError::Validation(
+            "url must be an https:// URL".to_string(),
+        ));
+    }
+    if body.secret.is_empty() {
+        return Err(ApiError::Validation("secret must not be empty".to_string()));
+    }
+
+    let pg_pool = state.primary_pg_pool();
+    let filter = serde_urlencoded::to_string(&body.filter)
+        .map_err(|err| ApiError::BadFilter(err.to_string()))?;
+    let subscription = Subscription::new(&pg_pool, body.event_type, filter.clone(), None)
+        .await
+        .map_err(|err| match err {
+            QueryError::BadQuery(message) => ApiError::BadFilter(message),
+            QueryError::Database(err) => ApiError::Database(err),
+        })?;
+    let res = sqlx::query!(
+        r#"
+        INSERT INTO webhooks (event_type, filter, url, secret, cursor_nanosec)
+        VALUES ($1, $2, $3, $4, $5)
+        RETURNING id, token
+        "#,
+        body.event_type.as_str(),
+        filter,
+        body.url,
+        body.secret,
+        subscription.start_block_timestamp_nanosec(),
+    )
+    .fetch_one(&pg_pool)
+    .await?;
+    Ok(HttpResponse::Created().json(json!({
+        "id": res.id,
+        "token": res.token,
+    })))
+}
+
+/// Delivery status. Requires `Authorization: Bearer <token>` with the token
+/// returned on creation.
+#[utoipa::path(
+    get,
+    path = "/v0/webhooks/{id}",
+    tag = "Webhooks",
+    params(("id" = i64, Path)),
+    security(("bearer" = [])),
+    responses(
+        (status = 200, description = "Delivery status: cursor, failures, and the last error"),
+        (status = 401, body = ErrorResponse),
+        (status = 404, body = ErrorResponse),
+    )
+)]
+#[get("/{id}")]
+pub async fn get_webhook(
+    state: web::Data<AppState>,
+    id: web::Path<i64>,
+    req: HttpRequest,
+) -> Result<HttpResponse, ApiError> {
+    let Some(token) = bearer_token(&req) else {
+        return Err(ApiError::Unauthorized);
+    };
+    let Some(res) = sqlx::query!(
+        r#"
+        SELECT id, event_type, filter, url, cursor_nanosec, failures, last_error, next_attempt_at
+        FROM webhooks
+        WHERE id = $1 AND token = $2
+        "#,
+        id.into_inner(),
+        token,
+    )
+    .fetch_optional(&state.primary_pg_pool())
+    .await?
+    else {
+        return Err(ApiError::NotFound("Webhook not found".to_string()));
+    };
+    Ok(HttpResponse::Ok().json(json!({
+        "id": res.id,
+        "event_type": res.event_type,
+        "filter": serde_urlencoded::from_str::<BTreeMap<String, String>>(&res.filter)
+            .unwrap_or_default(),
+        "url": res.url,
+        "cursor_nanosec": res.cursor_nanosec,
+        "failures": res.failures,
+        "last_error": res.last_error,
+        "next_attempt_at_nanosec": res.next_attempt_at.timestamp_nanos_opt(),
+    })))
+}
+
+/// Requires `Authorization: Bearer <token>` with the token returned on creation
+#[utoipa::path(
+    delete,
+    path = "/v0/webhooks/{id}",
+    tag = "Webhooks",
+    params(("id" = i64, Path)),
+    security(("bearer" = [])),
+    responses(
+        (status = 204),
+        (status = 401, body = ErrorResponse),
+        (status = 404, body = ErrorResponse),
+    )
+)]
+#[delete("/{id}")]
+pub async fn delete_webhook(
+    state: web::Data<AppState>,
+    id: web::Path<i64>,
+    req: HttpRequest,
+) -> Result<HttpResponse, ApiError> {
+    let Some(token) = bearer_token(&req) else {
+        return Err(ApiError::Unauthorized);
+    };
+    let res = sqlx::query!(
+        "DELETE FROM webhooks WHERE id = $1 AND token = $2",
+        id.into_inner(),
+        token,
+    )
+    .execute(&state.primary_pg_pool())
+    .await?;
+    if res.rows_affected() == 0 {
+        return Err(ApiError::NotFound("Webhook not found".to_string()));
+    }
+    Ok(HttpResponse::NoContent().finish())
+}
+
+pub(crate) fn bearer_token(req: &HttpRequest) -> Option<&str> {
+    req.headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::{http::StatusCode, test, App};
+    use sqlx::postgres::PgPoolOptions;
+
+    use super::*;
+    use crate::limits::{BlockLimits, HeavyQueries};
+
+    #[actix_web::test]
+    async fn requests_without_a_token_are_unauthorized() {
+        let pg_pool = PgPoolOptions::new()
+            .connect_lazy("postgres://localhost/events")
+            .unwrap();
+        let state = AppState::new(
+            pg_pool,
+            Vec::new(),
+            BlockLimits::from_env(),
+            HeavyQueries::from_env(),
+        );
+        let app = test::init_service(
+            App::new()
+                .app_data(web::Data::new(state))
+                .service(web::scope("/v0").service(scope())),
+        )
+        .await;
+
+        for req in [test::TestRequest::get(), test::TestRequest::delete()] {
+            let req = req.uri("/v0/webhooks/1").to_request();
+            let res = test::call_service(&app, req).await;
+            assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+            assert_eq!(
+                res.headers().get(header::WWW_AUTHENTICATE).unwrap(),
+                "Bearer"
+            );
+            let body: Value = test::read_body_json(res).await;
+            assert_eq!(body["error"]["code"], "unauthorized");
+        }
+    }
+}
@@ -0,0 +1,167 @@
+use std::collections::{BTreeMap, HashMap};
+
+use actix_web::{get, rt::task::JoinHandle, web, HttpRequest, HttpResponse};
+use actix_ws::{Message, MessageStream, Session};
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use crate::{
+    admin::QueryError,
+    live::{Subscription, POLL_INTERVAL},
+    meta::EventTable,
+    AppState,
+};
+
+const MAX_SUBSCRIPTIONS_PER_CONNECTION: usize = 16;
+
+#[derive(Deserialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+enum ClientMessage {
+    Subscribe {
+        id: String,
+        event_type: EventTable,
+        /// Same filters as the event type's endpoint accepts
+        #[serde(default)]
+        filter: BTreeMap<String, String>,
+        start_block_timestamp_nanosec: Option<i64>,
+    },
+    Unsubscribe {
+        id: String,
+    },
+}
+
+/// Live events over a WebSocket. Clients send `subscribe` and `unsubscribe`
+/// messages, and receive `{"id", "events"}` messages as new events land.
+#[get("/ws")]
+pub async fn ws(
+    state: web::Data<AppState>,
+    req: HttpRequest,
+    body: web::Payload,
+) -> Result<HttpResponse, actix_web::Error> {
+    let (response, session, messages) = actix_ws::handle(&req, body)?;
+    actix_web::rt::spawn(handle_connection(state, session, messages));
+    Ok(response)
+}
+
+async fn handle_connection(
+    state: web::Data<AppState>,
+    mut session: Session,
+    mut messages: MessageStream,
+) {
+    let mut subscriptions = HashMap::<String, JoinHandle<()>>::new();
+    while let Some(Ok(message)) = messages.recv().await {
+        let reply = match message {
+            Message::Text(text) => match serde_json::from_str::<ClientMessage>(&text) {
+                Ok(message) => handle_message(&state, &session, &mut subscriptions, message).await,
+                Err(err) => json!({ "id": null, "error": err.to_string() }),
+            },
+            Message::Ping(bytes) => {
+                if session.pong(&bytes).await.is_err() {
+                    break;
+                }
+                continue;
+            }
+            Message::Close(_) => break,
+            _ => continue,
+        };
+        if session.text(reply.to_string()).await.is_err() {
+            break;
+        }
+    }
+    for subscription in subscriptions.into_values() {
+        subscription.abort();
+    }
+    let _ = session.close(None).await;
+}
+
+async fn handle_message(
+    state: &web::Data<AppState>,
+    session: &Session,
+    subscriptions: &mut HashMap<String, JoinHandle<()>>,
+    message: ClientMessage,
+) -> Value {
+    match message {
+        ClientMessage::Subscribe {
+            id,
+            event_type,
+            filter,
+            start_block_timestamp_nanosec,
+        } => {
+            if subscriptions.contains_key(&id) {
+                return json!({ "id": id, "error": "Subscription with this id already exists" });
+            }
+            if subscriptions.len() >= MAX_SUBSCRIPTIONS_PER_CONNECTION {
+                return json!({
+                    "id": id,
+                    "error": format!("At most {MAX_SUBSCRIPTIONS_PER_CONNECTION} subscriptions per connection are allowed"),
+                });
+            }
+            let query = serde_urlencoded::to_string(&filter).unwrap();
+            let subscription = match Subscription::new(
+                &state.primary_pg_pool(),
+                event_type,
+                query,
+                start_block_timestamp_nanosec,
+            )
+            .await
+            {
+                Ok(subscription) => subscription,
+                Err(QueryError::BadQuery(err)) => return json!({ "id": id, "error": err }),
+                Err(QueryError::Database(_)) => {
+                    return json!({ "id": id, "error": "Internal server error" })
+                }
+            };
+            let task = actix_web::rt::spawn(stream_events(
+                state.clone(),
+                session.clone(),
+                id.clone(),
+                subscription,
+            ));
+            subscriptions.insert(id.clone(), task);
+            json!({ "id": id, "subscribed": true })
+        }
+        ClientMessage::Unsubscribe { id } => {
+            if let Some(subscription) = subscriptions.remove(&id) {
+                subscription.abort();
+                json!({ "id": id, "subscribed": false })
+            } else {
+                json!({ "id": id, "error": "No subscription with this id" })
+            }
+        }
+    }
+}
+
+async fn stream_events(
+    state: web::Data<AppState>,
+    mut session: Session,
+    id: String,
+    mut subscription: Subscription,
+) {
+    loop {
+        match subscription.next_page(&state).await {
+            Ok((events, has_more)) => {
+                if !events.is_empty() {
+                    let message = json!({ "id": id, "events": events });
+                    if session.text(message.to_string()).await.is_err() {
+                        return;
+                    }
+                }
+                if has_more {
+                    continue;
+                }
+            }
+            Err(QueryError::Database(err)) => {
+                log::warn!("Failed to fetch events for subscription {id}: {err}");
+                tokio::time::sleep(POLL_INTERVAL).await;
+                continue;
+            }
+            Err(QueryError::BadQuery(err)) => {
+                let _ = session
+                    .text(json!({ "id": id, "error": err }).to_string())
+                    .await;
+                return;
+            }
+        }
+        subscription.wait(&state).await;
+    }
+}